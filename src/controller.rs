@@ -0,0 +1,81 @@
+//! Step size control shared by the adaptive methods.
+use crate::Real;
+use crate::scalar::lit;
+
+/// Safety factor applied to the optimal step size, so that the next step is likely to be accepted.
+const SAFETY: f64 = 0.9;
+
+/// Smallest factor by which the step size can shrink after a step.
+const MIN_FACTOR: f64 = 0.2;
+
+/// Largest factor by which the step size can grow after an accepted step.
+const MAX_FACTOR: f64 = 5.0;
+
+/// Elementary controller choosing the next step size from the error norm of the current step.
+///
+/// The error norm is the local error estimate scaled by the tolerances, so that a step is accepted when it is at most 1.
+/// After a rejection the step size is not allowed to grow on the next accepted step.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct StepController<F> {
+    /// Exponent `1 / (q + 1)` of the error norm, `q` being the order of the error estimator.
+    exponent: F,
+    /// Whether the last step was rejected.
+    rejected: bool,
+}
+
+impl<F: Real> StepController<F> {
+    /// Creates a controller for an error estimator of order `order`.
+    pub(crate) fn new(order: usize) -> Self {
+        StepController { exponent: F::one() / lit((order + 1) as f64), rejected: false }
+    }
+
+    /// Adapts the controller to an error estimator whose order is now `order`, for the methods of variable order.
+    pub(crate) fn set_order(&mut self, order: usize) {
+        self.exponent = F::one() / lit((order + 1) as f64);
+    }
+
+    /// Returns `true` if a step whose scaled error is `error` can be accepted.
+    pub(crate) fn accepts(error: F) -> bool {
+        error <= F::one()
+    }
+
+    /// Records the outcome of a step of scaled error `error`, returning the factor to apply to its step size.
+    pub(crate) fn factor(&mut self, error: F) -> F {
+        let max_factor = if self.rejected { F::one() } else { lit(MAX_FACTOR) };
+        let factor = if error > F::zero() {
+            lit::<F>(SAFETY) * error.powf(-self.exponent)
+        } else {
+            max_factor
+        };
+
+        self.rejected = !Self::accepts(error);
+        let max_factor = if self.rejected { lit(SAFETY) } else { max_factor };
+        factor.max(lit(MIN_FACTOR)).min(max_factor)
+    }
+
+    /// Records the rejection of a step whose solution or error estimate is not finite, returning the factor to apply
+    /// to its step size, the smallest one allowed.
+    pub(crate) fn reject(&mut self) -> F {
+        self.rejected = true;
+        lit(MIN_FACTOR)
+    }
+}
+
+/// Returns `error` scaled by the tolerance `scale`, a zero error being within any tolerance, even a zero one.
+pub(crate) fn scaled_error<F: Real>(error: F, scale: F) -> F {
+    if error == F::zero() {
+        F::zero()
+    } else {
+        error / scale
+    }
+}
+
+/// Returns the root mean square of `values` scaled by the tolerances `scale`, see [`scaled_error`].
+pub(crate) fn rms_norm<F: Real>(values: &[F], scale: &[F]) -> F {
+    let mut sum = F::zero();
+    for (value, scale) in values.iter().zip(scale) {
+        let scaled = scaled_error(*value, *scale);
+        sum += scaled * scaled;
+    }
+    (sum / lit(values.len().max(1) as f64)).sqrt()
+}
@@ -0,0 +1,92 @@
+//! Errors reported by the solvers.
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::Real;
+use crate::scalar::to_f64;
+
+/// The reasons why a solver can fail to integrate an initial value problem.
+///
+/// Every variant carries the value `x` of the independent variable at which the failure happened.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SolverError {
+    /// The step size is zero, infinite or NaN.
+    InvalidStepSize { x: f64, h: f64 },
+    /// The state became infinite or NaN in the step starting at `x`, usually because `eval` returned such a value.
+    NonFiniteState { x: f64 },
+    /// The step size became too small to make progress in x.
+    StepSizeUnderflow { x: f64, h: f64 },
+    /// The maximum number of steps was reached before `x_target`.
+    MaxStepsExceeded { x: f64, max_steps: usize },
+    /// `ODESYS::eval` returned a vector whose length differs from the one of the state, or the number of absolute
+    /// tolerances of the `SolverOptions` is neither 1 nor the dimension of the state.
+    DimensionMismatch { x: f64, expected: usize, found: usize },
+    /// The order requested from a family of methods is not one of its orders, from `min` to `max`.
+    InvalidOrder { x: f64, order: usize, min: usize, max: usize },
+    /// A point of `Output::Points` lies before the previous one in the direction of integration, or outside the
+    /// interval from the initial x to `x_target`.
+    InvalidOutputPoint { x: f64, point: f64 },
+    /// The system has a mass matrix, see `ODESYS::mass_matrix_into`, which only the Radau IIA solver supports.
+    UnsupportedMassMatrix { x: f64 },
+}
+
+impl SolverError {
+    /// Returns the value of the independent variable at which the failure happened.
+    pub fn x(&self) -> f64 {
+        match *self {
+            SolverError::InvalidStepSize { x, .. }
+            | SolverError::NonFiniteState { x }
+            | SolverError::StepSizeUnderflow { x, .. }
+            | SolverError::MaxStepsExceeded { x, .. }
+            | SolverError::DimensionMismatch { x, .. }
+            | SolverError::InvalidOrder { x, .. }
+            | SolverError::InvalidOutputPoint { x, .. }
+            | SolverError::UnsupportedMassMatrix { x } => x,
+        }
+    }
+}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverError::InvalidStepSize { x, h } => write!(f, "invalid step size {} at x = {}", h, x),
+            SolverError::NonFiniteState { x } => write!(f, "non-finite state at x = {}", x),
+            SolverError::StepSizeUnderflow { x, h } => write!(f, "step size {} underflowed at x = {}", h, x),
+            SolverError::MaxStepsExceeded { x, max_steps } => {
+                write!(f, "maximum number of steps ({}) exceeded at x = {}", max_steps, x)
+            }
+            SolverError::DimensionMismatch { x, expected, found } => write!(
+                f,
+                "dimension {} does not match the dimension {} of the state at x = {}",
+                found, expected, x
+            ),
+            SolverError::InvalidOrder { x, order, min, max } => {
+                write!(f, "order {} is not between {} and {} at x = {}", order, min, max, x)
+            }
+            SolverError::InvalidOutputPoint { x, point } => {
+                write!(f, "output point {} is out of order or outside the integration from x = {}", point, x)
+            }
+            SolverError::UnsupportedMassMatrix { x } => {
+                write!(f, "the system has a mass matrix, which the solver does not support, at x = {}", x)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolverError {}
+
+/// Checks that `order` is one of the orders `orders` of a family of methods, for an integration starting at `x`.
+pub(crate) fn check_order<F: Real>(order: usize, orders: RangeInclusive<usize>, x: F) -> Result<(), SolverError> {
+    if orders.contains(&order) {
+        return Ok(());
+    }
+    Err(SolverError::InvalidOrder { x: to_f64(x), order, min: *orders.start(), max: *orders.end() })
+}
+
+/// Unwraps the result of an integration, panicking with the error message on failure.
+///
+/// Used by the infallible solver methods, which are thin wrappers around the `try_*` ones.
+#[track_caller]
+pub(crate) fn expect_solved<T>(result: Result<T, SolverError>) -> T {
+    result.unwrap_or_else(|error| panic!("{}", error))
+}
@@ -0,0 +1,52 @@
+//! Helpers shared by the fixed-step drivers to lay out the integration grid.
+use crate::SolverError;
+use crate::scalar::{Real, lit, to_f64};
+
+/// Relative tolerance, in units of the machine epsilon, under which `(x_target - x0) / h` is considered an integer.
+const GRID_TOLERANCE: f64 = 64.0;
+
+/// Maximum number of steps taken before giving up with [`SolverError::MaxStepsExceeded`].
+pub(crate) const DEFAULT_MAX_STEPS: usize = 10_000_000;
+
+/// Checks that `h` can be used as a step size, reporting the failure at `x` otherwise.
+pub(crate) fn check_step_size<F: Real>(x: F, h: F) -> Result<(), SolverError> {
+    if h == F::zero() || !h.is_finite() {
+        return Err(SolverError::InvalidStepSize { x: to_f64(x), h: to_f64(h) });
+    }
+    Ok(())
+}
+
+/// Returns the step size `h` signed according to the direction of integration.
+///
+/// `h` is interpreted as a magnitude: the step is negative when `x_target < x0`, so that the problem is integrated backwards.
+pub(crate) fn signed_step<F: Real>(x0: F, h: F, x_target: F) -> F {
+    if x_target < x0 {
+        -h.abs()
+    } else {
+        h.abs()
+    }
+}
+
+/// Returns the number of steps needed to go from `x0` to `x_target` with the signed step size `h`.
+///
+/// When `(x_target - x0) / h` is not an integer the last step is meant to be shortened, so the count is rounded up.
+pub(crate) fn step_count<F: Real>(x0: F, h: F, x_target: F) -> usize {
+    let steps = (x_target - x0) / h;
+    if steps.is_nan() || steps <= F::zero() {
+        return 0;
+    }
+
+    let rounded = steps.round();
+    let tolerance = F::epsilon() * lit(GRID_TOLERANCE) * rounded.max(F::one());
+    let count = if (steps - rounded).abs() <= tolerance { rounded } else { steps.ceil() };
+    count.to_usize().unwrap_or(usize::MAX)
+}
+
+/// Returns the `i`-th point of a grid of `n` steps of size `h` starting at `x0`, with the last point pinned to `x_target`.
+pub(crate) fn grid_point<F: Real>(x0: F, h: F, x_target: F, i: usize, n: usize) -> F {
+    if i >= n {
+        x_target
+    } else {
+        x0 + lit::<F>(i as f64) * h
+    }
+}
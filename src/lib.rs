@@ -2,8 +2,8 @@
 //! # Download
 //! To build with `damn-differential` you need to add the library to your project as a dependency.
 //! In your terminal run:
-//! ```
-//! cargo add damn-diff
+//! ```text
+//! cargo add damndiff
 //! ```
 //! 
 //! # Example
@@ -14,6 +14,7 @@
 //! 
 //! For example:
 //! ```
+//! # use damndiff::ode::{ODE, ODESolver, rk::RungeKuttaODESolver};
 //! struct MyODE;
 //! impl ODE for MyODE {
 //!     fn eval(&self, x: f64, y: f64) -> f64 {
@@ -36,3 +37,25 @@
 pub mod ode;
 /// Systems of ordinary differential equations
 pub mod ode_sys;
+/// Trajectories returned by the solvers
+pub mod solution;
+/// Errors reported by the solvers
+pub mod error;
+/// Scalar types the solvers can work with
+pub mod scalar;
+/// Settings shared by all the solvers
+pub mod options;
+/// Statistics of the integrations
+pub mod stats;
+/// Butcher tableaus of the explicit Runge-Kutta methods
+pub mod tableau;
+mod grid;
+mod controller;
+mod linalg;
+
+pub use error::SolverError;
+pub use options::{Output, SolverOptions};
+pub use scalar::Real;
+pub use solution::Solution;
+pub use stats::{MethodSwitch, Stats};
+pub use tableau::ButcherTableau;
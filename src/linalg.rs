@@ -0,0 +1,78 @@
+//! Dense linear algebra shared by the implicit methods.
+use crate::Real;
+
+/// LU factorization with partial pivoting of a square matrix, solving the linear systems of the Newton iterations.
+#[derive(Debug, Clone)]
+pub(crate) struct Lu<F> {
+    dim: usize,
+    /// Factors `L` (below the diagonal, with a unit diagonal) and `U` of the row-permuted matrix, row by row.
+    lu: Vec<F>,
+    /// Row swapped with row `i` at step `i` of the elimination.
+    pivots: Vec<usize>,
+}
+
+impl<F: Real> Lu<F> {
+    /// Creates the storage of the factorization of a matrix of dimension `dim`.
+    pub(crate) fn new(dim: usize) -> Self {
+        Lu { dim, lu: vec![F::zero(); dim * dim], pivots: vec![0; dim] }
+    }
+
+    /// Factorizes `matrix`, stored row by row, replacing the previous factorization.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the matrix is singular to working precision, in which case the factorization cannot be used.
+    pub(crate) fn factor(&mut self, matrix: &[F]) -> bool {
+        let n = self.dim;
+        self.lu.copy_from_slice(matrix);
+
+        for k in 0..n {
+            let mut pivot = k;
+            for i in k + 1..n {
+                if self.lu[i * n + k].abs() > self.lu[pivot * n + k].abs() {
+                    pivot = i;
+                }
+            }
+            self.pivots[k] = pivot;
+            if self.lu[pivot * n + k] == F::zero() || !self.lu[pivot * n + k].is_finite() {
+                return false;
+            }
+            if pivot != k {
+                for j in 0..n {
+                    self.lu.swap(k * n + j, pivot * n + j);
+                }
+            }
+
+            let diagonal = self.lu[k * n + k];
+            for i in k + 1..n {
+                let factor = self.lu[i * n + k] / diagonal;
+                self.lu[i * n + k] = factor;
+                if factor != F::zero() {
+                    for j in k + 1..n {
+                        let u = self.lu[k * n + j];
+                        self.lu[i * n + j] -= factor * u;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Overwrites `b` with the solution `x` of `A x = b`, `A` being the last factorized matrix.
+    pub(crate) fn solve(&self, b: &mut [F]) {
+        let n = self.dim;
+        for k in 0..n {
+            b.swap(k, self.pivots[k]);
+        }
+        for i in 0..n {
+            let row = &self.lu[i * n..i * n + i];
+            let sum = row.iter().zip(&b[..i]).fold(b[i], |sum, (l, b)| sum - *l * *b);
+            b[i] = sum;
+        }
+        for i in (0..n).rev() {
+            let row = &self.lu[i * n + i + 1..(i + 1) * n];
+            let sum = row.iter().zip(&b[i + 1..]).fold(b[i], |sum, (u, b)| sum - *u * *b);
+            b[i] = sum / self.lu[i * n + i];
+        }
+    }
+}
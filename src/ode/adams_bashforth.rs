@@ -1,11 +1,24 @@
 //! Adams-Bashforth method
-use super::{ODE, ODESolver};
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+
+use super::{ODE, ODESolver, fixed_step, fixed_step_traj, solve_with};
+use super::stepper::Stepper;
+use super::explicit_rk::ExplicitRk;
+use crate::{ButcherTableau, Real, Solution, SolverError, SolverOptions};
+use crate::error::{check_order, expect_solved};
+use crate::scalar::lit;
 
 /// Adams-Bashforth Ordinary Differential Equation (ODE) solver trait.
 ///
-/// This trait defines the [Adams-Bashforth method](https://web.mit.edu/10.001/Web/Course_Notes/Differential_Equations_Notes/node6.html) for solving initial value problems (IVPs)
-/// of ordinary differential equations (ODEs).
-pub trait ABODESolver {
+/// This trait defines the k-step [Adams-Bashforth methods](https://en.wikipedia.org/wiki/Linear_multistep_method#Adams%E2%80%93Bashforth_methods)
+/// of orders 1 to 5 for solving initial value problems (IVPs) of ordinary differential equations (ODEs). The method of
+/// order k extrapolates the derivatives at the last k points of a uniform grid, so that each step costs a single
+/// evaluation of the ODE. The first steps, and the steps following a change of the step size, are taken with the
+/// fourth-order Runge-Kutta method until the history holds k derivatives again.
+///
+/// The methods integrate with the method of order 4, except the `*_order` ones, which take the order as an argument.
+pub trait ABODESolver<F: Real = f64> {
     /// Solve the Initial Value Problem (IVP) for an ODE using the Adams-Bashforth method.
     ///
     /// # Arguments
@@ -19,10 +32,15 @@ pub trait ABODESolver {
     /// # Returns
     ///
     /// The estimated y value at `x_target`.
-    /// 
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ABODESolver::try_ab_ivp`].
+    ///
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, adams_bashforth::ABODESolver};
     /// struct MyODE;
     /// impl ODE for MyODE {
     ///     fn eval(&self, x: f64, y: f64) -> f64 {
@@ -39,16 +57,333 @@ pub trait ABODESolver {
     /// let x_target = 1.0;
     ///
     /// let result = solver.ab_ivp(&my_ode, x0, y0, h, x_target);
+    /// assert!((result - (2.0 * 1.0f64.exp() - 2.0)).abs() < 1e-3);
     /// println!("Solution at x = {}: {}", x_target, result);
     /// ```
-    fn ab_ivp(&self, ode: &dyn ODE, x0: f64, y0: f64, h: f64, x_target: f64) -> f64;
+    fn ab_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> F;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Adams-Bashforth method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size or increment for x.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from `x0` up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ABODESolver::try_ab_ivp`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, adams_bashforth::ABODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let solution = solver.ab_ivp_traj(&my_ode, x0, y0, h, x_target);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {}", x, y);
+    /// }
+    /// ```
+    fn ab_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Adams-Bashforth method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size or increment for x.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode::{ODE, ODESolver, adams_bashforth::ABODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.try_ab_ivp(&my_ode, x0, y0, h, x_target).unwrap();
+    /// assert!((result - (2.0 * 1.0f64.exp() - 2.0)).abs() < 1e-3);
+    ///
+    /// // The solution of y' = y^2 blows up at x = 1
+    /// let error = solver.try_ab_ivp(&|_x: f64, y: f64| y * y, x0, y0, h, 2.0).unwrap_err();
+    /// assert!(matches!(error, SolverError::NonFiniteState { x } if x > 1.0 && x < 2.0));
+    /// ```
+    fn try_ab_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError>;
+
+    /// Create a stepper integrating the Initial Value Problem (IVP) for an ODE with the Adams-Bashforth method, one step at a time.
+    ///
+    /// The stepper keeps the derivative history between calls, and restarts it whenever the step size changes, for
+    /// instance on the shortened last step of [`Stepper::advance_to`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// A [`Stepper`] holding the current state `(x, y)`, starting from `(x0, y0)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, adams_bashforth::ABODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let mut stepper = solver.ab_stepper(&my_ode, 0.0, 1.0, 0.1);
+    ///
+    /// // Integrate one unit of x at a time
+    /// for x in [1.0, 2.0, 3.0] {
+    ///     let y = stepper.advance_to(x).unwrap();
+    ///     assert_eq!(stepper.x(), x);
+    ///     assert!((y - (2.0 * x.exp() - x - 1.0)).abs() < 1e-4 * y);
+    /// }
+    ///
+    /// // Three startup steps of RK4 with four evaluations each, then one evaluation per step
+    /// assert_eq!(stepper.stats().evals, 3 * 4 + 27);
+    /// ```
+    fn ab_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, h: F) -> Stepper<'a, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Adams-Bashforth method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ABODESolver::try_ab_ivp_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, adams_bashforth::ABODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let options = SolverOptions::new().h0(0.1).output(Output::Final);
+    ///
+    /// let solution = solver.ab_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options);
+    /// assert_eq!(solution.x, vec![1.0]);
+    /// assert!((solution.y[0] - (2.0 * 1.0f64.exp() - 2.0)).abs() < 1e-3);
+    /// ```
+    fn ab_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Adams-Bashforth method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverError, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, adams_bashforth::ABODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// // The solution is requested at x = 0.5 on the way to x = 1
+    /// let options = SolverOptions::new().h0(0.1).output(Output::Points(vec![0.5]));
+    /// let solution = solver.try_ab_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options).unwrap();
+    /// assert_eq!(solution.x, vec![0.5]);
+    /// assert!((solution.y[0] - (2.0 * 0.5f64.exp() - 1.5)).abs() < 1e-4);
+    ///
+    /// // Ten steps of 0.1 are needed to reach x = 1
+    /// let options = SolverOptions::new().h0(0.1).max_steps(5);
+    /// let error = solver.try_ab_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options).unwrap_err();
+    /// assert_eq!(error, SolverError::MaxStepsExceeded { x: 0.0, max_steps: 5 });
+    /// ```
+    fn try_ab_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Adams-Bashforth method of a given order.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `order` - The order of the method, from 1 to 5, which is also its number of steps.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size or increment for x.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails or if `order` is not between 1 and 5, see [`ABODESolver::try_ab_ivp_order`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODESolver, adams_bashforth::ABODESolver};
+    /// let solver = ODESolver;
+    /// let decay = |_x: f64, y: f64| -y;
+    ///
+    /// // The error decreases with the order
+    /// let errors: Vec<f64> = (1..=5).map(|order| (solver.ab_ivp_order(&decay, order, 0.0, 1.0, 0.01, 1.0) - (-1.0f64).exp()).abs()).collect();
+    /// assert!(errors.windows(2).all(|pair| pair[1] < pair[0]));
+    /// ```
+    fn ab_ivp_order(&self, ode: &dyn ODE<F>, order: usize, x0: F, y0: F, h: F, x_target: F) -> F;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Adams-Bashforth method of a given order, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `order` - The order of the method, from 1 to 5, which is also its number of steps.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size or increment for x.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`, or the [`SolverError`] that stopped the integration, which is a
+    /// [`SolverError::InvalidOrder`] if `order` is not between 1 and 5.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode::{ODESolver, adams_bashforth::ABODESolver};
+    /// let solver = ODESolver;
+    /// let decay = |_x: f64, y: f64| -y;
+    ///
+    /// let result = solver.try_ab_ivp_order(&decay, 2, 0.0, 1.0, 0.01, 1.0).unwrap();
+    /// assert!((result - (-1.0f64).exp()).abs() < 1e-4);
+    ///
+    /// let error = solver.try_ab_ivp_order(&decay, 6, 0.0, 1.0, 0.01, 1.0).unwrap_err();
+    /// assert_eq!(error, SolverError::InvalidOrder { x: 0.0, order: 6, min: 1, max: 5 });
+    /// ```
+    fn try_ab_ivp_order(&self, ode: &dyn ODE<F>, order: usize, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError>;
+
+    /// Create a stepper integrating the Initial Value Problem (IVP) for an ODE with the Adams-Bashforth method of a given order, one step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `order` - The order of the method, from 1 to 5, which is also its number of steps.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// A [`Stepper`] holding the current state `(x, y)`, starting from `(x0, y0)`, or a [`SolverError::InvalidOrder`] if
+    /// `order` is not between 1 and 5.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode::{ODESolver, adams_bashforth::ABODESolver};
+    /// let solver = ODESolver;
+    /// let decay = |_x: f64, y: f64| -y;
+    ///
+    /// let error = solver.ab_stepper_order(&decay, 6, 0.0, 1.0, 0.1).err().unwrap();
+    /// assert_eq!(error, SolverError::InvalidOrder { x: 0.0, order: 6, min: 1, max: 5 });
+    ///
+    /// let mut stepper = solver.ab_stepper_order(&decay, 5, 0.0, 1.0, 0.1).unwrap();
+    ///
+    /// let y = stepper.advance_to(3.0).unwrap();
+    /// assert!((y - (-3.0f64).exp()).abs() < 1e-5);
+    /// // Four startup steps of RK4 with four evaluations each, then one evaluation per step
+    /// assert_eq!(stepper.stats().evals, 4 * 4 + 26);
+    /// ```
+    fn ab_stepper_order<'a>(&self, ode: &'a dyn ODE<F>, order: usize, x0: F, y0: F, h: F) -> Result<Stepper<'a, F>, SolverError>;
 }
 
 // Implementing the Adams-Bashforth method for the ODE Solver
-impl ABODESolver for ODESolver {
+impl<F: Real> ABODESolver<F> for ODESolver {
     /// Implementation of the Adams-Bashforth method to solve an IVP for an ODE.
     ///
-    /// This method approximates the solution to the ODE at the specified x_target.
+    /// This method approximates the solution to the ODE at the specified x_target. The k-step method advances the
+    /// solution with `y + h * sum(beta_j * f_{n-j})`, a combination of the derivatives at the current point and at the
+    /// k - 1 previous points of the grid, which is exact for polynomial solutions of degree k. The history is filled by
+    /// k - 1 steps of the fourth-order Runge-Kutta method, and rebuilt the same way after every change of step size.
     ///
     /// # Arguments
     ///
@@ -61,22 +396,23 @@ impl ABODESolver for ODESolver {
     /// # Returns
     ///
     /// The estimated y value at `x_target`.
-    /// 
-    /// # When to Use: 
-    /// 
-    /// Suitable for solving non-stiff differential equations.
-    /// 
+    ///
+    /// # When to Use:
+    ///
+    /// Suitable for non-stiff differential equations whose right-hand side is expensive to evaluate, integrated with a constant step size.
+    ///
     /// # Pros and Cons:
-    /// - Pros: High accuracy for non-stiff problems.
-    /// - Cons: Unstable for stiff problems, accuracy depends on the step size.
-    /// 
-    /// # Stability Analysis: 
-    /// 
-    /// Conditionally stable, more suited for non-stiff problems.
-    /// 
+    /// - Pros: A single evaluation of the ODE per step whatever the order.
+    /// - Cons: Needs a startup procedure, and a restart whenever the step size changes; the stability region shrinks as the order grows.
+    ///
+    /// # Stability Analysis:
+    ///
+    /// Conditionally stable, with a stability region smaller than the one of the Runge-Kutta methods of the same order: unsuited to stiff problems.
+    ///
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, adams_bashforth::ABODESolver};
     /// struct MyODE;
     /// impl ODE for MyODE {
     ///     fn eval(&self, x: f64, y: f64) -> f64 {
@@ -87,25 +423,151 @@ impl ABODESolver for ODESolver {
     ///
     /// let solver = ODESolver;
     /// let my_ode = MyODE;
-    /// let x0 = 0.0;
-    /// let y0 = 1.0;
-    /// let h = 0.1;
-    /// let x_target = 1.0;
+    /// let exact = 2.0 * 1.0f64.exp() - 2.0;
     ///
-    /// let result = solver.ab_ivp(&my_ode, x0, y0, h, x_target);
-    /// println!("Solution at x = {}: {}", x_target, result);
+    /// // Halving the step size divides the error by about 2^k
+    /// let coarse = (solver.ab_ivp_order(&my_ode, 3, 0.0, 1.0, 0.01, 1.0) - exact).abs();
+    /// let fine = (solver.ab_ivp_order(&my_ode, 3, 0.0, 1.0, 0.005, 1.0) - exact).abs();
+    /// assert!(coarse / fine > 7.0);
     /// ```
-    fn ab_ivp(&self, ode: &dyn ODE, x0: f64, y0: f64, h: f64, x_target: f64) -> f64 {
-        let mut x = x0;
-        let mut y = y0;
+    fn ab_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> F {
+        expect_solved(self.try_ab_ivp(ode, x0, y0, h, x_target))
+    }
 
-        while x < x_target {
-            let f0 = ode.eval(x, y);
-            let f1 = ode.eval(x + h, y + h * f0);
-            y += h * (1.5 * f1 - 0.5 * f0);
-            x += h;
+    /// Implementation of the Adams-Bashforth method returning the whole trajectory of the IVP.
+    ///
+    /// See [`ABODESolver::ab_ivp`] for the details of the method.
+    fn ab_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F> {
+        expect_solved(fixed_step_traj(ode, x0, y0, h, x_target, ab_method(DEFAULT_ORDER)))
+    }
+
+    /// Implementation of the Adams-Bashforth method reporting failures as a [`SolverError`].
+    ///
+    /// See [`ABODESolver::ab_ivp`] for the details of the method.
+    fn try_ab_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError> {
+        fixed_step(ode, x0, y0, h, x_target, ab_method(DEFAULT_ORDER), |_, _| {})
+    }
+
+    /// Implementation of the Adams-Bashforth method as a [`Stepper`].
+    ///
+    /// See [`ABODESolver::ab_ivp`] for the details of the method.
+    fn ab_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, h: F) -> Stepper<'a, F> {
+        Stepper::new(ode, ab_method(DEFAULT_ORDER), x0, y0, h)
+    }
+
+    /// Implementation of the Adams-Bashforth method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`ABODESolver::ab_ivp`] for the details of the method.
+    fn ab_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F> {
+        expect_solved(self.try_ab_ivp_with(ode, x0, y0, x_target, options))
+    }
+
+    /// Implementation of the Adams-Bashforth method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`ABODESolver::ab_ivp`] for the details of the method.
+    fn try_ab_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError> {
+        options.check(x0, x_target, 1)?;
+        let stepper = self.ab_stepper(ode, x0, y0, options.initial_step(x0, x_target));
+        solve_with(stepper.with_max_steps(options.max_steps), x0, y0, x_target, &options.output)
+    }
+
+    /// Implementation of the Adams-Bashforth method of a given order.
+    ///
+    /// See [`ABODESolver::ab_ivp`] for the details of the method.
+    fn ab_ivp_order(&self, ode: &dyn ODE<F>, order: usize, x0: F, y0: F, h: F, x_target: F) -> F {
+        expect_solved(self.try_ab_ivp_order(ode, order, x0, y0, h, x_target))
+    }
+
+    /// Implementation of the Adams-Bashforth method of a given order reporting failures as a [`SolverError`].
+    ///
+    /// See [`ABODESolver::ab_ivp`] for the details of the method.
+    fn try_ab_ivp_order(&self, ode: &dyn ODE<F>, order: usize, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError> {
+        check_order(order, AB_ORDERS, x0)?;
+        fixed_step(ode, x0, y0, h, x_target, ab_method(order), |_, _| {})
+    }
+
+    /// Implementation of the Adams-Bashforth method of a given order as a [`Stepper`].
+    ///
+    /// See [`ABODESolver::ab_ivp`] for the details of the method.
+    fn ab_stepper_order<'a>(&self, ode: &'a dyn ODE<F>, order: usize, x0: F, y0: F, h: F) -> Result<Stepper<'a, F>, SolverError> {
+        check_order(order, AB_ORDERS, x0)?;
+        Ok(Stepper::new(ode, ab_method(order), x0, y0, h))
+    }
+}
+
+/// Coefficients `beta_j` of the k-step Adams-Bashforth methods, weighting the derivative `f_{n-j}` from the most recent.
+pub(crate) const AB_COEFFICIENTS: [&[f64]; 5] = [
+    &[1.0],
+    &[3.0 / 2.0, -1.0 / 2.0],
+    &[23.0 / 12.0, -16.0 / 12.0, 5.0 / 12.0],
+    &[55.0 / 24.0, -59.0 / 24.0, 37.0 / 24.0, -9.0 / 24.0],
+    &[1901.0 / 720.0, -2774.0 / 720.0, 2616.0 / 720.0, -1274.0 / 720.0, 251.0 / 720.0],
+];
+
+/// Relative difference between two step sizes under which they are considered equal, absorbing the rounding of the grid.
+const STEP_TOLERANCE: f64 = 1e-6;
+
+/// Orders of the Adams-Bashforth methods.
+pub(crate) const AB_ORDERS: RangeInclusive<usize> = 1..=5;
+
+/// Order of the Adams-Bashforth method used by the methods that do not take the order as an argument.
+pub(crate) const DEFAULT_ORDER: usize = 4;
+
+/// Returns `true` if a step of size `h` from `x` continues the uniform grid whose last step went from `x_last` with size `h_last`.
+pub(crate) fn continues_grid<F: Real>(x_last: F, h_last: F, x: F, h: F) -> bool {
+    let tolerance = h_last.abs() * lit(STEP_TOLERANCE);
+    (h - h_last).abs() <= tolerance && (x - (x_last + h_last)).abs() <= tolerance
+}
+
+/// The k-step Adams-Bashforth method, keeping the derivatives at the last k points of the grid.
+#[derive(Debug, Clone)]
+struct AdamsBashforth<F> {
+    order: usize,
+    /// Derivatives at the last points of the grid, the most recent first.
+    history: VecDeque<F>,
+    /// Start point and size of the last step, telling whether the next one continues the grid.
+    x_last: F,
+    h_last: F,
+    /// Method of the steps taken while the history is incomplete.
+    startup: ExplicitRk<F>,
+}
+
+impl<F: Real> AdamsBashforth<F> {
+    fn new(order: usize) -> Self {
+        AdamsBashforth {
+            order,
+            history: VecDeque::with_capacity(order),
+            x_last: F::zero(),
+            h_last: F::zero(),
+            startup: ExplicitRk::new(ButcherTableau::rk4()),
+        }
+    }
+
+    /// Takes a step of size `h` from `(x, y)`, restarting the history if the step does not continue the grid.
+    fn advance(&mut self, ode: &dyn ODE<F>, x: F, y: F, h: F) -> F {
+        if !continues_grid(self.x_last, self.h_last, x, h) {
+            self.history.clear();
+        }
+        self.x_last = x;
+        self.h_last = h;
+
+        let f = ode.eval(x, y);
+        self.history.push_front(f);
+        self.history.truncate(self.order);
+        if self.history.len() < self.order {
+            return self.startup.advance_from(ode, x, y, f, h);
         }
 
-        y
+        let mut slope = F::zero();
+        for (beta, f) in AB_COEFFICIENTS[self.order - 1].iter().zip(&self.history) {
+            slope += *f * lit(*beta);
+        }
+        y + h * slope
     }
 }
+
+/// Returns the step function of a [`Stepper`] integrating with the Adams-Bashforth method of order `order`.
+fn ab_method<'a, F: Real>(order: usize) -> impl FnMut(&dyn ODE<F>, F, F, F) -> F + 'a {
+    let mut method = AdamsBashforth::new(order);
+    move |ode, x, y, h| method.advance(ode, x, y, h)
+}
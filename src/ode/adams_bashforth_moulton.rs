@@ -0,0 +1,542 @@
+//! Adams-Bashforth-Moulton method
+use std::collections::VecDeque;
+use super::{ODE, ODESolver, solve_with};
+use super::adaptive::{AdaptiveStepper, DenseMethod, EmbeddedMethod};
+use super::explicit_rk::hermite;
+use crate::{Output, Real, Solution, SolverError, SolverOptions};
+use crate::error::expect_solved;
+use crate::scalar::lit;
+
+/// Adams-Bashforth-Moulton Ordinary Differential Equation (ODE) solver trait.
+///
+/// This trait defines the [Adams-Bashforth-Moulton method](https://en.wikipedia.org/wiki/Linear_multistep_method#Adams%E2%80%93Moulton_methods)
+/// in PECE mode, with variable step size and variable order from 1 to 5, for solving initial value problems (IVPs) of
+/// ordinary differential equations (ODEs). Each step predicts the solution with an Adams-Bashforth formula, evaluates
+/// the ODE, corrects with the Adams-Moulton formula of the same order and evaluates again, estimating the local error
+/// from the difference between prediction and correction (Milne's device). The step size `h` given to the methods is
+/// the initial one, which is then adapted so that the error estimate stays within the tolerances, see
+/// [`AdaptiveStepper`]. Without a [`SolverOptions`], the tolerances are the defaults of [`SolverOptions::new`].
+pub trait ABMODESolver<F: Real = f64> {
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Adams-Bashforth-Moulton method.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ABMODESolver::try_abm_ivp`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, adams_bashforth_moulton::ABMODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.abm_ivp(&my_ode, x0, y0, h, x_target);
+    /// println!("Solution at x = {}: {}", x_target, result);
+    /// ```
+    fn abm_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> F;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Adams-Bashforth-Moulton method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from `x0` up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ABMODESolver::try_abm_ivp`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, adams_bashforth_moulton::ABMODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let solution = solver.abm_ivp_traj(&my_ode, x0, y0, h, x_target);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {}", x, y);
+    /// }
+    /// ```
+    fn abm_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Adams-Bashforth-Moulton method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, adams_bashforth_moulton::ABMODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// match solver.try_abm_ivp(&my_ode, x0, y0, h, x_target) {
+    ///     Ok(result) => println!("Solution at x = {}: {}", x_target, result),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_abm_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError>;
+
+    /// Create a stepper integrating the Initial Value Problem (IVP) for an ODE with the Adams-Bashforth-Moulton method, one step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// An [`AdaptiveStepper`] holding the current state `(x, y)`, starting from `(x0, y0)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, adams_bashforth_moulton::ABMODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let mut stepper = solver.abm_stepper(&my_ode, 0.0, 1.0, 0.1);
+    ///
+    /// // Integrate one unit of x at a time
+    /// for x in [1.0, 2.0, 3.0] {
+    ///     let y = stepper.advance_to(x).unwrap();
+    ///     println!("Solution at x = {}: {}", x, y);
+    /// }
+    /// ```
+    fn abm_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, h: F) -> AdaptiveStepper<'a, AdamsBashforthMoulton<F>, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Adams-Bashforth-Moulton method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the tolerances, the initial step size `h0` and its bounds.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ABMODESolver::try_abm_ivp_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, adams_bashforth_moulton::ABMODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let options = SolverOptions::new().rtol(1e-8).atol(1e-8).output(Output::Final);
+    ///
+    /// let solution = solver.abm_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options);
+    /// println!("Solution at x = 1: {:?}", solution.last());
+    /// ```
+    fn abm_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Adams-Bashforth-Moulton method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the tolerances, the initial step size `h0` and its bounds.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, adams_bashforth_moulton::ABMODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let options = SolverOptions::new().rtol(1e-8).atol(1e-8).output(Output::Final);
+    ///
+    /// match solver.try_abm_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options) {
+    ///     Ok(solution) => println!("Solution at x = 1: {:?}", solution.last()),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_abm_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError>;
+}
+
+// Implementing the Adams-Bashforth-Moulton method for the ODE Solver
+impl<F: Real> ABMODESolver<F> for ODESolver {
+    /// Implementation of the Adams-Bashforth-Moulton method to solve an IVP for an ODE.
+    ///
+    /// This method approximates the solution to the ODE at the specified x_target. The method of order k predicts the
+    /// solution at `x + h` by integrating the polynomial interpolating the derivatives at the last k points, evaluates
+    /// the ODE there, and corrects by integrating the polynomial interpolating this evaluation and the derivatives at
+    /// the last k - 1 points. The ODE is evaluated once more at the corrected value, which is the one propagated, so
+    /// that a step costs two evaluations. The weights of both formulas are computed for the actual, variable grid.
+    ///
+    /// The local error is estimated as `C* / (C - C*) * (y_c - y_p)`, `C` and `C*` being the error constants of the
+    /// Adams-Bashforth and Adams-Moulton formulas of order k. After each accepted step, the errors of the orders k - 1,
+    /// k and k + 1 are estimated from the divided differences of the derivatives, and the order decreases if it does
+    /// not reduce the error, or increases if the higher one has an error smaller and the order has been kept for k + 1
+    /// steps. The method starts at order 1, so that it needs no other starting method. The solution between two
+    /// accepted points is given by cubic Hermite interpolation, see [`AdaptiveStepper::interpolate`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size.
+    /// * `x_target` - The x value where the solution is desired.
+    /// 
+    /// # When to Use: 
+    /// 
+    /// Well-suited for non-stiff differential equations whose evaluations are expensive, solved to tight tolerances,
+    /// such as the equations of orbital mechanics.
+    /// 
+    /// # Pros and Cons:
+    /// Pros: Two evaluations per step whatever the order, which adapts to the smoothness of the solution along with the step size.
+    /// Cons: Small stability regions at high orders, and a bookkeeping overhead that dominates when the ODE is cheap.
+    /// 
+    /// # Stability Analysis: 
+    /// 
+    /// Conditionally stable: on stiff problems the step size control keeps the steps small enough to stay stable, at a high cost.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`.
+    /// 
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverOptions;
+    /// # use damndiff::ode::{ODE, ODESolver, adams_bashforth_moulton::ABMODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.abm_ivp(&my_ode, x0, y0, h, x_target);
+    /// assert!((result - (2.0 * 1.0f64.exp() - 2.0)).abs() < 1e-4);
+    ///
+    /// // The order rises to 5 on smooth problems, with few steps for tight tolerances
+    /// let options = SolverOptions::new().rtol(1e-10).atol(1e-10);
+    /// let mut stepper = solver.abm_stepper(&my_ode, x0, y0, h).with_options(&options);
+    /// let y = stepper.advance_to(x_target).unwrap();
+    /// assert!((y - (2.0 * 1.0f64.exp() - 2.0)).abs() < 1e-7);
+    /// assert!(stepper.stats().accepted_steps < 100);
+    /// ```
+    fn abm_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> F {
+        expect_solved(self.try_abm_ivp(ode, x0, y0, h, x_target))
+    }
+
+    /// Implementation of the Adams-Bashforth-Moulton method returning the whole trajectory of the IVP.
+    ///
+    /// See [`ABMODESolver::abm_ivp`] for the details of the method.
+    fn abm_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F> {
+        expect_solved(solve_with(self.abm_stepper(ode, x0, y0, h), x0, y0, x_target, &Output::Steps))
+    }
+
+    /// Implementation of the Adams-Bashforth-Moulton method reporting failures as a [`SolverError`].
+    ///
+    /// See [`ABMODESolver::abm_ivp`] for the details of the method.
+    fn try_abm_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError> {
+        self.abm_stepper(ode, x0, y0, h).advance_to(x_target)
+    }
+
+    /// Implementation of the Adams-Bashforth-Moulton method as an [`AdaptiveStepper`].
+    ///
+    /// See [`ABMODESolver::abm_ivp`] for the details of the method.
+    fn abm_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, h: F) -> AdaptiveStepper<'a, AdamsBashforthMoulton<F>, F> {
+        AdaptiveStepper::new(ode, AdamsBashforthMoulton::new(), x0, y0, h)
+    }
+
+    /// Implementation of the Adams-Bashforth-Moulton method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`ABMODESolver::abm_ivp`] for the details of the method.
+    fn abm_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F> {
+        expect_solved(self.try_abm_ivp_with(ode, x0, y0, x_target, options))
+    }
+
+    /// Implementation of the Adams-Bashforth-Moulton method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`ABMODESolver::abm_ivp`] for the details of the method.
+    fn try_abm_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError> {
+        options.check(x0, x_target, 1)?;
+        let stepper = self.abm_stepper(ode, x0, y0, options.initial_step(x0, x_target));
+        solve_with(stepper.with_options(options), x0, y0, x_target, &options.output)
+    }
+}
+
+/// Maximum order of the Adams-Bashforth-Moulton method.
+const MAX_ORDER: usize = 5;
+
+/// Error constants of the Adams-Bashforth formulas of orders 1 to 5.
+const AB_ERROR_CONSTANTS: [f64; MAX_ORDER] = [1.0 / 2.0, 5.0 / 12.0, 3.0 / 8.0, 251.0 / 720.0, 95.0 / 288.0];
+
+/// Error constants of the Adams-Moulton formulas of orders 1 to 5.
+const AM_ERROR_CONSTANTS: [f64; MAX_ORDER] = [-1.0 / 2.0, -1.0 / 12.0, -1.0 / 24.0, -19.0 / 720.0, -3.0 / 160.0];
+
+/// The Adams-Bashforth-Moulton PECE method with variable step size and order, parametrizing the [`AdaptiveStepper`] of
+/// the method.
+///
+/// It keeps the derivatives at the last accepted points, from which both formulas are rebuilt at every step for the
+/// actual step sizes, and selects the order after each accepted step.
+#[derive(Debug, Clone)]
+pub struct AdamsBashforthMoulton<F> {
+    order: usize,
+    /// Last accepted points and the derivatives there, the most recent first.
+    xs: VecDeque<F>,
+    fs: VecDeque<F>,
+    /// End point of the last attempted step and the derivative at its corrected value.
+    x_next: F,
+    f_next: F,
+    /// Number of steps accepted since the last change of order.
+    steps_at_order: usize,
+}
+
+impl<F: Real> AdamsBashforthMoulton<F> {
+    /// Creates the method at order 1, with an empty history.
+    pub(crate) fn new() -> Self {
+        AdamsBashforthMoulton {
+            order: 1,
+            xs: VecDeque::with_capacity(MAX_ORDER + 1),
+            fs: VecDeque::with_capacity(MAX_ORDER + 1),
+            x_next: F::zero(),
+            f_next: F::zero(),
+            steps_at_order: 0,
+        }
+    }
+
+    /// Returns the current order of the method.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Estimates the local error of the corrector of order `order` over the last attempted step of size `h`, from the
+    /// divided difference of the derivatives at its end point and at the last `order` points.
+    fn order_error(&self, order: usize, h: F) -> F {
+        let mut xs = vec![self.x_next];
+        let mut differences = vec![self.f_next];
+        xs.extend(self.xs.iter().take(order));
+        differences.extend(self.fs.iter().take(order));
+
+        let mut factorial = F::one();
+        for level in 1..=order {
+            for j in 0..=order - level {
+                differences[j] = (differences[j] - differences[j + 1]) / (xs[j] - xs[j + level]);
+            }
+            factorial *= lit(level as f64);
+        }
+        (lit::<F>(AM_ERROR_CONSTANTS[order - 1]) * factorial * differences[0] * h.powi(order as i32 + 1)).abs()
+    }
+}
+
+impl<F: Real> EmbeddedMethod<F> for AdamsBashforthMoulton<F> {
+    fn error_order(&self) -> usize {
+        self.order
+    }
+
+    fn step(&mut self, ode: &dyn ODE<F>, x: F, y: F, h: F) -> (F, F) {
+        if self.xs.front() != Some(&x) {
+            self.xs.clear();
+            self.fs.clear();
+            self.xs.push_front(x);
+            self.fs.push_front(ode.eval(x, y));
+            self.order = 1;
+            self.steps_at_order = 0;
+        }
+
+        // Nodes of the past derivatives, in units of the step from x
+        let k = self.order;
+        let nodes: Vec<F> = self.xs.iter().take(k).map(|&xj| (xj - x) / h).collect();
+
+        let weights = quadrature_weights(&nodes);
+        let mut slope = F::zero();
+        for (w, f) in weights.iter().zip(&self.fs) {
+            slope += *w * *f;
+        }
+        let y_predicted = y + h * slope;
+        let f_predicted = ode.eval(x + h, y_predicted);
+
+        let mut corrector_nodes = vec![F::one()];
+        corrector_nodes.extend_from_slice(&nodes[..k - 1]);
+        let weights = quadrature_weights(&corrector_nodes);
+        let mut slope = weights[0] * f_predicted;
+        for (w, f) in weights[1..].iter().zip(&self.fs) {
+            slope += *w * *f;
+        }
+        let y_corrected = y + h * slope;
+
+        self.x_next = x + h;
+        self.f_next = ode.eval(self.x_next, y_corrected);
+
+        let (c, c_star) = (lit::<F>(AB_ERROR_CONSTANTS[k - 1]), lit::<F>(AM_ERROR_CONSTANTS[k - 1]));
+        (y_corrected, c_star / (c - c_star) * (y_corrected - y_predicted))
+    }
+
+    fn accept(&mut self) {
+        let h = self.x_next - self.xs[0];
+        let k = self.order;
+        self.steps_at_order += 1;
+
+        // Compare the errors the neighbouring orders would have made on this step
+        let error = self.order_error(k, h);
+        let lower = if k > 1 { self.order_error(k - 1, h) } else { F::infinity() };
+        let higher = if k < MAX_ORDER && self.xs.len() > k && self.steps_at_order > k {
+            self.order_error(k + 1, h)
+        } else {
+            F::infinity()
+        };
+        if lower <= error {
+            self.order = k - 1;
+            self.steps_at_order = 0;
+        } else if higher < error {
+            self.order = k + 1;
+            self.steps_at_order = 0;
+        }
+
+        self.xs.push_front(self.x_next);
+        self.fs.push_front(self.f_next);
+        self.xs.truncate(MAX_ORDER + 1);
+        self.fs.truncate(MAX_ORDER + 1);
+    }
+}
+
+impl<F: Real> DenseMethod<F> for AdamsBashforthMoulton<F> {
+    fn interpolate(&self, theta: F, h: F, y_prev: F, y: F) -> F {
+        let (h00, h10, h01, h11) = hermite(theta);
+        h00 * y_prev + h10 * h * self.fs[1] + h01 * y + h11 * h * self.fs[0]
+    }
+}
+
+/// Returns the weights `w_j` such that `sum(w_j * g(t_j))` is the integral over `[0, 1]` of the polynomial interpolating
+/// `g` at the distinct nodes `t_j`.
+fn quadrature_weights<F: Real>(nodes: &[F]) -> Vec<F> {
+    nodes
+        .iter()
+        .enumerate()
+        .map(|(i, &ti)| {
+            // Coefficients of the Lagrange basis polynomial of the node i, by increasing degree
+            let mut basis = vec![F::one()];
+            for (m, &tm) in nodes.iter().enumerate() {
+                if m == i {
+                    continue;
+                }
+                let scale = F::one() / (ti - tm);
+                let mut next = vec![F::zero(); basis.len() + 1];
+                for (d, &b) in basis.iter().enumerate() {
+                    next[d + 1] += b * scale;
+                    next[d] -= b * tm * scale;
+                }
+                basis = next;
+            }
+            basis.iter().enumerate().fold(F::zero(), |sum, (d, &b)| sum + b / lit((d + 1) as f64))
+        })
+        .collect()
+}
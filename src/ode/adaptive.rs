@@ -0,0 +1,360 @@
+//! Adaptive integration of an ODE with embedded Runge-Kutta pairs.
+use super::ODE;
+use crate::{Real, SolverError, SolverOptions, Stats};
+use crate::stats::Counted;
+use crate::controller::{StepController, scaled_error};
+use crate::scalar::to_f64;
+use crate::grid::{check_step_size, signed_step};
+
+/// An embedded Runge-Kutta pair, computing a solution and an estimate of its local error.
+///
+/// It is implemented by the adaptive method types of the submodules, such as
+/// [`bogacki_shampine::BogackiShampine`](super::bogacki_shampine::BogackiShampine), which parametrize an
+/// [`AdaptiveStepper`]. The method may keep evaluations of the ODE between calls, as the methods with the
+/// first-same-as-last property do, since the stepper only moves to another point after [`EmbeddedMethod::accept`].
+pub trait EmbeddedMethod<F: Real> {
+    /// Returns the order of the error estimate, that is the lower order of the pair.
+    fn error_order(&self) -> usize;
+
+    /// Attempts a step of size `h` from `(x, y)`.
+    ///
+    /// # Returns
+    ///
+    /// The propagated solution at `x + h` and the estimate of its local error.
+    fn step(&mut self, ode: &dyn ODE<F>, x: F, y: F, h: F) -> (F, F);
+
+    /// Records that the last attempted step was accepted, so that the next one starts from its end point.
+    fn accept(&mut self) {}
+}
+
+/// An embedded pair providing a continuous extension of its solution over the last accepted step.
+pub trait DenseMethod<F: Real>: EmbeddedMethod<F> {
+    /// Computes the data of the continuous extension of the last accepted step, of size `h` from `y_prev` at `x` to
+    /// `y`, which the method did not compute while stepping.
+    ///
+    /// It is called at most once per accepted step, before the first interpolation in it. The default does nothing.
+    fn prepare(&mut self, _ode: &dyn ODE<F>, _x: F, _h: F, _y_prev: F, _y: F) {}
+
+    /// Returns the solution at `x + theta * h`, for `theta` in `[0, 1]`, of the last accepted step, which went from
+    /// `y_prev` at `x` to `y` at `x + h`.
+    fn interpolate(&self, theta: F, h: F, y_prev: F, y: F) -> F;
+}
+
+/// A stateful integrator advancing an ODE with the embedded pair `M`, adapting the step size to the tolerances.
+///
+/// It is created by the `*_stepper` methods of the adaptive solver traits, for instance
+/// [`BShampineODESolver::bs_stepper`](super::bogacki_shampine::BShampineODESolver::bs_stepper), and keeps the current
+/// state `(x, y)` and the step size proposed for the next step between calls. [`AdaptiveStepper::step`] and the
+/// [`Iterator`] implementation take one adaptive step in the direction given by the sign of the step size, while
+/// [`AdaptiveStepper::advance_to`] integrates up to a given point, shortening the last step so that it lands exactly on it.
+///
+/// A step is accepted when its error estimate is below `atol + rtol * max(|y|, |y_next|)`; otherwise it is rejected
+/// and retried with a smaller step. The next step size is the optimal one scaled by a safety factor of 0.9, and it can
+/// neither shrink nor grow by more than a factor 5 from one step to the next. An attempt whose solution or error
+/// estimate overflows is rejected as well, with the step size divided by 5, and the integration only fails with a
+/// [`SolverError::NonFiniteState`] once the step size has reached its lower bound.
+///
+/// For the methods with a continuous extension, [`AdaptiveStepper::interpolate`] evaluates the solution anywhere in
+/// the last accepted step, usually at no extra cost.
+///
+/// # Example
+///
+/// ```
+/// # use damndiff::SolverOptions;
+/// # use damndiff::ode::{ODESolver, bogacki_shampine::BShampineODESolver};
+/// let solver = ODESolver;
+/// let decay = |_x: f64, y: f64| -y;
+/// let options = SolverOptions::new().rtol(1e-8).atol(1e-10);
+///
+/// let mut stepper = solver.bs_stepper(&decay, 0.0, 1.0, 0.1).with_options(&options);
+/// let y = stepper.advance_to(1.0).unwrap();
+/// assert!((y - (-1.0f64).exp()).abs() < 1e-7);
+/// println!("{} accepted and {} rejected steps", stepper.stats().accepted_steps, stepper.stats().rejected_steps);
+/// ```
+///
+/// A first step far too large for the problem overflows, and is retried with smaller steps:
+///
+/// ```
+/// # use damndiff::ode::{ODESolver, dormand_prince::DormandPrinceODESolver};
+/// let solver = ODESolver;
+/// let quintic = |_x: f64, y: f64| -y.powi(5);
+///
+/// let mut stepper = solver.dopri5_stepper(&quintic, 0.0, 10.0, 1.0);
+/// let y = stepper.advance_to(1.0).unwrap();
+/// assert!((y - (4.0001f64).powf(-0.25)).abs() < 1e-5);
+/// assert!(stepper.stats().rejected_steps > 0);
+/// ```
+pub struct AdaptiveStepper<'a, M, F: Real = f64> {
+    ode: &'a dyn ODE<F>,
+    method: M,
+    x: F,
+    y: F,
+    /// State at the start of the last accepted step.
+    x_prev: F,
+    y_prev: F,
+    /// Whether the continuous extension of the last accepted step has been prepared.
+    dense_ready: bool,
+    h: F,
+    rtol: F,
+    atol: F,
+    h_min: F,
+    h_max: F,
+    /// Maximum number of steps taken by a call to `advance_to`.
+    max_steps: usize,
+    controller: StepController<F>,
+    stats: Stats<F>,
+    failed: bool,
+}
+
+impl<'a, M: EmbeddedMethod<F>, F: Real> AdaptiveStepper<'a, M, F> {
+    /// Creates a stepper at `(x0, y0)` integrating with `method`, with the default settings of [`SolverOptions::new`].
+    pub(crate) fn new(ode: &'a dyn ODE<F>, method: M, x0: F, y0: F, h: F) -> Self {
+        let controller = StepController::new(method.error_order());
+        let stepper = AdaptiveStepper {
+            ode,
+            method,
+            x: x0,
+            y: y0,
+            x_prev: x0,
+            y_prev: y0,
+            dense_ready: false,
+            h,
+            rtol: F::zero(),
+            atol: F::zero(),
+            h_min: F::zero(),
+            h_max: F::infinity(),
+            max_steps: 0,
+            controller,
+            stats: Stats::default(),
+            failed: false,
+        };
+        stepper.with_options(&SolverOptions::new())
+    }
+
+    /// Applies the tolerances, the bounds on the step size and the step cap of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `options` holds more than one absolute tolerance.
+    pub fn with_options(mut self, options: &SolverOptions<F>) -> Self {
+        assert_eq!(options.atol.len(), 1, "{} absolute tolerances given for an ODE", options.atol.len());
+
+        self.rtol = options.rtol;
+        self.atol = options.atol_at(0);
+        self.h_min = options.h_min;
+        self.h_max = options.h_max;
+        self.max_steps = options.max_steps;
+        self.h = self.bounded(self.h);
+        self
+    }
+
+    /// Returns the current value of the independent variable.
+    pub fn x(&self) -> F {
+        self.x
+    }
+
+    /// Returns the current value of the dependent variable.
+    pub fn y(&self) -> F {
+        self.y
+    }
+
+    /// Returns the current state `(x, y)`.
+    pub fn state(&self) -> (F, F) {
+        (self.x, self.y)
+    }
+
+    /// Returns the signed step size proposed for the next step.
+    pub fn h(&self) -> F {
+        self.h
+    }
+
+    /// Returns the statistics of the steps taken so far.
+    pub fn stats(&self) -> Stats<F> {
+        self.stats.clone()
+    }
+
+    /// Returns the method, for the settings specific to it.
+    pub(crate) fn method_mut(&mut self) -> &mut M {
+        &mut self.method
+    }
+
+    /// Returns the interval `(x_prev, x)` covered by the last accepted step, in which [`AdaptiveStepper::interpolate`]
+    /// can evaluate the solution. Both ends are the initial x before the first step.
+    pub fn last_step(&self) -> (F, F) {
+        (self.x_prev, self.x)
+    }
+
+    /// Takes a single adaptive step, retrying it with a smaller step size until its error is small enough.
+    ///
+    /// # Returns
+    ///
+    /// The new state `(x, y)`, or the [`SolverError`] that prevented the step, in which case the state is left unchanged.
+    pub fn step(&mut self) -> Result<(F, F), SolverError> {
+        check_step_size(self.x, self.h)?;
+        self.check_state()?;
+
+        self.take_step(None)?;
+        Ok(self.state())
+    }
+
+    /// Integrates up to `x_target`, which can lie on either side of the current x.
+    ///
+    /// # Returns
+    ///
+    /// The y value at `x_target`, or the [`SolverError`] that stopped the integration, in which case the stepper is
+    /// left at the last accepted point.
+    pub fn advance_to(&mut self, x_target: F) -> Result<F, SolverError> {
+        self.advance_with(x_target, |_, _| {})
+    }
+
+    /// Same as [`AdaptiveStepper::advance_to`], passing every accepted point after the current one to `record`.
+    pub(crate) fn advance_with<R: FnMut(F, F)>(&mut self, x_target: F, mut record: R) -> Result<F, SolverError> {
+        check_step_size(self.x, self.h)?;
+        self.check_state()?;
+
+        self.h = signed_step(self.x, self.h, x_target);
+        let direction = self.h.signum();
+        let mut steps = 0;
+
+        while (x_target - self.x) * direction > F::zero() {
+            if steps == self.max_steps {
+                return Err(SolverError::MaxStepsExceeded { x: to_f64(self.x), max_steps: self.max_steps });
+            }
+
+            self.take_step(Some(x_target))?;
+            steps += 1;
+            record(self.x, self.y);
+        }
+
+        Ok(self.y)
+    }
+
+    fn check_state(&self) -> Result<(), SolverError> {
+        if !self.y.is_finite() {
+            return Err(SolverError::NonFiniteState { x: to_f64(self.x) });
+        }
+        Ok(())
+    }
+
+    /// Takes one accepted step of size at most `h`, rejecting and retrying the attempts whose error is too large.
+    ///
+    /// When `x_target` is given, the step is shortened so that it does not go past it.
+    fn take_step(&mut self, x_target: Option<F>) -> Result<(), SolverError> {
+        loop {
+            // Shorten the last step so that it ends exactly at `x_target`
+            let (h, x_next) = match x_target {
+                Some(x_target) if (self.x + self.h - x_target) * self.h.signum() >= F::zero() => {
+                    (x_target - self.x, x_target)
+                }
+                _ => (self.h, self.x + self.h),
+            };
+            if x_next == self.x {
+                return Err(SolverError::StepSizeUnderflow { x: to_f64(self.x), h: to_f64(h) });
+            }
+
+            let ode = Counted::new(self.ode);
+            let (y_next, error) = self.method.step(&ode, self.x, self.y, h);
+            self.stats.evals += ode.evals();
+            // An overflowing attempt is rejected like an inaccurate one, with the largest reduction of the step size
+            if !y_next.is_finite() || !error.is_finite() {
+                self.stats.rejected_steps += 1;
+                if h.abs() <= self.h_min {
+                    return Err(SolverError::NonFiniteState { x: to_f64(self.x) });
+                }
+                let factor = self.controller.reject();
+                self.h = self.bounded(h * factor);
+                continue;
+            }
+
+            let scale = self.atol + self.rtol * self.y.abs().max(y_next.abs());
+            let error = scaled_error(error.abs(), scale);
+            self.controller.set_order(self.method.error_order());
+            let factor = self.controller.factor(error);
+
+            if StepController::accepts(error) {
+                self.method.accept();
+                self.dense_ready = false;
+                self.x_prev = self.x;
+                self.y_prev = self.y;
+                self.x = x_next;
+                self.y = y_next;
+                self.h = self.bounded(h * factor);
+                self.stats.accepted_steps += 1;
+                self.stats.h_final = h;
+                return Ok(());
+            }
+
+            self.stats.rejected_steps += 1;
+            if h.abs() <= self.h_min {
+                return Err(SolverError::StepSizeUnderflow { x: to_f64(self.x), h: to_f64(h) });
+            }
+            self.h = self.bounded(h * factor);
+        }
+    }
+
+    /// Clamps the magnitude of the step size `h` between `h_min` and `h_max`, keeping its sign.
+    fn bounded(&self, h: F) -> F {
+        h.abs().max(self.h_min).min(self.h_max).copysign(h)
+    }
+}
+
+impl<M: DenseMethod<F>, F: Real> AdaptiveStepper<'_, M, F> {
+    /// Evaluates the continuous extension of the method at `x`, without taking any step.
+    ///
+    /// Some methods, such as the [`ExplicitRk`](super::explicit_rk::ExplicitRk) methods without the first-same-as-last
+    /// property nor a continuous extension of their own, evaluate the ODE once more on the first interpolation in each step.
+    ///
+    /// # Returns
+    ///
+    /// The y value at `x`, or `None` if `x` lies outside the last accepted step, see [`AdaptiveStepper::last_step`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODESolver, bogacki_shampine::BShampineODESolver};
+    /// let solver = ODESolver;
+    /// let decay = |_x: f64, y: f64| -y;
+    /// let mut stepper = solver.bs_stepper(&decay, 0.0, 1.0, 0.1);
+    ///
+    /// stepper.step().unwrap();
+    /// let (x_prev, x) = stepper.last_step();
+    /// let middle = stepper.interpolate((x_prev + x) / 2.0).unwrap();
+    /// assert!((middle - (-(x_prev + x) / 2.0).exp()).abs() < 1e-5);
+    /// assert_eq!(stepper.interpolate(x + 1.0), None);
+    /// ```
+    pub fn interpolate(&mut self, x: F) -> Option<F> {
+        if x == self.x {
+            return Some(self.y);
+        }
+
+        let h = self.x - self.x_prev;
+        let theta = (x - self.x_prev) / h;
+        if h == F::zero() || !(F::zero()..=F::one()).contains(&theta) {
+            return None;
+        }
+
+        if !self.dense_ready {
+            let ode = Counted::new(self.ode);
+            self.method.prepare(&ode, self.x_prev, h, self.y_prev, self.y);
+            self.stats.evals += ode.evals();
+            self.dense_ready = true;
+        }
+
+        Some(self.method.interpolate(theta, h, self.y_prev, self.y))
+    }
+}
+
+impl<M: EmbeddedMethod<F>, F: Real> Iterator for AdaptiveStepper<'_, M, F> {
+    type Item = Result<(F, F), SolverError>;
+
+    /// Takes a single adaptive step, yielding the new state. The iteration ends after the first failed step.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let state = self.step();
+        self.failed = state.is_err();
+        Some(state)
+    }
+}
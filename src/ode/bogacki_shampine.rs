@@ -1,11 +1,17 @@
 //! Bogacki–Shampine method
-use super::{ODE, ODESolver};
+use super::{ODE, ODESolver, solve_with};
+use super::adaptive::AdaptiveStepper;
+use super::explicit_rk::ExplicitRk;
+use crate::{ButcherTableau, Output, Real, Solution, SolverError, SolverOptions};
+use crate::error::expect_solved;
 
 /// Bogacki–Shampine Ordinary Differential Equation (ODE) solver trait.
 ///
 /// This trait defines the [Bogacki–Shampine method](https://en.wikipedia.org/wiki/Bogacki%E2%80%93Shampine_method#:~:text=The%20Bogacki%E2%80%93Shampine%20method%20is%20a%20Runge%E2%80%93Kutta%20method%20of,to%20implement%20adaptive%20step%20size.) for solving initial value problems (IVPs)
-/// of ordinary differential equations (ODEs).
-pub trait BShampineODESolver {
+/// of ordinary differential equations (ODEs). It is the adaptive 3(2) pair of Bogacki and Shampine: the step size `h`
+/// given to the methods is the initial one, which is then adapted so that the local error estimate stays within the
+/// tolerances, see [`AdaptiveStepper`]. Without a [`SolverOptions`], the tolerances are the defaults of [`SolverOptions::new`].
+pub trait BShampineODESolver<F: Real = f64> {
     /// Solve the Initial Value Problem (IVP) for an ODE using the Bogacki–Shampine method.
     ///
     /// # Arguments
@@ -13,16 +19,21 @@ pub trait BShampineODESolver {
     /// * `ode` - The ODE object implementing the `ODE` trait.
     /// * `x0` - The initial x value.
     /// * `y0` - The initial y value (corresponding to the initial x).
-    /// * `h` - The step size or increment for x.
+    /// * `h` - The initial step size.
     /// * `x_target` - The x value where the solution is desired.
     ///
     /// # Returns
     ///
     /// The estimated y value at `x_target`.
     ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`BShampineODESolver::try_bs_ivp`].
+    ///
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, bogacki_shampine::BShampineODESolver};
     /// struct MyODE;
     /// impl ODE for MyODE {
     ///     fn eval(&self, x: f64, y: f64) -> f64 {
@@ -41,34 +52,246 @@ pub trait BShampineODESolver {
     /// let result = solver.bs_ivp(&my_ode, x0, y0, h, x_target);
     /// println!("Solution at x = {}: {}", x_target, result);
     /// ```
-    fn bs_ivp(&self, ode: &dyn ODE, x0: f64, y0: f64, h: f64, x_target: f64) -> f64;
+    fn bs_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> F;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Bogacki–Shampine method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from `x0` up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`BShampineODESolver::try_bs_ivp`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, bogacki_shampine::BShampineODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let solution = solver.bs_ivp_traj(&my_ode, x0, y0, h, x_target);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {}", x, y);
+    /// }
+    /// ```
+    fn bs_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Bogacki–Shampine method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode::{ODE, ODESolver, bogacki_shampine::BShampineODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.try_bs_ivp(&my_ode, x0, y0, h, x_target).unwrap();
+    /// assert!((result - (2.0 * 1.0f64.exp() - 2.0)).abs() < 1e-4);
+    ///
+    /// // The solution of y' = y^2 blows up at x = 1, where the step size collapses
+    /// let error = solver.try_bs_ivp(&|_x: f64, y: f64| y * y, x0, y0, h, 2.0).unwrap_err();
+    /// assert!(matches!(error, SolverError::StepSizeUnderflow { x, .. } if (x - 1.0).abs() < 1e-3));
+    /// ```
+    fn try_bs_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError>;
+
+    /// Create a stepper integrating the Initial Value Problem (IVP) for an ODE with the Bogacki–Shampine method, one step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// An [`AdaptiveStepper`] holding the current state `(x, y)`, starting from `(x0, y0)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, bogacki_shampine::BShampineODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let mut stepper = solver.bs_stepper(&my_ode, 0.0, 1.0, 0.1);
+    ///
+    /// // Integrate one unit of x at a time
+    /// for x in [1.0, 2.0, 3.0] {
+    ///     let y = stepper.advance_to(x).unwrap();
+    ///     assert_eq!(stepper.x(), x);
+    ///     assert!((y - (2.0 * x.exp() - x - 1.0)).abs() < 1e-5 * y);
+    /// }
+    /// ```
+    fn bs_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, h: F) -> AdaptiveStepper<'a, BogackiShampine<F>, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Bogacki–Shampine method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the tolerances, the initial step size `h0` and its bounds.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`BShampineODESolver::try_bs_ivp_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, bogacki_shampine::BShampineODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let options = SolverOptions::new().rtol(1e-8).atol(1e-8).output(Output::Final);
+    ///
+    /// let solution = solver.bs_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options);
+    /// assert_eq!(solution.x, vec![1.0]);
+    /// assert!((solution.y[0] - (2.0 * 1.0f64.exp() - 2.0)).abs() < 1e-6);
+    /// ```
+    fn bs_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Bogacki–Shampine method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the tolerances, the initial step size `h0` and its bounds.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverError, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, bogacki_shampine::BShampineODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// // The solution is requested at x = 0.5 on the way to x = 1
+    /// let options = SolverOptions::new().rtol(1e-8).atol(1e-8).output(Output::Points(vec![0.5]));
+    /// let solution = solver.try_bs_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options).unwrap();
+    /// assert_eq!(solution.x, vec![0.5]);
+    /// assert!((solution.y[0] - (2.0 * 0.5f64.exp() - 1.5)).abs() < 1e-6);
+    ///
+    /// // Five steps are not enough at this tolerance
+    /// let options = SolverOptions::new().rtol(1e-8).atol(1e-8).max_steps(5);
+    /// let error = solver.try_bs_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options).unwrap_err();
+    /// assert!(matches!(error, SolverError::MaxStepsExceeded { max_steps: 5, .. }));
+    /// ```
+    fn try_bs_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError>;
 }
 
 // Implementing the Bogacki–Shampine method for the ODE Solver
-impl BShampineODESolver for ODESolver {
+impl<F: Real> BShampineODESolver<F> for ODESolver {
     /// Implementation of the Bogacki–Shampine method to solve an IVP for an ODE.
     ///
-    /// This method approximates the solution to the ODE at the specified x_target.
+    /// This method approximates the solution to the ODE at the specified x_target. Each step propagates the 3rd-order
+    /// solution and estimates its error with the embedded 2nd-order one. The last stage is evaluated at the end point
+    /// of the step (first same as last), so that it is reused as the first stage of the next step and an accepted step
+    /// costs three evaluations of the ODE. The solution between two accepted points is given by cubic Hermite
+    /// interpolation, see [`AdaptiveStepper::interpolate`].
     ///
     /// # Arguments
     ///
     /// * `ode` - The ODE object implementing the `ODE` trait.
     /// * `x0` - The initial x value.
     /// * `y0` - The initial y value (corresponding to the initial x).
-    /// * `h` - The step size or increment for x.
+    /// * `h` - The initial step size.
     /// * `x_target` - The x value where the solution is desired.
     /// 
     /// # When to Use: 
     /// 
-    /// Well-suited for stiff differential equations and situations requiring adaptive step sizes.
+    /// Well-suited for non-stiff differential equations solved to moderate tolerances, where its low cost per step pays off.
     /// 
     /// # Pros and Cons:
-    /// Pros: Adaptive step-size control, maintains good accuracy with fewer evaluations.
-    /// Cons: Requires more memory and computational effort compared to fixed-step methods.
+    /// Pros: Adaptive step-size control, only three evaluations per step thanks to the first-same-as-last property.
+    /// Cons: Low order, so that tight tolerances require many small steps.
     /// 
     /// # Stability Analysis: 
     /// 
-    /// Stable and efficient for a wide range of problems, especially those with stiffness.
+    /// Conditionally stable: on stiff problems the step size control keeps the steps small enough to stay stable, at a high cost.
     ///
     /// # Returns
     ///
@@ -77,6 +300,7 @@ impl BShampineODESolver for ODESolver {
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, bogacki_shampine::BShampineODESolver};
     /// struct MyODE;
     /// impl ODE for MyODE {
     ///     fn eval(&self, x: f64, y: f64) -> f64 {
@@ -95,25 +319,48 @@ impl BShampineODESolver for ODESolver {
     /// let result = solver.bs_ivp(&my_ode, x0, y0, h, x_target);
     /// println!("Solution at x = {}: {}", x_target, result);
     /// ```
-    fn bs_ivp(&self, ode: &dyn ODE, x0: f64, y0: f64, h: f64, x_target: f64) -> f64 {
-        let mut x = x0;
-        let mut y = y0;
-
-        while x < x_target {
-            let k1 = h * ode.eval(x, y);
-            let k2 = h * ode.eval(x + h / 2.0, y + k1 / 2.0);
-            let k3 = h * ode.eval(x + h * 3.0 / 4.0, y + 3.0 / 4.0 * k2);
+    fn bs_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> F {
+        expect_solved(self.try_bs_ivp(ode, x0, y0, h, x_target))
+    }
 
-            let y_ = y + 2.0 / 3.0 * k1 + 1.0 / 9.0 * k2 + 4.0 / 9.0 * k3;
+    /// Implementation of the Bogacki–Shampine method returning the whole trajectory of the IVP.
+    ///
+    /// See [`BShampineODESolver::bs_ivp`] for the details of the method.
+    fn bs_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F> {
+        expect_solved(solve_with(self.bs_stepper(ode, x0, y0, h), x0, y0, x_target, &Output::Steps))
+    }
 
-            let k4 = h * ode.eval(x, y_);
+    /// Implementation of the Bogacki–Shampine method reporting failures as a [`SolverError`].
+    ///
+    /// See [`BShampineODESolver::bs_ivp`] for the details of the method.
+    fn try_bs_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError> {
+        self.bs_stepper(ode, x0, y0, h).advance_to(x_target)
+    }
 
-            let q = y + 7.0 / 24.0 * k1 + 1.0 / 4.0 * k2 + 1.0 / 3.0 * k3 + 1.0 / 8.0 * k4;
+    /// Implementation of the Bogacki–Shampine method as an [`AdaptiveStepper`].
+    ///
+    /// See [`BShampineODESolver::bs_ivp`] for the details of the method.
+    fn bs_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, h: F) -> AdaptiveStepper<'a, BogackiShampine<F>, F> {
+        AdaptiveStepper::new(ode, ExplicitRk::new(ButcherTableau::bogacki_shampine()), x0, y0, h)
+    }
 
-            y = q;
-            x += h;
-        }
+    /// Implementation of the Bogacki–Shampine method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`BShampineODESolver::bs_ivp`] for the details of the method.
+    fn bs_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F> {
+        expect_solved(self.try_bs_ivp_with(ode, x0, y0, x_target, options))
+    }
 
-        y
+    /// Implementation of the Bogacki–Shampine method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`BShampineODESolver::bs_ivp`] for the details of the method.
+    fn try_bs_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError> {
+        options.check(x0, x_target, 1)?;
+        let stepper = self.bs_stepper(ode, x0, y0, options.initial_step(x0, x_target));
+        solve_with(stepper.with_options(options), x0, y0, x_target, &options.output)
     }
 }
+
+/// The embedded 3(2) pair of Bogacki and Shampine, parametrizing the [`AdaptiveStepper`] of the method: the
+/// [`ExplicitRk`] method of [`ButcherTableau::bogacki_shampine`].
+pub type BogackiShampine<F> = ExplicitRk<F>;
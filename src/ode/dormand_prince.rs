@@ -0,0 +1,479 @@
+//! Dormand–Prince (DOPRI5) method
+use super::{ODE, ODESolver, solve_with};
+use super::adaptive::{AdaptiveStepper, DenseMethod, EmbeddedMethod};
+use crate::{Output, Real, Solution, SolverError, SolverOptions};
+use crate::error::expect_solved;
+use crate::scalar::lit;
+
+/// Dormand–Prince Ordinary Differential Equation (ODE) solver trait.
+///
+/// This trait defines the [Dormand–Prince method](https://en.wikipedia.org/wiki/Dormand%E2%80%93Prince_method) for solving initial value problems (IVPs)
+/// of ordinary differential equations (ODEs). It is the adaptive 5(4) pair of Dormand and Prince: the step size `h`
+/// given to the methods is the initial one, which is then adapted so that the local error estimate stays within the
+/// tolerances, see [`AdaptiveStepper`]. Without a [`SolverOptions`], the tolerances are the defaults of [`SolverOptions::new`].
+pub trait DormandPrinceODESolver<F: Real = f64> {
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Dormand–Prince (DOPRI5) method.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`DormandPrinceODESolver::try_dopri5_ivp`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, dormand_prince::DormandPrinceODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.dopri5_ivp(&my_ode, x0, y0, h, x_target);
+    /// println!("Solution at x = {}: {}", x_target, result);
+    /// ```
+    fn dopri5_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> F;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Dormand–Prince (DOPRI5) method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from `x0` up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`DormandPrinceODESolver::try_dopri5_ivp`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, dormand_prince::DormandPrinceODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let solution = solver.dopri5_ivp_traj(&my_ode, x0, y0, h, x_target);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {}", x, y);
+    /// }
+    /// ```
+    fn dopri5_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Dormand–Prince (DOPRI5) method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, dormand_prince::DormandPrinceODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// match solver.try_dopri5_ivp(&my_ode, x0, y0, h, x_target) {
+    ///     Ok(result) => println!("Solution at x = {}: {}", x_target, result),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_dopri5_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError>;
+
+    /// Create a stepper integrating the Initial Value Problem (IVP) for an ODE with the Dormand–Prince (DOPRI5) method, one step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// An [`AdaptiveStepper`] holding the current state `(x, y)`, starting from `(x0, y0)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, dormand_prince::DormandPrinceODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let mut stepper = solver.dopri5_stepper(&my_ode, 0.0, 1.0, 0.1);
+    ///
+    /// // Integrate one unit of x at a time
+    /// for x in [1.0, 2.0, 3.0] {
+    ///     let y = stepper.advance_to(x).unwrap();
+    ///     println!("Solution at x = {}: {}", x, y);
+    /// }
+    /// ```
+    fn dopri5_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, h: F) -> AdaptiveStepper<'a, Dopri5<F>, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Dormand–Prince (DOPRI5) method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the tolerances, the initial step size `h0` and its bounds.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`DormandPrinceODESolver::try_dopri5_ivp_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, dormand_prince::DormandPrinceODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let options = SolverOptions::new().rtol(1e-8).atol(1e-8).output(Output::Final);
+    ///
+    /// let solution = solver.dopri5_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options);
+    /// println!("Solution at x = 1: {:?}", solution.last());
+    /// ```
+    fn dopri5_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Dormand–Prince (DOPRI5) method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the tolerances, the initial step size `h0` and its bounds.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, dormand_prince::DormandPrinceODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let options = SolverOptions::new().rtol(1e-8).atol(1e-8).output(Output::Final);
+    ///
+    /// match solver.try_dopri5_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options) {
+    ///     Ok(solution) => println!("Solution at x = 1: {:?}", solution.last()),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_dopri5_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError>;
+}
+
+// Implementing the Dormand–Prince (DOPRI5) method for the ODE Solver
+impl<F: Real> DormandPrinceODESolver<F> for ODESolver {
+    /// Implementation of the Dormand–Prince (DOPRI5) method to solve an IVP for an ODE.
+    ///
+    /// This method approximates the solution to the ODE at the specified x_target. Each step propagates the 5th-order
+    /// solution and estimates its error with the embedded 4th-order one. The last stage is evaluated at the end point
+    /// of the step (first same as last), so that it is reused as the first stage of the next step and an accepted step
+    /// costs six evaluations of the ODE. The solution between two accepted points is given by the 4th-order continuous
+    /// extension of Shampine, without further evaluations, see [`AdaptiveStepper::interpolate`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size.
+    /// * `x_target` - The x value where the solution is desired.
+    /// 
+    /// # When to Use: 
+    /// 
+    /// The default choice for non-stiff differential equations, from loose to fairly tight tolerances.
+    /// 
+    /// # Pros and Cons:
+    /// Pros: Adaptive step-size control, high accuracy per evaluation, accurate dense output at no extra cost.
+    /// Cons: Inefficient on stiff problems, and very tight tolerances are better served by higher-order methods.
+    /// 
+    /// # Stability Analysis: 
+    /// 
+    /// Conditionally stable: on stiff problems the step size control keeps the steps small enough to stay stable, at a high cost.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`.
+    /// 
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, dormand_prince::DormandPrinceODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.dopri5_ivp(&my_ode, x0, y0, h, x_target);
+    /// println!("Solution at x = {}: {}", x_target, result);
+    /// ```
+    fn dopri5_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> F {
+        expect_solved(self.try_dopri5_ivp(ode, x0, y0, h, x_target))
+    }
+
+    /// Implementation of the Dormand–Prince (DOPRI5) method returning the whole trajectory of the IVP.
+    ///
+    /// See [`DormandPrinceODESolver::dopri5_ivp`] for the details of the method.
+    fn dopri5_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F> {
+        expect_solved(solve_with(self.dopri5_stepper(ode, x0, y0, h), x0, y0, x_target, &Output::Steps))
+    }
+
+    /// Implementation of the Dormand–Prince (DOPRI5) method reporting failures as a [`SolverError`].
+    ///
+    /// See [`DormandPrinceODESolver::dopri5_ivp`] for the details of the method.
+    fn try_dopri5_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError> {
+        self.dopri5_stepper(ode, x0, y0, h).advance_to(x_target)
+    }
+
+    /// Implementation of the Dormand–Prince (DOPRI5) method as an [`AdaptiveStepper`].
+    ///
+    /// See [`DormandPrinceODESolver::dopri5_ivp`] for the details of the method.
+    fn dopri5_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, h: F) -> AdaptiveStepper<'a, Dopri5<F>, F> {
+        AdaptiveStepper::new(ode, Dopri5::default(), x0, y0, h)
+    }
+
+    /// Implementation of the Dormand–Prince (DOPRI5) method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`DormandPrinceODESolver::dopri5_ivp`] for the details of the method.
+    fn dopri5_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F> {
+        expect_solved(self.try_dopri5_ivp_with(ode, x0, y0, x_target, options))
+    }
+
+    /// Implementation of the Dormand–Prince (DOPRI5) method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`DormandPrinceODESolver::dopri5_ivp`] for the details of the method.
+    fn try_dopri5_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError> {
+        options.check(x0, x_target, 1)?;
+        let stepper = self.dopri5_stepper(ode, x0, y0, options.initial_step(x0, x_target));
+        solve_with(stepper.with_options(options), x0, y0, x_target, &options.output)
+    }
+}
+
+/// The embedded 5(4) pair of Dormand and Prince, parametrizing the [`AdaptiveStepper`] of the method.
+///
+/// It keeps the derivative at the current point, computed as the last stage of the previous step, and the data of the
+/// continuous extension over the last accepted step.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Dopri5<F> {
+    /// Derivative at the current point, once known.
+    f: Option<F>,
+    /// Derivative at the end of the last attempted step.
+    f_next: F,
+    /// Derivative at the start of the last accepted step.
+    f_prev: F,
+    /// Combination of the stages of the last attempted step used by the continuous extension.
+    dense_next: F,
+    /// Combination of the stages of the last accepted step used by the continuous extension.
+    dense: F,
+}
+
+impl<F: Real> EmbeddedMethod<F> for Dopri5<F> {
+    fn error_order(&self) -> usize {
+        4
+    }
+
+    fn step(&mut self, ode: &dyn ODE<F>, x: F, y: F, h: F) -> (F, F) {
+        let k1 = match self.f {
+            Some(f) => f,
+            None => ode.eval(x, y),
+        };
+        self.f = Some(k1);
+
+        let k2 = ode.eval(x + h * lit(C2), y + h * (k1 * lit(A21)));
+        let k3 = ode.eval(x + h * lit(C3), y + h * (k1 * lit(A31) + k2 * lit(A32)));
+        let k4 = ode.eval(x + h * lit(C4), y + h * (k1 * lit(A41) + k2 * lit(A42) + k3 * lit(A43)));
+        let k5 = ode.eval(
+            x + h * lit(C5),
+            y + h * (k1 * lit(A51) + k2 * lit(A52) + k3 * lit(A53) + k4 * lit(A54)),
+        );
+        let k6 = ode.eval(
+            x + h,
+            y + h * (k1 * lit(A61) + k2 * lit(A62) + k3 * lit(A63) + k4 * lit(A64) + k5 * lit(A65)),
+        );
+        let y_next = y + h * (k1 * lit(B1) + k3 * lit(B3) + k4 * lit(B4) + k5 * lit(B5) + k6 * lit(B6));
+        let k7 = ode.eval(x + h, y_next);
+        self.f_next = k7;
+
+        self.dense_next =
+            k1 * lit(D1) + k3 * lit(D3) + k4 * lit(D4) + k5 * lit(D5) + k6 * lit(D6) + k7 * lit(D7);
+        let error = h * (k1 * lit(E1) + k3 * lit(E3) + k4 * lit(E4) + k5 * lit(E5) + k6 * lit(E6) + k7 * lit(E7));
+        (y_next, error)
+    }
+
+    fn accept(&mut self) {
+        self.f_prev = self.f.unwrap_or(self.f_next);
+        self.f = Some(self.f_next);
+        self.dense = self.dense_next;
+    }
+}
+
+impl<F: Real> DenseMethod<F> for Dopri5<F> {
+    fn interpolate(&self, theta: F, h: F, y_prev: F, y: F) -> F {
+        let f = self.f.unwrap_or(self.f_prev);
+        let rcont = continuous_extension(h, y_prev, y, self.f_prev, f, self.dense);
+        evaluate_extension(theta, rcont)
+    }
+}
+
+// Nodes of the Dormand–Prince pair, the last two stages being evaluated at `x + h`.
+pub(crate) const C2: f64 = 1.0 / 5.0;
+pub(crate) const C3: f64 = 3.0 / 10.0;
+pub(crate) const C4: f64 = 4.0 / 5.0;
+pub(crate) const C5: f64 = 8.0 / 9.0;
+
+// Coefficients of the stages of the Dormand–Prince pair.
+pub(crate) const A21: f64 = 1.0 / 5.0;
+pub(crate) const A31: f64 = 3.0 / 40.0;
+pub(crate) const A32: f64 = 9.0 / 40.0;
+pub(crate) const A41: f64 = 44.0 / 45.0;
+pub(crate) const A42: f64 = -56.0 / 15.0;
+pub(crate) const A43: f64 = 32.0 / 9.0;
+pub(crate) const A51: f64 = 19372.0 / 6561.0;
+pub(crate) const A52: f64 = -25360.0 / 2187.0;
+pub(crate) const A53: f64 = 64448.0 / 6561.0;
+pub(crate) const A54: f64 = -212.0 / 729.0;
+pub(crate) const A61: f64 = 9017.0 / 3168.0;
+pub(crate) const A62: f64 = -355.0 / 33.0;
+pub(crate) const A63: f64 = 46732.0 / 5247.0;
+pub(crate) const A64: f64 = 49.0 / 176.0;
+pub(crate) const A65: f64 = -5103.0 / 18656.0;
+
+// Weights of the 5th-order solution, which are also the coefficients of the last stage.
+pub(crate) const B1: f64 = 35.0 / 384.0;
+pub(crate) const B3: f64 = 500.0 / 1113.0;
+pub(crate) const B4: f64 = 125.0 / 192.0;
+pub(crate) const B5: f64 = -2187.0 / 6784.0;
+pub(crate) const B6: f64 = 11.0 / 84.0;
+
+// Differences between the weights of the 5th- and 4th-order solutions.
+pub(crate) const E1: f64 = 71.0 / 57600.0;
+pub(crate) const E3: f64 = -71.0 / 16695.0;
+pub(crate) const E4: f64 = 71.0 / 1920.0;
+pub(crate) const E5: f64 = -17253.0 / 339200.0;
+pub(crate) const E6: f64 = 22.0 / 525.0;
+pub(crate) const E7: f64 = -1.0 / 40.0;
+
+// Weights of the stages in the 4th-order continuous extension of Shampine.
+pub(crate) const D1: f64 = -12715105075.0 / 11282082432.0;
+pub(crate) const D3: f64 = 87487479700.0 / 32700410799.0;
+pub(crate) const D4: f64 = -10690763975.0 / 1880347072.0;
+pub(crate) const D5: f64 = 701980252875.0 / 199316789632.0;
+pub(crate) const D6: f64 = -1453857185.0 / 822651844.0;
+pub(crate) const D7: f64 = 69997945.0 / 29380423.0;
+
+/// Returns the coefficients of the continuous extension of a step of size `h` from `y_prev` to `y`, with derivatives
+/// `f_prev` and `f` at its ends and `dense` the combination of its stages weighted by the `D*` constants.
+pub(crate) fn continuous_extension<F: Real>(h: F, y_prev: F, y: F, f_prev: F, f: F, dense: F) -> [F; 5] {
+    let y_diff = y - y_prev;
+    let bspl = h * f_prev - y_diff;
+    [y_prev, y_diff, bspl, y_diff - h * f - bspl, h * dense]
+}
+
+/// Evaluates at `theta` the continuous extension of coefficients `rcont`.
+pub(crate) fn evaluate_extension<F: Real>(theta: F, rcont: [F; 5]) -> F {
+    let theta1 = F::one() - theta;
+    rcont[0] + theta * (rcont[1] + theta1 * (rcont[2] + theta * (rcont[3] + theta1 * rcont[4])))
+}
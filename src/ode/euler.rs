@@ -1,11 +1,14 @@
 //! Euler method
-use super::{ODE, ODESolver};
+use super::{ODE, ODESolver, fixed_step, fixed_step_traj, solve_with};
+use super::stepper::Stepper;
+use crate::{Real, Solution, SolverError, SolverOptions};
+use crate::error::expect_solved;
 
 /// Euler Ordinary Differential Equation (ODE) solver trait.
 ///
 /// This trait defines the [Euler method](https://en.wikipedia.org/wiki/Euler_method) for solving initial value problems (IVPs)
 /// of ordinary differential equations (ODEs).
-pub trait EulerODESolver {
+pub trait EulerODESolver<F: Real = f64> {
     /// Solve the Initial Value Problem (IVP) for an ODE using the Euler method.
     ///
     /// # Arguments
@@ -20,9 +23,14 @@ pub trait EulerODESolver {
     ///
     /// The estimated y value at `x_target`.
     ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`EulerODESolver::try_eu_ivp`].
+    ///
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, euler::EulerODESolver};
     /// struct MyODE;
     /// impl ODE for MyODE {
     ///     fn eval(&self, x: f64, y: f64) -> f64 {
@@ -41,11 +49,219 @@ pub trait EulerODESolver {
     /// let result = solver.eu_ivp(&my_ode, x0, y0, h, x_target);
     /// println!("Solution at x = {}: {}", x_target, result);
     /// ```
-    fn eu_ivp(&self, ode: &dyn ODE, x0: f64, y0: f64, h: f64, x_target: f64) -> f64;
+    fn eu_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> F;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Euler method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size or increment for x.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from `x0` up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`EulerODESolver::try_eu_ivp`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, euler::EulerODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let solution = solver.eu_ivp_traj(&my_ode, x0, y0, h, x_target);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {}", x, y);
+    /// }
+    /// ```
+    fn eu_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Euler method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size or increment for x.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode::{ODE, ODESolver, euler::EulerODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.try_eu_ivp(&my_ode, x0, y0, h, x_target).unwrap();
+    /// assert!((result - (2.0 * 1.0f64.exp() - 2.0)).abs() < 0.3);
+    ///
+    /// // The solution of y' = y^2 blows up at x = 1
+    /// let error = solver.try_eu_ivp(&|_x: f64, y: f64| y * y, x0, y0, h, 3.0).unwrap_err();
+    /// assert!(matches!(error, SolverError::NonFiniteState { x } if x > 1.0 && x < 3.0));
+    /// ```
+    fn try_eu_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError>;
+
+    /// Create a stepper integrating the Initial Value Problem (IVP) for an ODE with the Euler method, one step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// A [`Stepper`] holding the current state `(x, y)`, starting from `(x0, y0)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, euler::EulerODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let mut stepper = solver.eu_stepper(&my_ode, 0.0, 1.0, 0.1);
+    ///
+    /// // Integrate one unit of x at a time
+    /// for x in [1.0, 2.0, 3.0] {
+    ///     let y = stepper.advance_to(x).unwrap();
+    ///     assert_eq!(stepper.x(), x);
+    ///     assert!((y - (2.0 * x.exp() - x - 1.0)).abs() < 0.2 * y);
+    /// }
+    /// ```
+    fn eu_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, h: F) -> Stepper<'a, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Euler method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`EulerODESolver::try_eu_ivp_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, euler::EulerODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let options = SolverOptions::new().h0(0.1).output(Output::Final);
+    ///
+    /// let solution = solver.eu_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options);
+    /// assert_eq!(solution.x, vec![1.0]);
+    /// assert!((solution.y[0] - (2.0 * 1.0f64.exp() - 2.0)).abs() < 0.3);
+    /// ```
+    fn eu_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Euler method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverError, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, euler::EulerODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// // The solution is requested at x = 0.5 on the way to x = 1
+    /// let options = SolverOptions::new().h0(0.1).output(Output::Points(vec![0.5]));
+    /// let solution = solver.try_eu_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options).unwrap();
+    /// assert_eq!(solution.x, vec![0.5]);
+    /// assert!((solution.y[0] - (2.0 * 0.5f64.exp() - 1.5)).abs() < 0.1);
+    ///
+    /// // Ten steps of 0.1 are needed to reach x = 1
+    /// let options = SolverOptions::new().h0(0.1).max_steps(5);
+    /// let error = solver.try_eu_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options).unwrap_err();
+    /// assert_eq!(error, SolverError::MaxStepsExceeded { x: 0.0, max_steps: 5 });
+    /// ```
+    fn try_eu_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError>;
 }
 
 // Implementing the Euler method for the ODE Solver
-impl EulerODESolver for ODESolver {
+impl<F: Real> EulerODESolver<F> for ODESolver {
     /// Implementation of the Euler method to solve an IVP for an ODE.
     ///
     /// This method approximates the solution to the ODE at the specified x_target.
@@ -77,6 +293,7 @@ impl EulerODESolver for ODESolver {
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, euler::EulerODESolver};
     /// struct MyODE;
     /// impl ODE for MyODE {
     ///     fn eval(&self, x: f64, y: f64) -> f64 {
@@ -95,16 +312,49 @@ impl EulerODESolver for ODESolver {
     /// let result = solver.eu_ivp(&my_ode, x0, y0, h, x_target);
     /// println!("Solution at x = {}: {}", x_target, result);
     /// ```
-    fn eu_ivp(&self, ode: &dyn ODE, x0: f64, y0: f64, h: f64, x_target: f64) -> f64 {
-        let mut x = x0;
-        let mut y = y0;
+    fn eu_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> F {
+        expect_solved(self.try_eu_ivp(ode, x0, y0, h, x_target))
+    }
+
+    /// Implementation of the Euler method returning the whole trajectory of the IVP.
+    ///
+    /// See [`EulerODESolver::eu_ivp`] for the details of the method.
+    fn eu_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F> {
+        expect_solved(fixed_step_traj(ode, x0, y0, h, x_target, euler_step))
+    }
+
+    /// Implementation of the Euler method reporting failures as a [`SolverError`].
+    ///
+    /// See [`EulerODESolver::eu_ivp`] for the details of the method.
+    fn try_eu_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError> {
+        fixed_step(ode, x0, y0, h, x_target, euler_step, |_, _| {})
+    }
+
+    /// Implementation of the Euler method as a [`Stepper`].
+    ///
+    /// See [`EulerODESolver::eu_ivp`] for the details of the method.
+    fn eu_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, h: F) -> Stepper<'a, F> {
+        Stepper::new(ode, euler_step, x0, y0, h)
+    }
 
-        while x < x_target {
-            let slope = ode.eval(x, y);
-            y += h * slope;
-            x += h;
-        }
+    /// Implementation of the Euler method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`EulerODESolver::eu_ivp`] for the details of the method.
+    fn eu_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F> {
+        expect_solved(self.try_eu_ivp_with(ode, x0, y0, x_target, options))
+    }
 
-        y
+    /// Implementation of the Euler method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`EulerODESolver::eu_ivp`] for the details of the method.
+    fn try_eu_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError> {
+        options.check(x0, x_target, 1)?;
+        let stepper = self.eu_stepper(ode, x0, y0, options.initial_step(x0, x_target));
+        solve_with(stepper.with_max_steps(options.max_steps), x0, y0, x_target, &options.output)
     }
 }
+
+/// Performs a single step of the Euler method.
+fn euler_step<F: Real>(ode: &dyn ODE<F>, x: F, y: F, h: F) -> F {
+    y + h * ode.eval(x, y)
+}
@@ -0,0 +1,509 @@
+//! Explicit Runge-Kutta methods given by their Butcher tableau
+use super::{ODE, ODESolver, fixed_step, fixed_step_traj, solve_with};
+use super::stepper::Stepper;
+use super::adaptive::{AdaptiveStepper, DenseMethod, EmbeddedMethod};
+use crate::{ButcherTableau, Output, Real, Solution, SolverError, SolverOptions};
+use crate::error::expect_solved;
+use crate::scalar::lit;
+
+/// Explicit Runge-Kutta Ordinary Differential Equation (ODE) solver trait.
+///
+/// This trait integrates initial value problems (IVPs) of ordinary differential equations (ODEs) with any
+/// [explicit Runge-Kutta method](https://en.wikipedia.org/wiki/Runge%E2%80%93Kutta_methods#Explicit_Runge%E2%80%93Kutta_methods)
+/// described by a [`ButcherTableau`], such as the ones of the crate or a user-defined one. The methods whose tableau
+/// has an embedded solution are adaptive: the step size `h` given to them is the initial one, which is then adapted so
+/// that the local error estimate stays within the tolerances, see [`AdaptiveStepper`]. The other ones take fixed steps
+/// of size `h`, see [`Stepper`].
+pub trait ExplicitRungeKuttaODESolver<F: Real = f64> {
+    /// Solve the Initial Value Problem (IVP) for an ODE using the explicit Runge-Kutta method of `tableau`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `tableau` - The Butcher tableau of the method.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size, or the initial one if the tableau has an embedded solution.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ExplicitRungeKuttaODESolver::try_erk_ivp`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ButcherTableau;
+    /// # use damndiff::ode::{ODE, ODESolver, explicit_rk::ExplicitRungeKuttaODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.erk_ivp(&my_ode, &ButcherTableau::ralston(), x0, y0, h, x_target);
+    /// assert!((result - (2.0 * 1.0f64.exp() - 2.0)).abs() < 1e-2);
+    /// println!("Solution at x = {}: {}", x_target, result);
+    /// ```
+    fn erk_ivp(&self, ode: &dyn ODE<F>, tableau: &ButcherTableau, x0: F, y0: F, h: F, x_target: F) -> F;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the explicit Runge-Kutta method of `tableau`, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `tableau` - The Butcher tableau of the method.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size, or the initial one if the tableau has an embedded solution.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from `(x0, y0)` up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ExplicitRungeKuttaODESolver::try_erk_ivp`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ButcherTableau;
+    /// # use damndiff::ode::{ODESolver, explicit_rk::ExplicitRungeKuttaODESolver};
+    /// let solver = ODESolver;
+    /// // The Cash–Karp pair is adaptive: the accepted points are as far apart as the tolerances allow
+    /// let solution = solver.erk_ivp_traj(&|x: f64, y: f64| x.cos() * y, &ButcherTableau::cash_karp(), 0.0, 1.0, 0.1, 10.0);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {}", x, y);
+    /// }
+    /// ```
+    fn erk_ivp_traj(&self, ode: &dyn ODE<F>, tableau: &ButcherTableau, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the explicit Runge-Kutta method of `tableau`, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `tableau` - The Butcher tableau of the method.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size, or the initial one if the tableau has an embedded solution.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ButcherTableau;
+    /// # use damndiff::ode::{ODESolver, explicit_rk::ExplicitRungeKuttaODESolver};
+    /// let solver = ODESolver;
+    /// // The solution blows up at x = 1
+    /// let blow_up = |_x: f64, y: f64| y * y;
+    ///
+    /// match solver.try_erk_ivp(&blow_up, &ButcherTableau::tsitouras5(), 0.0, 1.0, 0.1, 2.0) {
+    ///     Ok(result) => println!("Solution at x = 2: {}", result),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_erk_ivp(&self, ode: &dyn ODE<F>, tableau: &ButcherTableau, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError>;
+
+    /// Create a stepper integrating the Initial Value Problem (IVP) for an ODE with the explicit Runge-Kutta method of `tableau`, one fixed step at a time.
+    ///
+    /// The embedded solution of the tableau, if any, is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `tableau` - The Butcher tableau of the method.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// A [`Stepper`] holding the current state `(x, y)`, starting from `(x0, y0)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ButcherTableau;
+    /// # use damndiff::ode::{ODESolver, explicit_rk::ExplicitRungeKuttaODESolver};
+    /// let solver = ODESolver;
+    /// // Linear advection of a single Fourier mode, a typical use of SSPRK3
+    /// let mode = |_x: f64, y: f64| -2.0 * y;
+    /// let mut stepper = solver.erk_stepper(&mode, &ButcherTableau::ssprk3(), 0.0, 1.0, 0.05);
+    ///
+    /// for state in stepper.by_ref().take(20) {
+    ///     let (x, y) = state.unwrap();
+    ///     println!("Solution at x = {}: {}", x, y);
+    /// }
+    /// assert!((stepper.y() - (-2.0f64).exp()).abs() < 1e-4);
+    /// ```
+    fn erk_stepper<'a>(&self, ode: &'a dyn ODE<F>, tableau: &ButcherTableau, x0: F, y0: F, h: F) -> Stepper<'a, F>;
+
+    /// Create a stepper integrating the Initial Value Problem (IVP) for an ODE with the embedded pair of `tableau`, one adaptive step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `tableau` - The Butcher tableau of the method, with an embedded solution.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// An [`AdaptiveStepper`] holding the current state `(x, y)` and step size, starting from `(x0, y0)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tableau has no embedded solution.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{ButcherTableau, SolverOptions};
+    /// # use damndiff::ode::{ODESolver, explicit_rk::ExplicitRungeKuttaODESolver};
+    /// let solver = ODESolver;
+    /// let decay = |_x: f64, y: f64| -y;
+    /// let options = SolverOptions::new().rtol(1e-10).atol(1e-10);
+    /// let mut stepper = solver.erk_adaptive_stepper(&decay, &ButcherTableau::tsitouras5(), 0.0, 1.0, 0.1).with_options(&options);
+    ///
+    /// stepper.step().unwrap();
+    /// let (x_prev, x) = stepper.last_step();
+    /// // The continuous extension of the tableau gives the solution inside the step
+    /// let middle = stepper.interpolate((x_prev + x) / 2.0).unwrap();
+    /// assert!((middle - (-(x_prev + x) / 2.0).exp()).abs() < 1e-9);
+    /// ```
+    fn erk_adaptive_stepper<'a>(&self, ode: &'a dyn ODE<F>, tableau: &ButcherTableau, x0: F, y0: F, h: F) -> AdaptiveStepper<'a, ExplicitRk<F>, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the explicit Runge-Kutta method of `tableau`, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `tableau` - The Butcher tableau of the method.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the tolerances apply to the tableaus with an embedded solution,
+    ///   while the other ones take steps of size `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ExplicitRungeKuttaODESolver::try_erk_ivp_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{ButcherTableau, Output, SolverOptions};
+    /// # use damndiff::ode::{ODESolver, explicit_rk::ExplicitRungeKuttaODESolver};
+    /// let solver = ODESolver;
+    /// // Explicit midpoint method with an embedded Euler solution
+    /// let midpoint = ButcherTableau::new(&[0.0, 0.5], &[&[], &[0.5]], &[0.0, 1.0], 2).with_embedded(&[1.0, 0.0], 1);
+    /// let options = SolverOptions::new().rtol(1e-6).atol(1e-9).output(Output::Points(vec![0.5, 1.0]));
+    ///
+    /// let solution = solver.erk_ivp_with(&|_x: f64, y: f64| -y, &midpoint, 0.0, 1.0, 1.0, &options);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {}", x, y);
+    /// }
+    /// ```
+    fn erk_ivp_with(&self, ode: &dyn ODE<F>, tableau: &ButcherTableau, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the explicit Runge-Kutta method of `tableau`, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `tableau` - The Butcher tableau of the method.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the tolerances apply to the tableaus with an embedded solution,
+    ///   while the other ones take steps of size `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{ButcherTableau, Output, SolverOptions};
+    /// # use damndiff::ode::{ODESolver, explicit_rk::ExplicitRungeKuttaODESolver};
+    /// let solver = ODESolver;
+    /// let options = SolverOptions::new().h0(0.1).max_steps(5).output(Output::Final);
+    ///
+    /// match solver.try_erk_ivp_with(&|_x: f64, y: f64| -y, &ButcherTableau::rk4(), 0.0, 1.0, 1.0, &options) {
+    ///     Ok(solution) => println!("Solution at x = 1: {:?}", solution.last()),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_erk_ivp_with(&self, ode: &dyn ODE<F>, tableau: &ButcherTableau, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError>;
+}
+
+// Implementing the explicit Runge-Kutta methods for the ODE Solver
+impl<F: Real> ExplicitRungeKuttaODESolver<F> for ODESolver {
+    /// Implementation of the explicit Runge-Kutta method of a Butcher tableau to solve an IVP for an ODE.
+    ///
+    /// This method approximates the solution to the ODE at the specified x_target. Each step evaluates the stages of the
+    /// tableau one after the other, and combines them with its weights. With an embedded solution, the difference
+    /// between both solutions estimates the local error, and the step is rejected and retried with a smaller size when
+    /// it exceeds the tolerances. When the last stage is evaluated at the end point of the step (first same as last), an
+    /// accepted adaptive step saves one evaluation of the ODE.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `tableau` - The Butcher tableau of the method.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size, or the initial one if the tableau has an embedded solution.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`.
+    ///
+    /// # When to Use:
+    ///
+    /// Experimenting with methods the crate does not name, or picking a tableau suited to the problem, such as SSPRK3
+    /// for discretized hyperbolic equations or Tsitouras 5(4) for non-stiff problems at moderate tolerances.
+    ///
+    /// # Pros and Cons:
+    /// - Pros: Any explicit method at the cost of writing its coefficients, with step size control and dense output when the tableau provides them.
+    /// - Cons: Slightly slower than a method written out by hand, and no check that the coefficients reach the stated order.
+    ///
+    /// # Stability Analysis:
+    ///
+    /// Conditionally stable, with the stability region of the chosen tableau: like every explicit method, it is inefficient on stiff problems.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ButcherTableau;
+    /// # use damndiff::ode::{ODESolver, explicit_rk::ExplicitRungeKuttaODESolver};
+    /// let solver = ODESolver;
+    /// let decay = |_x: f64, y: f64| -y;
+    ///
+    /// let rk4 = solver.erk_ivp(&decay, &ButcherTableau::rk4(), 0.0, 1.0, 0.1, 1.0);
+    /// let tsit5 = solver.erk_ivp(&decay, &ButcherTableau::tsitouras5(), 0.0, 1.0, 0.1, 1.0);
+    /// println!("RK4: {}, Tsit5: {}", rk4, tsit5);
+    /// ```
+    fn erk_ivp(&self, ode: &dyn ODE<F>, tableau: &ButcherTableau, x0: F, y0: F, h: F, x_target: F) -> F {
+        expect_solved(self.try_erk_ivp(ode, tableau, x0, y0, h, x_target))
+    }
+
+    /// Implementation of the explicit Runge-Kutta method of a Butcher tableau returning the whole trajectory of the IVP.
+    ///
+    /// See [`ExplicitRungeKuttaODESolver::erk_ivp`] for the details of the method.
+    fn erk_ivp_traj(&self, ode: &dyn ODE<F>, tableau: &ButcherTableau, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F> {
+        if tableau.embedded_order().is_some() {
+            let stepper = self.erk_adaptive_stepper(ode, tableau, x0, y0, h);
+            expect_solved(solve_with(stepper, x0, y0, x_target, &Output::Steps))
+        } else {
+            expect_solved(fixed_step_traj(ode, x0, y0, h, x_target, fixed_method(tableau)))
+        }
+    }
+
+    /// Implementation of the explicit Runge-Kutta method of a Butcher tableau reporting failures as a [`SolverError`].
+    ///
+    /// See [`ExplicitRungeKuttaODESolver::erk_ivp`] for the details of the method.
+    fn try_erk_ivp(&self, ode: &dyn ODE<F>, tableau: &ButcherTableau, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError> {
+        if tableau.embedded_order().is_some() {
+            self.erk_adaptive_stepper(ode, tableau, x0, y0, h).advance_to(x_target)
+        } else {
+            fixed_step(ode, x0, y0, h, x_target, fixed_method(tableau), |_, _| {})
+        }
+    }
+
+    /// Implementation of the explicit Runge-Kutta method of a Butcher tableau as a [`Stepper`].
+    ///
+    /// See [`ExplicitRungeKuttaODESolver::erk_ivp`] for the details of the method.
+    fn erk_stepper<'a>(&self, ode: &'a dyn ODE<F>, tableau: &ButcherTableau, x0: F, y0: F, h: F) -> Stepper<'a, F> {
+        Stepper::new(ode, fixed_method(tableau), x0, y0, h)
+    }
+
+    /// Implementation of the explicit Runge-Kutta method of a Butcher tableau as an [`AdaptiveStepper`].
+    ///
+    /// See [`ExplicitRungeKuttaODESolver::erk_ivp`] for the details of the method.
+    fn erk_adaptive_stepper<'a>(&self, ode: &'a dyn ODE<F>, tableau: &ButcherTableau, x0: F, y0: F, h: F) -> AdaptiveStepper<'a, ExplicitRk<F>, F> {
+        assert!(tableau.embedded_order().is_some(), "the adaptive steppers need a tableau with an embedded solution");
+        AdaptiveStepper::new(ode, ExplicitRk::new(tableau.clone()), x0, y0, h)
+    }
+
+    /// Implementation of the explicit Runge-Kutta method of a Butcher tableau with the settings of a [`SolverOptions`].
+    ///
+    /// See [`ExplicitRungeKuttaODESolver::erk_ivp`] for the details of the method.
+    fn erk_ivp_with(&self, ode: &dyn ODE<F>, tableau: &ButcherTableau, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F> {
+        expect_solved(self.try_erk_ivp_with(ode, tableau, x0, y0, x_target, options))
+    }
+
+    /// Implementation of the explicit Runge-Kutta method of a Butcher tableau with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`ExplicitRungeKuttaODESolver::erk_ivp`] for the details of the method.
+    fn try_erk_ivp_with(&self, ode: &dyn ODE<F>, tableau: &ButcherTableau, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError> {
+        options.check(x0, x_target, 1)?;
+        let h = options.initial_step(x0, x_target);
+        if tableau.embedded_order().is_some() {
+            let stepper = self.erk_adaptive_stepper(ode, tableau, x0, y0, h);
+            solve_with(stepper.with_options(options), x0, y0, x_target, &options.output)
+        } else {
+            let stepper = self.erk_stepper(ode, tableau, x0, y0, h);
+            solve_with(stepper.with_max_steps(options.max_steps), x0, y0, x_target, &options.output)
+        }
+    }
+}
+
+/// An explicit Runge-Kutta method given by its [`ButcherTableau`], parametrizing the [`AdaptiveStepper`] of the
+/// tableaus with an embedded solution, such as the one of [`bogacki_shampine`](super::bogacki_shampine).
+///
+/// It keeps the derivatives of the stages of the last accepted step for the continuous extension. The methods without
+/// a continuous extension of their own are interpolated with a cubic Hermite polynomial, whose derivative at the end
+/// of the step is the last stage for the methods with the first-same-as-last property.
+#[derive(Debug, Clone)]
+pub struct ExplicitRk<F> {
+    tableau: ButcherTableau,
+    /// Derivatives of the stages of the last attempted step.
+    k: Vec<F>,
+    /// Derivatives of the stages of the last accepted step.
+    k_prev: Vec<F>,
+    /// Whether `k[0]` holds the derivative at the current point.
+    fsal: bool,
+    /// Derivative at the end of the last accepted step, once prepared for the Hermite interpolation.
+    f_end: F,
+}
+
+impl<F: Real> ExplicitRk<F> {
+    /// Creates the method of `tableau`.
+    pub fn new(tableau: ButcherTableau) -> Self {
+        let stages = tableau.stages();
+        ExplicitRk { tableau, k: vec![F::zero(); stages], k_prev: vec![F::zero(); stages], fsal: false, f_end: F::zero() }
+    }
+
+    /// Returns the Butcher tableau of the method.
+    pub fn tableau(&self) -> &ButcherTableau {
+        &self.tableau
+    }
+
+    /// Takes a step of size `h` from `(x, y)` without reusing any derivative, as the fixed-step methods do.
+    pub(crate) fn advance(&mut self, ode: &dyn ODE<F>, x: F, y: F, h: F) -> F {
+        self.fsal = false;
+        self.stages(ode, x, y, h)
+    }
+
+    /// Takes a step of size `h` from `(x, y)`, where the derivative `f` is already known.
+    pub(crate) fn advance_from(&mut self, ode: &dyn ODE<F>, x: F, y: F, f: F, h: F) -> F {
+        self.k[0] = f;
+        self.fsal = true;
+        self.stages(ode, x, y, h)
+    }
+
+    /// Evaluates the stages of a step of size `h` from `(x, y)`, returning the propagated solution.
+    fn stages(&mut self, ode: &dyn ODE<F>, x: F, y: F, h: F) -> F {
+        if !self.fsal {
+            self.k[0] = ode.eval(x, y);
+            self.fsal = true;
+        }
+        for i in 1..self.k.len() {
+            let increment = weighted_sum(&self.tableau.a[i], &self.k);
+            self.k[i] = ode.eval(x + h * lit(self.tableau.c[i]), y + h * increment);
+        }
+        y + h * weighted_sum(&self.tableau.b, &self.k)
+    }
+}
+
+impl<F: Real> EmbeddedMethod<F> for ExplicitRk<F> {
+    fn error_order(&self) -> usize {
+        self.tableau.error_order()
+    }
+
+    fn step(&mut self, ode: &dyn ODE<F>, x: F, y: F, h: F) -> (F, F) {
+        let y_next = self.stages(ode, x, y, h);
+        let e = self.tableau.e.as_ref().expect("the adaptive steppers need a tableau with an embedded solution");
+        (y_next, h * weighted_sum(e, &self.k))
+    }
+
+    fn accept(&mut self) {
+        std::mem::swap(&mut self.k, &mut self.k_prev);
+
+        // The last stage of a first-same-as-last method is the derivative at the new point
+        self.fsal = self.tableau.is_fsal();
+        if self.fsal {
+            self.k[0] = self.k_prev[self.k_prev.len() - 1];
+        }
+    }
+}
+
+impl<F: Real> DenseMethod<F> for ExplicitRk<F> {
+    fn prepare(&mut self, ode: &dyn ODE<F>, x: F, h: F, _y_prev: F, y: F) {
+        if !self.tableau.has_dense_output() && !self.tableau.is_fsal() {
+            self.f_end = ode.eval(x + h, y);
+        }
+    }
+
+    fn interpolate(&self, theta: F, h: F, y_prev: F, y: F) -> F {
+        if self.tableau.has_dense_output() {
+            let mut increment = F::zero();
+            for (i, k) in self.k_prev.iter().enumerate() {
+                increment += self.tableau.dense_weight(i, theta) * *k;
+            }
+            return y_prev + h * increment;
+        }
+
+        let f_end = if self.tableau.is_fsal() { self.k_prev[self.k_prev.len() - 1] } else { self.f_end };
+        let (h00, h10, h01, h11) = hermite(theta);
+        h00 * y_prev + h10 * h * self.k_prev[0] + h01 * y + h11 * h * f_end
+    }
+}
+
+/// Returns the step function of a fixed-step [`Stepper`] integrating with the method of `tableau`.
+pub(crate) fn fixed_method<'a, F: Real>(tableau: &ButcherTableau) -> impl FnMut(&dyn ODE<F>, F, F, F) -> F + 'a {
+    let mut method = ExplicitRk::new(tableau.clone());
+    move |ode, x, y, h| method.advance(ode, x, y, h)
+}
+
+/// Returns `sum(weights[i] * k[i])`, over the weights given.
+fn weighted_sum<F: Real>(weights: &[f64], k: &[F]) -> F {
+    let mut sum = F::zero();
+    for (weight, k) in weights.iter().zip(k) {
+        if *weight != 0.0 {
+            sum += *k * lit(*weight);
+        }
+    }
+    sum
+}
+
+/// Returns the cubic Hermite basis functions at `theta`, weighting the value and the scaled derivative at both ends.
+pub(crate) fn hermite<F: Real>(theta: F) -> (F, F, F, F) {
+    let one = F::one();
+    let two: F = lit(2.0);
+    let three: F = lit(3.0);
+    (
+        (one + two * theta) * (one - theta) * (one - theta),
+        theta * (one - theta) * (one - theta),
+        theta * theta * (three - two * theta),
+        theta * theta * (theta - one),
+    )
+}
@@ -0,0 +1,436 @@
+//! Implicit one-step methods: backward Euler, trapezoidal rule and implicit midpoint rule
+use super::{ODE, ODESolver, fixed_step, fixed_step_traj, partial_derivative, solve_with};
+use super::stepper::Stepper;
+use crate::{Real, Solution, SolverError, SolverOptions};
+use crate::error::expect_solved;
+use crate::scalar::lit;
+
+/// The implicit one-step methods of [`ImplicitODESolver`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImplicitMethod {
+    /// Backward Euler method, `y_{n+1} = y_n + h * f(x_{n+1}, y_{n+1})`, of order 1.
+    BackwardEuler,
+    /// Trapezoidal rule (Crank-Nicolson), `y_{n+1} = y_n + h/2 * (f(x_n, y_n) + f(x_{n+1}, y_{n+1}))`, of order 2.
+    Trapezoidal,
+    /// Implicit midpoint rule, `y_{n+1} = y_n + h * f(x_n + h/2, (y_n + y_{n+1}) / 2)`, of order 2.
+    ImplicitMidpoint,
+}
+
+impl ImplicitMethod {
+    /// Returns the order of the method.
+    pub fn order(&self) -> usize {
+        match self {
+            ImplicitMethod::BackwardEuler => 1,
+            ImplicitMethod::Trapezoidal | ImplicitMethod::ImplicitMidpoint => 2,
+        }
+    }
+}
+
+/// Implicit Ordinary Differential Equation (ODE) solver trait.
+///
+/// This trait defines the [backward Euler method](https://en.wikipedia.org/wiki/Backward_Euler_method), the
+/// [trapezoidal rule](https://en.wikipedia.org/wiki/Trapezoidal_rule_(differential_equations)) and the
+/// [implicit midpoint rule](https://en.wikipedia.org/wiki/Midpoint_method) for solving initial value problems (IVPs) of
+/// stiff ordinary differential equations (ODEs) with a constant step size. The new value of each step is the solution
+/// of an equation, solved by Newton iteration with the derivative [`ODE::dfdy`] when the ODE provides it, and a
+/// difference quotient otherwise. A step whose iteration diverges, or does not converge, is taken as two steps of half
+/// the size, up to ten times in a row.
+pub trait ImplicitODESolver<F: Real = f64> {
+    /// Solve the Initial Value Problem (IVP) for an ODE using an implicit one-step method.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `method` - The implicit method to use.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size or increment for x.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ImplicitODESolver::try_implicit_ivp`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, implicit::{ImplicitMethod, ImplicitODESolver}};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.01;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.implicit_ivp(&my_ode, ImplicitMethod::Trapezoidal, x0, y0, h, x_target);
+    /// assert!((result - (2.0 * 1.0f64.exp() - 2.0)).abs() < 1e-4);
+    /// println!("Solution at x = {}: {}", x_target, result);
+    /// ```
+    fn implicit_ivp(&self, ode: &dyn ODE<F>, method: ImplicitMethod, x0: F, y0: F, h: F, x_target: F) -> F;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using an implicit one-step method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `method` - The implicit method to use.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size or increment for x.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from `x0` up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ImplicitODESolver::try_implicit_ivp`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, implicit::{ImplicitMethod, ImplicitODESolver}};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let solution = solver.implicit_ivp_traj(&my_ode, ImplicitMethod::ImplicitMidpoint, x0, y0, h, x_target);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {}", x, y);
+    /// }
+    /// ```
+    fn implicit_ivp_traj(&self, ode: &dyn ODE<F>, method: ImplicitMethod, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using an implicit one-step method, reporting failures instead of panicking.
+    ///
+    /// The integration fails with [`SolverError::NonFiniteState`] when the Newton iteration of a step still fails
+    /// after ten halvings of the step.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `method` - The implicit method to use.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size or increment for x.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODESolver, implicit::{ImplicitMethod, ImplicitODESolver}};
+    /// let solver = ODESolver;
+    /// // The solution 1 / (1 - x) blows up at x = 1
+    /// let blow_up = |_x: f64, y: f64| y * y;
+    ///
+    /// match solver.try_implicit_ivp(&blow_up, ImplicitMethod::BackwardEuler, 0.0, 1.0, 0.1, 2.0) {
+    ///     Ok(result) => println!("Solution at x = 2: {}", result),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_implicit_ivp(&self, ode: &dyn ODE<F>, method: ImplicitMethod, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError>;
+
+    /// Create a stepper integrating the Initial Value Problem (IVP) for an ODE with an implicit one-step method, one step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `method` - The implicit method to use.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// A [`Stepper`] holding the current state `(x, y)`, starting from `(x0, y0)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODESolver, implicit::{ImplicitMethod, ImplicitODESolver}};
+    /// let solver = ODESolver;
+    /// let stiff = |x: f64, y: f64| -1000.0 * (y - x.cos());
+    /// let mut stepper = solver.implicit_stepper(&stiff, ImplicitMethod::BackwardEuler, 0.0, 0.0, 0.1);
+    ///
+    /// // Integrate one unit of x at a time
+    /// for x in [1.0, 2.0, 3.0] {
+    ///     let y = stepper.advance_to(x).unwrap();
+    ///     println!("Solution at x = {}: {}", x, y);
+    /// }
+    /// ```
+    fn implicit_stepper<'a>(&self, ode: &'a dyn ODE<F>, method: ImplicitMethod, x0: F, y0: F, h: F) -> Stepper<'a, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using an implicit one-step method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `method` - The implicit method to use.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ImplicitODESolver::try_implicit_ivp_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode::{ODESolver, implicit::{ImplicitMethod, ImplicitODESolver}};
+    /// let solver = ODESolver;
+    /// let stiff = |x: f64, y: f64| -1000.0 * (y - x.cos());
+    /// let options = SolverOptions::new().h0(0.05).output(Output::Points(vec![1.0, 2.0]));
+    ///
+    /// let solution = solver.implicit_ivp_with(&stiff, ImplicitMethod::BackwardEuler, 0.0, 0.0, 2.0, &options);
+    /// for (x, y) in solution.iter() {
+    ///     assert!((y - x.cos()).abs() < 1e-3);
+    /// }
+    /// ```
+    fn implicit_ivp_with(&self, ode: &dyn ODE<F>, method: ImplicitMethod, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using an implicit one-step method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `method` - The implicit method to use.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode::{ODESolver, implicit::{ImplicitMethod, ImplicitODESolver}};
+    /// let solver = ODESolver;
+    /// let stiff = |x: f64, y: f64| -1000.0 * (y - x.cos());
+    /// let options = SolverOptions::new().h0(0.05).output(Output::Final);
+    ///
+    /// match solver.try_implicit_ivp_with(&stiff, ImplicitMethod::Trapezoidal, 0.0, 0.0, 2.0, &options) {
+    ///     Ok(solution) => println!("Solution at x = 2: {:?}", solution.last()),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_implicit_ivp_with(&self, ode: &dyn ODE<F>, method: ImplicitMethod, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError>;
+}
+
+// Implementing the implicit one-step methods for the ODE Solver
+impl<F: Real> ImplicitODESolver<F> for ODESolver {
+    /// Implementation of the implicit one-step methods to solve an IVP for an ODE.
+    ///
+    /// This method approximates the solution to the ODE at the specified x_target. Each method reduces its step to an
+    /// equation `z = c + a * f(x', z)`: `z` is the new value for the backward Euler method (`a = h`) and the trapezoidal
+    /// rule (`a = h/2`), and the value at the midpoint of the step for the implicit midpoint rule (`a = h/2`), from which
+    /// the new value is extrapolated. The equation is solved by Newton iteration from the current value, until the
+    /// Newton correction falls to the rounding error. The step is halved when the correction grows from one iteration
+    /// to the next, or when it has not converged after ten iterations.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `method` - The implicit method to use.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size or increment for x.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`.
+    ///
+    /// # When to Use:
+    ///
+    /// Stiff equations, whose fast transients make the explicit methods unstable unless the step size is tiny, solved
+    /// with a constant step size chosen for the accuracy of the slow part of the solution.
+    ///
+    /// # Pros and Cons:
+    /// - Pros: Stability for any step size on decaying problems, and a one-dimensional Newton iteration costing a few
+    ///   evaluations of the ODE per step.
+    /// - Cons: Orders 1 and 2 only, and no error control: the step size sets the accuracy.
+    ///
+    /// # Stability Analysis:
+    ///
+    /// The three methods are A-stable. The backward Euler method is also L-stable and damps the stiff transients in a
+    /// single step, while the trapezoidal and implicit midpoint rules only keep them bounded: with steps much larger
+    /// than the transients, their error oscillates in sign from one step to the next and decays slowly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODESolver, euler::EulerODESolver, implicit::{ImplicitMethod, ImplicitODESolver}};
+    /// let solver = ODESolver;
+    /// let stiff = |x: f64, y: f64| -1000.0 * (y - x.cos());
+    ///
+    /// // The explicit Euler method blows up with a step size of 0.01, the backward Euler method does not
+    /// assert!(solver.try_eu_ivp(&stiff, 0.0, 0.0, 0.01, 1.0).map_or(true, |y| y.abs() > 1e10));
+    /// let result = solver.implicit_ivp(&stiff, ImplicitMethod::BackwardEuler, 0.0, 0.0, 0.01, 1.0);
+    /// assert!((result - 1.0f64.cos()).abs() < 1e-3);
+    ///
+    /// // Halving the step size divides the error of the second-order methods by about 4
+    /// let decay = |x: f64, y: f64| x - y;
+    /// let exact = 2.0 * (-1.0f64).exp();
+    /// for method in [ImplicitMethod::Trapezoidal, ImplicitMethod::ImplicitMidpoint] {
+    ///     let coarse = (solver.implicit_ivp(&decay, method, 0.0, 1.0, 0.02, 1.0) - exact).abs();
+    ///     let fine = (solver.implicit_ivp(&decay, method, 0.0, 1.0, 0.01, 1.0) - exact).abs();
+    ///     assert!(coarse / fine > 3.9);
+    /// }
+    ///
+    /// // The equation of a step of the backward Euler method for y' = y^2 has no solution when h * y > 1/4: such
+    /// // steps are halved until it has one, and the result is as coarse as the halved steps
+    /// let result = solver.implicit_ivp(&|_x: f64, y: f64| y * y, ImplicitMethod::BackwardEuler, 0.0, 1.0, 0.5, 0.5);
+    /// assert!((result - 2.0).abs() < 1.0);
+    /// ```
+    fn implicit_ivp(&self, ode: &dyn ODE<F>, method: ImplicitMethod, x0: F, y0: F, h: F, x_target: F) -> F {
+        expect_solved(self.try_implicit_ivp(ode, method, x0, y0, h, x_target))
+    }
+
+    /// Implementation of the implicit one-step methods returning the whole trajectory of the IVP.
+    ///
+    /// See [`ImplicitODESolver::implicit_ivp`] for the details of the methods.
+    fn implicit_ivp_traj(&self, ode: &dyn ODE<F>, method: ImplicitMethod, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F> {
+        expect_solved(fixed_step_traj(ode, x0, y0, h, x_target, implicit_method(method)))
+    }
+
+    /// Implementation of the implicit one-step methods reporting failures as a [`SolverError`].
+    ///
+    /// See [`ImplicitODESolver::implicit_ivp`] for the details of the methods.
+    fn try_implicit_ivp(&self, ode: &dyn ODE<F>, method: ImplicitMethod, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError> {
+        fixed_step(ode, x0, y0, h, x_target, implicit_method(method), |_, _| {})
+    }
+
+    /// Implementation of the implicit one-step methods as a [`Stepper`].
+    ///
+    /// See [`ImplicitODESolver::implicit_ivp`] for the details of the methods.
+    fn implicit_stepper<'a>(&self, ode: &'a dyn ODE<F>, method: ImplicitMethod, x0: F, y0: F, h: F) -> Stepper<'a, F> {
+        Stepper::new(ode, implicit_method(method), x0, y0, h)
+    }
+
+    /// Implementation of the implicit one-step methods with the settings of a [`SolverOptions`].
+    ///
+    /// See [`ImplicitODESolver::implicit_ivp`] for the details of the methods.
+    fn implicit_ivp_with(&self, ode: &dyn ODE<F>, method: ImplicitMethod, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F> {
+        expect_solved(self.try_implicit_ivp_with(ode, method, x0, y0, x_target, options))
+    }
+
+    /// Implementation of the implicit one-step methods with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`ImplicitODESolver::implicit_ivp`] for the details of the methods.
+    fn try_implicit_ivp_with(&self, ode: &dyn ODE<F>, method: ImplicitMethod, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError> {
+        options.check(x0, x_target, 1)?;
+        let stepper = self.implicit_stepper(ode, method, x0, y0, options.initial_step(x0, x_target));
+        solve_with(stepper.with_max_steps(options.max_steps), x0, y0, x_target, &options.output)
+    }
+}
+
+/// Maximum number of Newton iterations for the equation of a step.
+const MAX_NEWTON_ITERATIONS: usize = 10;
+
+/// Maximum number of times in a row a step is halved when its Newton iteration fails.
+pub(super) const MAX_HALVINGS: usize = 10;
+
+/// Returns the step function of a [`Stepper`] integrating with `method`.
+fn implicit_method<'a, F: Real>(method: ImplicitMethod) -> impl FnMut(&dyn ODE<F>, F, F, F) -> F + 'a {
+    move |ode, x, y, h| advance(ode, method, x, y, h, MAX_HALVINGS)
+}
+
+/// Takes a step of size `h` from `(x, y)` with `method`, as two steps of half the size when its Newton iteration fails,
+/// at most `halvings` times in a row.
+///
+/// # Returns
+///
+/// The value at `x + h`, or NaN if the iteration still fails after the last halving.
+pub(super) fn advance<F: Real>(ode: &dyn ODE<F>, method: ImplicitMethod, x: F, y: F, h: F, halvings: usize) -> F {
+    if let Some(y_next) = step(ode, method, x, y, h) {
+        return y_next;
+    }
+    if halvings == 0 {
+        return F::nan();
+    }
+
+    let h_half = h / lit(2.0);
+    let y_half = advance(ode, method, x, y, h_half, halvings - 1);
+    if !y_half.is_finite() {
+        return y_half;
+    }
+    advance(ode, method, x + h_half, y_half, h - h_half, halvings - 1)
+}
+
+/// Takes a step of size `h` from `(x, y)` with `method`.
+///
+/// # Returns
+///
+/// The value at `x + h`, or `None` if the Newton iteration failed.
+fn step<F: Real>(ode: &dyn ODE<F>, method: ImplicitMethod, x: F, y: F, h: F) -> Option<F> {
+    let h_half = h / lit(2.0);
+    match method {
+        ImplicitMethod::BackwardEuler => newton(ode, x + h, y, h, y),
+        ImplicitMethod::Trapezoidal => newton(ode, x + h, y + h_half * ode.eval(x, y), h_half, y),
+        ImplicitMethod::ImplicitMidpoint => newton(ode, x + h_half, y, h_half, y).map(|z| z + z - y),
+    }
+}
+
+/// Solves `z = c + a * f(x, z)` by Newton iteration starting from `z`.
+///
+/// # Returns
+///
+/// The solution, or `None` if the correction grew from one iteration to the next or did not fall to the rounding error
+/// within [`MAX_NEWTON_ITERATIONS`] iterations.
+pub(super) fn newton<F: Real>(ode: &dyn ODE<F>, x: F, c: F, a: F, mut z: F) -> Option<F> {
+    let mut last_change = F::infinity();
+    for _ in 0..MAX_NEWTON_ITERATIONS {
+        let f = ode.eval(x, z);
+        let correction = (z - c - a * f) / (F::one() - a * partial_derivative(ode, x, z, f));
+        let change = correction.abs();
+        if !change.is_finite() || change >= last_change {
+            return None;
+        }
+
+        z -= correction;
+        if change <= F::epsilon() * lit(100.0) * (F::one() + z.abs()) {
+            return Some(z);
+        }
+        last_change = change;
+    }
+    None
+}
@@ -3,14 +3,242 @@ pub mod rk;
 pub mod rkf;
 pub mod adams_bashforth;
 pub mod adams_moulton;
+pub mod adams_bashforth_moulton;
 pub mod heun;
 pub mod bogacki_shampine;
+pub mod dormand_prince;
+pub mod explicit_rk;
+pub mod implicit;
 pub mod qss;
+pub mod stepper;
+pub mod adaptive;
+
+use crate::{Output, Real, Solution, SolverError, Stats};
+use crate::stats::Counted;
+use qss::Qss1Stepper;
+use stepper::Stepper;
+use adaptive::{AdaptiveStepper, EmbeddedMethod};
 
 /// Trait defining the ODE
-pub trait ODE {
-    fn eval(&self, x: f64, y: f64) -> f64;
+///
+/// The scalar type `F` defaults to `f64` and can be any [`Real`] type.
+/// It is implemented for every closure `Fn(F, F) -> F`, so an equation can be passed to the solvers without declaring a type for it.
+///
+/// # Example
+///
+/// ```
+/// # use damndiff::ode::{ODESolver, rk::RungeKuttaODESolver};
+/// let solver = ODESolver;
+/// let rate = 0.5;
+///
+/// // dy/dx = rate * y, with `rate` captured from the environment
+/// let result = solver.rk4_ivp(&|_x: f64, y: f64| rate * y, 0.0, 1.0, 0.1, 1.0);
+/// assert!((result - 0.5f64.exp()).abs() < 1e-6);
+///
+/// // A named function works the same way, here dy/dx = x with the solution y = x^2 / 2
+/// fn ramp(x: f64, _y: f64) -> f64 {
+///     x
+/// }
+/// assert!((solver.rk4_ivp(&ramp, 0.0, 0.0, 0.1, 1.0) - 0.5).abs() < 1e-12);
+/// ```
+pub trait ODE<F: Real = f64> {
+    fn eval(&self, x: F, y: F) -> F;
+
+    /// Returns the derivative `df/dy` of the right-hand side at a given x and y value.
+    ///
+    /// The implicit methods call it in the Newton iterations solving their equations. The default implementation provides
+    /// no derivative and returns `None`, in which case the methods approximate it by a forward difference of
+    /// [`ODE::eval`], at the cost of one more evaluation of the ODE per iteration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, implicit::{ImplicitMethod, ImplicitODESolver}};
+    /// // Fast relaxation towards cos(x)
+    /// struct Relaxation {
+    ///     rate: f64,
+    /// }
+    ///
+    /// impl ODE for Relaxation {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         -self.rate * (y - x.cos())
+    ///     }
+    ///
+    ///     fn dfdy(&self, _x: f64, _y: f64) -> Option<f64> {
+    ///         Some(-self.rate)
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let stiff = Relaxation { rate: 1000.0 };
+    /// let solution = solver.implicit_ivp_traj(&stiff, ImplicitMethod::BackwardEuler, 0.0, 0.0, 0.1, 1.0);
+    /// // The equation is linear in y: one Newton iteration and a check per step
+    /// assert_eq!(solution.stats.evals, 2 * solution.stats.accepted_steps);
+    /// ```
+    fn dfdy(&self, _x: F, _y: F) -> Option<F> {
+        None
+    }
+}
+
+impl<F, Fun> ODE<F> for Fun
+where
+    F: Real,
+    Fun: Fn(F, F) -> F,
+{
+    fn eval(&self, x: F, y: F) -> F {
+        self(x, y)
+    }
+}
+
+impl<F: Real, T: ODE<F> + ?Sized> ODE<F> for Counted<'_, T> {
+    fn eval(&self, x: F, y: F) -> F {
+        self.count();
+        self.inner.eval(x, y)
+    }
+
+    fn dfdy(&self, x: F, y: F) -> Option<F> {
+        self.inner.dfdy(x, y)
+    }
+}
+
+/// Struct implementing the solver for an ODE. It has various function associated with it, defining the specifict method to use
+///
+/// The step size `h` given to the methods is a magnitude: when `x_target` is less than `x0` the IVP is integrated backwards.
+/// The `try_*` methods report a failed integration as a [`SolverError`], while the other ones panic with its message.
+/// The `*_stepper` methods return a stateful stepper, such as [`Stepper`] or [`AdaptiveStepper`], integrating the IVP
+/// incrementally, while the `*_with` methods take their settings from a [`SolverOptions`](crate::SolverOptions).
+///
+/// # Example
+///
+/// ```
+/// # use damndiff::ode::{ODESolver, rk::RungeKuttaODESolver, rkf::RKFODESolver};
+/// let solver = ODESolver;
+/// let decay = |_x: f64, y: f64| -y;
+///
+/// // A step size of 0.3 does not divide the interval [0, 1]: the last step is shortened to end exactly at x = 1
+/// let solution = solver.rk4_ivp_traj(&decay, 0.0, 1.0, 0.3, 1.0);
+/// assert_eq!(solution.x.len(), 5);
+/// assert_eq!(solution.x[4], 1.0);
+/// assert!((solution.y[4] - (-1.0f64).exp()).abs() < 1e-4);
+///
+/// // The adaptive methods shorten their last step as well
+/// let solution = solver.rkf_ivp_traj(&decay, 0.0, 1.0, 0.3, 1.0);
+/// assert_eq!(*solution.x.last().unwrap(), 1.0);
+/// assert!((solution.y.last().unwrap() - (-1.0f64).exp()).abs() < 1e-5);
+///
+/// // With x_target less than x0 the IVP is integrated backwards, with steps of the same magnitude
+/// let solution = solver.rk4_ivp_traj(&decay, 1.0, (-1.0f64).exp(), 0.3, 0.0);
+/// assert_eq!(solution.x.len(), 5);
+/// assert!(solution.x.windows(2).all(|pair| pair[1] < pair[0]));
+/// assert_eq!(solution.x[4], 0.0);
+/// assert!((solution.y[4] - 1.0).abs() < 1e-4);
+///
+/// let y = solver.try_rkf_ivp(&decay, 1.0, (-1.0f64).exp(), 0.3, 0.0).unwrap();
+/// assert!((y - 1.0).abs() < 1e-5);
+/// ```
+pub struct ODESolver;
+
+/// Advances a one-step method with fixed step size `h` from `(x0, y0)` up to `x_target`, through a [`Stepper`].
+///
+/// `method` maps `(ode, x, y, h)` to the value at `x + h`; every accepted point, including the initial one, is passed to `record`.
+fn fixed_step<'a, F, M, R>(ode: &'a dyn ODE<F>, x0: F, y0: F, h: F, x_target: F, method: M, mut record: R) -> Result<F, SolverError>
+where
+    F: Real,
+    M: FnMut(&dyn ODE<F>, F, F, F) -> F + 'a,
+    R: FnMut(F, F),
+{
+    record(x0, y0);
+    Stepper::new(ode, method, x0, y0, h).advance_with(x_target, record)
+}
+
+/// Same as [`fixed_step`], collecting every accepted point into a [`Solution`].
+fn fixed_step_traj<'a, F, M>(ode: &'a dyn ODE<F>, x0: F, y0: F, h: F, x_target: F, method: M) -> Result<Solution<F, F>, SolverError>
+where
+    F: Real,
+    M: FnMut(&dyn ODE<F>, F, F, F) -> F + 'a,
+{
+    let mut solution = Solution::new();
+    solution.push(x0, y0);
+    let mut stepper = Stepper::new(ode, method, x0, y0, h);
+    stepper.advance_with(x_target, |x, y| solution.push(x, y))?;
+    solution.stats = stepper.stats();
+    Ok(solution)
+}
+
+/// A stepper the `*_with` methods can drive towards the points of an [`Output`].
+trait Advance<F: Real> {
+    /// Integrates up to `x_target`, passing every accepted point after the current one to `record`.
+    fn advance(&mut self, x_target: F, record: &mut dyn FnMut(F, F)) -> Result<F, SolverError>;
+
+    /// Returns the statistics of the steps taken so far.
+    fn stats(&self) -> Stats<F>;
+}
+
+impl<F: Real> Advance<F> for Stepper<'_, F> {
+    fn advance(&mut self, x_target: F, record: &mut dyn FnMut(F, F)) -> Result<F, SolverError> {
+        self.advance_with(x_target, record)
+    }
+
+    fn stats(&self) -> Stats<F> {
+        Stepper::stats(self)
+    }
+}
+
+impl<M: EmbeddedMethod<F>, F: Real> Advance<F> for AdaptiveStepper<'_, M, F> {
+    fn advance(&mut self, x_target: F, record: &mut dyn FnMut(F, F)) -> Result<F, SolverError> {
+        self.advance_with(x_target, record)
+    }
+
+    fn stats(&self) -> Stats<F> {
+        AdaptiveStepper::stats(self)
+    }
 }
 
-/// Struct implementing the solver for an ODE. It has various function associated with it, defining the specifict method to use 
-pub struct ODESolver;
\ No newline at end of file
+impl<F: Real> Advance<F> for Qss1Stepper<'_, F> {
+    fn advance(&mut self, x_target: F, record: &mut dyn FnMut(F, F)) -> Result<F, SolverError> {
+        self.advance_with(x_target, record)
+    }
+
+    fn stats(&self) -> Stats<F> {
+        Qss1Stepper::stats(self)
+    }
+}
+
+/// Returns the derivative `df/dy` of `ode` at `(x, y)`, approximating it by a forward difference when the ODE provides
+/// none, `f` being the derivative at `(x, y)`.
+fn partial_derivative<F: Real>(ode: &dyn ODE<F>, x: F, y: F, f: F) -> F {
+    if let Some(dfdy) = ode.dfdy(x, y) {
+        return dfdy;
+    }
+
+    let y_delta = y + F::epsilon().sqrt() * y.abs().max(F::one());
+    (ode.eval(x, y_delta) - f) / (y_delta - y)
+}
+
+/// Integrates from `(x0, y0)` up to `x_target` with `stepper`, storing the points selected by `output`.
+fn solve_with<F, S>(mut stepper: S, x0: F, y0: F, x_target: F, output: &Output<F>) -> Result<Solution<F, F>, SolverError>
+where
+    F: Real,
+    S: Advance<F>,
+{
+    let mut solution = Solution::new();
+    match output {
+        Output::Steps => {
+            solution.push(x0, y0);
+            stepper.advance(x_target, &mut |x, y| solution.push(x, y))?;
+        }
+        Output::Final => {
+            let y = stepper.advance(x_target, &mut |_, _| {})?;
+            solution.push(x_target, y);
+        }
+        Output::Points(points) => {
+            for &x in points {
+                let y = stepper.advance(x, &mut |_, _| {})?;
+                solution.push(x, y);
+            }
+            stepper.advance(x_target, &mut |_, _| {})?;
+        }
+    }
+    solution.stats = stepper.stats();
+    Ok(solution)
+}
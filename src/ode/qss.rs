@@ -1,11 +1,17 @@
 //! Quantized state systems method (QSS1)
-use super::{ODE, ODESolver};
+use super::{ODE, ODESolver, solve_with};
+use crate::{Output, Real, Solution, SolverError, SolverOptions, Stats};
+use crate::error::expect_solved;
+use crate::scalar::{lit, to_f64};
+use crate::grid::{DEFAULT_MAX_STEPS, check_step_size};
 
 /// QSS1 solver trait.
 ///
 /// This trait defines the [QSS1](https://www.fceia.unr.edu.ar/~kofman/files/scsc_08_cellier.pdf) for solving initial value problems (IVPs)
-/// of ordinary differential equations (ODEs).
-pub trait QSSODESolver {
+/// of ordinary differential equations (ODEs). Instead of discretizing x, the method quantizes y: the `quantum` given to
+/// the methods is the change of the state after which its derivative is evaluated again, and the integration advances
+/// from one such event to the next, see [`Qss1Stepper`].
+pub trait QSSODESolver<F: Real = f64> {
     /// Solve the Initial Value Problem (IVP) for an ODE using the QSS1.
     ///
     /// # Arguments
@@ -13,118 +19,736 @@ pub trait QSSODESolver {
     /// * `ode` - The ODE object implementing the `ODE` trait.
     /// * `x0` - The initial x value.
     /// * `y0` - The initial y value (corresponding to the initial x).
-    /// * `h` - The step size or increment for x.
+    /// * `quantum` - The quantum `ΔQ`, the change of y that triggers a new evaluation of the derivative.
     /// * `x_target` - The x value where the solution is desired.
     ///
     /// # Returns
     ///
     /// The estimated y value at `x_target`.
     ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`QSSODESolver::try_qss1_ivp`].
+    ///
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, qss::QSSODESolver};
     /// struct MyODE;
     /// impl ODE for MyODE {
     ///     fn eval(&self, x: f64, y: f64) -> f64 {
-    ///     u(x) - q(y)
-    ///}
-    /// }
-    /// 
-    /// fn u(t: f64)->f64{
-    ///     if t < 1.0 {
-    ///         return 0.0
-    ///     } else {
-    ///         return 10.0
+    ///         u(x) - y
     ///     }
     /// }
     ///
-    /// fn q(t: f64)->f64{
-    ///     t.trunc() 
+    /// // Input switched on at x = 1
+    /// fn u(x: f64) -> f64 {
+    ///     if x < 1.0 { 0.0 } else { 10.0 }
     /// }
     ///
-    /// let solver = ODESolver; 
+    /// let solver = ODESolver;
     /// let my_ode = MyODE;
     /// let x0 = 0.0;
     /// let y0 = 1.0;
-    /// let h = 0.1;
+    /// let quantum = 0.01;
     /// let x_target = 10.0;
     ///
-    /// let result = solver.qss1_ivp(&my_ode, x0, y0, h, x_target);
+    /// let result = solver.qss1_ivp(&my_ode, x0, y0, quantum, x_target);
+    /// assert!((result - 10.0).abs() < 0.05);
     /// println!("Solution at x = {}: {}", x_target, result);
     /// ```
-    fn qss1_ivp(&self, mod_ode: &dyn ODE, x0: f64, y0: f64, h: f64, x_target: f64) -> f64;
-}
+    fn qss1_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, quantum: F, x_target: F) -> F;
 
-// Implementing QSS1 for the ODE Solver
-impl QSSODESolver for ODESolver {
-    /// Solve the Initial Value Problem (IVP) for an ODE using the QSS1.
+    /// Solve the Initial Value Problem (IVP) for an ODE using the QSS1, keeping the whole trajectory.
     ///
     /// # Arguments
     ///
     /// * `ode` - The ODE object implementing the `ODE` trait.
     /// * `x0` - The initial x value.
     /// * `y0` - The initial y value (corresponding to the initial x).
-    /// * `h` - The step size or increment for x.
+    /// * `quantum` - The quantum `ΔQ`, the change of y that triggers a new evaluation of the derivative.
     /// * `x_target` - The x value where the solution is desired.
     ///
     /// # Returns
     ///
-    /// The estimated y value at `x_target`.
-    /// 
-    /// # When to Use: 
-    /// 
-    /// When the technique is used to solve a stable linear time-invariant (LTI) system, the global error is bounded by a constant that is proportional to the quantum, but independent of the duration of the simulation.
-    /// 
-    /// # Pros and Cons:
-    /// - Pros: Allow for modeling discontinuities in the system due to their discrete-event nature and asynchronous nature, it also has remarkable global stability and error bounds.
-    /// - Cons: Complex.
-    /// 
-    /// # Stability Analysis: 
-    /// 
-    /// When the technique is used to solve a stable linear time-invariant (LTI) system, the global error is bounded by a constant that is proportional to the quantum, but independent of the duration of the simulation.
-    /// 
+    /// A [`Solution`] holding `(x0, y0)`, the point of every internal event and the final point at `x_target`. The
+    /// solution is linear in between.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`QSSODESolver::try_qss1_ivp`].
+    ///
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, qss::QSSODESolver};
     /// struct MyODE;
     /// impl ODE for MyODE {
     ///     fn eval(&self, x: f64, y: f64) -> f64 {
-    ///     u(x) - q(y)
-    ///}
-    /// }
-    /// 
-    /// fn u(t: f64)->f64{
-    ///     if t < 1.0 {
-    ///         return 0.0
-    ///     } else {
-    ///         return 10.0
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
     ///     }
     /// }
     ///
-    /// fn q(t: f64)->f64{
-    ///     t.trunc() 
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let quantum = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// // Consecutive points differ by one quantum, except for the last one
+    /// let solution = solver.qss1_ivp_traj(&my_ode, x0, y0, quantum, x_target);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {}", x, y);
+    /// }
+    /// assert!((solution.y[1] - solution.y[0] - quantum).abs() < 1e-12);
+    /// ```
+    fn qss1_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, quantum: F, x_target: F) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the QSS1, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `quantum` - The quantum `ΔQ`, the change of y that triggers a new evaluation of the derivative.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`, or the [`SolverError`] that stopped the integration, such as a
+    /// [`SolverError::InvalidStepSize`] when the quantum is not positive.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode::{ODE, ODESolver, qss::QSSODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
     /// }
     ///
-    /// let solver = ODESolver; 
+    /// let solver = ODESolver;
     /// let my_ode = MyODE;
     /// let x0 = 0.0;
     /// let y0 = 1.0;
-    /// let h = 0.1;
-    /// let x_target = 10.0;
+    /// let quantum = 0.1;
+    /// let x_target = 1.0;
     ///
-    /// let result = solver.qss1_ivp(&my_ode, x0, y0, h, x_target);
-    /// println!("Solution at x = {}: {}", x_target, result);
+    /// let result = solver.try_qss1_ivp(&my_ode, x0, y0, quantum, x_target).unwrap();
+    /// assert!((result - (2.0 * 1.0f64.exp() - 2.0)).abs() < 0.2);
+    ///
+    /// let error = solver.try_qss1_ivp(&my_ode, x0, y0, 0.0, x_target).unwrap_err();
+    /// assert_eq!(error, SolverError::InvalidStepSize { x: 0.0, h: 0.0 });
+    /// ```
+    fn try_qss1_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, quantum: F, x_target: F) -> Result<F, SolverError>;
+
+    /// Create a stepper integrating the Initial Value Problem (IVP) for an ODE with the QSS1, one event at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `quantum` - The quantum `ΔQ`, the change of y that triggers a new evaluation of the derivative.
+    ///
+    /// # Returns
+    ///
+    /// A [`Qss1Stepper`] holding the current state `(x, y)`, starting from `(x0, y0)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, qss::QSSODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let mut stepper = solver.qss1_stepper(&my_ode, 0.0, 1.0, 0.1);
+    ///
+    /// // Integrate one unit of x at a time
+    /// for x in [1.0, 2.0, 3.0] {
+    ///     let y = stepper.advance_to(x).unwrap();
+    ///     assert_eq!(stepper.x(), x);
+    ///     assert!((y - (2.0 * x.exp() - x - 1.0)).abs() < 0.05 * y);
+    /// }
+    /// // Every internal event is an accepted step
+    /// assert_eq!(stepper.events().len(), stepper.stats().accepted_steps);
+    /// ```
+    fn qss1_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, quantum: F) -> Qss1Stepper<'a, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the QSS1, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the quantum is `max(atol, rtol * |y|)`, and the derivative is
+    ///   evaluated at least every `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, the steps being the internal events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`QSSODESolver::try_qss1_ivp_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, qss::QSSODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let options = SolverOptions::new().rtol(1e-3).atol(1e-3).output(Output::Final);
+    ///
+    /// let solution = solver.qss1_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options);
+    /// assert_eq!(solution.x, vec![1.0]);
+    /// assert!((solution.y[0] - (2.0 * 1.0f64.exp() - 2.0)).abs() < 5e-3);
+    /// ```
+    fn qss1_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the QSS1, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the quantum is `max(atol, rtol * |y|)`, and the derivative is
+    ///   evaluated at least every `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverError, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, qss::QSSODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// // The solution is requested at x = 0.5 on the way to x = 1
+    /// let options = SolverOptions::new().rtol(1e-3).atol(1e-3).output(Output::Points(vec![0.5]));
+    /// let solution = solver.try_qss1_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options).unwrap();
+    /// assert_eq!(solution.x, vec![0.5]);
+    /// assert!((solution.y[0] - (2.0 * 0.5f64.exp() - 1.5)).abs() < 2e-3);
+    ///
+    /// // A hundred internal events are not enough at this quantum
+    /// let options = SolverOptions::new().rtol(1e-3).atol(1e-3).max_steps(100);
+    /// let error = solver.try_qss1_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options).unwrap_err();
+    /// assert!(matches!(error, SolverError::MaxStepsExceeded { max_steps: 100, .. }));
+    /// ```
+    fn try_qss1_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError>;
+}
+
+// Implementing QSS1 for the ODE Solver
+impl<F: Real> QSSODESolver<F> for ODESolver {
+    /// Solve the Initial Value Problem (IVP) for an ODE using the QSS1.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `quantum` - The quantum `ΔQ`, the change of y that triggers a new evaluation of the derivative.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`.
+    ///
+    /// The method integrates `y' = f(x, q)`, where the quantized state `q` follows `y` with hysteresis: it is set to
+    /// `y` whenever `y` leaves the band `[q - ΔQ, q + ΔQ]`, and stays constant otherwise. Between two changes of `q`
+    /// the derivative is constant, so that `y` is linear and the point where it reaches the border of the band, the
+    /// next internal event, is known in advance. The integration is run by a discrete-event scheduler: at each
+    /// internal event `q` is updated, the derivative evaluated again and the next crossing scheduled, one evaluation of
+    /// the ODE being made per event. The hysteresis keeps `q` from switching back and forth when `y` oscillates around
+    /// the border of a quantization level.
+    ///
+    /// # When to Use:
+    ///
+    /// Problems with discontinuities or with long quiet phases, such as switched or event-driven models, where the
+    /// events adapt to the activity of the state rather than to a grid of x.
+    ///
+    /// # Pros and Cons:
+    /// - Pros: Allow for modeling discontinuities in the system due to their discrete-event nature and asynchronous nature, it also has remarkable global stability and error bounds.
+    /// - Cons: Only first-order accurate, so that the number of events grows like the inverse of the quantum, and a
+    ///   derivative depending on x needs time events to be followed between the internal events.
+    ///
+    /// # Stability Analysis:
+    ///
+    /// When the technique is used to solve a stable linear time-invariant (LTI) system, the global error is bounded by a constant that is proportional to the quantum, but independent of the duration of the simulation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODESolver, qss::QSSODESolver};
+    /// let solver = ODESolver;
+    /// let decay = |_x: f64, y: f64| -y;
+    ///
+    /// // The error stays below the quantum over the whole integration
+    /// let quantum = 1e-3;
+    /// let mut stepper = solver.qss1_stepper(&decay, 0.0, 1.0, quantum);
+    /// for x in 1..=20 {
+    ///     let y = stepper.advance_to(x as f64).unwrap();
+    ///     assert!((y - (-x as f64).exp()).abs() < quantum);
+    /// }
+    ///
+    /// // About one evaluation per event, the state having crossed 1000 quantization levels on its way to 0
+    /// assert_eq!(stepper.events().len(), 1000);
+    /// assert!(stepper.stats().evals < stepper.events().len() + 10);
+    /// ```
+    ///
+    /// Outside of this case the error is only proportional to the quantum, the time events keeping a derivative that
+    /// depends on x up to date:
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODESolver, qss::QSSODESolver};
+    /// let solver = ODESolver;
+    /// let system = |x: f64, y: f64| y * x.cos();
+    ///
+    /// let errors: Vec<f64> = [1e-2, 1e-3, 1e-4].iter()
+    ///     .map(|&quantum| (solver.qss1_ivp(&system, 0.0, 1.0, quantum, 10.0) - 10.0f64.sin().exp()).abs())
+    ///     .collect();
+    /// assert!(errors[1] < 0.05 && errors[2] < 0.005);
+    /// ```
+    fn qss1_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, quantum: F, x_target: F) -> F {
+        expect_solved(self.try_qss1_ivp(ode, x0, y0, quantum, x_target))
+    }
+
+    /// Implementation of the QSS1 returning the whole trajectory of the IVP.
+    ///
+    /// See [`QSSODESolver::qss1_ivp`] for the details of the method.
+    fn qss1_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, quantum: F, x_target: F) -> Solution<F, F> {
+        expect_solved(solve_with(self.qss1_stepper(ode, x0, y0, quantum), x0, y0, x_target, &Output::Steps))
+    }
+
+    /// Implementation of the QSS1 reporting failures as a [`SolverError`].
+    ///
+    /// See [`QSSODESolver::qss1_ivp`] for the details of the method.
+    fn try_qss1_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, quantum: F, x_target: F) -> Result<F, SolverError> {
+        self.qss1_stepper(ode, x0, y0, quantum).advance_to(x_target)
+    }
+
+    /// Implementation of the QSS1 as a [`Qss1Stepper`].
+    ///
+    /// See [`QSSODESolver::qss1_ivp`] for the details of the method.
+    fn qss1_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, quantum: F) -> Qss1Stepper<'a, F> {
+        Qss1Stepper::new(ode, x0, y0, quantum)
+    }
+
+    /// Implementation of the QSS1 with the settings of a [`SolverOptions`].
+    ///
+    /// See [`QSSODESolver::qss1_ivp`] for the details of the method.
+    fn qss1_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F> {
+        expect_solved(self.try_qss1_ivp_with(ode, x0, y0, x_target, options))
+    }
+
+    /// Implementation of the QSS1 with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`QSSODESolver::qss1_ivp`] for the details of the method.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode::{ODESolver, qss::QSSODESolver};
+    /// let solver = ODESolver;
+    /// // Input switched on at x = 1, while the state is at rest
+    /// let system = |x: f64, y: f64| if x < 1.0 { -y } else { 10.0 - y };
+    ///
+    /// // Without events, the state would never notice the input: evaluate the derivative at least every 0.01
+    /// let options = SolverOptions::new().rtol(1e-3).atol(1e-3).h_max(0.01).output(Output::Points(vec![1.0, 5.0]));
+    /// let solution = solver.try_qss1_ivp_with(&system, 0.0, 0.0, 5.0, &options).unwrap();
+    /// assert_eq!(solution.y[0], 0.0);
+    /// assert!((solution.y[1] - 10.0 * (1.0 - (-4.0f64).exp())).abs() < 0.05);
+    /// ```
+    fn try_qss1_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError> {
+        options.check(x0, x_target, 1)?;
+        let stepper = self.qss1_stepper(ode, x0, y0, options.atol_at(0)).with_options(options);
+        solve_with(stepper, x0, y0, x_target, &options.output)
+    }
+}
+
+/// The events of the QSS1, in the order in which they are handled when they happen at the same x.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Event {
+    /// Internal event: the state reaches the border of the hysteresis band around the quantized state.
+    Crossing = 0,
+    /// Time event: the derivative is evaluated again, `h_max` after the last evaluation.
+    Update = 1,
+}
+
+/// Discrete-event scheduler of the QSS1, holding the next occurrence of each kind of event.
+///
+/// The events are not scheduled while their x is infinite, which is the case of the internal events when the
+/// derivative is zero.
+#[derive(Debug, Clone)]
+struct Scheduler<F> {
+    times: [F; 2],
+}
+
+impl<F: Real> Scheduler<F> {
+    fn new() -> Self {
+        Scheduler { times: [F::infinity(); 2] }
+    }
+
+    /// Schedules the next occurrence of `event` at `x`, replacing the previous one.
+    fn schedule(&mut self, event: Event, x: F) {
+        self.times[event as usize] = x;
+    }
+
+    /// Returns the next event when moving in the direction of the sign of `direction`, and its x.
+    fn next(&self, direction: F) -> (Event, F) {
+        let (crossing, update) = (self.times[Event::Crossing as usize], self.times[Event::Update as usize]);
+        if (update - crossing) * direction < F::zero() {
+            (Event::Update, update)
+        } else {
+            (Event::Crossing, crossing)
+        }
+    }
+}
+
+/// A stateful integrator advancing an ODE with the QSS1, from one event to the next.
+///
+/// It is created by [`QSSODESolver::qss1_stepper`], and keeps the current state `(x, y)`, the quantized state `q` and
+/// the derivative `f(x, q)` between calls. The state is linear between two events, so that
+/// [`Qss1Stepper::advance_to`] can stop anywhere without taking a step, and without changing the trajectory.
+/// [`Qss1Stepper::step`] and the [`Iterator`] implementation move to the next event, towards increasing x unless the
+/// last call to `advance_to` integrated backwards.
+///
+/// The internal events, where `q` changes, are counted as the accepted steps of the [`Stats`] and listed by
+/// [`Qss1Stepper::events`]. Time events evaluate the derivative again without changing `q`, which lets a derivative
+/// depending on x be followed between internal events. They are scheduled from the rate of change of the derivative
+/// between the last two evaluations, before its drift can move the state by more than one band, and at least every
+/// `h_max` when a bound is set by [`Qss1Stepper::with_options`]. A derivative that is zero at both evaluations shows
+/// no drift, so that a state starting at rest needs `h_max` to notice a change of the derivative with x.
+///
+/// # Example
+///
+/// ```
+/// # use damndiff::ode::{ODESolver, qss::QSSODESolver};
+/// let solver = ODESolver;
+/// let growth = |_x: f64, y: f64| y;
+/// let mut stepper = solver.qss1_stepper(&growth, 0.0, 1.0, 0.5);
+///
+/// // Every event moves the quantized state by one quantum
+/// for event in stepper.by_ref().take(3) {
+///     let (x, y) = event.unwrap();
+///     println!("Event at x = {}: {}", x, y);
+/// }
+/// assert_eq!(stepper.events().iter().map(|&(_, q)| q).collect::<Vec<_>>(), vec![1.5, 2.0, 2.5]);
+/// ```
+pub struct Qss1Stepper<'a, F: Real = f64> {
+    ode: &'a dyn ODE<F>,
+    x: F,
+    y: F,
+    /// Quantized state, and derivative `f(x_update, q)` at the last evaluation.
+    q: F,
+    slope: F,
+    x_update: F,
+    /// Estimated rate of change of the derivative along the trajectory, from the last two evaluations.
+    slope_rate: F,
+    /// Absolute and relative quantum, the width of the hysteresis band being `max(quantum, rtol * |q|)`.
+    quantum: F,
+    rtol: F,
+    /// Maximum distance between two evaluations of the derivative.
+    h_max: F,
+    /// Sign of the direction of integration.
+    direction: F,
+    scheduler: Scheduler<F>,
+    /// Whether the derivative has been evaluated at the initial point.
+    started: bool,
+    /// Point and new quantized state of every internal event.
+    events: Vec<(F, F)>,
+    /// Maximum number of events handled by a call to `advance_to`.
+    max_steps: usize,
+    stats: Stats<F>,
+    failed: bool,
+}
+
+impl<'a, F: Real> Qss1Stepper<'a, F> {
+    /// Creates a stepper at `(x0, y0)` with the quantum `quantum`, handling at most 10 000 000 events per call to `advance_to`.
+    pub(crate) fn new(ode: &'a dyn ODE<F>, x0: F, y0: F, quantum: F) -> Self {
+        Qss1Stepper {
+            ode,
+            x: x0,
+            y: y0,
+            q: y0,
+            slope: F::zero(),
+            x_update: x0,
+            slope_rate: F::zero(),
+            quantum,
+            rtol: F::zero(),
+            h_max: F::infinity(),
+            direction: F::one(),
+            scheduler: Scheduler::new(),
+            started: false,
+            events: Vec::new(),
+            max_steps: DEFAULT_MAX_STEPS,
+            stats: Stats::default(),
+            failed: false,
+        }
+    }
+
+    /// Applies the settings of `options`: the quantum becomes `max(atol, rtol * |q|)`, the derivative is evaluated at
+    /// least every `h_max`, and a call to [`Qss1Stepper::advance_to`] handles at most `max_steps` events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `options` holds more than one absolute tolerance.
+    pub fn with_options(mut self, options: &SolverOptions<F>) -> Self {
+        assert_eq!(options.atol.len(), 1, "{} absolute tolerances given for an ODE", options.atol.len());
+
+        self.quantum = options.atol_at(0);
+        self.rtol = options.rtol;
+        self.h_max = options.h_max;
+        self.max_steps = options.max_steps;
+        if self.started {
+            self.schedule();
+        }
+        self
+    }
+
+    /// Returns the current value of the independent variable.
+    pub fn x(&self) -> F {
+        self.x
+    }
+
+    /// Returns the current value of the dependent variable.
+    pub fn y(&self) -> F {
+        self.y
+    }
+
+    /// Returns the current state `(x, y)`.
+    pub fn state(&self) -> (F, F) {
+        (self.x, self.y)
+    }
+
+    /// Returns the current quantized state, from which the derivative is evaluated.
+    pub fn q(&self) -> F {
+        self.q
+    }
+
+    /// Returns the current width of the hysteresis band, `max(quantum, rtol * |q|)`.
+    pub fn quantum(&self) -> F {
+        self.quantum.max(self.rtol * self.q.abs())
+    }
+
+    /// Returns the statistics of the integration so far, the accepted steps being the internal events.
+    pub fn stats(&self) -> Stats<F> {
+        self.stats.clone()
+    }
+
+    /// Returns the point `x` and the new quantized state `q` of every internal event so far.
+    pub fn events(&self) -> &[(F, F)] {
+        &self.events
+    }
+
+    /// Moves to the next event, internal or time event.
+    ///
+    /// # Returns
+    ///
+    /// The new state `(x, y)`, or the [`SolverError`] that prevented the step, in which case the state is left unchanged.
+    /// When the derivative is zero and no time event is scheduled, no event will ever happen: the state is at rest and
+    /// is returned unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODESolver, qss::QSSODESolver};
+    /// let solver = ODESolver;
+    /// let rest = |_x: f64, y: f64| -y;
+    /// let mut stepper = solver.qss1_stepper(&rest, 0.0, 0.0, 0.1);
+    ///
+    /// assert_eq!(stepper.step().unwrap(), (0.0, 0.0));
+    /// // The iteration ends instead of yielding the same state forever
+    /// assert!(stepper.next().is_none());
     /// ```
-    fn qss1_ivp(&self, mod_ode: &dyn ODE, x0: f64, y0: f64, h: f64, x_target: f64) -> f64 {
-        let mut x = x0;
-        let mut y = y0;
+    pub fn step(&mut self) -> Result<(F, F), SolverError> {
+        self.start()?;
+
+        let (event, x_event) = self.scheduler.next(self.direction);
+        if !x_event.is_finite() {
+            return Ok(self.state());
+        }
+        self.handle(event, x_event)?;
+        Ok(self.state())
+    }
 
-        while x < x_target {
-            let slope = mod_ode.eval(x, y);
-            y += h * slope;
-            x += h;
+    /// Integrates up to `x_target`, which can lie on either side of the current x.
+    ///
+    /// # Returns
+    ///
+    /// The y value at `x_target`, or the [`SolverError`] that stopped the integration, in which case the stepper is
+    /// left at the last event.
+    pub fn advance_to(&mut self, x_target: F) -> Result<F, SolverError> {
+        self.advance_with(x_target, |_, _| {})
+    }
+
+    /// Same as [`Qss1Stepper::advance_to`], passing the point of every event after the current one and the final point
+    /// to `record`.
+    pub(crate) fn advance_with<R: FnMut(F, F)>(&mut self, x_target: F, mut record: R) -> Result<F, SolverError> {
+        if x_target == self.x {
+            return Ok(self.y);
+        }
+        let direction = (x_target - self.x).signum();
+        if direction != self.direction {
+            self.direction = direction;
+            if self.started {
+                self.schedule();
+            }
+        }
+        self.start()?;
+
+        let mut steps = 0;
+        loop {
+            let (event, x_event) = self.scheduler.next(self.direction);
+            if (x_event - x_target) * self.direction > F::zero() {
+                break;
+            }
+            if steps == self.max_steps {
+                return Err(SolverError::MaxStepsExceeded { x: to_f64(self.x), max_steps: self.max_steps });
+            }
+
+            self.handle(event, x_event)?;
+            steps += 1;
+            record(self.x, self.y);
         }
 
-        y
+        // The state is linear up to the next event
+        if self.x != x_target {
+            self.y += self.slope * (x_target - self.x);
+            self.x = x_target;
+            record(self.x, self.y);
+        }
+        Ok(self.y)
+    }
+
+    /// Evaluates the derivative at the initial point and schedules the first events, on the first call.
+    fn start(&mut self) -> Result<(), SolverError> {
+        if self.quantum <= F::zero() {
+            return Err(SolverError::InvalidStepSize { x: to_f64(self.x), h: to_f64(self.quantum) });
+        }
+        check_step_size(self.x, self.quantum)?;
+        if !self.y.is_finite() {
+            return Err(SolverError::NonFiniteState { x: to_f64(self.x) });
+        }
+        if !self.started {
+            self.update()?;
+            self.started = true;
+        }
+        Ok(())
     }
-}
\ No newline at end of file
+
+    /// Moves the state to the event `event` at `x_event` and handles it.
+    fn handle(&mut self, event: Event, x_event: F) -> Result<(), SolverError> {
+        let x_last = self.x;
+        match event {
+            Event::Crossing => {
+                // Land exactly on the border of the band, so that the quantized states stay a whole quantum apart
+                let band = self.quantum();
+                self.q = if self.slope * self.direction > F::zero() { self.q + band } else { self.q - band };
+                self.y = self.q;
+                self.x = x_event;
+                self.events.push((self.x, self.q));
+                self.stats.accepted_steps += 1;
+            }
+            Event::Update => {
+                self.y += self.slope * (x_event - self.x);
+                self.x = x_event;
+            }
+        }
+        self.stats.h_final = self.x - x_last;
+        self.update()
+    }
+
+    /// Evaluates the derivative at the current x and quantized state, and schedules the next events from it.
+    fn update(&mut self) -> Result<(), SolverError> {
+        let slope = self.ode.eval(self.x, self.q);
+        self.stats.evals += 1;
+        if !slope.is_finite() {
+            return Err(SolverError::NonFiniteState { x: to_f64(self.x) });
+        }
+        if self.started && self.x != self.x_update {
+            self.slope_rate = ((slope - self.slope) / (self.x - self.x_update)).abs();
+        }
+        self.slope = slope;
+        self.x_update = self.x;
+        self.schedule();
+        Ok(())
+    }
+
+    /// Schedules the next crossing of the border of the band in the direction of integration, and the next time event.
+    fn schedule(&mut self) {
+        let band = self.quantum();
+        let rate = self.slope * self.direction;
+        let distance = if rate > F::zero() {
+            (self.q + band - self.y) / rate
+        } else if rate < F::zero() {
+            (self.q - band - self.y) / rate
+        } else {
+            F::infinity()
+        };
+        self.scheduler.schedule(Event::Crossing, self.x + self.direction * distance.max(F::zero()));
+
+        // The time events are laid out from the last evaluation, in the direction of integration, close enough for the
+        // drift of the derivative to move the state by at most one band
+        let x_update = if (self.x - self.x_update) * self.direction < F::zero() { self.x } else { self.x_update };
+        let drift = (lit::<F>(2.0) * band / self.slope_rate).sqrt();
+        self.scheduler.schedule(Event::Update, x_update + self.direction * drift.min(self.h_max));
+    }
+}
+
+impl<F: Real> Iterator for Qss1Stepper<'_, F> {
+    type Item = Result<(F, F), SolverError>;
+
+    /// Moves to the next event, yielding the new state. The iteration ends after the first failed step, or when no
+    /// event will ever happen.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        if self.started && !self.scheduler.next(self.direction).1.is_finite() {
+            return None;
+        }
+
+        let state = self.step();
+        self.failed = state.is_err();
+        Some(state)
+    }
+}
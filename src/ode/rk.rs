@@ -1,11 +1,15 @@
 //! Runge-Kutta method
-use super::{ODE, ODESolver};
+use super::{ODE, ODESolver, fixed_step, fixed_step_traj, solve_with};
+use super::stepper::Stepper;
+use super::explicit_rk::fixed_method;
+use crate::{ButcherTableau, Real, Solution, SolverError, SolverOptions};
+use crate::error::expect_solved;
 
 /// Runge-Kutta Ordinary Differential Equation (ODE) solver trait.
 ///
 /// This trait defines the [Runge-Kutta methods](https://en.wikipedia.org/wiki/Runge%E2%80%93Kutta_methods) for solving initial value problems (IVPs)
 /// of ordinary differential equations (ODEs).
-pub trait RungeKuttaODESolver {
+pub trait RungeKuttaODESolver<F: Real = f64> {
     /// Solve the Initial Value Problem (IVP) for an ODE using the fourth-order Runge-Kutta method (RK4).
     ///
     /// # Arguments
@@ -21,9 +25,14 @@ pub trait RungeKuttaODESolver {
     /// The estimated y value at `x_target`.
     /// The estimated y value at `x_target`.
     ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RungeKuttaODESolver::try_rk4_ivp`].
+    ///
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, rk::RungeKuttaODESolver};
     /// struct MyODE;
     /// impl ODE for MyODE {
     ///     fn eval(&self, x: f64, y: f64) -> f64 {
@@ -42,8 +51,215 @@ pub trait RungeKuttaODESolver {
     /// let result = solver.rk4_ivp(&my_ode, x0, y0, h, x_target);
     /// println!("Solution at x = {}: {}", x_target, result);
     /// ```
+    fn rk4_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> F;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the fourth-order Runge-Kutta method (RK4), keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size or increment for x.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from `x0` up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RungeKuttaODESolver::try_rk4_ivp`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, rk::RungeKuttaODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let solution = solver.rk4_ivp_traj(&my_ode, x0, y0, h, x_target);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {}", x, y);
+    /// }
+    /// ```
+    fn rk4_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F>;
 
-    fn rk4_ivp(&self, ode: &dyn ODE, x0: f64, y0: f64, h: f64, x_target: f64) -> f64;
+    /// Solve the Initial Value Problem (IVP) for an ODE using the fourth-order Runge-Kutta method (RK4), reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size or increment for x.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode::{ODE, ODESolver, rk::RungeKuttaODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.try_rk4_ivp(&my_ode, x0, y0, h, x_target).unwrap();
+    /// assert!((result - (2.0 * 1.0f64.exp() - 2.0)).abs() < 1e-5);
+    ///
+    /// // A zero step size never reaches x_target
+    /// let error = solver.try_rk4_ivp(&my_ode, x0, y0, 0.0, x_target).unwrap_err();
+    /// assert_eq!(error, SolverError::InvalidStepSize { x: 0.0, h: 0.0 });
+    /// ```
+    fn try_rk4_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError>;
+
+    /// Create a stepper integrating the Initial Value Problem (IVP) for an ODE with the fourth-order Runge-Kutta method (RK4), one step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// A [`Stepper`] holding the current state `(x, y)`, starting from `(x0, y0)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, rk::RungeKuttaODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let mut stepper = solver.rk4_stepper(&my_ode, 0.0, 1.0, 0.1);
+    ///
+    /// // Integrate one unit of x at a time
+    /// for x in [1.0, 2.0, 3.0] {
+    ///     let y = stepper.advance_to(x).unwrap();
+    ///     assert_eq!(stepper.x(), x);
+    ///     assert!((y - (2.0 * x.exp() - x - 1.0)).abs() < 1e-5 * y);
+    /// }
+    /// ```
+    fn rk4_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, h: F) -> Stepper<'a, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the fourth-order Runge-Kutta method (RK4), with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RungeKuttaODESolver::try_rk4_ivp_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, rk::RungeKuttaODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let options = SolverOptions::new().h0(0.1).output(Output::Final);
+    ///
+    /// let solution = solver.rk4_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options);
+    /// assert_eq!(solution.x, vec![1.0]);
+    /// assert!((solution.y[0] - (2.0 * 1.0f64.exp() - 2.0)).abs() < 1e-5);
+    /// ```
+    fn rk4_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the fourth-order Runge-Kutta method (RK4), with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverError, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, rk::RungeKuttaODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// // The solution is requested at x = 0.5 on the way to x = 1
+    /// let options = SolverOptions::new().h0(0.1).output(Output::Points(vec![0.5]));
+    /// let solution = solver.try_rk4_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options).unwrap();
+    /// assert_eq!(solution.x, vec![0.5]);
+    /// assert!((solution.y[0] - (2.0 * 0.5f64.exp() - 1.5)).abs() < 1e-5);
+    ///
+    /// // Ten steps of 0.1 are needed to reach x = 1
+    /// let options = SolverOptions::new().h0(0.1).max_steps(5);
+    /// let error = solver.try_rk4_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options).unwrap_err();
+    /// assert_eq!(error, SolverError::MaxStepsExceeded { x: 0.0, max_steps: 5 });
+    /// ```
+    fn try_rk4_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError>;
 
     /// Solve the Initial Value Problem (IVP) for an ODE using the second-order Runge-Kutta method (RK2).
     ///
@@ -60,9 +276,14 @@ pub trait RungeKuttaODESolver {
     /// The estimated y value at `x_target`.
         /// The estimated y value at `x_target`.
     ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RungeKuttaODESolver::try_rk2_ivp`].
+    ///
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, rk::RungeKuttaODESolver};
     /// struct MyODE;
     /// impl ODE for MyODE {
     ///     fn eval(&self, x: f64, y: f64) -> f64 {
@@ -81,12 +302,218 @@ pub trait RungeKuttaODESolver {
     /// let result = solver.rk2_ivp(&my_ode, x0, y0, h, x_target);
     /// println!("Solution at x = {}: {}", x_target, result);
     /// ```
+    fn rk2_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> F;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the second-order Runge-Kutta method (RK2), keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size or increment for x.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from `x0` up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RungeKuttaODESolver::try_rk2_ivp`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, rk::RungeKuttaODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let solution = solver.rk2_ivp_traj(&my_ode, x0, y0, h, x_target);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {}", x, y);
+    /// }
+    /// ```
+    fn rk2_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the second-order Runge-Kutta method (RK2), reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size or increment for x.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode::{ODE, ODESolver, rk::RungeKuttaODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.try_rk2_ivp(&my_ode, x0, y0, h, x_target).unwrap();
+    /// assert!((result - (2.0 * 1.0f64.exp() - 2.0)).abs() < 1e-2);
+    ///
+    /// let error = solver.try_rk2_ivp(&my_ode, x0, y0, f64::NAN, x_target).unwrap_err();
+    /// assert!(matches!(error, SolverError::InvalidStepSize { x, h } if x == 0.0 && h.is_nan()));
+    /// ```
+    fn try_rk2_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError>;
 
-    fn rk2_ivp(&self, ode: &dyn ODE, x0: f64, y0: f64, h: f64, x_target: f64) -> f64;
+    /// Create a stepper integrating the Initial Value Problem (IVP) for an ODE with the second-order Runge-Kutta method (RK2), one step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// A [`Stepper`] holding the current state `(x, y)`, starting from `(x0, y0)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, rk::RungeKuttaODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let mut stepper = solver.rk2_stepper(&my_ode, 0.0, 1.0, 0.1);
+    ///
+    /// // Integrate one unit of x at a time
+    /// for x in [1.0, 2.0, 3.0] {
+    ///     let y = stepper.advance_to(x).unwrap();
+    ///     assert_eq!(stepper.x(), x);
+    ///     assert!((y - (2.0 * x.exp() - x - 1.0)).abs() < 1e-2 * y);
+    /// }
+    /// ```
+    fn rk2_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, h: F) -> Stepper<'a, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the second-order Runge-Kutta method (RK2), with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RungeKuttaODESolver::try_rk2_ivp_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, rk::RungeKuttaODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let options = SolverOptions::new().h0(0.1).output(Output::Final);
+    ///
+    /// let solution = solver.rk2_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options);
+    /// assert_eq!(solution.x, vec![1.0]);
+    /// assert!((solution.y[0] - (2.0 * 1.0f64.exp() - 2.0)).abs() < 1e-2);
+    /// ```
+    fn rk2_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the second-order Runge-Kutta method (RK2), with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverError, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, rk::RungeKuttaODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// // The solution is requested at x = 0.5 on the way to x = 1
+    /// let options = SolverOptions::new().h0(0.1).output(Output::Points(vec![0.5]));
+    /// let solution = solver.try_rk2_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options).unwrap();
+    /// assert_eq!(solution.x, vec![0.5]);
+    /// assert!((solution.y[0] - (2.0 * 0.5f64.exp() - 1.5)).abs() < 5e-3);
+    ///
+    /// // Ten steps of 0.1 are needed to reach x = 1
+    /// let options = SolverOptions::new().h0(0.1).max_steps(5);
+    /// let error = solver.try_rk2_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options).unwrap_err();
+    /// assert_eq!(error, SolverError::MaxStepsExceeded { x: 0.0, max_steps: 5 });
+    /// ```
+    fn try_rk2_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError>;
 }
 
 // Implementing the Runge-Kutta methods for the ODE Solver
-impl RungeKuttaODESolver for ODESolver {
+impl<F: Real> RungeKuttaODESolver<F> for ODESolver {
     /// Implementation of the fourth-order Runge-Kutta method (RK4) to solve an IVP for an ODE.
     ///
     /// This method approximates the solution to the ODE at the specified x_target.
@@ -118,6 +545,7 @@ impl RungeKuttaODESolver for ODESolver {
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, rk::RungeKuttaODESolver};
     /// struct MyODE;
     /// impl ODE for MyODE {
     ///     fn eval(&self, x: f64, y: f64) -> f64 {
@@ -136,24 +564,45 @@ impl RungeKuttaODESolver for ODESolver {
     /// let result = solver.rk4_ivp(&my_ode, x0, y0, h, x_target);
     /// println!("Solution at x = {}: {}", x_target, result);
     /// ```
+    fn rk4_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> F {
+        expect_solved(self.try_rk4_ivp(ode, x0, y0, h, x_target))
+    }
 
-    fn rk4_ivp(&self, ode: &dyn ODE, x0: f64, y0: f64, h: f64, x_target: f64) -> f64 {
-        let mut x = x0;
-        let mut y = y0;
+    /// Implementation of the fourth-order Runge-Kutta method (RK4) returning the whole trajectory of the IVP.
+    ///
+    /// See [`RungeKuttaODESolver::rk4_ivp`] for the details of the method.
+    fn rk4_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F> {
+        expect_solved(fixed_step_traj(ode, x0, y0, h, x_target, fixed_method(&ButcherTableau::rk4())))
+    }
 
-        while x < x_target {
-            let k1 = h * ode.eval(x, y);
-            let k2 = h * ode.eval(x + 0.5 * h, y + 0.5 * k1);
-            let k3 = h * ode.eval(x + 0.5 * h, y + 0.5 * k2);
-            let k4 = h * ode.eval(x + h, y + k3);
+    /// Implementation of the fourth-order Runge-Kutta method (RK4) reporting failures as a [`SolverError`].
+    ///
+    /// See [`RungeKuttaODESolver::rk4_ivp`] for the details of the method.
+    fn try_rk4_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError> {
+        fixed_step(ode, x0, y0, h, x_target, fixed_method(&ButcherTableau::rk4()), |_, _| {})
+    }
 
-            let slope = (k1 + 2.0 * k2 + 2.0 * k3 + k4) / 6.0;
+    /// Implementation of the fourth-order Runge-Kutta method (RK4) as a [`Stepper`].
+    ///
+    /// See [`RungeKuttaODESolver::rk4_ivp`] for the details of the method.
+    fn rk4_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, h: F) -> Stepper<'a, F> {
+        Stepper::new(ode, fixed_method(&ButcherTableau::rk4()), x0, y0, h)
+    }
 
-            y += slope;
-            x += h;
-        }
+    /// Implementation of the fourth-order Runge-Kutta method (RK4) with the settings of a [`SolverOptions`].
+    ///
+    /// See [`RungeKuttaODESolver::rk4_ivp`] for the details of the method.
+    fn rk4_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F> {
+        expect_solved(self.try_rk4_ivp_with(ode, x0, y0, x_target, options))
+    }
 
-        y
+    /// Implementation of the fourth-order Runge-Kutta method (RK4) with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`RungeKuttaODESolver::rk4_ivp`] for the details of the method.
+    fn try_rk4_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError> {
+        options.check(x0, x_target, 1)?;
+        let stepper = self.rk4_stepper(ode, x0, y0, options.initial_step(x0, x_target));
+        solve_with(stepper.with_max_steps(options.max_steps), x0, y0, x_target, &options.output)
     }
 
     /// Implementation of the second-order Runge-Kutta method (RK2) to solve an IVP for an ODE.
@@ -187,6 +636,7 @@ impl RungeKuttaODESolver for ODESolver {
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, rk::RungeKuttaODESolver};
     /// struct MyODE;
     /// impl ODE for MyODE {
     ///     fn eval(&self, x: f64, y: f64) -> f64 {
@@ -205,21 +655,44 @@ impl RungeKuttaODESolver for ODESolver {
     /// let result = solver.rk2_ivp(&my_ode, x0, y0, h, x_target);
     /// println!("Solution at x = {}: {}", x_target, result);
     /// ```
+    fn rk2_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> F {
+        expect_solved(self.try_rk2_ivp(ode, x0, y0, h, x_target))
+    }
 
-    fn rk2_ivp(&self, ode: &dyn ODE, x0: f64, y0: f64, h: f64, x_target: f64) -> f64 {
-        let mut x = x0;
-        let mut y = y0;
+    /// Implementation of the second-order Runge-Kutta method (RK2) returning the whole trajectory of the IVP.
+    ///
+    /// See [`RungeKuttaODESolver::rk2_ivp`] for the details of the method.
+    fn rk2_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F> {
+        expect_solved(fixed_step_traj(ode, x0, y0, h, x_target, fixed_method(&ButcherTableau::heun())))
+    }
 
-        while x < x_target {
-            let k1 = h * ode.eval(x, y);
-            let k2 = h * ode.eval(x + h, y + k1);
+    /// Implementation of the second-order Runge-Kutta method (RK2) reporting failures as a [`SolverError`].
+    ///
+    /// See [`RungeKuttaODESolver::rk2_ivp`] for the details of the method.
+    fn try_rk2_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError> {
+        fixed_step(ode, x0, y0, h, x_target, fixed_method(&ButcherTableau::heun()), |_, _| {})
+    }
 
-            let slope = 0.5 * (k1 + k2);
+    /// Implementation of the second-order Runge-Kutta method (RK2) as a [`Stepper`].
+    ///
+    /// See [`RungeKuttaODESolver::rk2_ivp`] for the details of the method.
+    fn rk2_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, h: F) -> Stepper<'a, F> {
+        Stepper::new(ode, fixed_method(&ButcherTableau::heun()), x0, y0, h)
+    }
 
-            y += slope;
-            x += h;
-        }
+    /// Implementation of the second-order Runge-Kutta method (RK2) with the settings of a [`SolverOptions`].
+    ///
+    /// See [`RungeKuttaODESolver::rk2_ivp`] for the details of the method.
+    fn rk2_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F> {
+        expect_solved(self.try_rk2_ivp_with(ode, x0, y0, x_target, options))
+    }
 
-        y
+    /// Implementation of the second-order Runge-Kutta method (RK2) with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`RungeKuttaODESolver::rk2_ivp`] for the details of the method.
+    fn try_rk2_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError> {
+        options.check(x0, x_target, 1)?;
+        let stepper = self.rk2_stepper(ode, x0, y0, options.initial_step(x0, x_target));
+        solve_with(stepper.with_max_steps(options.max_steps), x0, y0, x_target, &options.output)
     }
 }
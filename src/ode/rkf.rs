@@ -1,11 +1,20 @@
 //! Runge-Kutta-Fehlberg method
-use super::{ODE,ODESolver};
+use super::{ODE, ODESolver, solve_with};
+use super::adaptive::{AdaptiveStepper, DenseMethod, EmbeddedMethod};
+use super::explicit_rk::ExplicitRk;
+use crate::{ButcherTableau, Output, Real, Solution, SolverError, SolverOptions};
+use crate::controller::StepController;
+use crate::error::expect_solved;
+use crate::scalar::lit;
+
+/// Local error tolerance used to adapt the step size.
+const TOLERANCE: f64 = 1e-6;
 
 /// Runge-Kutta-Fehlberg Ordinary Differential Equation (ODE) solver trait.
 ///
 /// This trait defines the [Runge-Kutta-Fehlberg (RKF) method](https://en.wikipedia.org/wiki/Runge%E2%80%93Kutta%E2%80%93Fehlberg_method) for solving initial value problems (IVPs)
 /// of ordinary differential equations (ODEs).
-pub trait RKFODESolver {
+pub trait RKFODESolver<F: Real = f64> {
     /// Solve the Initial Value Problem (IVP) for an ODE using the Runge-Kutta-Fehlberg (RKF) method.
     ///
     /// # Arguments
@@ -19,7 +28,221 @@ pub trait RKFODESolver {
     /// # Returns
     ///
     /// The estimated y value at `x_target`.
-    fn rkf_ivp(&self, ode: &dyn ODE, x0: f64, y0: f64, h: f64, x_target: f64) -> f64;
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RKFODESolver::try_rkf_ivp`].
+    fn rkf_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> F;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Runge-Kutta-Fehlberg (RKF) method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from `x0` up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RKFODESolver::try_rkf_ivp`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, rkf::RKFODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let solution = solver.rkf_ivp_traj(&my_ode, x0, y0, h, x_target);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {}", x, y);
+    /// }
+    /// ```
+    fn rkf_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Runge-Kutta-Fehlberg (RKF) method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size.
+    /// * `x_target` - The x value where the solution is desired.
+    ///
+    /// # Returns
+    ///
+    /// The estimated y value at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode::{ODE, ODESolver, rkf::RKFODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let x0 = 0.0;
+    /// let y0 = 1.0;
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.try_rkf_ivp(&my_ode, x0, y0, h, x_target).unwrap();
+    /// assert!((result - (2.0 * 1.0f64.exp() - 2.0)).abs() < 1e-4);
+    ///
+    /// // The solution of y' = y^2 blows up at x = 1, where the step size collapses
+    /// let error = solver.try_rkf_ivp(&|_x: f64, y: f64| y * y, x0, y0, h, 2.0).unwrap_err();
+    /// assert!(matches!(error, SolverError::StepSizeUnderflow { x, .. } if (x - 1.0).abs() < 1e-3));
+    /// ```
+    fn try_rkf_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError>;
+
+    /// Create a stepper integrating the Initial Value Problem (IVP) for an ODE with the Runge-Kutta-Fehlberg (RKF) method, one step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `h` - The initial step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// An [`AdaptiveStepper`] holding the current state `(x, y)` and step size, starting from `(x0, y0)`. Until
+    /// [`AdaptiveStepper::with_options`] is called, it keeps the absolute error of each step below `1e-6`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, rkf::RKFODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let mut stepper = solver.rkf_stepper(&my_ode, 0.0, 1.0, 0.1);
+    ///
+    /// // Take adaptive steps until x = 1 is passed
+    /// while stepper.x() < 1.0 {
+    ///     let x_previous = stepper.x();
+    ///     let (x, y) = stepper.step().unwrap();
+    ///     assert!(x > x_previous);
+    ///     assert!((y - (2.0 * x.exp() - x - 1.0)).abs() < 1e-5 * y);
+    /// }
+    /// ```
+    fn rkf_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, h: F) -> AdaptiveStepper<'a, Rkf45<F>, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Runge-Kutta-Fehlberg (RKF) method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the tolerances and the bounds on the step size drive the adaptive step size control.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RKFODESolver::try_rkf_ivp_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, rkf::RKFODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// let options = SolverOptions::new().rtol(1e-8).atol(1e-10).output(Output::Final);
+    ///
+    /// let solution = solver.rkf_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options);
+    /// assert_eq!(solution.x, vec![1.0]);
+    /// assert!((solution.y[0] - (2.0 * 1.0f64.exp() - 2.0)).abs() < 1e-6);
+    /// ```
+    fn rkf_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F>;
+
+    /// Solve the Initial Value Problem (IVP) for an ODE using the Runge-Kutta-Fehlberg (RKF) method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODE` trait.
+    /// * `x0` - The initial x value.
+    /// * `y0` - The initial y value (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the tolerances and the bounds on the step size drive the adaptive step size control.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverError, SolverOptions};
+    /// # use damndiff::ode::{ODE, ODESolver, rkf::RKFODESolver};
+    /// struct MyODE;
+    /// impl ODE for MyODE {
+    ///     fn eval(&self, x: f64, y: f64) -> f64 {
+    ///         // Define the ODE equation, for instance: dy/dx = x + y
+    ///         x + y
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESolver;
+    /// let my_ode = MyODE;
+    /// // The solution is requested at x = 0.5 on the way to x = 1
+    /// let options = SolverOptions::new().rtol(1e-8).atol(1e-10).output(Output::Points(vec![0.5]));
+    /// let solution = solver.try_rkf_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options).unwrap();
+    /// assert_eq!(solution.x, vec![0.5]);
+    /// assert!((solution.y[0] - (2.0 * 0.5f64.exp() - 1.5)).abs() < 1e-6);
+    ///
+    /// // Five steps are not enough at this tolerance
+    /// let options = SolverOptions::new().rtol(1e-8).atol(1e-10).max_steps(5);
+    /// let error = solver.try_rkf_ivp_with(&my_ode, 0.0, 1.0, 1.0, &options).unwrap_err();
+    /// assert!(matches!(error, SolverError::MaxStepsExceeded { max_steps: 5, .. }));
+    /// ```
+    fn try_rkf_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError>;
 
     /// Perform a single step of the RKF method.
     ///
@@ -34,10 +257,14 @@ pub trait RKFODESolver {
     /// # Returns
     ///
     /// A tuple containing the next estimated `y` value and the new step size `h`.
+    ///
+    /// The step is never rejected, even when its error is above `tolerance`: the steppers created by
+    /// [`RKFODESolver::rkf_stepper`] retry such steps with a smaller step size.
     /// 
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, rkf::RKFODESolver};
     /// struct MyODE;
     /// impl ODE for MyODE {
     ///     fn eval(&self, x: f64, y: f64) -> f64 {
@@ -56,11 +283,10 @@ pub trait RKFODESolver {
     /// let result = solver.rkf_ivp(&my_ode, x0, y0, h, x_target);
     /// println!("Solution at x = {}: {}", x_target, result);
     /// ```
-
-    fn step(ode: &dyn ODE, x: f64, y: f64, h: f64, tolerance: f64) -> (f64, f64);
+    fn step(ode: &dyn ODE<F>, x: F, y: F, h: F, tolerance: F) -> (F, F);
 }
 
-impl RKFODESolver for ODESolver {
+impl<F: Real> RKFODESolver<F> for ODESolver {
     /// Solve the Initial Value Problem (IVP) for an ODE using the Runge-Kutta-Fehlberg (RKF) method.
     ///
     /// # Arguments
@@ -90,6 +316,7 @@ impl RKFODESolver for ODESolver {
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode::{ODE, ODESolver, rkf::RKFODESolver};
     /// struct MyODE;
     /// impl ODE for MyODE {
     ///     fn eval(&self, x: f64, y: f64) -> f64 {
@@ -108,70 +335,136 @@ impl RKFODESolver for ODESolver {
     /// let result = solver.rkf_ivp(&my_ode, x0, y0, h, x_target);
     /// println!("Solution at x = {}: {}", x_target, result);
     /// ```
-    fn rkf_ivp(&self, ode: &dyn ODE, x0: f64, y0: f64, h: f64, x_target: f64) -> f64 {
-        let mut h = h;
-        let tolerance = 1e-6;
-        let mut x = x0;
-        let mut y = y0;
+    fn rkf_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> F {
+        expect_solved(self.try_rkf_ivp(ode, x0, y0, h, x_target))
+    }
 
-        while x < x_target {
-            let (y_next, h_new) = Self::step(ode, x, y, h, tolerance);
-            y = y_next;
-            x += h;
+    /// Implementation of the Runge-Kutta-Fehlberg (RKF) method returning the whole trajectory of the IVP.
+    ///
+    /// See [`RKFODESolver::rkf_ivp`] for the details of the method.
+    fn rkf_ivp_traj(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Solution<F, F> {
+        expect_solved(solve_with(self.rkf_stepper(ode, x0, y0, h), x0, y0, x_target, &Output::Steps))
+    }
+
+    /// Implementation of the Runge-Kutta-Fehlberg (RKF) method reporting failures as a [`SolverError`].
+    ///
+    /// See [`RKFODESolver::rkf_ivp`] for the details of the method.
+    fn try_rkf_ivp(&self, ode: &dyn ODE<F>, x0: F, y0: F, h: F, x_target: F) -> Result<F, SolverError> {
+        self.rkf_stepper(ode, x0, y0, h).advance_to(x_target)
+    }
+
+    /// Implementation of the Runge-Kutta-Fehlberg (RKF) method as an [`AdaptiveStepper`].
+    ///
+    /// See [`RKFODESolver::rkf_ivp`] for the details of the method.
+    fn rkf_stepper<'a>(&self, ode: &'a dyn ODE<F>, x0: F, y0: F, h: F) -> AdaptiveStepper<'a, Rkf45<F>, F> {
+        let options = SolverOptions::new().rtol(F::zero()).atol(lit(TOLERANCE));
+        AdaptiveStepper::new(ode, Rkf45::new(Propagation::Fourth), x0, y0, h).with_options(&options)
+    }
+
+    /// Implementation of the Runge-Kutta-Fehlberg (RKF) method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`RKFODESolver::rkf_ivp`] for the details of the method.
+    fn rkf_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Solution<F, F> {
+        expect_solved(self.try_rkf_ivp_with(ode, x0, y0, x_target, options))
+    }
+
+    /// Implementation of the Runge-Kutta-Fehlberg (RKF) method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`RKFODESolver::rkf_ivp`] for the details of the method.
+    fn try_rkf_ivp_with(&self, ode: &dyn ODE<F>, x0: F, y0: F, x_target: F, options: &SolverOptions<F>) -> Result<Solution<F, F>, SolverError> {
+        options.check(x0, x_target, 1)?;
+        let stepper = self.rkf_stepper(ode, x0, y0, options.initial_step(x0, x_target));
+        solve_with(stepper.with_options(options), x0, y0, x_target, &options.output)
+    }
+
+    fn step(ode: &dyn ODE<F>, x: F, y: F, h: F, tolerance: F) -> (F, F) {
+        let (y4, error) = Rkf45::new(Propagation::Fourth).step(ode, x, y, h);
+        (y4, h * StepController::new(4).factor(error.abs() / tolerance))
+    }
+}
+
+/// The solution of the embedded RKF45 pair propagated from one step to the next.
+///
+/// Both solutions are computed at every step and their difference estimates the local error, which drives the step
+/// size control in either mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Propagation {
+    /// Propagate the 4th-order solution, whose local error is the one estimated (the classic Fehlberg method).
+    #[default]
+    Fourth,
+    /// Propagate the 5th-order solution (local extrapolation), more accurate for the same step size.
+    Fifth,
+}
 
-            h = h_new;
+/// The embedded RKF45 pair, the [`ExplicitRk`] method of [`ButcherTableau::rkf45`] propagating the solution selected
+/// by a [`Propagation`], which parametrizes the [`AdaptiveStepper`] of [`RKFODESolver::rkf_stepper`].
+///
+/// On a rejected step, the derivative at the current point is reused by the next attempt.
+#[derive(Debug, Clone)]
+pub struct Rkf45<F> {
+    method: ExplicitRk<F>,
+    propagation: Propagation,
+}
+
+impl<F: Real> Rkf45<F> {
+    /// Creates the pair, propagating the solution selected by `propagation`.
+    pub fn new(propagation: Propagation) -> Self {
+        Rkf45 { method: ExplicitRk::new(ButcherTableau::rkf45()), propagation }
+    }
+}
+
+impl<F: Real> EmbeddedMethod<F> for Rkf45<F> {
+    fn error_order(&self) -> usize {
+        self.method.error_order()
+    }
+
+    fn step(&mut self, ode: &dyn ODE<F>, x: F, y: F, h: F) -> (F, F) {
+        // The error estimate is the difference between the 4th- and 5th-order solutions
+        let (y4, error) = self.method.step(ode, x, y, h);
+        match self.propagation {
+            Propagation::Fourth => (y4, error),
+            Propagation::Fifth => (y4 - error, error),
         }
+    }
+
+    fn accept(&mut self) {
+        self.method.accept();
+    }
+}
 
-        y
+impl<F: Real> DenseMethod<F> for Rkf45<F> {
+    fn prepare(&mut self, ode: &dyn ODE<F>, x: F, h: F, y_prev: F, y: F) {
+        self.method.prepare(ode, x, h, y_prev, y);
     }
 
-    fn step(ode: &dyn ODE, x: f64, y: f64, h: f64, tolerance: f64) -> (f64, f64) {
-        let a2 = 1.0 / 4.0;
-        let a3 = 3.0 / 8.0;
-        let a4 = 12.0 / 13.0;
-        let a5 = 1.0;
-        let a6 = 1.0 / 2.0;
-
-        let b21 = 1.0 / 4.0;
-        let b31 = 3.0 / 32.0;
-        let b32 = 9.0 / 32.0;
-        let b41 = 1932.0 / 2197.0;
-        let b42 = -7200.0 / 2197.0;
-        let b43 = 7296.0 / 2197.0;
-        let b51 = 439.0 / 216.0;
-        let b52 = -8.0;
-        let b53 = 3680.0 / 513.0;
-        let b54 = -845.0 / 4104.0;
-        let b61 = -8.0 / 27.0;
-        let b62 = 2.0;
-        let b63 = -3544.0 / 2565.0;
-        let b64 = 1859.0 / 4104.0;
-        let b65 = -11.0 / 40.0;
-
-        let c1 = 25.0 / 216.0;
-        let c3 = 1408.0 / 2565.0;
-        let c4 = 2197.0 / 4104.0;
-        let c5 = -1.0 / 5.0;
-
-        let d1 = 16.0 / 135.0;
-        let d3 = 6656.0 / 12825.0;
-        let d4 = 28561.0 / 56430.0;
-        let d5 = -9.0 / 50.0;
-        let d6 = 2.0 / 55.0;
-
-        let k1 = h * ode.eval(x, y);
-        let k2 = h * ode.eval(x + a2 * h, y + b21 * k1);
-        let k3 = h * ode.eval(x + a3 * h, y + b31 * k1 + b32 * k2);
-        let k4 = h * ode.eval(x + a4 * h, y + b41 * k1 + b42 * k2 + b43 * k3);
-        let k5 = h * ode.eval(x + a5 * h, y + b51 * k1 + b52 * k2 + b53 * k3 + b54 * k4);
-        let k6 = h * ode.eval(x + a6 * h, y + b61 * k1 + b62 * k2 + b63 * k3 + b64 * k4 + b65 * k5);
-
-        let y_next = y + c1 * k1 + c3 * k3 + c4 * k4 + c5 * k5;
-        let y_next_star = y + d1 * k1 + d3 * k3 + d4 * k4 + d5 * k5 + d6 * k6;
-
-        let error = (y_next - y_next_star).abs();
-        let h_new = h * (tolerance / error).powf(0.2);
-
-        (y_next, h_new)
+    fn interpolate(&self, theta: F, h: F, y_prev: F, y: F) -> F {
+        self.method.interpolate(theta, h, y_prev, y)
     }
-}
\ No newline at end of file
+}
+
+impl<F: Real> AdaptiveStepper<'_, Rkf45<F>, F> {
+    /// Selects the solution propagated from one step to the next, the 4th-order one by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverOptions;
+    /// # use damndiff::ode::{ODESolver, rkf::{Propagation, RKFODESolver}};
+    /// let solver = ODESolver;
+    /// let decay = |_x: f64, y: f64| -y;
+    /// let options = SolverOptions::new().rtol(1e-8).atol(1e-10);
+    ///
+    /// let mut fourth = solver.rkf_stepper(&decay, 0.0, 1.0, 0.1).with_options(&options);
+    /// let mut fifth = solver.rkf_stepper(&decay, 0.0, 1.0, 0.1).with_options(&options).with_propagation(Propagation::Fifth);
+    /// let error_fourth = (fourth.advance_to(1.0).unwrap() - (-1.0f64).exp()).abs();
+    /// let error_fifth = (fifth.advance_to(1.0).unwrap() - (-1.0f64).exp()).abs();
+    ///
+    /// // Both take the same steps, the local extrapolation is more accurate
+    /// assert_eq!(fourth.stats().accepted_steps, fifth.stats().accepted_steps);
+    /// assert!(error_fifth < error_fourth / 10.0);
+    /// ```
+    pub fn with_propagation(mut self, propagation: Propagation) -> Self {
+        self.method_mut().propagation = propagation;
+        self
+    }
+}
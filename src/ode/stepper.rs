@@ -0,0 +1,204 @@
+//! Stateful steppers for incremental integration of an ODE.
+use super::ODE;
+use crate::{Real, SolverError, Stats};
+use crate::stats::Counted;
+use crate::scalar::{lit, to_f64};
+use crate::grid::{DEFAULT_MAX_STEPS, check_step_size, grid_point, signed_step, step_count};
+
+/// One step of a fixed-step method, mapping `(ode, x, y, h)` to the value at `x + h`.
+type StepFn<'a, F> = Box<dyn FnMut(&dyn ODE<F>, F, F, F) -> F + 'a>;
+
+/// A stateful integrator advancing an ODE with a fixed-step method, one step or one interval at a time.
+///
+/// It is created by the `*_stepper` methods of the solver traits, for instance
+/// [`RungeKuttaODESolver::rk4_stepper`](super::rk::RungeKuttaODESolver::rk4_stepper), and keeps the current state `(x, y)`
+/// between calls, so that the integration can be interleaved with other work.
+///
+/// The step size `h` is signed: [`Stepper::step`] and the [`Iterator`] implementation move towards increasing x when
+/// it is positive and towards decreasing x when it is negative. [`Stepper::advance_to`] uses its magnitude in the
+/// direction of the requested point, shortening the last step so that it lands exactly on it.
+///
+/// # Example
+///
+/// ```
+/// # use damndiff::ode::{ODESolver, rk::RungeKuttaODESolver};
+/// let solver = ODESolver;
+/// let decay = |_x: f64, y: f64| -y;
+/// let mut stepper = solver.rk4_stepper(&decay, 0.0, 1.0, 0.01);
+///
+/// // Advance one frame of the simulation at a time
+/// for frame in 1..=10 {
+///     let x = frame as f64 * 0.1;
+///     let y = stepper.advance_to(x).unwrap();
+///     assert_eq!(stepper.x(), x);
+///     assert!((y - (-x).exp()).abs() < 1e-9);
+/// }
+///
+/// // Or take single steps through the iterator
+/// for (i, state) in stepper.by_ref().take(5).enumerate() {
+///     let (x, y) = state.unwrap();
+///     assert!((x - (1.0 + (i + 1) as f64 * 0.01)).abs() < 1e-12);
+///     assert!((y - (-x).exp()).abs() < 1e-9);
+/// }
+/// ```
+pub struct Stepper<'a, F: Real = f64> {
+    ode: &'a dyn ODE<F>,
+    method: StepFn<'a, F>,
+    x: F,
+    y: F,
+    h: F,
+    /// Point from which the grid of [`Stepper::step`] is laid out.
+    x_start: F,
+    /// Number of steps taken from `x_start`.
+    steps: usize,
+    /// Maximum number of steps taken by a call to `advance_to`.
+    max_steps: usize,
+    stats: Stats<F>,
+    failed: bool,
+}
+
+impl<'a, F: Real> Stepper<'a, F> {
+    /// Creates a stepper at `(x0, y0)`, where `method` maps `(ode, x, y, h)` to the value at `x + h`.
+    pub(crate) fn new<M>(ode: &'a dyn ODE<F>, method: M, x0: F, y0: F, h: F) -> Self
+    where
+        M: FnMut(&dyn ODE<F>, F, F, F) -> F + 'a,
+    {
+        Stepper {
+            ode,
+            method: Box::new(method),
+            x: x0,
+            y: y0,
+            h,
+            x_start: x0,
+            steps: 0,
+            max_steps: DEFAULT_MAX_STEPS,
+            stats: Stats::default(),
+            failed: false,
+        }
+    }
+
+    /// Sets the maximum number of steps a call to [`Stepper::advance_to`] may take, 10 000 000 by default.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Returns the current value of the independent variable.
+    pub fn x(&self) -> F {
+        self.x
+    }
+
+    /// Returns the current value of the dependent variable.
+    pub fn y(&self) -> F {
+        self.y
+    }
+
+    /// Returns the current state `(x, y)`.
+    pub fn state(&self) -> (F, F) {
+        (self.x, self.y)
+    }
+
+    /// Returns the signed step size.
+    pub fn h(&self) -> F {
+        self.h
+    }
+
+    /// Returns the statistics of the steps taken so far.
+    pub fn stats(&self) -> Stats<F> {
+        self.stats.clone()
+    }
+
+    /// Takes a single step of size `h`.
+    ///
+    /// # Returns
+    ///
+    /// The new state `(x, y)`, or the [`SolverError`] that prevented the step, in which case the state is left unchanged.
+    pub fn step(&mut self) -> Result<(F, F), SolverError> {
+        check_step_size(self.x, self.h)?;
+        self.check_state()?;
+
+        let x_next = self.x_start + lit::<F>((self.steps + 1) as f64) * self.h;
+        self.take_step(x_next)?;
+        self.steps += 1;
+        Ok(self.state())
+    }
+
+    /// Integrates up to `x_target`, which can lie on either side of the current x.
+    ///
+    /// # Returns
+    ///
+    /// The y value at `x_target`, or the [`SolverError`] that stopped the integration, in which case the stepper is
+    /// left at the last accepted point.
+    pub fn advance_to(&mut self, x_target: F) -> Result<F, SolverError> {
+        self.advance_with(x_target, |_, _| {})
+    }
+
+    /// Same as [`Stepper::advance_to`], passing every accepted point after the current one to `record`.
+    pub(crate) fn advance_with<R: FnMut(F, F)>(&mut self, x_target: F, mut record: R) -> Result<F, SolverError> {
+        check_step_size(self.x, self.h)?;
+        self.check_state()?;
+
+        let x0 = self.x;
+        let h = signed_step(x0, self.h, x_target);
+        let n = step_count(x0, h, x_target);
+        if n > self.max_steps {
+            return Err(SolverError::MaxStepsExceeded { x: to_f64(x0), max_steps: self.max_steps });
+        }
+
+        let mut result = Ok(());
+        for i in 0..n {
+            result = self.take_step(grid_point(x0, h, x_target, i + 1, n));
+            if result.is_err() {
+                break;
+            }
+            record(self.x, self.y);
+        }
+
+        // Later single steps are laid out from the point reached
+        self.x_start = self.x;
+        self.steps = 0;
+        result.map(|_| self.y)
+    }
+
+    fn check_state(&self) -> Result<(), SolverError> {
+        if !self.y.is_finite() {
+            return Err(SolverError::NonFiniteState { x: to_f64(self.x) });
+        }
+        Ok(())
+    }
+
+    /// Advances the state from the current x to `x_next`.
+    fn take_step(&mut self, x_next: F) -> Result<(), SolverError> {
+        if x_next == self.x {
+            return Err(SolverError::StepSizeUnderflow { x: to_f64(self.x), h: to_f64(self.h) });
+        }
+
+        let h = x_next - self.x;
+        let ode = Counted::new(self.ode);
+        let y = (self.method)(&ode, self.x, self.y, h);
+        self.stats.evals += ode.evals();
+        if !y.is_finite() {
+            return Err(SolverError::NonFiniteState { x: to_f64(self.x) });
+        }
+        self.x = x_next;
+        self.y = y;
+        self.stats.accepted_steps += 1;
+        self.stats.h_final = h;
+        Ok(())
+    }
+}
+
+impl<F: Real> Iterator for Stepper<'_, F> {
+    type Item = Result<(F, F), SolverError>;
+
+    /// Takes a single step, yielding the new state. The iteration ends after the first failed step.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let state = self.step();
+        self.failed = state.is_err();
+        Some(state)
+    }
+}
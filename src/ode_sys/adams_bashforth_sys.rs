@@ -0,0 +1,498 @@
+//! Adams-Bashforth methods for solving systems of ordinary differential equations (ODEs).
+use super::{ODESYS, ODESysSolver, SysMethod, fixed_step, fixed_step_traj, solve_with};
+use super::stepper::SysStepper;
+use super::explicit_rk_sys::ExplicitRk;
+use crate::{ButcherTableau, Real, Solution, SolverError, SolverOptions};
+use crate::error::{check_order, expect_solved};
+use crate::scalar::lit;
+use crate::ode::adams_bashforth::{AB_COEFFICIENTS, AB_ORDERS, DEFAULT_ORDER, continues_grid};
+
+/// Adams-Bashforth methods for solving systems of Ordinary Differential Equations (ODEs).
+///
+/// This trait defines the k-step [Adams-Bashforth methods](https://en.wikipedia.org/wiki/Linear_multistep_method#Adams%E2%80%93Bashforth_methods)
+/// of orders 1 to 5 for solving systems of ordinary differential equations. The method of order k extrapolates the
+/// derivatives at the last k points of a uniform grid, so that each step costs a single evaluation of the system. The
+/// first steps, and the steps following a change of the step size, are taken with the fourth-order Runge-Kutta method
+/// until the history holds k derivatives again.
+///
+/// The methods integrate with the method of order 4, except the `*_order` ones, which take the order as an argument.
+pub trait ABODESysSolver<T: ODESYS<F>, F: Real = f64> {
+    /// Solve the system of ODEs using the Adams-Bashforth method.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size or increment for x.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ABODESysSolver::try_ab_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, adams_bashforth_sys::ABODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.ab_solve(&my_ode_system, x0, y0, x_target, h);
+    /// assert!((result[0] - 0.5f64.exp()).abs() < 1e-3);
+    /// println!("Solution at x = {}: {:?}", x_target, result);
+    /// ```
+    fn ab_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F>;
+
+    /// Solve the system of ODEs using the Adams-Bashforth method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size or increment for x.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from the initial point up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ABODESysSolver::try_ab_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, adams_bashforth_sys::ABODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    ///
+    /// let solution = solver.ab_solve_traj(&oscillator, 0.0, vec![1.0, 0.0], 1.0, 0.1);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn ab_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Adams-Bashforth method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size or increment for x.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode_sys::{ODESysSolver, adams_bashforth_sys::ABODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // The first component blows up at x = 1
+    /// let blow_up = |_x: &f64, y: &Vec<f64>| vec![y[0] * y[0], -y[1]];
+    ///
+    /// let error = solver.try_ab_solve(&blow_up, 0.0, vec![1.0, 1.0], 2.0, 0.01).unwrap_err();
+    /// assert!(matches!(error, SolverError::NonFiniteState { x } if x > 1.0 && x < 1.2));
+    /// ```
+    fn try_ab_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError>;
+
+    /// Create a stepper integrating the system of ODEs with the Adams-Bashforth method, one step at a time.
+    ///
+    /// The stepper keeps the derivative history between calls, and restarts it whenever the step size changes, for
+    /// instance on the shortened last step of [`SysStepper::advance_to`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// A [`SysStepper`] holding the current state, starting from the initial `(x, y)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, adams_bashforth_sys::ABODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let mut stepper = solver.ab_stepper(&oscillator, 0.0, vec![1.0, 0.0], 0.01);
+    ///
+    /// stepper.advance_to(1.0).unwrap();
+    /// // One evaluation checking the dimension of the system, three startup steps of RK4 with four evaluations each,
+    /// // then one evaluation per step
+    /// assert_eq!(stepper.stats().evals, 1 + 3 * 4 + 97);
+    ///
+    /// // The shortened last step restarts the history
+    /// stepper.advance_to(1.005).unwrap();
+    /// assert_eq!(stepper.stats().evals, 1 + 3 * 4 + 97 + 4);
+    /// ```
+    fn ab_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> SysStepper<'a, AdamsBashforth<F>, T, F>;
+
+    /// Solve the system of ODEs using the Adams-Bashforth method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ABODESysSolver::try_ab_solve_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, adams_bashforth_sys::ABODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let options = SolverOptions::new().h0(0.01).output(Output::Points(vec![1.0, 2.0, 3.0]));
+    ///
+    /// let solution = solver.ab_solve_with(&oscillator, 0.0, vec![1.0, 0.0], 3.0, &options);
+    /// for (x, y) in solution.iter() {
+    ///     assert!((y[0] - x.cos()).abs() < 1e-8);
+    /// }
+    /// ```
+    fn ab_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Adams-Bashforth method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the
+    /// integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverError, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, adams_bashforth_sys::ABODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    ///
+    /// // Ten steps of 0.1 are needed to reach x = 1
+    /// let options = SolverOptions::new().h0(0.1).max_steps(5).output(Output::Final);
+    /// let error = solver.try_ab_solve_with(&oscillator, 0.0, vec![1.0, 0.0], 1.0, &options).unwrap_err();
+    /// assert!(matches!(error, SolverError::MaxStepsExceeded { .. }));
+    ///
+    /// let options = SolverOptions::new().h0(0.1).max_steps(10).output(Output::Final);
+    /// let solution = solver.try_ab_solve_with(&oscillator, 0.0, vec![1.0, 0.0], 1.0, &options).unwrap();
+    /// assert_eq!(solution.x, vec![1.0]);
+    /// assert!((solution.y[0][0] - 1.0f64.cos()).abs() < 1e-4);
+    /// ```
+    fn try_ab_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError>;
+
+    /// Solve the system of ODEs using the Adams-Bashforth method of a given order.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `order` - The order of the method, from 1 to 5, which is also its number of steps.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size or increment for x.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails or if `order` is not between 1 and 5, see [`ABODESysSolver::try_ab_solve_order`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, adams_bashforth_sys::ABODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    ///
+    /// // The error decreases with the order
+    /// let errors: Vec<f64> = (1..=5)
+    ///     .map(|order| (solver.ab_solve_order(&oscillator, order, 0.0, vec![1.0, 0.0], 1.0, 0.01)[0] - 1.0f64.cos()).abs())
+    ///     .collect();
+    /// assert!(errors.windows(2).all(|pair| pair[1] < pair[0]));
+    /// ```
+    fn ab_solve_order(&self, ode: &T, order: usize, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F>;
+
+    /// Solve the system of ODEs using the Adams-Bashforth method of a given order, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `order` - The order of the method, from 1 to 5, which is also its number of steps.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size or increment for x.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`, or the [`SolverError`] that stopped the integration, which is a
+    /// [`SolverError::InvalidOrder`] if `order` is not between 1 and 5.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode_sys::{ODESysSolver, adams_bashforth_sys::ABODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    ///
+    /// let result = solver.try_ab_solve_order(&oscillator, 2, 0.0, vec![1.0, 0.0], 1.0, 0.01).unwrap();
+    /// assert!((result[0] - 1.0f64.cos()).abs() < 1e-4);
+    ///
+    /// let error = solver.try_ab_solve_order(&oscillator, 0, 0.0, vec![1.0, 0.0], 1.0, 0.01).unwrap_err();
+    /// assert_eq!(error, SolverError::InvalidOrder { x: 0.0, order: 0, min: 1, max: 5 });
+    /// ```
+    fn try_ab_solve_order(&self, ode: &T, order: usize, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError>;
+
+    /// Create a stepper integrating the system of ODEs with the Adams-Bashforth method of a given order, one step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `order` - The order of the method, from 1 to 5, which is also its number of steps.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// A [`SysStepper`] holding the current state, starting from the initial `(x, y)`, or a [`SolverError::InvalidOrder`]
+    /// if `order` is not between 1 and 5.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode_sys::{ODESysSolver, adams_bashforth_sys::ABODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    ///
+    /// let error = solver.ab_stepper_order(&oscillator, 6, 0.0, vec![1.0, 0.0], 0.01).err().unwrap();
+    /// assert_eq!(error, SolverError::InvalidOrder { x: 0.0, order: 6, min: 1, max: 5 });
+    ///
+    /// let mut stepper = solver.ab_stepper_order(&oscillator, 5, 0.0, vec![1.0, 0.0], 0.01).unwrap();
+    /// let y = stepper.advance_to(1.0).unwrap();
+    /// assert!((y[0] - 1.0f64.cos()).abs() < 1e-8);
+    /// // One evaluation checking the dimension of the system, four startup steps of RK4 with four evaluations each,
+    /// // then one evaluation per step
+    /// assert_eq!(stepper.stats().evals, 1 + 4 * 4 + 96);
+    /// ```
+    fn ab_stepper_order<'a>(&self, ode: &'a T, order: usize, x: F, y: Vec<F>, h: F) -> Result<SysStepper<'a, AdamsBashforth<F>, T, F>, SolverError>;
+}
+
+// Implementing the Adams-Bashforth methods for the system of ODEs Solver
+impl<F: Real, T: ODESYS<F>> ABODESysSolver<T, F> for ODESysSolver {
+    /// Implementation of the Adams-Bashforth method for solving systems of ODEs.
+    ///
+    /// This method approximates the solution of the system at the specified x_target. The k-step method advances the
+    /// solution with `y + h * sum(beta_j * f_{n-j})`, a combination of the derivatives at the current point and at the
+    /// k - 1 previous points of the grid. The history is filled by k - 1 steps of the fourth-order Runge-Kutta method,
+    /// and rebuilt the same way after every change of step size.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size or increment for x.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`.
+    ///
+    /// # When to Use:
+    ///
+    /// Suitable for non-stiff systems whose right-hand side is expensive to evaluate, such as large semi-discretized
+    /// problems or N-body simulations, integrated with a constant step size.
+    ///
+    /// # Pros and Cons:
+    /// - Pros: A single evaluation of the system per step whatever the order.
+    /// - Cons: Needs a startup procedure, and a restart whenever the step size changes; the stability region shrinks as the order grows.
+    ///
+    /// # Stability Analysis:
+    ///
+    /// Conditionally stable, with a stability region smaller than the one of the Runge-Kutta methods of the same order: unsuited to stiff systems.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, adams_bashforth_sys::ABODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    ///
+    /// let result = solver.ab_solve(&oscillator, 0.0, vec![1.0, 0.0], 1.0, 0.01);
+    /// println!("Error of the method: {:e}", (result[0] - 1.0f64.cos()).abs());
+    /// ```
+    fn ab_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        expect_solved(self.try_ab_solve(ode, x, y, x_target, h))
+    }
+
+    /// Implementation of the Adams-Bashforth method returning the whole trajectory of the system of ODEs.
+    ///
+    /// See [`ABODESysSolver::ab_solve`] for the details of the method.
+    fn ab_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F> {
+        let method = AdamsBashforth::new(DEFAULT_ORDER, y.len());
+        expect_solved(fixed_step_traj(ode, method, x, y, h, x_target))
+    }
+
+    /// Implementation of the Adams-Bashforth method reporting failures as a [`SolverError`].
+    ///
+    /// See [`ABODESysSolver::ab_solve`] for the details of the method.
+    fn try_ab_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError> {
+        self.try_ab_solve_order(ode, DEFAULT_ORDER, x, y, x_target, h)
+    }
+
+    /// Implementation of the Adams-Bashforth method as a [`SysStepper`].
+    ///
+    /// See [`ABODESysSolver::ab_solve`] for the details of the method.
+    fn ab_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> SysStepper<'a, AdamsBashforth<F>, T, F> {
+        let method = AdamsBashforth::new(DEFAULT_ORDER, y.len());
+        SysStepper::new(ode, method, x, y, h)
+    }
+
+    /// Implementation of the Adams-Bashforth method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`ABODESysSolver::ab_solve`] for the details of the method.
+    fn ab_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F> {
+        expect_solved(self.try_ab_solve_with(ode, x, y, x_target, options))
+    }
+
+    /// Implementation of the Adams-Bashforth method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`ABODESysSolver::ab_solve`] for the details of the method.
+    fn try_ab_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError> {
+        options.check(x, x_target, y.len())?;
+        let stepper = self.ab_stepper(ode, x, y, options.initial_step(x, x_target));
+        solve_with(stepper.with_max_steps(options.max_steps), x_target, &options.output)
+    }
+
+    /// Implementation of the Adams-Bashforth method of a given order.
+    ///
+    /// See [`ABODESysSolver::ab_solve`] for the details of the method.
+    fn ab_solve_order(&self, ode: &T, order: usize, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        expect_solved(self.try_ab_solve_order(ode, order, x, y, x_target, h))
+    }
+
+    /// Implementation of the Adams-Bashforth method of a given order reporting failures as a [`SolverError`].
+    ///
+    /// See [`ABODESysSolver::ab_solve`] for the details of the method.
+    fn try_ab_solve_order(&self, ode: &T, order: usize, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError> {
+        check_order(order, AB_ORDERS, x)?;
+        let method = AdamsBashforth::new(order, y.len());
+        fixed_step(ode, method, x, y, h, x_target, |_, _| {})
+    }
+
+    /// Implementation of the Adams-Bashforth method of a given order as a [`SysStepper`].
+    ///
+    /// See [`ABODESysSolver::ab_solve`] for the details of the method.
+    fn ab_stepper_order<'a>(&self, ode: &'a T, order: usize, x: F, y: Vec<F>, h: F) -> Result<SysStepper<'a, AdamsBashforth<F>, T, F>, SolverError> {
+        check_order(order, AB_ORDERS, x)?;
+        Ok(SysStepper::new(ode, AdamsBashforth::new(order, y.len()), x, y, h))
+    }
+}
+
+/// Derivative history of the k-step Adams-Bashforth method for a system of ODEs, parametrizing the [`SysStepper`] of the method.
+pub struct AdamsBashforth<F> {
+    order: usize,
+    /// Derivatives at the last points of the grid, the most recent first; only the first `known` are valid.
+    history: Vec<Vec<F>>,
+    known: usize,
+    /// Start point and size of the last step, telling whether the next one continues the grid.
+    x_last: F,
+    h_last: F,
+    /// Method of the steps taken while the history is incomplete.
+    startup: ExplicitRk<F>,
+}
+
+impl<F: Real> AdamsBashforth<F> {
+    /// Creates the method of order `order`, with the buffers needed to integrate a system of dimension `dim`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is not between 1 and 5.
+    pub fn new(order: usize, dim: usize) -> Self {
+        assert!(AB_ORDERS.contains(&order), "the Adams-Bashforth methods have orders 1 to 5, not {}", order);
+        AdamsBashforth {
+            order,
+            history: vec![vec![F::zero(); dim]; order],
+            known: 0,
+            x_last: F::zero(),
+            h_last: F::zero(),
+            startup: ExplicitRk::new(ButcherTableau::rk4(), dim),
+        }
+    }
+}
+
+impl<F: Real> SysMethod<F> for AdamsBashforth<F> {
+    fn step<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &mut [F], h: F) {
+        if !continues_grid(self.x_last, self.h_last, x, h) {
+            self.known = 0;
+        }
+        self.x_last = x;
+        self.h_last = h;
+
+        // The buffer of the oldest derivative receives the newest one
+        self.history.rotate_right(1);
+        ode.eval_into(x, y, &mut self.history[0]);
+        self.known = (self.known + 1).min(self.order);
+        if self.known < self.order {
+            self.startup.step_from(ode, x, y, &self.history[0], h);
+            return;
+        }
+
+        for (beta, f) in AB_COEFFICIENTS[self.order - 1].iter().zip(&self.history) {
+            let scale = h * lit(*beta);
+            for (y, f) in y.iter_mut().zip(f) {
+                *y += scale * *f;
+            }
+        }
+    }
+}
@@ -0,0 +1,622 @@
+//! Adaptive integration of systems of ODEs with embedded Runge-Kutta pairs.
+use super::{ODESYS, check_dimension, check_no_mass_matrix, is_finite};
+use crate::{Real, SolverError, SolverOptions, Stats};
+use crate::stats::Counted;
+use crate::controller::{StepController, rms_norm};
+use crate::scalar::to_f64;
+use crate::grid::{check_step_size, signed_step};
+
+/// An embedded Runge-Kutta pair for systems of ODEs, or any other method computing a solution and an estimate of its
+/// local error, such as the [`bdf::Bdf`](super::bdf::Bdf) formulas.
+///
+/// It is implemented by the adaptive method types of the submodules, such as [`rkf_sys::Rkf45`](super::rkf_sys::Rkf45),
+/// which parametrize an [`AdaptiveSysStepper`]. The method may keep evaluations of the system between calls, as the
+/// methods with the first-same-as-last property do, since the stepper only moves to another point after [`EmbeddedSysMethod::accept`].
+pub trait EmbeddedSysMethod<F: Real> {
+    /// Returns the order of the error estimate, that is the lower order of the pair.
+    fn error_order(&self) -> usize;
+
+    /// Attempts a step of size `h` from `(x, y)`, writing the propagated solution into `y_next` and the estimate of its
+    /// local error into `error`.
+    fn step<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F], h: F, y_next: &mut [F], error: &mut [F]);
+
+    /// Records that the last attempted step was accepted, so that the next one starts from its end point.
+    fn accept(&mut self) {}
+
+    /// Returns the norm of the error estimate of the last attempted step, a step being accepted when it is at most 1.
+    ///
+    /// `scale` holds the tolerance of each component, `atol + rtol * max(|y|, |y_next|)`. The default is the weighted
+    /// root mean square of `error`. The methods that could not complete the step, such as the implicit methods whose
+    /// iteration diverged, return infinity so that it is retried with a smaller step size.
+    fn error_norm(&self, error: &[F], scale: &[F]) -> F {
+        rms_norm(error, scale)
+    }
+
+    /// Returns the factor by which the size of the last attempted step, whose error norm is `error`, should be scaled
+    /// for the next attempt, or `None` to leave the choice to the controller of the stepper.
+    ///
+    /// It is called after [`EmbeddedSysMethod::accept`] when the step is accepted, so that the methods of variable order
+    /// can choose the step size along with the order. The default returns `None`.
+    fn step_factor(&self, _error: F) -> Option<F> {
+        None
+    }
+
+    /// Receives the tolerances of the stepper, `rtol` and one absolute tolerance per component, for the methods whose
+    /// inner iterations are stopped according to them. The default does nothing.
+    fn set_tolerances(&mut self, _rtol: F, _atol: &[F]) {}
+
+    /// Adds to `stats` the evaluations of the Jacobian and the linear solves of the method since the last call.
+    ///
+    /// It is called after every attempted step. The explicit methods do neither, and the default does nothing.
+    fn record_work(&mut self, _stats: &mut Stats<F>) {}
+
+    /// Returns `true` if the method integrates the systems with a mass matrix, see [`ODESYS::mass_matrix_into`].
+    ///
+    /// The stepper of the other methods fails with a [`SolverError::UnsupportedMassMatrix`] on such a system. The
+    /// default returns `false`.
+    fn supports_mass_matrix(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if the method detected, over the last accepted steps, that the problem became stiff.
+    ///
+    /// The methods without stiffness detection always return `false`.
+    fn is_stiff(&self) -> bool {
+        false
+    }
+}
+
+/// An embedded pair providing a continuous extension of its solution over the last accepted step.
+pub trait DenseSysMethod<F: Real>: EmbeddedSysMethod<F> {
+    /// Computes the data of the continuous extension of the last accepted step, of size `h` from `y_prev` at `x` to
+    /// `y`, which the method did not compute while stepping.
+    ///
+    /// It is called at most once per accepted step, before the first interpolation in it. The default does nothing.
+    fn prepare<T: ODESYS<F>>(&mut self, _ode: &T, _x: F, _h: F, _y_prev: &[F], _y: &[F]) {}
+
+    /// Writes into `out` the solution at `x + theta * h`, for `theta` in `[0, 1]`, of the last accepted step, which
+    /// went from `y_prev` at `x` to `y` at `x + h`.
+    fn interpolate(&self, theta: F, h: F, y_prev: &[F], y: &[F], out: &mut [F]);
+}
+
+/// Evaluation of the solution at a point of the last accepted step of the stepper `S`.
+type Interpolant<S, F> = fn(&mut S, F) -> Option<Vec<F>>;
+
+/// A stateful integrator advancing a system of ODEs with the embedded pair `M`, adapting the step size to the tolerances.
+///
+/// It is created by the `*_stepper` methods of the adaptive solver traits, for instance
+/// [`RKFODESysSolver::rkf_stepper`](super::rkf_sys::RKFODESysSolver::rkf_stepper), and keeps the current state `(x, y)`
+/// and the step size proposed for the next step between calls. [`AdaptiveSysStepper::step`] and the [`Iterator`]
+/// implementation take one adaptive step in the direction given by the sign of the step size, while
+/// [`AdaptiveSysStepper::advance_to`] integrates up to a given point, shortening the last step so that it lands exactly
+/// on it. The steppers of the BDF and automatic switching methods step past the point instead, and interpolate the
+/// solution there, so that the history of the BDF method keeps its step size.
+///
+/// A step is accepted when the weighted root mean square of its error estimate,
+/// `sqrt(sum((error[i] / (atol[i] + rtol * max(|y[i]|, |y_next[i]|)))^2) / n)`, is at most 1; otherwise it is rejected
+/// and retried with a smaller step. A component whose error is zero counts as zero, even when its tolerance is zero. The next step size is the optimal one scaled by a safety factor of 0.9, and it can
+/// neither shrink nor grow by more than a factor 5 from one step to the next, unless the method chooses it, see
+/// [`EmbeddedSysMethod::step_factor`]. An attempt whose solution or error estimate overflows is rejected as well, with
+/// the step size divided by 5, and the integration only fails with a [`SolverError::NonFiniteState`] once the step
+/// size has reached its lower bound.
+///
+/// For the methods with a continuous extension, [`AdaptiveSysStepper::interpolate`] evaluates the solution anywhere in
+/// the last accepted step, usually at no extra cost.
+///
+/// # Example
+///
+/// ```
+/// # use damndiff::SolverOptions;
+/// # use damndiff::ode_sys::{ODESysSolver, rkf_sys::RKFODESysSolver};
+/// let solver = ODESysSolver;
+/// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+///
+/// // Position known to 1e-9, velocity to 1e-6
+/// let options = SolverOptions::new().rtol(1e-8).atol_per_component(vec![1e-9, 1e-6]);
+/// let mut stepper = solver.rkf_stepper(&oscillator, 0.0, vec![1.0, 0.0], 0.1).with_options(&options);
+///
+/// let y = stepper.advance_to(1.0).unwrap();
+/// assert!((y[0] - 1.0f64.cos()).abs() < 1e-6);
+/// println!("{} accepted and {} rejected steps", stepper.stats().accepted_steps, stepper.stats().rejected_steps);
+/// ```
+///
+/// A zero absolute tolerance is fine for the components that stay exactly at zero:
+///
+/// ```
+/// # use damndiff::SolverOptions;
+/// # use damndiff::ode_sys::{ODESysSolver, rkf_sys::RKFODESysSolver};
+/// let solver = ODESysSolver;
+/// let constant = |_x: &f64, y: &Vec<f64>| vec![y[1], 0.0];
+/// let options = SolverOptions::new().atol(0.0);
+///
+/// let solution = solver.try_rkf_solve_with(&constant, 0.0, vec![1.0, 0.0], 1.0, &options).unwrap();
+/// assert_eq!(solution.y.last().unwrap(), &vec![1.0, 0.0]);
+/// ```
+///
+/// A first step far too large for the problem overflows, and is retried with smaller steps:
+///
+/// ```
+/// # use damndiff::ode_sys::{ODESysSolver, dop853::DOP853ODESysSolver};
+/// let solver = ODESysSolver;
+/// let quintic = |_x: &f64, y: &Vec<f64>| vec![-y[0].powi(5)];
+///
+/// let y = solver.try_dop853_solve(&quintic, 0.0, vec![10.0], 1.0, 1.0).unwrap();
+/// assert!((y[0] - (4.0001f64).powf(-0.25)).abs() < 1e-5);
+/// ```
+pub struct AdaptiveSysStepper<'a, M, T, F: Real = f64> {
+    ode: &'a T,
+    method: M,
+    x: F,
+    y: Vec<F>,
+    /// State at the start of the last accepted step.
+    x_prev: F,
+    y_prev: Vec<F>,
+    /// Buffer receiving the candidate state at the end of a step, swapped with `y` once the step is accepted.
+    y_next: Vec<F>,
+    error: Vec<F>,
+    /// Tolerance of each component in the last attempted step.
+    scale: Vec<F>,
+    /// Whether the continuous extension of the last accepted step has been prepared.
+    dense_ready: bool,
+    /// Continuous extension used by `advance_to` to step past its target, `None` to shorten the last step instead.
+    interpolant: Option<Interpolant<Self, F>>,
+    /// Point inside the last accepted step where `advance_to` stopped, with the interpolated y values there.
+    stop: Option<(F, Vec<F>)>,
+    h: F,
+    rtol: F,
+    atol: Vec<F>,
+    h_min: F,
+    h_max: F,
+    /// Maximum number of steps taken by a call to `advance_to`.
+    max_steps: usize,
+    controller: StepController<F>,
+    stats: Stats<F>,
+    /// Whether the dimension of the system has been checked against the one of the state, and its mass matrix against
+    /// the method.
+    dimension_checked: bool,
+    failed: bool,
+}
+
+impl<'a, M, T, F> AdaptiveSysStepper<'a, M, T, F>
+where
+    M: EmbeddedSysMethod<F>,
+    T: ODESYS<F>,
+    F: Real,
+{
+    /// Creates a stepper at `(x0, y0)` integrating with `method`, whose buffers match the dimension of `y0`, with the
+    /// default settings of [`SolverOptions::new`].
+    pub(crate) fn new(ode: &'a T, method: M, x0: F, y0: Vec<F>, h: F) -> Self {
+        let dim = y0.len();
+        let controller = StepController::new(method.error_order());
+        let stepper = AdaptiveSysStepper {
+            ode,
+            method,
+            x: x0,
+            y_prev: y0.clone(),
+            y: y0,
+            x_prev: x0,
+            y_next: vec![F::zero(); dim],
+            error: vec![F::zero(); dim],
+            scale: vec![F::zero(); dim],
+            dense_ready: false,
+            interpolant: None,
+            stop: None,
+            h,
+            rtol: F::zero(),
+            atol: Vec::new(),
+            h_min: F::zero(),
+            h_max: F::infinity(),
+            max_steps: 0,
+            controller,
+            stats: Stats::default(),
+            dimension_checked: false,
+            failed: false,
+        };
+        stepper.with_options(&SolverOptions::new())
+    }
+
+    /// Applies the tolerances, the bounds on the step size and the step cap of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `options` holds one absolute tolerance per component and their number differs from the dimension of the system.
+    pub fn with_options(mut self, options: &SolverOptions<F>) -> Self {
+        let dim = self.y.len();
+        assert!(
+            options.atol.len() == 1 || options.atol.len() == dim,
+            "{} absolute tolerances given for a system of dimension {}",
+            options.atol.len(),
+            dim
+        );
+
+        self.rtol = options.rtol;
+        self.atol = (0..dim).map(|i| options.atol_at(i)).collect();
+        self.h_min = options.h_min;
+        self.h_max = options.h_max;
+        self.max_steps = options.max_steps;
+        self.h = self.bounded(self.h);
+        self.method.set_tolerances(self.rtol, &self.atol);
+        self
+    }
+
+    /// Returns the current value of the independent variable.
+    pub fn x(&self) -> F {
+        self.state().0
+    }
+
+    /// Returns the current values of the dependent variables.
+    pub fn y(&self) -> &[F] {
+        self.state().1
+    }
+
+    /// Returns the current state `(x, y)`.
+    pub fn state(&self) -> (F, &[F]) {
+        match &self.stop {
+            Some((x, y)) => (*x, y),
+            None => (self.x, &self.y),
+        }
+    }
+
+    /// Returns the signed step size proposed for the next step.
+    pub fn h(&self) -> F {
+        self.h
+    }
+
+    /// Returns the statistics of the steps taken so far.
+    pub fn stats(&self) -> Stats<F> {
+        self.stats.clone()
+    }
+
+    /// Returns the method, for instance to query the state it keeps between steps.
+    pub fn method(&self) -> &M {
+        &self.method
+    }
+
+    /// Consumes the stepper, returning the current values of the dependent variables.
+    pub fn into_y(self) -> Vec<F> {
+        match self.stop {
+            Some((_, y)) => y,
+            None => self.y,
+        }
+    }
+
+    /// Returns the interval `(x_prev, x)` covered by the last accepted step, in which [`AdaptiveSysStepper::interpolate`]
+    /// can evaluate the solution. Both ends are the initial x before the first step.
+    ///
+    /// The current x lies inside this interval when [`AdaptiveSysStepper::advance_to`] stepped past its target.
+    pub fn last_step(&self) -> (F, F) {
+        (self.x_prev, self.x)
+    }
+
+    /// Takes a single adaptive step, retrying it with a smaller step size until its error is small enough.
+    ///
+    /// The step starts from the end of the last accepted step, which is ahead of the current x when
+    /// [`AdaptiveSysStepper::advance_to`] stepped past its target.
+    ///
+    /// # Returns
+    ///
+    /// The new state `(x, y)`, or the [`SolverError`] that prevented the step, in which case the state is left unchanged.
+    pub fn step(&mut self) -> Result<(F, &[F]), SolverError> {
+        check_step_size(self.x, self.h)?;
+        self.check_state()?;
+
+        self.take_step(None)?;
+        self.stop = None;
+        Ok(self.state())
+    }
+
+    /// Integrates up to `x_target`, which can lie on either side of the current x.
+    ///
+    /// The steppers of the BDF and automatic switching methods step past `x_target`, evaluating the system beyond it,
+    /// and interpolate the solution there: the current x is then `x_target`, inside the last accepted step, and the
+    /// integration goes on from the end of that step. The other steppers shorten their last step so that it lands
+    /// exactly on `x_target`.
+    ///
+    /// # Returns
+    ///
+    /// The y values at `x_target`, or the [`SolverError`] that stopped the integration, in which case the stepper is
+    /// left at the last accepted point.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverOptions;
+    /// # use damndiff::ode_sys::{ODESysSolver, bdf::BDFODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let robertson = |_x: &f64, y: &Vec<f64>| {
+    ///     vec![
+    ///         -0.04 * y[0] + 1e4 * y[1] * y[2],
+    ///         0.04 * y[0] - 1e4 * y[1] * y[2] - 3e7 * y[1] * y[1],
+    ///         3e7 * y[1] * y[1],
+    ///     ]
+    /// };
+    /// let options = SolverOptions::new().rtol(1e-6).atol_per_component(vec![1e-8, 1e-12, 1e-8]);
+    ///
+    /// let mut once = solver.bdf_stepper(&robertson, 0.0, vec![1.0, 0.0, 0.0], 1e-6).with_options(&options);
+    /// once.advance_to(100.0).unwrap();
+    ///
+    /// // Sampling the solution every 0.1 barely adds any step
+    /// let mut sampled = solver.bdf_stepper(&robertson, 0.0, vec![1.0, 0.0, 0.0], 1e-6).with_options(&options);
+    /// for i in 1..=1000 {
+    ///     let y = sampled.advance_to(i as f64 * 0.1).unwrap();
+    ///     assert!((y[0] + y[1] + y[2] - 1.0).abs() < 1e-8);
+    /// }
+    /// assert_eq!(sampled.x(), 100.0);
+    /// assert!((sampled.y()[0] - once.y()[0]).abs() < 1e-6);
+    /// assert!(sampled.stats().accepted_steps < 2 * once.stats().accepted_steps);
+    /// ```
+    pub fn advance_to(&mut self, x_target: F) -> Result<&[F], SolverError> {
+        if self.interpolant.is_some() {
+            self.advance_past(x_target)?;
+        } else {
+            self.advance_with(x_target, |_, _| {})?;
+        }
+        Ok(self.y())
+    }
+
+    /// Same as [`AdaptiveSysStepper::advance_to`], passing every accepted point after the current one to `record`, and
+    /// always landing on `x_target`.
+    pub(crate) fn advance_with<R: FnMut(F, &[F])>(&mut self, x_target: F, mut record: R) -> Result<(), SolverError> {
+        check_step_size(self.x, self.h)?;
+        self.check_state()?;
+        if self.resume(x_target) {
+            self.stop_at(x_target);
+            return Ok(());
+        }
+
+        self.h = signed_step(self.x, self.h, x_target);
+        let direction = self.h.signum();
+        let mut steps = 0;
+
+        while (x_target - self.x) * direction > F::zero() {
+            if steps == self.max_steps {
+                return Err(SolverError::MaxStepsExceeded { x: to_f64(self.x), max_steps: self.max_steps });
+            }
+
+            self.take_step(Some(x_target))?;
+            steps += 1;
+            record(self.x, &self.y);
+        }
+
+        Ok(())
+    }
+
+    /// Same as [`AdaptiveSysStepper::advance_to`], stepping past `x_target` and interpolating the solution there.
+    fn advance_past(&mut self, x_target: F) -> Result<(), SolverError> {
+        check_step_size(self.x, self.h)?;
+        self.check_state()?;
+        if !self.resume(x_target) {
+            self.h = signed_step(self.x, self.h, x_target);
+            let direction = self.h.signum();
+            let mut steps = 0;
+
+            while (x_target - self.x) * direction > F::zero() {
+                if steps == self.max_steps {
+                    return Err(SolverError::MaxStepsExceeded { x: to_f64(self.x), max_steps: self.max_steps });
+                }
+
+                self.take_step(None)?;
+                steps += 1;
+            }
+        }
+
+        self.stop_at(x_target);
+        Ok(())
+    }
+
+    /// Leaves the point where [`AdaptiveSysStepper::advance_to`] stopped inside the last accepted step, if any, before
+    /// integrating towards `x_target`: the integration goes on from the end of the step when `x_target` lies ahead of
+    /// that point, and from the point itself otherwise.
+    ///
+    /// Returns `true` if `x_target` lies between that point and the end of the step, where no step is needed.
+    fn resume(&mut self, x_target: F) -> bool {
+        let Some((x_stop, y_stop)) = self.stop.take() else {
+            return false;
+        };
+
+        let direction = self.x - x_stop;
+        if (x_target - x_stop) * direction >= F::zero() {
+            return (x_target - self.x) * direction <= F::zero();
+        }
+        // Going back, the last accepted step is reduced to that point
+        self.x = x_stop;
+        self.y = y_stop;
+        self.x_prev = x_stop;
+        self.y_prev.copy_from_slice(&self.y);
+        false
+    }
+
+    /// Stops at `x` inside the last accepted step, evaluating the solution there with the interpolant of the stepper.
+    fn stop_at(&mut self, x: F) {
+        if let Some(interpolant) = self.interpolant.filter(|_| x != self.x) {
+            let y = interpolant(self, x).expect("the target lies in the last accepted step");
+            self.stop = Some((x, y));
+        }
+    }
+
+    fn check_state(&mut self) -> Result<(), SolverError> {
+        if !is_finite(&self.y) {
+            return Err(SolverError::NonFiniteState { x: to_f64(self.x) });
+        }
+        if !self.dimension_checked {
+            let ode = Counted::new(self.ode);
+            let result = check_dimension(&ode, self.x, &self.y);
+            self.stats.evals += ode.evals();
+            result?;
+            if !self.method.supports_mass_matrix() {
+                check_no_mass_matrix(self.ode, self.x, self.y.len())?;
+            }
+            self.dimension_checked = true;
+        }
+        Ok(())
+    }
+
+    /// Takes one accepted step of size at most `h`, rejecting and retrying the attempts whose error is too large.
+    ///
+    /// When `x_target` is given, the step is shortened so that it does not go past it. After a failure, the attempts
+    /// have overwritten the buffers of the method, so that the last accepted step is reduced to the current point.
+    fn take_step(&mut self, x_target: Option<F>) -> Result<(), SolverError> {
+        let result = self.try_step(x_target);
+        if result.is_err() {
+            self.x_prev = self.x;
+            self.y_prev.copy_from_slice(&self.y);
+        }
+        result
+    }
+
+    /// Same as [`AdaptiveSysStepper::take_step`], leaving the last accepted step untouched on failure.
+    fn try_step(&mut self, x_target: Option<F>) -> Result<(), SolverError> {
+        loop {
+            // Shorten the last step so that it ends exactly at `x_target`
+            let (h, x_next) = match x_target {
+                Some(x_target) if (self.x + self.h - x_target) * self.h.signum() >= F::zero() => {
+                    (x_target - self.x, x_target)
+                }
+                _ => (self.h, self.x + self.h),
+            };
+            if x_next == self.x {
+                return Err(SolverError::StepSizeUnderflow { x: to_f64(self.x), h: to_f64(h) });
+            }
+            let ode = Counted::new(self.ode);
+            self.method.step(&ode, self.x, &self.y, h, &mut self.y_next, &mut self.error);
+            self.stats.evals += ode.evals();
+            self.method.record_work(&mut self.stats);
+
+            let error = self.error_norm();
+            // An overflowing attempt is rejected like an inaccurate one, with the largest reduction of the step size
+            if !is_finite(&self.y_next) || !is_finite(&self.error) || !error.is_finite() {
+                self.stats.rejected_steps += 1;
+                if h.abs() <= self.h_min {
+                    return Err(SolverError::NonFiniteState { x: to_f64(self.x) });
+                }
+                let factor = self.controller.reject();
+                self.h = self.bounded(h * factor);
+                continue;
+            }
+
+            self.controller.set_order(self.method.error_order());
+            let factor = self.controller.factor(error);
+
+            if StepController::accepts(error) {
+                std::mem::swap(&mut self.y_prev, &mut self.y);
+                std::mem::swap(&mut self.y, &mut self.y_next);
+                self.method.accept();
+                let factor = self.method.step_factor(error).unwrap_or(factor);
+                self.dense_ready = false;
+                self.x_prev = self.x;
+                self.x = x_next;
+                self.h = self.bounded(h * factor);
+                self.stats.accepted_steps += 1;
+                self.stats.h_final = h;
+                if self.stats.stiffness_detected.is_none() && self.method.is_stiff() {
+                    self.stats.stiffness_detected = Some(x_next);
+                }
+                return Ok(());
+            }
+
+            self.stats.rejected_steps += 1;
+            if h.abs() <= self.h_min {
+                return Err(SolverError::StepSizeUnderflow { x: to_f64(self.x), h: to_f64(h) });
+            }
+            self.h = self.bounded(h * self.method.step_factor(error).unwrap_or(factor));
+        }
+    }
+
+    /// Returns the norm of the error estimate of the last attempted step, scaled by the tolerances.
+    fn error_norm(&mut self) -> F {
+        for (i, scale) in self.scale.iter_mut().enumerate() {
+            *scale = self.atol[i] + self.rtol * self.y[i].abs().max(self.y_next[i].abs());
+        }
+        self.method.error_norm(&self.error, &self.scale)
+    }
+
+    /// Clamps the magnitude of the step size `h` between `h_min` and `h_max`, keeping its sign.
+    fn bounded(&self, h: F) -> F {
+        h.abs().max(self.h_min).min(self.h_max).copysign(h)
+    }
+}
+
+impl<M, T, F> AdaptiveSysStepper<'_, M, T, F>
+where
+    M: DenseSysMethod<F>,
+    T: ODESYS<F>,
+    F: Real,
+{
+    /// Makes [`AdaptiveSysStepper::advance_to`] step past its target and interpolate the solution there, for the methods
+    /// whose history would have to be rescaled by a shortened step.
+    pub(crate) fn stepping_past_targets(mut self) -> Self {
+        self.interpolant = Some(Self::interpolate);
+        self
+    }
+
+    /// Evaluates the continuous extension of the method at `x`, without taking any step.
+    ///
+    /// Some methods, such as [`Dop853`](super::dop853::Dop853), evaluate the system a few more times on the first
+    /// interpolation in each step.
+    ///
+    /// # Returns
+    ///
+    /// The y values at `x`, or `None` if `x` lies outside the last accepted step, see [`AdaptiveSysStepper::last_step`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, bogacki_shampine_sys::BShampineODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let mut stepper = solver.bs_stepper(&oscillator, 0.0, vec![1.0, 0.0], 0.1);
+    ///
+    /// // Sample the solution every 0.01 without constraining the steps
+    /// let mut samples = Vec::new();
+    /// for i in 0..=100 {
+    ///     let x = i as f64 * 0.01;
+    ///     while stepper.x() < x {
+    ///         stepper.step().unwrap();
+    ///     }
+    ///     samples.push(stepper.interpolate(x).unwrap());
+    /// }
+    /// assert!((samples[100][0] - 1.0f64.cos()).abs() < 1e-4);
+    /// ```
+    pub fn interpolate(&mut self, x: F) -> Option<Vec<F>> {
+        if x == self.x {
+            return Some(self.y.clone());
+        }
+
+        let h = self.x - self.x_prev;
+        let theta = (x - self.x_prev) / h;
+        if h == F::zero() || !(F::zero()..=F::one()).contains(&theta) {
+            return None;
+        }
+
+        if !self.dense_ready {
+            let ode = Counted::new(self.ode);
+            self.method.prepare(&ode, self.x_prev, h, &self.y_prev, &self.y);
+            self.stats.evals += ode.evals();
+            self.dense_ready = true;
+        }
+
+        let mut y = vec![F::zero(); self.y.len()];
+        self.method.interpolate(theta, h, &self.y_prev, &self.y, &mut y);
+        Some(y)
+    }
+}
+
+impl<M, T, F> Iterator for AdaptiveSysStepper<'_, M, T, F>
+where
+    M: EmbeddedSysMethod<F>,
+    T: ODESYS<F>,
+    F: Real,
+{
+    type Item = Result<(F, Vec<F>), SolverError>;
+
+    /// Takes a single adaptive step, yielding a copy of the new state. The iteration ends after the first failed step.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let state = self.step().map(|(x, y)| (x, y.to_vec()));
+        self.failed = state.is_err();
+        Some(state)
+    }
+}
@@ -0,0 +1,545 @@
+//! Automatic switching between a non-stiff and a stiff method for solving systems of ordinary differential equations (ODEs).
+use super::{ODESYS, ODESysSolver, solve_with};
+use super::adaptive::{AdaptiveSysStepper, DenseSysMethod, EmbeddedSysMethod};
+use super::bdf::Bdf;
+use super::dormand_prince_sys::Dopri5;
+use crate::{MethodSwitch, Output, Real, Solution, SolverError, SolverOptions, Stats};
+use crate::error::expect_solved;
+use crate::scalar::lit;
+
+/// Automatic stiffness switching method for solving systems of Ordinary Differential Equations (ODEs).
+///
+/// This trait defines a solver for the systems of ordinary differential equations that are not known in advance to be
+/// stiff, or that are stiff over part of the integration only, in the spirit of Petzold's LSODA. It starts with the
+/// explicit Dormand–Prince 5(4) method, monitors the stiffness of the problem along the way, and switches to the
+/// backward differentiation formulas (BDF) when it becomes stiff, then back to the explicit method when it is no longer
+/// stiff. The step size `h` given to the methods is the initial one, which is then adapted so that the weighted root
+/// mean square of the local error estimates stays within the tolerances, see [`AdaptiveSysStepper`]. Without a
+/// [`SolverOptions`], the tolerances are the defaults of [`SolverOptions::new`].
+///
+/// Every change of method is reported, with its x and its direction, in the `switches` field of the [`Stats`], and
+/// the first switch to the stiff method in its `stiffness_detected` field.
+pub trait AutoSwitchODESysSolver<T: ODESYS<F>, F: Real = f64> {
+    /// Solve the system of ODEs, switching automatically between a non-stiff and a stiff method.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`AutoSwitchODESysSolver::try_auto_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, auto_switch::AutoSwitchODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.auto_solve(&my_ode_system, x0, y0, x_target, h);
+    /// assert!((result[0] - 0.5f64.exp()).abs() < 1e-5);
+    /// println!("Solution at x = {}: {:?}", x_target, result);
+    /// ```
+    fn auto_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F>;
+
+    /// Solve the system of ODEs, switching automatically between a non-stiff and a stiff method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from the initial x up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`AutoSwitchODESysSolver::try_auto_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, auto_switch::AutoSwitchODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    ///
+    /// // A non-stiff problem is solved by the explicit method from start to end
+    /// let solution = solver.auto_solve_traj(&oscillator, 0.0, vec![1.0, 0.0], 10.0, 0.1);
+    /// assert!(solution.stats.switches.is_empty());
+    /// assert_eq!(solution.stats.jacobian_evals, 0);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn auto_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs, switching automatically between a non-stiff and a stiff method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`, or the [`SolverError`] that stopped the integration, such as a
+    /// [`SolverError::StepSizeUnderflow`] when the solution blows up.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode_sys::{ODESysSolver, auto_switch::AutoSwitchODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // The first component blows up at x = 1
+    /// let blow_up = |_x: &f64, y: &Vec<f64>| vec![y[0] * y[0], -y[1]];
+    ///
+    /// match solver.try_auto_solve(&blow_up, 0.0, vec![1.0, 1.0], 2.0, 0.1) {
+    ///     Ok(result) => println!("Solution at x = 2: {:?}", result),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_auto_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError>;
+
+    /// Create a stepper integrating the system of ODEs one adaptive step at a time, switching automatically between a
+    /// non-stiff and a stiff method.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The initial step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// An [`AdaptiveSysStepper`] holding the current state, starting from the initial `(x, y)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverOptions;
+    /// # use damndiff::ode_sys::{ODESysSolver, auto_switch::AutoSwitchODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // Van der Pol oscillator, stiff along its slow phases and non-stiff in its fast transitions
+    /// let van_der_pol = |_x: &f64, y: &Vec<f64>| vec![y[1], 1000.0 * (1.0 - y[0] * y[0]) * y[1] - y[0]];
+    /// let options = SolverOptions::new().rtol(1e-6).atol(1e-6);
+    ///
+    /// let mut stepper = solver.auto_stepper(&van_der_pol, 0.0, vec![2.0, 0.0], 1e-4).with_options(&options);
+    /// stepper.advance_to(3000.0).unwrap();
+    /// assert!((stepper.y()[0] + 1.5106).abs() < 1e-2);
+    ///
+    /// let stats = stepper.stats();
+    /// assert!(stats.stiffness_detected.is_some() && stats.switches.len() >= 2);
+    /// for switch in &stats.switches {
+    ///     println!("Switched to the {} method at x = {}", if switch.to_stiff { "stiff" } else { "explicit" }, switch.x);
+    /// }
+    /// ```
+    fn auto_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, AutoSwitch<F>, T, F>;
+
+    /// Solve the system of ODEs, switching automatically between a non-stiff and a stiff method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration, including one absolute tolerance per component if needed.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`AutoSwitchODESysSolver::try_auto_solve_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, auto_switch::AutoSwitchODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // Robertson's chemical kinetics, which becomes stiff after a short transient
+    /// let robertson = |_x: &f64, y: &Vec<f64>| {
+    ///     vec![
+    ///         -0.04 * y[0] + 1e4 * y[1] * y[2],
+    ///         0.04 * y[0] - 1e4 * y[1] * y[2] - 3e7 * y[1] * y[1],
+    ///         3e7 * y[1] * y[1],
+    ///     ]
+    /// };
+    /// // The first step must be small enough for the explicit method to resolve the transient
+    /// let options = SolverOptions::new()
+    ///     .rtol(1e-6)
+    ///     .atol_per_component(vec![1e-8, 1e-12, 1e-8])
+    ///     .h0(1e-6)
+    ///     .output(Output::Points(vec![40.0, 1e5]));
+    ///
+    /// let solution = solver.auto_solve_with(&robertson, 0.0, vec![1.0, 0.0, 0.0], 1e5, &options);
+    /// let (_, y) = solution.last().unwrap();
+    /// assert!((y[0] - 1.786592e-2).abs() < 1e-5);
+    /// assert!(solution.stats.stiffness_detected.is_some());
+    /// assert!(solution.stats.accepted_steps < 2000);
+    /// ```
+    ///
+    /// The switches are reported in the statistics of the solution, here on a problem alternating stiff and non-stiff phases:
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, auto_switch::AutoSwitchODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // Van der Pol oscillator, stiff along its slow phases and non-stiff in its fast transitions
+    /// let van_der_pol = |_x: &f64, y: &Vec<f64>| vec![y[1], 1000.0 * (1.0 - y[0] * y[0]) * y[1] - y[0]];
+    /// let options = SolverOptions::new().rtol(1e-6).atol(1e-6).h0(1e-4).output(Output::Final);
+    ///
+    /// let solution = solver.auto_solve_with(&van_der_pol, 0.0, vec![2.0, 0.0], 3000.0, &options);
+    /// let switches = &solution.stats.switches;
+    /// assert!(switches.len() >= 2);
+    ///
+    /// // The first switch is the detection of the stiffness, and the method then alternates along increasing x
+    /// assert_eq!(solution.stats.stiffness_detected, Some(switches[0].x));
+    /// for (i, switch) in switches.iter().enumerate() {
+    ///     assert_eq!(switch.to_stiff, i % 2 == 0);
+    ///     assert!(switch.x > 0.0 && switch.x < 3000.0);
+    /// }
+    /// assert!(switches.windows(2).all(|pair| pair[0].x < pair[1].x));
+    /// ```
+    fn auto_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs, switching automatically between a non-stiff and a stiff method, with the settings of a
+    /// [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration, including one absolute tolerance per component if needed.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, auto_switch::AutoSwitchODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let options = SolverOptions::new().rtol(1e-9).atol(1e-9).max_steps(10).output(Output::Final);
+    ///
+    /// match solver.try_auto_solve_with(&oscillator, 0.0, vec![1.0, 0.0], 100.0, &options) {
+    ///     Ok(solution) => println!("Solution at x = 100: {:?}", solution.last()),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_auto_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError>;
+}
+
+// Implementing the automatic stiffness switching method for the system of ODEs Solver
+impl<F: Real, T: ODESYS<F>> AutoSwitchODESysSolver<T, F> for ODESysSolver {
+    /// Implementation of the automatic stiffness switching method to solve a system of ODEs.
+    ///
+    /// The integration starts with the Dormand–Prince 5(4) method, see
+    /// [`DormandPrinceODESysSolver`](super::dormand_prince_sys::DormandPrinceODESysSolver). After every step, the
+    /// difference between the derivatives of its last stage and of its end point, both at `x + h`, gives an estimate of
+    /// `|h * lambda|`, `lambda` being the dominant eigenvalue of the Jacobian. When it exceeds 3.25, close to the
+    /// boundary of the stability region, the step size is limited by the stability of the method rather than by its
+    /// accuracy; after 15 such steps without 6 non-stiff steps in between, the problem is considered stiff and the
+    /// integration goes on with the BDF method, see [`BDFODESysSolver`](super::bdf::BDFODESysSolver), restarted at order 1.
+    ///
+    /// While the BDF method is in use, its Jacobian is evaluated again every 20 steps, and its maximum absolute row sum
+    /// bounds the magnitude of the eigenvalues. When the step sizes chosen by the BDF method stay small enough for the
+    /// explicit method to be stable over 15 steps, the problem is no longer stiff and the integration goes back to the
+    /// explicit method, whose steps are much cheaper. Each switch is made at the start of a step, from the last accepted
+    /// point, and the dense output is the one of the method that took the step.
+    ///
+    /// # When to Use:
+    ///
+    /// Problems whose stiffness is not known in advance, or changes along the integration, such as relaxation
+    /// oscillations, combustion or chemical kinetics with fast transients.
+    ///
+    /// # Pros and Cons:
+    /// - Pros: No need to know whether the problem is stiff, cheap explicit steps over the non-stiff phases and steps
+    ///   limited by the accuracy only over the stiff ones.
+    /// - Cons: A few wasted steps around each switch while the stiffness is detected, and the dense linear algebra of
+    ///   the BDF method when the problem is stiff.
+    ///
+    /// # Stability Analysis:
+    ///
+    /// The explicit method is only used while its step sizes are stable, and the BDF method takes over with its A-stable
+    /// and A(alpha)-stable formulas when they are not, see [`BDFODESysSolver::bdf_solve`](super::bdf::BDFODESysSolver::bdf_solve).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, auto_switch::AutoSwitchODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // Stiff problem: after a fast transient, the solution follows cos(x) closely
+    /// let stiff = |x: &f64, y: &Vec<f64>| vec![-1000.0 * (y[0] - x.cos())];
+    ///
+    /// let solution = solver.auto_solve_traj(&stiff, 0.0, vec![0.0], 10.0, 1e-3);
+    /// let (_, y) = solution.last().unwrap();
+    /// let exact = (1e6 * 10.0f64.cos() + 1e3 * 10.0f64.sin()) / (1e6 + 1.0);
+    /// assert!((y[0] - exact).abs() < 1e-4);
+    ///
+    /// // The explicit method alone would need about 3000 steps
+    /// assert_eq!(solution.stats.switches.len(), 1);
+    /// assert!(solution.stats.switches[0].to_stiff);
+    /// assert!(solution.stats.accepted_steps < 1000);
+    /// ```
+    fn auto_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        expect_solved(self.try_auto_solve(ode, x, y, x_target, h))
+    }
+
+    /// Implementation of the automatic stiffness switching method returning the whole trajectory of the system of ODEs.
+    ///
+    /// See [`AutoSwitchODESysSolver::auto_solve`] for the details of the method.
+    fn auto_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F> {
+        expect_solved(solve_with(self.auto_stepper(ode, x, y, h), x_target, &Output::Steps))
+    }
+
+    /// Implementation of the automatic stiffness switching method reporting failures as a [`SolverError`].
+    ///
+    /// See [`AutoSwitchODESysSolver::auto_solve`] for the details of the method.
+    fn try_auto_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError> {
+        let mut stepper = self.auto_stepper(ode, x, y, h);
+        stepper.advance_with(x_target, |_, _| {})?;
+        Ok(stepper.into_y())
+    }
+
+    /// Implementation of the automatic stiffness switching method as an [`AdaptiveSysStepper`].
+    ///
+    /// See [`AutoSwitchODESysSolver::auto_solve`] for the details of the method.
+    fn auto_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, AutoSwitch<F>, T, F> {
+        let method = AutoSwitch::new(y.len());
+        AdaptiveSysStepper::new(ode, method, x, y, h).stepping_past_targets()
+    }
+
+    /// Implementation of the automatic stiffness switching method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`AutoSwitchODESysSolver::auto_solve`] for the details of the method.
+    fn auto_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F> {
+        expect_solved(self.try_auto_solve_with(ode, x, y, x_target, options))
+    }
+
+    /// Implementation of the automatic stiffness switching method with the settings of a [`SolverOptions`], reporting
+    /// failures as a [`SolverError`].
+    ///
+    /// See [`AutoSwitchODESysSolver::auto_solve`] for the details of the method.
+    fn try_auto_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError> {
+        options.check(x, x_target, y.len())?;
+        let stepper = self.auto_stepper(ode, x, y, options.initial_step(x, x_target));
+        solve_with(stepper.with_options(options), x_target, &options.output)
+    }
+}
+
+/// Value of `|h * lambda|` above which a step of the explicit method is considered stiff, close to the boundary of its
+/// stability region on the negative real axis.
+const STIFF_RATIO: f64 = 3.25;
+
+/// Number of stiff steps after which the integration switches to the stiff method, and number of consecutive steps of
+/// the stiff method small enough for the explicit one after which it switches back.
+const SWITCH_STEPS: usize = 15;
+
+/// Number of consecutive non-stiff steps resetting the count of stiff steps of the explicit method.
+const NONSTIFF_STEPS: usize = 6;
+
+/// Number of steps of the stiff method after which its Jacobian is evaluated again, to follow the stiffness.
+const JACOBIAN_STEPS: usize = 20;
+
+/// State of the automatic stiffness switching method for a system of ODEs, parametrizing the [`AdaptiveSysStepper`] of
+/// the method.
+///
+/// It holds both the [`Dopri5`] and the [`Bdf`] methods, only one of which takes the steps, and the counters deciding
+/// when to switch from one to the other.
+pub struct AutoSwitch<F> {
+    explicit: Dopri5<F>,
+    bdf: Bdf<F>,
+    /// Whether the BDF method took the last attempted step.
+    stiff: bool,
+    /// Whether the next step changes the method, as decided after the last accepted step.
+    switch_pending: bool,
+    /// Estimate of `|h * lambda|` over the last attempted step of the explicit method.
+    stiffness_ratio: F,
+    /// Number of accepted steps of the explicit method found stiff, reset after 6 consecutive non-stiff steps.
+    stiff_steps: usize,
+    nonstiff_steps: usize,
+    /// Number of consecutive accepted steps of the BDF method small enough for the explicit method to be stable.
+    explicit_steps: usize,
+    /// Number of accepted steps of the BDF method since its Jacobian was last evaluated here, and the bound on its eigenvalues.
+    jacobian_age: usize,
+    jacobian_norm: F,
+    /// Size of the last attempted step.
+    h: F,
+    /// Switches made since the last call of `record_work`.
+    new_switches: Vec<MethodSwitch<F>>,
+}
+
+impl<F: Real> AutoSwitch<F> {
+    /// Creates the state of the method for a system of dimension `dim`, starting with the explicit method.
+    pub fn new(dim: usize) -> Self {
+        AutoSwitch {
+            explicit: Dopri5::with_dim(dim),
+            bdf: Bdf::new(dim),
+            stiff: false,
+            switch_pending: false,
+            stiffness_ratio: F::zero(),
+            stiff_steps: 0,
+            nonstiff_steps: 0,
+            explicit_steps: 0,
+            jacobian_age: 0,
+            jacobian_norm: F::zero(),
+            h: F::zero(),
+            new_switches: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if the next step is taken by the stiff BDF method, and `false` if it is taken by the explicit one.
+    pub fn is_using_bdf(&self) -> bool {
+        self.stiff != self.switch_pending
+    }
+
+    /// Changes the method taking the next steps at `x`, the new one starting afresh from the current point.
+    fn switch(&mut self, x: F) {
+        self.stiff = !self.stiff;
+        self.switch_pending = false;
+        self.stiff_steps = 0;
+        self.nonstiff_steps = 0;
+        self.explicit_steps = 0;
+        self.jacobian_age = 0;
+        if self.stiff {
+            self.bdf.restart();
+        } else {
+            self.explicit.restart();
+        }
+        self.new_switches.push(MethodSwitch { x, to_stiff: self.stiff });
+    }
+}
+
+impl<F: Real> EmbeddedSysMethod<F> for AutoSwitch<F> {
+    fn error_order(&self) -> usize {
+        if self.stiff { self.bdf.error_order() } else { self.explicit.error_order() }
+    }
+
+    fn step<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F], h: F, y_next: &mut [F], error: &mut [F]) {
+        if self.switch_pending {
+            self.switch(x);
+        }
+        self.h = h;
+
+        if !self.stiff {
+            self.explicit.step(ode, x, y, h, y_next, error);
+            self.stiffness_ratio = self.explicit.stiffness_ratio(h, y_next);
+            return;
+        }
+
+        // The BDF method only evaluates its Jacobian when the Newton iteration fails, which it rarely does once the
+        // problem is no longer stiff, so that it is refreshed here to notice it. It is evaluated when the method starts.
+        if self.jacobian_age >= JACOBIAN_STEPS {
+            self.bdf.refresh_jacobian(ode, x, y);
+            self.jacobian_age = 0;
+        }
+        self.bdf.step(ode, x, y, h, y_next, error);
+        if self.jacobian_age == 0 {
+            self.jacobian_norm = self.bdf.jacobian_norm();
+        }
+    }
+
+    fn accept(&mut self) {
+        if !self.stiff {
+            self.explicit.accept();
+            if self.stiffness_ratio > lit(STIFF_RATIO) {
+                self.stiff_steps += 1;
+                self.nonstiff_steps = 0;
+            } else {
+                self.nonstiff_steps += 1;
+                if self.nonstiff_steps == NONSTIFF_STEPS {
+                    self.stiff_steps = 0;
+                }
+            }
+            self.switch_pending = self.stiff_steps >= SWITCH_STEPS;
+            return;
+        }
+
+        self.bdf.accept();
+        self.jacobian_age += 1;
+        if self.h.abs() * self.jacobian_norm <= lit(STIFF_RATIO) {
+            self.explicit_steps += 1;
+        } else {
+            self.explicit_steps = 0;
+        }
+        self.switch_pending = self.explicit_steps >= SWITCH_STEPS;
+    }
+
+    fn error_norm(&self, error: &[F], scale: &[F]) -> F {
+        if self.stiff { self.bdf.error_norm(error, scale) } else { self.explicit.error_norm(error, scale) }
+    }
+
+    fn step_factor(&self, error: F) -> Option<F> {
+        if self.stiff { self.bdf.step_factor(error) } else { self.explicit.step_factor(error) }
+    }
+
+    fn set_tolerances(&mut self, rtol: F, atol: &[F]) {
+        self.bdf.set_tolerances(rtol, atol);
+    }
+
+    fn record_work(&mut self, stats: &mut Stats<F>) {
+        self.bdf.record_work(stats);
+        stats.switches.append(&mut self.new_switches);
+    }
+
+    fn is_stiff(&self) -> bool {
+        self.is_using_bdf()
+    }
+}
+
+impl<F: Real> DenseSysMethod<F> for AutoSwitch<F> {
+    fn prepare<T: ODESYS<F>>(&mut self, ode: &T, x: F, h: F, y_prev: &[F], y: &[F]) {
+        if self.stiff {
+            self.bdf.prepare(ode, x, h, y_prev, y);
+        } else {
+            self.explicit.prepare(ode, x, h, y_prev, y);
+        }
+    }
+
+    fn interpolate(&self, theta: F, h: F, y_prev: &[F], y: &[F], out: &mut [F]) {
+        if self.stiff {
+            self.bdf.interpolate(theta, h, y_prev, y, out);
+        } else {
+            self.explicit.interpolate(theta, h, y_prev, y, out);
+        }
+    }
+}
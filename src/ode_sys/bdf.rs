@@ -0,0 +1,761 @@
+//! Backward differentiation formulas (BDF) for solving stiff systems of ordinary differential equations (ODEs).
+use super::{ODESYS, ODESysSolver, jacobian, solve_with};
+use super::adaptive::{AdaptiveSysStepper, DenseSysMethod, EmbeddedSysMethod};
+use crate::{Output, Real, Solution, SolverError, SolverOptions, Stats};
+use crate::controller::{rms_norm, scaled_error};
+use crate::error::expect_solved;
+use crate::linalg::Lu;
+use crate::scalar::lit;
+
+/// BDF method for solving systems of Ordinary Differential Equations (ODEs).
+///
+/// This trait defines the [backward differentiation formulas](https://en.wikipedia.org/wiki/Backward_differentiation_formula)
+/// of orders 1 to 5 for solving stiff systems of ordinary differential equations, with variable step size and order.
+/// Each step solves the implicit formula by a Newton iteration whose Jacobian is reused over many steps. The step size
+/// `h` given to the methods is the initial one, which is then adapted so that the weighted root mean square of the
+/// local error estimates stays within the tolerances, see [`AdaptiveSysStepper`]. Without a [`SolverOptions`], the
+/// tolerances are the defaults of [`SolverOptions::new`].
+///
+/// The Jacobian is the one of [`ODESYS::jacobian_into`] when the system provides it, and a finite-difference
+/// approximation otherwise.
+pub trait BDFODESysSolver<T: ODESYS<F>, F: Real = f64> {
+    /// Solve the system of ODEs using the BDF method.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`BDFODESysSolver::try_bdf_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, bdf::BDFODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.bdf_solve(&my_ode_system, x0, y0, x_target, h);
+    /// assert!((result[0] - 0.5f64.exp()).abs() < 1e-4);
+    /// println!("Solution at x = {}: {:?}", x_target, result);
+    /// ```
+    fn bdf_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F>;
+
+    /// Solve the system of ODEs using the BDF method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from the initial x up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`BDFODESysSolver::try_bdf_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, bdf::BDFODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    ///
+    /// // The accepted points are as far apart as the tolerances allow
+    /// let solution = solver.bdf_solve_traj(&oscillator, 0.0, vec![1.0, 0.0], 10.0, 0.1);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn bdf_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the BDF method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`, or the [`SolverError`] that stopped the integration, such as a
+    /// [`SolverError::StepSizeUnderflow`] when the solution blows up.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode_sys::{ODESysSolver, bdf::BDFODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // The first component blows up at x = 1
+    /// let blow_up = |_x: &f64, y: &Vec<f64>| vec![y[0] * y[0], -y[1]];
+    ///
+    /// match solver.try_bdf_solve(&blow_up, 0.0, vec![1.0, 1.0], 2.0, 0.1) {
+    ///     Ok(result) => println!("Solution at x = 2: {:?}", result),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_bdf_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError>;
+
+    /// Create a stepper integrating the system of ODEs with the BDF method, one adaptive step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The initial step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// An [`AdaptiveSysStepper`] holding the current state, starting from the initial `(x, y)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, bdf::BDFODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let mut stepper = solver.bdf_stepper(&oscillator, 0.0, vec![1.0, 0.0], 0.1);
+    ///
+    /// for state in stepper.by_ref().take(5) {
+    ///     let (x, y) = state.unwrap();
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// println!("Next step size: {}", stepper.h());
+    /// ```
+    fn bdf_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, Bdf<F>, T, F>;
+
+    /// Solve the system of ODEs using the BDF method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration, including one absolute tolerance per component if needed.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`BDFODESysSolver::try_bdf_solve_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, bdf::BDFODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // Species concentrations of very different magnitudes
+    /// let kinetics = |_x: &f64, y: &Vec<f64>| vec![-0.04 * y[0], 0.04 * y[0] - 10.0 * y[1]];
+    /// let options = SolverOptions::new()
+    ///     .rtol(1e-6)
+    ///     .atol_per_component(vec![1e-8, 1e-12])
+    ///     .output(Output::Points(vec![1.0, 10.0, 100.0]));
+    ///
+    /// let solution = solver.bdf_solve_with(&kinetics, 0.0, vec![1.0, 0.0], 100.0, &options);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn bdf_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the BDF method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration, including one absolute tolerance per component if needed.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, bdf::BDFODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let options = SolverOptions::new().rtol(1e-9).atol(1e-9).max_steps(10).output(Output::Final);
+    ///
+    /// match solver.try_bdf_solve_with(&oscillator, 0.0, vec![1.0, 0.0], 100.0, &options) {
+    ///     Ok(solution) => println!("Solution at x = 100: {:?}", solution.last()),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_bdf_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError>;
+}
+
+// Implementing the BDF method for the system of ODEs Solver
+impl<F: Real, T: ODESYS<F>> BDFODESysSolver<T, F> for ODESysSolver {
+    /// Implementation of the BDF method to solve a system of ODEs.
+    ///
+    /// The formula of order k defines the new value `y_{n+1}` as the one for which the polynomial interpolating it and
+    /// the last k accepted values has the derivative `f(x_{n+1}, y_{n+1})` at `x_{n+1}`. The history is kept as the
+    /// backward differences of the solution on a uniform grid: when the step size changes, the interpolating polynomial
+    /// is evaluated on the new grid, so that the leading coefficient of the formula only depends on the order (the
+    /// quasi-constant step size implementation of Shampine and Reichelt).
+    ///
+    /// The implicit equation is solved by a simplified Newton iteration, starting from the extrapolation of the history,
+    /// whose matrix `I - h / alpha_k * J` is factorized once and reused as long as the step size and the order stay the
+    /// same. The Jacobian `J` is only recomputed when the iteration fails to converge; if it fails again with a fresh
+    /// one, the step is retried with half the step size. The local error is estimated from the difference between the
+    /// prediction and the solution. The order starts at 1 and is reconsidered after k + 1 steps of the same size, by
+    /// comparing the error estimates of the orders k - 1, k and k + 1, the step size being kept constant in between.
+    /// The solution between two accepted points is given by the interpolating polynomial, see
+    /// [`AdaptiveSysStepper::interpolate`].
+    ///
+    /// # When to Use:
+    ///
+    /// The method of choice for large stiff systems, such as chemical kinetics or spatially discretized parabolic
+    /// equations, solved to moderate tolerances.
+    ///
+    /// # Pros and Cons:
+    /// - Pros: Steps limited by the accuracy only, whatever the stiffness, with one evaluation of the system and one
+    ///   linear solve per Newton iteration, and few Jacobians.
+    /// - Cons: Dense linear algebra in the dimension of the system, and poor efficiency on oscillatory problems with
+    ///   eigenvalues close to the imaginary axis, where the orders above 2 are not stable.
+    ///
+    /// # Stability Analysis:
+    ///
+    /// The orders 1 and 2 are A-stable, and the orders 3 to 5 are A(alpha)-stable with angles of 86, 73 and 51 degrees:
+    /// stiff components decaying quickly are damped whatever the step size.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverOptions;
+    /// # use damndiff::ode_sys::{ODESysSolver, bdf::BDFODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // Robertson's chemical kinetics, with rate constants from 0.04 to 3e7
+    /// let robertson = |_x: &f64, y: &Vec<f64>| {
+    ///     vec![
+    ///         -0.04 * y[0] + 1e4 * y[1] * y[2],
+    ///         0.04 * y[0] - 1e4 * y[1] * y[2] - 3e7 * y[1] * y[1],
+    ///         3e7 * y[1] * y[1],
+    ///     ]
+    /// };
+    /// let options = SolverOptions::new().rtol(1e-6).atol_per_component(vec![1e-8, 1e-12, 1e-8]);
+    ///
+    /// let mut stepper = solver.bdf_stepper(&robertson, 0.0, vec![1.0, 0.0, 0.0], 1e-6).with_options(&options);
+    /// let y = stepper.advance_to(1e5).unwrap().to_vec();
+    /// assert!((y[0] - 1.786592e-2).abs() < 1e-6);
+    /// assert!((y[0] + y[1] + y[2] - 1.0).abs() < 1e-8);
+    ///
+    /// // An explicit method would need hundreds of millions of steps
+    /// let stats = stepper.stats();
+    /// assert!(stats.accepted_steps < 1000);
+    /// println!("{} steps, {} Jacobians, {} linear solves", stats.accepted_steps, stats.jacobian_evals, stats.linear_solves);
+    /// ```
+    fn bdf_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        expect_solved(self.try_bdf_solve(ode, x, y, x_target, h))
+    }
+
+    /// Implementation of the BDF method returning the whole trajectory of the system of ODEs.
+    ///
+    /// See [`BDFODESysSolver::bdf_solve`] for the details of the method.
+    fn bdf_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F> {
+        expect_solved(solve_with(self.bdf_stepper(ode, x, y, h), x_target, &Output::Steps))
+    }
+
+    /// Implementation of the BDF method reporting failures as a [`SolverError`].
+    ///
+    /// See [`BDFODESysSolver::bdf_solve`] for the details of the method.
+    fn try_bdf_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError> {
+        let mut stepper = self.bdf_stepper(ode, x, y, h);
+        stepper.advance_with(x_target, |_, _| {})?;
+        Ok(stepper.into_y())
+    }
+
+    /// Implementation of the BDF method as an [`AdaptiveSysStepper`].
+    ///
+    /// See [`BDFODESysSolver::bdf_solve`] for the details of the method.
+    fn bdf_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, Bdf<F>, T, F> {
+        let method = Bdf::new(y.len());
+        AdaptiveSysStepper::new(ode, method, x, y, h).stepping_past_targets()
+    }
+
+    /// Implementation of the BDF method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`BDFODESysSolver::bdf_solve`] for the details of the method.
+    fn bdf_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F> {
+        expect_solved(self.try_bdf_solve_with(ode, x, y, x_target, options))
+    }
+
+    /// Implementation of the BDF method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`BDFODESysSolver::bdf_solve`] for the details of the method.
+    fn try_bdf_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError> {
+        options.check(x, x_target, y.len())?;
+        let stepper = self.bdf_stepper(ode, x, y, options.initial_step(x, x_target));
+        solve_with(stepper.with_options(options), x_target, &options.output)
+    }
+}
+
+/// Maximum order of the BDF method.
+const MAX_ORDER: usize = 5;
+
+/// Maximum number of Newton iterations in a step.
+const NEWTON_MAX_ITERATIONS: usize = 4;
+
+/// Smallest factor by which the step size shrinks after a rejected step.
+const MIN_FACTOR: f64 = 0.2;
+
+/// Largest factor by which the step size grows when the order is reconsidered.
+const MAX_FACTOR: f64 = 10.0;
+
+/// State of the BDF method for a system of ODEs, parametrizing the [`AdaptiveSysStepper`] of the method.
+///
+/// It keeps the backward differences of the solution on a grid of the current step size, the Jacobian and the
+/// factorized Newton matrix, which are reused from one step to the next.
+#[derive(Debug, Clone)]
+pub struct Bdf<F> {
+    order: usize,
+    /// Order of the last accepted step, whose interpolating polynomial gives the dense output.
+    step_order: usize,
+    /// Backward differences `D[j] = nabla^j y` of the solution at `x_d`, for a grid of step size `h_d`, scaled so that
+    /// the extrapolation to the next point is their sum.
+    differences: Vec<Vec<F>>,
+    x_d: F,
+    h_d: F,
+    /// Whether the differences have been initialized at the start of the integration.
+    started: bool,
+    /// Number of accepted steps since the last change of the step size or of the order.
+    equal_steps: usize,
+    /// Sums `gamma_k = 1 + 1/2 + ... + 1/k` giving the leading coefficients, and the error constants `1 / (k + 1)`.
+    gamma: [F; MAX_ORDER + 2],
+    error_constants: [F; MAX_ORDER + 2],
+    jacobian: Vec<F>,
+    /// Whether the Jacobian has been evaluated at the current step, so that recomputing it cannot help the iteration.
+    jacobian_current: bool,
+    matrix: Vec<F>,
+    lu: Lu<F>,
+    /// Coefficient `c` of the factorized matrix `I - c * J`, `None` when it must be factorized again.
+    lu_coefficient: Option<F>,
+    rtol: F,
+    atol: Vec<F>,
+    /// Buffers of the Newton iteration.
+    y_predict: Vec<F>,
+    psi: Vec<F>,
+    correction: Vec<F>,
+    y_new: Vec<F>,
+    f: Vec<F>,
+    dy: Vec<F>,
+    scale: Vec<F>,
+    /// Outcome of the last attempted step: its end point, whether the iteration converged and how many iterations it took.
+    x_new: F,
+    converged: bool,
+    iterations: usize,
+    /// Factor proposed for the step size after the last accepted step.
+    next_factor: F,
+    jacobian_evals: usize,
+    linear_solves: usize,
+}
+
+impl<F: Real> Bdf<F> {
+    /// Creates the state of the BDF method for a system of dimension `dim`, starting at order 1.
+    pub fn new(dim: usize) -> Self {
+        let mut gamma = [F::zero(); MAX_ORDER + 2];
+        let mut error_constants = [F::zero(); MAX_ORDER + 2];
+        for k in 1..MAX_ORDER + 2 {
+            gamma[k] = gamma[k - 1] + F::one() / lit(k as f64);
+            error_constants[k] = F::one() / lit((k + 1) as f64);
+        }
+
+        Bdf {
+            order: 1,
+            step_order: 1,
+            differences: vec![vec![F::zero(); dim]; MAX_ORDER + 3],
+            x_d: F::zero(),
+            h_d: F::zero(),
+            started: false,
+            equal_steps: 0,
+            gamma,
+            error_constants,
+            jacobian: vec![F::zero(); dim * dim],
+            jacobian_current: false,
+            matrix: vec![F::zero(); dim * dim],
+            lu: Lu::new(dim),
+            lu_coefficient: None,
+            rtol: F::zero(),
+            atol: vec![F::zero(); dim],
+            y_predict: vec![F::zero(); dim],
+            psi: vec![F::zero(); dim],
+            correction: vec![F::zero(); dim],
+            y_new: vec![F::zero(); dim],
+            f: vec![F::zero(); dim],
+            dy: vec![F::zero(); dim],
+            scale: vec![F::zero(); dim],
+            x_new: F::zero(),
+            converged: false,
+            iterations: 0,
+            next_factor: F::one(),
+            jacobian_evals: 0,
+            linear_solves: 0,
+        }
+    }
+
+    /// Returns the order of the formula used for the next step.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Starts the integration at `(x, y)` with order 1, evaluating the system and its Jacobian there.
+    fn start<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F], h: F) {
+        self.differences[0].copy_from_slice(y);
+        ode.eval_into(x, y, &mut self.f);
+        for (d, f) in self.differences[1].iter_mut().zip(&self.f) {
+            *d = h * *f;
+        }
+        for d in &mut self.differences[2..] {
+            d.fill(F::zero());
+        }
+
+        self.y_new.copy_from_slice(y);
+        self.update_jacobian(ode, x);
+        self.x_d = x;
+        self.h_d = h;
+        self.order = 1;
+        self.step_order = 1;
+        self.equal_steps = 0;
+        self.started = true;
+    }
+
+    /// Makes the next step start the integration again at order 1, discarding the history.
+    pub(crate) fn restart(&mut self) {
+        self.started = false;
+    }
+
+    /// Evaluates the Jacobian at the current point `(x, y)`, where the last accepted step ended, instead of waiting for
+    /// the Newton iteration to fail.
+    pub(crate) fn refresh_jacobian<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F]) {
+        self.y_new.copy_from_slice(y);
+        ode.eval_into(x, y, &mut self.f);
+        self.update_jacobian(ode, x);
+    }
+
+    /// Returns the maximum absolute row sum of the last evaluated Jacobian, a bound on the magnitude of its eigenvalues.
+    pub(crate) fn jacobian_norm(&self) -> F {
+        let dim = self.y_new.len();
+        let mut norm = F::zero();
+        for row in self.jacobian.chunks(dim.max(1)) {
+            norm = norm.max(row.iter().fold(F::zero(), |sum, j| sum + j.abs()));
+        }
+        norm
+    }
+
+    /// Evaluates the Jacobian at `(x, y_new)`, `f` holding the derivatives there.
+    fn update_jacobian<T: ODESYS<F>>(&mut self, ode: &T, x: F) {
+        jacobian(ode, x, &mut self.y_new, &self.f, &mut self.jacobian, &mut self.dy);
+        self.jacobian_evals += 1;
+        self.jacobian_current = true;
+        self.lu_coefficient = None;
+    }
+
+    /// Rescales the differences of the current order to a grid whose step size is `factor` times the current one.
+    fn rescale(&mut self, factor: F) {
+        let k = self.order;
+        let r = rescaling_matrix(k, factor);
+        let u = rescaling_matrix(k, F::one());
+
+        // D <- (R U)^T D, computed from a copy of the differences involved
+        let ru: Vec<Vec<F>> = r
+            .iter()
+            .map(|row| (0..=k).map(|j| row.iter().zip(&u).fold(F::zero(), |sum, (r, u)| sum + *r * u[j])).collect())
+            .collect();
+        let old: Vec<Vec<F>> = self.differences[..=k].to_vec();
+        for (j, d) in self.differences[..=k].iter_mut().enumerate() {
+            d.fill(F::zero());
+            for (ru, old) in ru.iter().zip(&old) {
+                if ru[j] != F::zero() {
+                    for (d, old) in d.iter_mut().zip(old) {
+                        *d += ru[j] * *old;
+                    }
+                }
+            }
+        }
+        self.h_d *= factor;
+        self.equal_steps = 0;
+        self.lu_coefficient = None;
+    }
+
+    /// Solves the BDF equation of the current order by simplified Newton iteration, with the matrix `I - c * J`.
+    ///
+    /// The solution is left in `y_new` and its difference to the prediction in `correction`.
+    fn newton<T: ODESYS<F>>(&mut self, ode: &T, c: F) {
+        let tolerance = (lit::<F>(10.0) * F::epsilon() / self.rtol).max(lit::<F>(0.03).min(self.rtol.sqrt()));
+        self.y_new.copy_from_slice(&self.y_predict);
+        self.correction.fill(F::zero());
+        self.converged = false;
+
+        let mut last_norm: Option<F> = None;
+        for iteration in 0..NEWTON_MAX_ITERATIONS {
+            self.iterations = iteration + 1;
+            ode.eval_into(self.x_new, &self.y_new, &mut self.f);
+            if !self.f.iter().all(|f| f.is_finite()) {
+                return;
+            }
+
+            for i in 0..self.dy.len() {
+                self.dy[i] = c * self.f[i] - self.psi[i] - self.correction[i];
+            }
+            self.lu.solve(&mut self.dy);
+            self.linear_solves += 1;
+            let norm = rms_norm(&self.dy, &self.scale);
+
+            let rate = last_norm.map(|last| norm / last);
+            if let Some(rate) = rate {
+                let remaining = (NEWTON_MAX_ITERATIONS - iteration) as i32;
+                if rate >= F::one() || rate.powi(remaining) / (F::one() - rate) * norm > tolerance {
+                    return;
+                }
+            }
+
+            for i in 0..self.dy.len() {
+                self.y_new[i] += self.dy[i];
+                self.correction[i] += self.dy[i];
+            }
+            if norm == F::zero() || rate.is_some_and(|rate| rate / (F::one() - rate) * norm < tolerance) {
+                self.converged = true;
+                return;
+            }
+            last_norm = Some(norm);
+        }
+    }
+
+    /// Returns the weighted root mean square of `scale_by * D[index]` over the tolerances at the end of the last step.
+    fn difference_norm(&self, index: usize, scale_by: F) -> F {
+        let mut sum = F::zero();
+        for (i, d) in self.differences[index].iter().enumerate() {
+            let scaled = scaled_error(scale_by * *d, self.atol[i] + self.rtol * self.y_new[i].abs());
+            sum += scaled * scaled;
+        }
+        (sum / lit(self.y_new.len().max(1) as f64)).sqrt()
+    }
+
+    /// Safety factor applied to the optimal step size, smaller when the Newton iteration needed many iterations.
+    fn safety(&self) -> F {
+        let iterations = lit::<F>((2 * NEWTON_MAX_ITERATIONS + 1) as f64);
+        lit::<F>(0.9) * iterations / lit((2 * NEWTON_MAX_ITERATIONS + self.iterations) as f64)
+    }
+}
+
+impl<F: Real> EmbeddedSysMethod<F> for Bdf<F> {
+    fn error_order(&self) -> usize {
+        self.order
+    }
+
+    fn step<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F], h: F, y_next: &mut [F], error: &mut [F]) {
+        if !self.started || x != self.x_d {
+            self.start(ode, x, y, h);
+        }
+        if h != self.h_d {
+            self.rescale(h / self.h_d);
+        }
+
+        // Predict by extrapolating the history, and gather its part of the BDF equation
+        let k = self.order;
+        let alpha = self.gamma[k];
+        for i in 0..y.len() {
+            let mut predict = F::zero();
+            let mut psi = F::zero();
+            for j in 0..=k {
+                predict += self.differences[j][i];
+                psi += self.gamma[j] * self.differences[j][i];
+            }
+            self.y_predict[i] = predict;
+            self.psi[i] = psi / alpha;
+            self.scale[i] = self.atol[i] + self.rtol * predict.abs();
+        }
+
+        self.x_new = x + h;
+        let c = h / alpha;
+        loop {
+            if self.lu_coefficient != Some(c) {
+                for (index, m) in self.matrix.iter_mut().enumerate() {
+                    let diagonal = if index % (y.len() + 1) == 0 { F::one() } else { F::zero() };
+                    *m = diagonal - c * self.jacobian[index];
+                }
+                if self.lu.factor(&self.matrix) {
+                    self.lu_coefficient = Some(c);
+                    self.newton(ode, c);
+                } else {
+                    self.lu_coefficient = None;
+                    self.converged = false;
+                }
+            } else {
+                self.newton(ode, c);
+            }
+
+            if self.converged || self.jacobian_current {
+                break;
+            }
+            // Retry with a Jacobian at the predicted point
+            self.y_new.copy_from_slice(&self.y_predict);
+            ode.eval_into(self.x_new, &self.y_new, &mut self.f);
+            self.update_jacobian(ode, self.x_new);
+        }
+
+        if !self.converged {
+            y_next.copy_from_slice(y);
+            error.fill(F::zero());
+            return;
+        }
+        y_next.copy_from_slice(&self.y_new);
+        for (error, correction) in error.iter_mut().zip(&self.correction) {
+            *error = self.error_constants[k] * *correction;
+        }
+    }
+
+    fn accept(&mut self) {
+        let k = self.order;
+        self.step_order = k;
+        self.x_d = self.x_new;
+        self.jacobian_current = false;
+        self.equal_steps += 1;
+
+        // Update the differences with the correction, from the highest one down to the solution itself
+        for i in 0..self.correction.len() {
+            let correction = self.correction[i];
+            self.differences[k + 2][i] = correction - self.differences[k + 1][i];
+            self.differences[k + 1][i] = correction;
+            for j in (0..=k).rev() {
+                let next = self.differences[j + 1][i];
+                self.differences[j][i] += next;
+            }
+        }
+
+        self.next_factor = F::one();
+        if self.equal_steps < k + 1 {
+            return;
+        }
+
+        // Choose the order whose error estimate allows the largest step
+        let error = self.difference_norm(k + 1, self.error_constants[k]);
+        let lower = if k > 1 { self.difference_norm(k, self.error_constants[k - 1]) } else { F::infinity() };
+        let higher = if k < MAX_ORDER { self.difference_norm(k + 2, self.error_constants[k + 1]) } else { F::infinity() };
+        let factor = |norm: F, order: usize| {
+            if norm == F::zero() {
+                F::infinity()
+            } else {
+                norm.powf(-F::one() / lit((order + 1) as f64))
+            }
+        };
+
+        let candidates = [(lower, k.saturating_sub(1)), (error, k), (higher, k + 1)];
+        let mut best = (factor(error, k), k);
+        for (norm, order) in candidates {
+            let candidate = factor(norm, order);
+            if norm.is_finite() && candidate > best.0 {
+                best = (candidate, order);
+            }
+        }
+        self.order = best.1;
+        self.next_factor = (self.safety() * best.0).min(lit(MAX_FACTOR));
+        self.equal_steps = 0;
+    }
+
+    fn error_norm(&self, error: &[F], scale: &[F]) -> F {
+        if !self.converged {
+            return F::infinity();
+        }
+
+        rms_norm(error, scale)
+    }
+
+    fn step_factor(&self, error: F) -> Option<F> {
+        if !self.converged {
+            return Some(lit(0.5));
+        }
+        if error > F::one() {
+            let factor = self.safety() * error.powf(-F::one() / lit((self.order + 1) as f64));
+            return Some(factor.max(lit(MIN_FACTOR)));
+        }
+        Some(self.next_factor)
+    }
+
+    fn set_tolerances(&mut self, rtol: F, atol: &[F]) {
+        self.rtol = rtol;
+        self.atol.copy_from_slice(atol);
+    }
+
+    fn record_work(&mut self, stats: &mut Stats<F>) {
+        stats.jacobian_evals += self.jacobian_evals;
+        stats.linear_solves += self.linear_solves;
+        self.jacobian_evals = 0;
+        self.linear_solves = 0;
+    }
+}
+
+impl<F: Real> DenseSysMethod<F> for Bdf<F> {
+    fn interpolate(&self, theta: F, _h: F, _y_prev: &[F], _y: &[F], out: &mut [F]) {
+        out.copy_from_slice(&self.differences[0]);
+
+        // Newton form of the polynomial interpolating the solution on the grid ending at the last point
+        let mut product = F::one();
+        for j in 0..self.step_order {
+            product *= (theta - F::one() + lit(j as f64)) / lit((j + 1) as f64);
+            for (out, d) in out.iter_mut().zip(&self.differences[j + 1]) {
+                *out += product * *d;
+            }
+        }
+    }
+}
+
+/// Returns the matrix `R` of order `order` changing the backward differences of a grid of step size `h` into the ones
+/// of a grid of step size `factor * h`, up to the multiplication by the same matrix for a factor 1.
+fn rescaling_matrix<F: Real>(order: usize, factor: F) -> Vec<Vec<F>> {
+    let mut r = vec![vec![F::one(); order + 1]; order + 1];
+    for i in 1..=order {
+        let (previous, current) = r.split_at_mut(i);
+        for (j, (value, previous)) in current[0].iter_mut().zip(&previous[i - 1]).enumerate() {
+            let m = if j == 0 {
+                F::zero()
+            } else {
+                (lit::<F>((i - 1) as f64) - factor * lit(j as f64)) / lit(i as f64)
+            };
+            *value = *previous * m;
+        }
+    }
+    r
+}
+
@@ -0,0 +1,288 @@
+//! Bogacki–Shampine method for solving systems of ordinary differential equations (ODEs).
+use super::{ODESYS, ODESysSolver, solve_with};
+use super::adaptive::AdaptiveSysStepper;
+use super::explicit_rk_sys::ExplicitRk;
+use crate::{ButcherTableau, Output, Real, Solution, SolverError, SolverOptions};
+use crate::error::expect_solved;
+
+/// Bogacki–Shampine method for solving systems of Ordinary Differential Equations (ODEs).
+///
+/// This trait defines the adaptive 3(2) [Bogacki–Shampine method](https://en.wikipedia.org/wiki/Bogacki%E2%80%93Shampine_method)
+/// for solving systems of ordinary differential equations. The step size `h` given to the methods is the initial one,
+/// which is then adapted so that the weighted root mean square of the local error estimates stays within the tolerances,
+/// see [`AdaptiveSysStepper`]. Without a [`SolverOptions`], the tolerances are the defaults of [`SolverOptions::new`].
+pub trait BShampineODESysSolver<T: ODESYS<F>, F: Real = f64> {
+    /// Solve the system of ODEs using the Bogacki–Shampine method.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`BShampineODESysSolver::try_bs_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, bogacki_shampine_sys::BShampineODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.bs_solve(&my_ode_system, x0, y0, x_target, h);
+    /// assert!((result[0] - 0.5f64.exp()).abs() < 1e-5);
+    /// println!("Solution at x = {}: {:?}", x_target, result);
+    /// ```
+    fn bs_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F>;
+
+    /// Solve the system of ODEs using the Bogacki–Shampine method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from the initial x up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`BShampineODESysSolver::try_bs_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, bogacki_shampine_sys::BShampineODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    ///
+    /// // The accepted points are as far apart as the tolerances allow
+    /// let solution = solver.bs_solve_traj(&oscillator, 0.0, vec![1.0, 0.0], 10.0, 0.1);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn bs_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Bogacki–Shampine method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`, or the [`SolverError`] that stopped the integration, such as a
+    /// [`SolverError::StepSizeUnderflow`] when the solution blows up.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode_sys::{ODESysSolver, bogacki_shampine_sys::BShampineODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // The first component blows up at x = 1
+    /// let blow_up = |_x: &f64, y: &Vec<f64>| vec![y[0] * y[0], -y[1]];
+    ///
+    /// match solver.try_bs_solve(&blow_up, 0.0, vec![1.0, 1.0], 2.0, 0.1) {
+    ///     Ok(result) => println!("Solution at x = 2: {:?}", result),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_bs_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError>;
+
+    /// Create a stepper integrating the system of ODEs with the Bogacki–Shampine method, one adaptive step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The initial step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// An [`AdaptiveSysStepper`] holding the current state, starting from the initial `(x, y)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, bogacki_shampine_sys::BShampineODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let mut stepper = solver.bs_stepper(&oscillator, 0.0, vec![1.0, 0.0], 0.1);
+    ///
+    /// for state in stepper.by_ref().take(5) {
+    ///     let (x, y) = state.unwrap();
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// println!("Next step size: {}", stepper.h());
+    /// ```
+    fn bs_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, BogackiShampine<F>, T, F>;
+
+    /// Solve the system of ODEs using the Bogacki–Shampine method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration, including one absolute tolerance per component if needed.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`BShampineODESysSolver::try_bs_solve_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, bogacki_shampine_sys::BShampineODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // Species concentrations of very different magnitudes
+    /// let kinetics = |_x: &f64, y: &Vec<f64>| vec![-0.04 * y[0], 0.04 * y[0] - 10.0 * y[1]];
+    /// let options = SolverOptions::new()
+    ///     .rtol(1e-6)
+    ///     .atol_per_component(vec![1e-8, 1e-12])
+    ///     .output(Output::Points(vec![1.0, 10.0, 100.0]));
+    ///
+    /// let solution = solver.bs_solve_with(&kinetics, 0.0, vec![1.0, 0.0], 100.0, &options);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn bs_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Bogacki–Shampine method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration, including one absolute tolerance per component if needed.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, bogacki_shampine_sys::BShampineODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let options = SolverOptions::new().rtol(1e-9).atol(1e-9).max_steps(10).output(Output::Final);
+    ///
+    /// match solver.try_bs_solve_with(&oscillator, 0.0, vec![1.0, 0.0], 100.0, &options) {
+    ///     Ok(solution) => println!("Solution at x = 100: {:?}", solution.last()),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_bs_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError>;
+}
+
+// Implementing the Bogacki–Shampine method for the system of ODEs Solver
+impl<F: Real, T: ODESYS<F>> BShampineODESysSolver<T, F> for ODESysSolver {
+    /// Implementation of the Bogacki–Shampine method to solve a system of ODEs.
+    ///
+    /// Each step propagates the 3rd-order solution and estimates its error with the embedded 2nd-order one. The last
+    /// stage is evaluated at the end point of the step (first same as last), so that it is reused as the first stage of
+    /// the next step and an accepted step costs three evaluations of the system. The solution between two accepted
+    /// points is given by cubic Hermite interpolation, see [`AdaptiveSysStepper::interpolate`].
+    ///
+    /// # When to Use:
+    ///
+    /// Suitable for non-stiff systems solved to moderate tolerances, where its low cost per step pays off.
+    ///
+    /// # Pros and Cons:
+    /// - Pros: Controls the local error of every component, only three evaluations per step.
+    /// - Cons: Low order, so that tight tolerances require many small steps.
+    ///
+    /// # Stability Analysis:
+    ///
+    /// Conditionally stable: on stiff systems the step size control keeps the steps small enough to stay stable, at a high cost.
+    fn bs_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        expect_solved(self.try_bs_solve(ode, x, y, x_target, h))
+    }
+
+    /// Implementation of the Bogacki–Shampine method returning the whole trajectory of the system of ODEs.
+    ///
+    /// See [`BShampineODESysSolver::bs_solve`] for the details of the method.
+    fn bs_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F> {
+        expect_solved(solve_with(self.bs_stepper(ode, x, y, h), x_target, &Output::Steps))
+    }
+
+    /// Implementation of the Bogacki–Shampine method reporting failures as a [`SolverError`].
+    ///
+    /// See [`BShampineODESysSolver::bs_solve`] for the details of the method.
+    fn try_bs_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError> {
+        let mut stepper = self.bs_stepper(ode, x, y, h);
+        stepper.advance_to(x_target)?;
+        Ok(stepper.into_y())
+    }
+
+    /// Implementation of the Bogacki–Shampine method as an [`AdaptiveSysStepper`].
+    ///
+    /// See [`BShampineODESysSolver::bs_solve`] for the details of the method.
+    fn bs_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, BogackiShampine<F>, T, F> {
+        let method = ExplicitRk::new(ButcherTableau::bogacki_shampine(), y.len());
+        AdaptiveSysStepper::new(ode, method, x, y, h)
+    }
+
+    /// Implementation of the Bogacki–Shampine method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`BShampineODESysSolver::bs_solve`] for the details of the method.
+    fn bs_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F> {
+        expect_solved(self.try_bs_solve_with(ode, x, y, x_target, options))
+    }
+
+    /// Implementation of the Bogacki–Shampine method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`BShampineODESysSolver::bs_solve`] for the details of the method.
+    fn try_bs_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError> {
+        options.check(x, x_target, y.len())?;
+        let stepper = self.bs_stepper(ode, x, y, options.initial_step(x, x_target));
+        solve_with(stepper.with_options(options), x_target, &options.output)
+    }
+}
+
+/// Stage buffers of the embedded Bogacki–Shampine 3(2) pair for a system of ODEs, parametrizing the
+/// [`AdaptiveSysStepper`] of the method: the [`ExplicitRk`] method of [`ButcherTableau::bogacki_shampine`].
+pub type BogackiShampine<F> = ExplicitRk<F>;
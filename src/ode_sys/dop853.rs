@@ -0,0 +1,571 @@
+//! Dormand–Prince 8(5,3) (DOP853) method for solving systems of ordinary differential equations (ODEs).
+use super::{ODESYS, ODESysSolver, solve_with};
+use super::adaptive::{AdaptiveSysStepper, DenseSysMethod, EmbeddedSysMethod};
+use crate::{Output, Real, Solution, SolverError, SolverOptions};
+use crate::controller::scaled_error;
+use crate::error::expect_solved;
+use crate::scalar::lit;
+
+/// Dormand–Prince 8(5,3) (DOP853) method for solving systems of Ordinary Differential Equations (ODEs).
+///
+/// This trait defines the adaptive 8th-order [Dormand–Prince method](https://en.wikipedia.org/wiki/Dormand%E2%80%93Prince_method)
+/// of Hairer's DOP853, whose error is estimated with embedded 5th and 3rd-order solutions, for solving systems of
+/// ordinary differential equations at tight tolerances. The step size `h` given to the methods is the initial one,
+/// which is then adapted so that the weighted root mean square of the local error estimates stays within the tolerances,
+/// see [`AdaptiveSysStepper`]. Without a [`SolverOptions`], the tolerances are the defaults of [`SolverOptions::new`].
+pub trait DOP853ODESysSolver<T: ODESYS<F>, F: Real = f64> {
+    /// Solve the system of ODEs using the Dormand–Prince 8(5,3) (DOP853) method.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`DOP853ODESysSolver::try_dop853_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, dop853::DOP853ODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.dop853_solve(&my_ode_system, x0, y0, x_target, h);
+    /// assert!((result[0] - 0.5f64.exp()).abs() < 1e-5);
+    /// println!("Solution at x = {}: {:?}", x_target, result);
+    /// ```
+    fn dop853_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F>;
+
+    /// Solve the system of ODEs using the Dormand–Prince 8(5,3) (DOP853) method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from the initial x up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`DOP853ODESysSolver::try_dop853_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, dop853::DOP853ODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    ///
+    /// // The accepted points are as far apart as the tolerances allow
+    /// let solution = solver.dop853_solve_traj(&oscillator, 0.0, vec![1.0, 0.0], 10.0, 0.1);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn dop853_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Dormand–Prince 8(5,3) (DOP853) method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`, or the [`SolverError`] that stopped the integration, such as a
+    /// [`SolverError::StepSizeUnderflow`] when the solution blows up.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode_sys::{ODESysSolver, dop853::DOP853ODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // The first component blows up at x = 1
+    /// let blow_up = |_x: &f64, y: &Vec<f64>| vec![y[0] * y[0], -y[1]];
+    ///
+    /// match solver.try_dop853_solve(&blow_up, 0.0, vec![1.0, 1.0], 2.0, 0.1) {
+    ///     Ok(result) => println!("Solution at x = 2: {:?}", result),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_dop853_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError>;
+
+    /// Create a stepper integrating the system of ODEs with the Dormand–Prince 8(5,3) (DOP853) method, one adaptive step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The initial step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// An [`AdaptiveSysStepper`] holding the current state, starting from the initial `(x, y)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, dop853::DOP853ODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // Stiff problem: after a fast transient, the solution follows cos(x) closely
+    /// let stiff = |x: &f64, y: &Vec<f64>| vec![-1000.0 * (y[0] - x.cos())];
+    /// let mut stepper = solver.dop853_stepper(&stiff, 0.0, vec![0.0], 0.01);
+    ///
+    /// stepper.advance_to(10.0).unwrap();
+    /// let exact = (1e6 * 10.0f64.cos() + 1e3 * 10.0f64.sin()) / (1e6 + 1.0);
+    /// assert!((stepper.y()[0] - exact).abs() < 1e-5);
+    /// if let Some(x) = stepper.stats().stiffness_detected {
+    ///     println!("The problem became stiff at x = {}, an implicit method would take fewer steps", x);
+    /// }
+    /// ```
+    fn dop853_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, Dop853<F>, T, F>;
+
+    /// Solve the system of ODEs using the Dormand–Prince 8(5,3) (DOP853) method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration, including one absolute tolerance per component if needed.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`DOP853ODESysSolver::try_dop853_solve_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, dop853::DOP853ODESysSolver};
+    /// # use std::f64::consts::PI;
+    /// let solver = ODESysSolver;
+    /// // Kepler problem: position (y[0], y[1]) and velocity (y[2], y[3]) of a body on a circular orbit
+    /// let kepler = |_t: &f64, y: &Vec<f64>| {
+    ///     let r3 = (y[0] * y[0] + y[1] * y[1]).powf(1.5);
+    ///     vec![y[2], y[3], -y[0] / r3, -y[1] / r3]
+    /// };
+    /// let options = SolverOptions::new()
+    ///     .rtol(1e-12)
+    ///     .atol(1e-12)
+    ///     .output(Output::Points(vec![PI / 2.0, PI, 2.0 * PI]));
+    ///
+    /// let solution = solver.dop853_solve_with(&kepler, 0.0, vec![1.0, 0.0, 0.0, 1.0], 2.0 * PI, &options);
+    /// for (t, y) in solution.iter() {
+    ///     println!("Position at t = {}: ({}, {})", t, y[0], y[1]);
+    /// }
+    /// // Back to the initial position after one period
+    /// let (_, y) = solution.last().unwrap();
+    /// assert!((y[0] - 1.0).abs() < 1e-10 && y[1].abs() < 1e-10);
+    /// ```
+    fn dop853_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Dormand–Prince 8(5,3) (DOP853) method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration, including one absolute tolerance per component if needed.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, dop853::DOP853ODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let options = SolverOptions::new().rtol(1e-9).atol(1e-9).max_steps(10).output(Output::Final);
+    ///
+    /// match solver.try_dop853_solve_with(&oscillator, 0.0, vec![1.0, 0.0], 100.0, &options) {
+    ///     Ok(solution) => println!("Solution at x = 100: {:?}", solution.last()),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_dop853_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError>;
+}
+
+// Implementing the Dormand–Prince 8(5,3) (DOP853) method for the system of ODEs Solver
+impl<F: Real, T: ODESYS<F>> DOP853ODESysSolver<T, F> for ODESysSolver {
+    /// Implementation of the Dormand–Prince 8(5,3) (DOP853) method to solve a system of ODEs.
+    ///
+    /// Each step propagates the 8th-order solution of 12 stages. Its error norm combines the 5th and 3rd-order
+    /// estimates as `err5^2 / sqrt(err5^2 + 0.01 * err3^2)`, which behaves like a 7th-order estimate while staying
+    /// reliable for large steps. The derivative at the end point of the step is reused as the first stage of the next
+    /// one (first same as last), so that an accepted step costs twelve evaluations of the system.
+    ///
+    /// The solution between two accepted points is given by a 7th-order continuous extension, which needs three more
+    /// evaluations the first time it is used in a step, see [`AdaptiveSysStepper::interpolate`].
+    ///
+    /// After every accepted step, the method compares the derivatives of its last two stages to estimate the stiffness
+    /// of the problem along the step. When it is found stiff on 15 steps without 6 non-stiff steps in between, the
+    /// first such point is recorded in the `stiffness_detected` field of the [`Stats`](crate::Stats) of the stepper.
+    ///
+    /// # When to Use:
+    ///
+    /// Non-stiff systems solved at tight tolerances, typically below 1e-8, such as orbits or long time integrations.
+    ///
+    /// # Pros and Cons:
+    /// - Pros: Very high accuracy per evaluation at tight tolerances, accurate 7th-order dense output, warns when the problem is stiff.
+    /// - Cons: Expensive steps that do not pay off at loose tolerances, and inefficient on stiff systems.
+    ///
+    /// # Stability Analysis:
+    ///
+    /// Conditionally stable, with a larger stability region than the lower-order methods: on stiff systems the step
+    /// size control keeps the steps small enough to stay stable, at a high cost, which the stiffness detection reports.
+    fn dop853_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        expect_solved(self.try_dop853_solve(ode, x, y, x_target, h))
+    }
+
+    /// Implementation of the Dormand–Prince 8(5,3) (DOP853) method returning the whole trajectory of the system of ODEs.
+    ///
+    /// See [`DOP853ODESysSolver::dop853_solve`] for the details of the method.
+    fn dop853_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F> {
+        expect_solved(solve_with(self.dop853_stepper(ode, x, y, h), x_target, &Output::Steps))
+    }
+
+    /// Implementation of the Dormand–Prince 8(5,3) (DOP853) method reporting failures as a [`SolverError`].
+    ///
+    /// See [`DOP853ODESysSolver::dop853_solve`] for the details of the method.
+    fn try_dop853_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError> {
+        let mut stepper = self.dop853_stepper(ode, x, y, h);
+        stepper.advance_to(x_target)?;
+        Ok(stepper.into_y())
+    }
+
+    /// Implementation of the Dormand–Prince 8(5,3) (DOP853) method as an [`AdaptiveSysStepper`].
+    ///
+    /// See [`DOP853ODESysSolver::dop853_solve`] for the details of the method.
+    fn dop853_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, Dop853<F>, T, F> {
+        AdaptiveSysStepper::new(ode, Dop853::with_dim(y.len()), x, y, h)
+    }
+
+    /// Implementation of the Dormand–Prince 8(5,3) (DOP853) method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`DOP853ODESysSolver::dop853_solve`] for the details of the method.
+    fn dop853_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F> {
+        expect_solved(self.try_dop853_solve_with(ode, x, y, x_target, options))
+    }
+
+    /// Implementation of the Dormand–Prince 8(5,3) (DOP853) method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`DOP853ODESysSolver::dop853_solve`] for the details of the method.
+    fn try_dop853_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError> {
+        options.check(x, x_target, y.len())?;
+        let stepper = self.dop853_stepper(ode, x, y, options.initial_step(x, x_target));
+        solve_with(stepper.with_options(options), x_target, &options.output)
+    }
+}
+
+/// Stage buffers of the Dormand–Prince 8(5,3) method for a system of ODEs, parametrizing the [`AdaptiveSysStepper`] of the method.
+///
+/// `k[0]` holds the derivative at the current point once it is known, computed as the 13th stage of the previous step.
+/// After a step is accepted, `k[12]` holds the derivative at its start point, and `dense` the last coefficients of its
+/// continuous extension once they are prepared.
+pub struct Dop853<F> {
+    /// Derivatives of the 12 stages, of the end point of the step and of the 3 extra stages of the continuous extension.
+    k: Vec<Vec<F>>,
+    y_temp: Vec<F>,
+    /// Error estimate of the embedded 3rd-order solution.
+    err3: Vec<F>,
+    dense: Vec<Vec<F>>,
+    /// Whether `k[0]` holds the derivative at the current point.
+    fsal: bool,
+    /// Estimate of `|h * lambda|` over the last attempted step, `lambda` being the dominant eigenvalue of the Jacobian.
+    stiffness_ratio: F,
+    /// Number of accepted steps found stiff, reset after 6 consecutive non-stiff steps.
+    stiff_steps: usize,
+    nonstiff_steps: usize,
+}
+
+/// Value of `|h * lambda|` above which a step is considered stiff, close to the boundary of the stability region.
+const STIFF_RATIO: f64 = 6.1;
+
+/// Number of stiff steps after which the problem is reported as stiff.
+const STIFF_STEPS: usize = 15;
+
+/// Number of consecutive non-stiff steps resetting the count of stiff steps.
+const NONSTIFF_STEPS: usize = 6;
+
+/// Writes into `out` the argument `y + h * sum(a[j] * k[j])` of a stage.
+fn stage_argument<F: Real>(out: &mut [F], y: &[F], h: F, a: &[f64], k: &[Vec<F>]) {
+    out.copy_from_slice(y);
+    for (a, k) in a.iter().zip(k) {
+        if *a != 0.0 {
+            let a = h * lit(*a);
+            for (out, k) in out.iter_mut().zip(k) {
+                *out += a * *k;
+            }
+        }
+    }
+}
+
+impl<F: Real> Dop853<F> {
+    /// Allocates the buffers needed to integrate a system of dimension `dim`.
+    pub fn with_dim(dim: usize) -> Self {
+        Dop853 {
+            k: vec![vec![F::zero(); dim]; 16],
+            y_temp: vec![F::zero(); dim],
+            err3: vec![F::zero(); dim],
+            dense: vec![vec![F::zero(); dim]; 4],
+            fsal: false,
+            stiffness_ratio: F::zero(),
+            stiff_steps: 0,
+            nonstiff_steps: 0,
+        }
+    }
+}
+
+impl<F: Real> EmbeddedSysMethod<F> for Dop853<F> {
+    fn error_order(&self) -> usize {
+        7
+    }
+
+    fn step<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F], h: F, y_next: &mut [F], error: &mut [F]) {
+        if !self.fsal {
+            ode.eval_into(x, y, &mut self.k[0]);
+            self.fsal = true;
+        }
+        for s in 1..12 {
+            let (done, next) = self.k.split_at_mut(s);
+            stage_argument(&mut self.y_temp, y, h, A[s], done);
+            ode.eval_into(x + h * lit(C[s]), &self.y_temp, &mut next[0]);
+        }
+        stage_argument(y_next, y, h, A[12], &self.k);
+        ode.eval_into(x + h, y_next, &mut self.k[12]);
+
+        for (i, (error, err3)) in error.iter_mut().zip(self.err3.iter_mut()).enumerate() {
+            let mut sum5 = F::zero();
+            let mut sum3 = F::zero();
+            for (j, k) in self.k[..12].iter().enumerate() {
+                sum5 += k[i] * lit(E5[j]);
+                sum3 += k[i] * lit(E3[j]);
+            }
+            *error = h * sum5;
+            *err3 = h * sum3;
+        }
+
+        // The last stage and the end point are both at x + h, so that their difference probes the Jacobian there
+        let mut numerator = F::zero();
+        let mut denominator = F::zero();
+        for (i, y_next) in y_next.iter().enumerate() {
+            numerator += (self.k[12][i] - self.k[11][i]).powi(2);
+            denominator += (*y_next - self.y_temp[i]).powi(2);
+        }
+        self.stiffness_ratio = if denominator > F::zero() { h.abs() * (numerator / denominator).sqrt() } else { F::zero() };
+    }
+
+    fn accept(&mut self) {
+        if self.stiffness_ratio > lit(STIFF_RATIO) {
+            self.stiff_steps += 1;
+            self.nonstiff_steps = 0;
+        } else {
+            self.nonstiff_steps += 1;
+            if self.nonstiff_steps == NONSTIFF_STEPS {
+                self.stiff_steps = 0;
+            }
+        }
+
+        // The derivative at the end of the step becomes the first stage of the next one
+        self.k.swap(0, 12);
+    }
+
+    /// Combines the 5th and 3rd-order error estimates as in DOP853, which behaves like a 7th-order estimate.
+    fn error_norm(&self, error: &[F], scale: &[F]) -> F {
+        let mut err5 = F::zero();
+        let mut err3 = F::zero();
+        for (i, scale) in scale.iter().enumerate() {
+            err5 += scaled_error(error[i], *scale).powi(2);
+            err3 += scaled_error(self.err3[i], *scale).powi(2);
+        }
+        if err5 == F::zero() && err3 == F::zero() {
+            return F::zero();
+        }
+
+        err5 / (lit::<F>(error.len() as f64) * (err5 + err3 * lit(0.01))).sqrt()
+    }
+
+    fn is_stiff(&self) -> bool {
+        self.stiff_steps >= STIFF_STEPS
+    }
+}
+
+impl<F: Real> DenseSysMethod<F> for Dop853<F> {
+    /// Evaluates the 3 extra stages of the continuous extension and combines all the stages into its last coefficients.
+    fn prepare<T: ODESYS<F>>(&mut self, ode: &T, x: F, h: F, y_prev: &[F], _y: &[F]) {
+        // Restore the order of the stages of the step, which `accept` rotated
+        self.k.swap(0, 12);
+        for s in 13..16 {
+            let (done, next) = self.k.split_at_mut(s);
+            stage_argument(&mut self.y_temp, y_prev, h, A[s], done);
+            ode.eval_into(x + h * lit(C[s]), &self.y_temp, &mut next[0]);
+        }
+
+        for (dense, d) in self.dense.iter_mut().zip(&D) {
+            for (i, dense) in dense.iter_mut().enumerate() {
+                let mut sum = F::zero();
+                for (j, k) in self.k.iter().enumerate() {
+                    sum += k[i] * lit(d[j]);
+                }
+                *dense = h * sum;
+            }
+        }
+        self.k.swap(0, 12);
+    }
+
+    fn interpolate(&self, theta: F, h: F, y_prev: &[F], y: &[F], out: &mut [F]) {
+        // `k[12]` and `k[0]` hold the derivatives at the start and at the end of the step
+        let theta1 = F::one() - theta;
+        for (i, out) in out.iter_mut().enumerate() {
+            let dy = y[i] - y_prev[i];
+            let f_prev = h * self.k[12][i];
+            let f = h * self.k[0][i];
+
+            let mut sum = self.dense[2][i] + theta * self.dense[3][i];
+            sum = self.dense[1][i] + theta1 * sum;
+            sum = self.dense[0][i] + theta * sum;
+            sum = dy * lit(2.0) - f - f_prev + theta1 * sum;
+            sum = f_prev - dy + theta * sum;
+            sum = dy + theta1 * sum;
+            *out = y_prev[i] + theta * sum;
+        }
+    }
+}
+
+/// Nodes of the stages: the 12 stages of a step, its end point, and the 3 extra stages of the continuous extension.
+const C: [f64; 16] = [
+    0.0, 0.05260015195876773, 0.0789002279381516, 0.1183503419072274,
+    0.2816496580927726, 0.3333333333333333, 0.25, 0.3076923076923077,
+    0.6512820512820513, 0.6, 0.8571428571428571, 1.0,
+    1.0, 0.1, 0.2, 0.7777777777777778,
+];
+
+/// Coefficients of the stages, row `s` weighting the derivatives of the previous stages in the argument of stage `s`.
+/// Row 12 holds the weights of the 8th-order solution, and the derivative at its end point is the 13th stage.
+const A: [&[f64]; 16] = [
+    &[],
+    &[0.05260015195876773],
+    &[0.0197250569845379, 0.0591751709536137],
+    &[0.02958758547680685, 0.0, 0.08876275643042054],
+    &[0.2413651341592667, 0.0, -0.8845494793282861, 0.924834003261792],
+    &[0.037037037037037035, 0.0, 0.0, 0.17082860872947386, 0.12546768756682242],
+    &[0.037109375, 0.0, 0.0, 0.17025221101954405, 0.06021653898045596, -0.017578125],
+    &[
+        0.03709200011850479, 0.0, 0.0, 0.17038392571223998, 0.10726203044637328, -0.015319437748624402,
+        0.008273789163814023,
+    ],
+    &[
+        0.6241109587160757, 0.0, 0.0, -3.3608926294469414, -0.868219346841726, 27.59209969944671, 20.154067550477894,
+        -43.48988418106996,
+    ],
+    &[
+        0.47766253643826434, 0.0, 0.0, -2.4881146199716677, -0.590290826836843, 21.230051448181193, 15.279233632882423,
+        -33.28821096898486, -0.020331201708508627,
+    ],
+    &[
+        -0.9371424300859873, 0.0, 0.0, 5.186372428844064, 1.0914373489967295, -8.149787010746927, -18.52006565999696,
+        22.739487099350505, 2.4936055526796523, -3.0467644718982196,
+    ],
+    &[
+        2.273310147516538, 0.0, 0.0, -10.53449546673725, -2.0008720582248625, -17.9589318631188, 27.94888452941996,
+        -2.8589982771350235, -8.87285693353063, 12.360567175794303, 0.6433927460157636,
+    ],
+    &[
+        0.054293734116568765, 0.0, 0.0, 0.0, 0.0, 4.450312892752409, 1.8915178993145003, -5.801203960010585,
+        0.3111643669578199, -0.1521609496625161, 0.20136540080403034, 0.04471061572777259,
+    ],
+    &[
+        0.056167502283047954, 0.0, 0.0, 0.0, 0.0, 0.0, 0.25350021021662483, -0.2462390374708025, -0.12419142326381637,
+        0.15329179827876568, 0.00820105229563469, 0.007567897660545699, -0.008298,
+    ],
+    &[
+        0.03183464816350214, 0.0, 0.0, 0.0, 0.0, 0.028300909672366776, 0.053541988307438566, -0.05492374857139099, 0.0,
+        0.0, -0.00010834732869724932, 0.0003825710908356584, -0.00034046500868740456, 0.1413124436746325,
+    ],
+    &[
+        -0.42889630158379194, 0.0, 0.0, 0.0, 0.0, -4.697621415361164, 7.683421196062599, 4.06898981839711,
+        0.3567271874552811, 0.0, 0.0, 0.0, -0.0013990241651590145, 2.9475147891527724, -9.15095847217987,
+    ],
+];
+
+/// Differences between the weights of the 8th-order solution and of the embedded 3rd-order one.
+const E3: [f64; 12] = [
+    -0.18980075407240762, 0.0, 0.0, 0.0,
+    0.0, 4.450312892752409, 1.8915178993145003, -5.801203960010585,
+    -0.4226823213237919, -0.1521609496625161, 0.20136540080403034, 0.02265179219836082,
+];
+
+/// Differences between the weights of the 8th-order solution and of the embedded 5th-order one.
+const E5: [f64; 12] = [
+    0.01312004499419488, 0.0, 0.0, 0.0,
+    0.0, -1.2251564463762044, -0.4957589496572502, 1.6643771824549864,
+    -0.35032884874997366, 0.3341791187130175, 0.08192320648511571, -0.022355307863886294,
+];
+
+/// Weights of the 16 stages in the last 4 coefficients of the 7th-order continuous extension.
+const D: [[f64; 16]; 4] = [
+    [
+        -8.428938276109013, 0.0, 0.0, 0.0, 0.0, 0.5667149535193777, -3.0689499459498917, 2.38466765651207,
+        2.117034582445028, -0.871391583777973, 2.2404374302607883, 0.6315787787694688, -0.08899033645133331,
+        18.148505520854727, -9.194632392478356, -4.436036387594894,
+    ],
+    [
+        10.427508642579134, 0.0, 0.0, 0.0, 0.0, 242.28349177525817, 165.20045171727028, -374.5467547226902,
+        -22.113666853125306, 7.733432668472264, -30.674084731089398, -9.332130526430229, 15.697238121770845,
+        -31.139403219565178, -9.35292435884448, 35.81684148639408,
+    ],
+    [
+        19.985053242002433, 0.0, 0.0, 0.0, 0.0, -387.0373087493518, -189.17813819516758, 527.8081592054236,
+        -11.57390253995963, 6.8812326946963, -1.0006050966910838, 0.7777137798053443, -2.778205752353508,
+        -60.19669523126412, 84.32040550667716, 11.99229113618279,
+    ],
+    [
+        -25.69393346270375, 0.0, 0.0, 0.0, 0.0, -154.18974869023643, -231.5293791760455, 357.6391179106141,
+        93.40532418362432, -37.45832313645163, 104.0996495089623, 29.8402934266605, -43.53345659001114,
+        96.32455395918828, -39.17726167561544, -149.72683625798564,
+    ],
+];
@@ -0,0 +1,411 @@
+//! Dormand–Prince (DOPRI5) method for solving systems of ordinary differential equations (ODEs).
+use super::{ODESYS, ODESysSolver, add_scaled, solve_with};
+use super::adaptive::{AdaptiveSysStepper, DenseSysMethod, EmbeddedSysMethod};
+use crate::{Output, Real, Solution, SolverError, SolverOptions};
+use crate::error::expect_solved;
+use crate::scalar::lit;
+use crate::ode::dormand_prince::{
+    A21, A31, A32, A41, A42, A43, A51, A52, A53, A54, A61, A62, A63, A64, A65, B1, B3, B4, B5, B6, C2, C3, C4, C5, D1,
+    D3, D4, D5, D6, D7, E1, E3, E4, E5, E6, E7, continuous_extension, evaluate_extension,
+};
+
+/// Dormand–Prince (DOPRI5) method for solving systems of Ordinary Differential Equations (ODEs).
+///
+/// This trait defines the adaptive 5(4) [Dormand–Prince method](https://en.wikipedia.org/wiki/Dormand%E2%80%93Prince_method)
+/// for solving systems of ordinary differential equations. The step size `h` given to the methods is the initial one,
+/// which is then adapted so that the weighted root mean square of the local error estimates stays within the tolerances,
+/// see [`AdaptiveSysStepper`]. Without a [`SolverOptions`], the tolerances are the defaults of [`SolverOptions::new`].
+pub trait DormandPrinceODESysSolver<T: ODESYS<F>, F: Real = f64> {
+    /// Solve the system of ODEs using the Dormand–Prince (DOPRI5) method.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`DormandPrinceODESysSolver::try_dopri5_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, dormand_prince_sys::DormandPrinceODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.dopri5_solve(&my_ode_system, x0, y0, x_target, h);
+    /// assert!((result[0] - 0.5f64.exp()).abs() < 1e-5);
+    /// println!("Solution at x = {}: {:?}", x_target, result);
+    /// ```
+    fn dopri5_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F>;
+
+    /// Solve the system of ODEs using the Dormand–Prince (DOPRI5) method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from the initial x up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`DormandPrinceODESysSolver::try_dopri5_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, dormand_prince_sys::DormandPrinceODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    ///
+    /// // The accepted points are as far apart as the tolerances allow
+    /// let solution = solver.dopri5_solve_traj(&oscillator, 0.0, vec![1.0, 0.0], 10.0, 0.1);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn dopri5_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Dormand–Prince (DOPRI5) method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`, or the [`SolverError`] that stopped the integration, such as a
+    /// [`SolverError::StepSizeUnderflow`] when the solution blows up.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode_sys::{ODESysSolver, dormand_prince_sys::DormandPrinceODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // The first component blows up at x = 1
+    /// let blow_up = |_x: &f64, y: &Vec<f64>| vec![y[0] * y[0], -y[1]];
+    ///
+    /// match solver.try_dopri5_solve(&blow_up, 0.0, vec![1.0, 1.0], 2.0, 0.1) {
+    ///     Ok(result) => println!("Solution at x = 2: {:?}", result),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_dopri5_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError>;
+
+    /// Create a stepper integrating the system of ODEs with the Dormand–Prince (DOPRI5) method, one adaptive step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The initial step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// An [`AdaptiveSysStepper`] holding the current state, starting from the initial `(x, y)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, dormand_prince_sys::DormandPrinceODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let mut stepper = solver.dopri5_stepper(&oscillator, 0.0, vec![1.0, 0.0], 0.1);
+    ///
+    /// for state in stepper.by_ref().take(5) {
+    ///     let (x, y) = state.unwrap();
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// println!("Next step size: {}", stepper.h());
+    /// ```
+    fn dopri5_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, Dopri5<F>, T, F>;
+
+    /// Solve the system of ODEs using the Dormand–Prince (DOPRI5) method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration, including one absolute tolerance per component if needed.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`DormandPrinceODESysSolver::try_dopri5_solve_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, dormand_prince_sys::DormandPrinceODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // Species concentrations of very different magnitudes
+    /// let kinetics = |_x: &f64, y: &Vec<f64>| vec![-0.04 * y[0], 0.04 * y[0] - 10.0 * y[1]];
+    /// let options = SolverOptions::new()
+    ///     .rtol(1e-6)
+    ///     .atol_per_component(vec![1e-8, 1e-12])
+    ///     .output(Output::Points(vec![1.0, 10.0, 100.0]));
+    ///
+    /// let solution = solver.dopri5_solve_with(&kinetics, 0.0, vec![1.0, 0.0], 100.0, &options);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn dopri5_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Dormand–Prince (DOPRI5) method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration, including one absolute tolerance per component if needed.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, dormand_prince_sys::DormandPrinceODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let options = SolverOptions::new().rtol(1e-9).atol(1e-9).max_steps(10).output(Output::Final);
+    ///
+    /// match solver.try_dopri5_solve_with(&oscillator, 0.0, vec![1.0, 0.0], 100.0, &options) {
+    ///     Ok(solution) => println!("Solution at x = 100: {:?}", solution.last()),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_dopri5_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError>;
+}
+
+// Implementing the Dormand–Prince (DOPRI5) method for the system of ODEs Solver
+impl<F: Real, T: ODESYS<F>> DormandPrinceODESysSolver<T, F> for ODESysSolver {
+    /// Implementation of the Dormand–Prince (DOPRI5) method to solve a system of ODEs.
+    ///
+    /// Each step propagates the 5th-order solution and estimates its error with the embedded 4th-order one. The last
+    /// stage is evaluated at the end point of the step (first same as last), so that it is reused as the first stage of
+    /// the next step and an accepted step costs six evaluations of the system. The solution between two accepted points
+    /// is given by the 4th-order continuous extension of Shampine, without further evaluations, see
+    /// [`AdaptiveSysStepper::interpolate`].
+    ///
+    /// # When to Use:
+    ///
+    /// The default choice for non-stiff systems, from loose to fairly tight tolerances.
+    ///
+    /// # Pros and Cons:
+    /// - Pros: Controls the local error of every component, high accuracy per evaluation, accurate dense output at no extra cost.
+    /// - Cons: Inefficient on stiff systems, and very tight tolerances are better served by higher-order methods.
+    ///
+    /// # Stability Analysis:
+    ///
+    /// Conditionally stable: on stiff systems the step size control keeps the steps small enough to stay stable, at a high cost.
+    fn dopri5_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        expect_solved(self.try_dopri5_solve(ode, x, y, x_target, h))
+    }
+
+    /// Implementation of the Dormand–Prince (DOPRI5) method returning the whole trajectory of the system of ODEs.
+    ///
+    /// See [`DormandPrinceODESysSolver::dopri5_solve`] for the details of the method.
+    fn dopri5_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F> {
+        expect_solved(solve_with(self.dopri5_stepper(ode, x, y, h), x_target, &Output::Steps))
+    }
+
+    /// Implementation of the Dormand–Prince (DOPRI5) method reporting failures as a [`SolverError`].
+    ///
+    /// See [`DormandPrinceODESysSolver::dopri5_solve`] for the details of the method.
+    fn try_dopri5_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError> {
+        let mut stepper = self.dopri5_stepper(ode, x, y, h);
+        stepper.advance_to(x_target)?;
+        Ok(stepper.into_y())
+    }
+
+    /// Implementation of the Dormand–Prince (DOPRI5) method as an [`AdaptiveSysStepper`].
+    ///
+    /// See [`DormandPrinceODESysSolver::dopri5_solve`] for the details of the method.
+    fn dopri5_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, Dopri5<F>, T, F> {
+        AdaptiveSysStepper::new(ode, Dopri5::with_dim(y.len()), x, y, h)
+    }
+
+    /// Implementation of the Dormand–Prince (DOPRI5) method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`DormandPrinceODESysSolver::dopri5_solve`] for the details of the method.
+    fn dopri5_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F> {
+        expect_solved(self.try_dopri5_solve_with(ode, x, y, x_target, options))
+    }
+
+    /// Implementation of the Dormand–Prince (DOPRI5) method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`DormandPrinceODESysSolver::dopri5_solve`] for the details of the method.
+    fn try_dopri5_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError> {
+        options.check(x, x_target, y.len())?;
+        let stepper = self.dopri5_stepper(ode, x, y, options.initial_step(x, x_target));
+        solve_with(stepper.with_options(options), x_target, &options.output)
+    }
+}
+
+/// Stage buffers of the embedded Dormand–Prince 5(4) pair for a system of ODEs, parametrizing the [`AdaptiveSysStepper`] of the method.
+///
+/// `k1` holds the derivative at the current point once it is known, computed as the last stage of the previous step,
+/// while `f_prev` and `dense` hold the data of the continuous extension over the last accepted step.
+pub struct Dopri5<F> {
+    k1: Vec<F>,
+    k2: Vec<F>,
+    k3: Vec<F>,
+    k4: Vec<F>,
+    k5: Vec<F>,
+    k6: Vec<F>,
+    k7: Vec<F>,
+    f_prev: Vec<F>,
+    dense: Vec<F>,
+    y_temp: Vec<F>,
+    /// Whether `k1` holds the derivative at the current point.
+    fsal: bool,
+}
+
+impl<F: Real> Dopri5<F> {
+    /// Allocates the buffers needed to integrate a system of dimension `dim`.
+    pub fn with_dim(dim: usize) -> Self {
+        Dopri5 {
+            k1: vec![F::zero(); dim],
+            k2: vec![F::zero(); dim],
+            k3: vec![F::zero(); dim],
+            k4: vec![F::zero(); dim],
+            k5: vec![F::zero(); dim],
+            k6: vec![F::zero(); dim],
+            k7: vec![F::zero(); dim],
+            f_prev: vec![F::zero(); dim],
+            dense: vec![F::zero(); dim],
+            y_temp: vec![F::zero(); dim],
+            fsal: false,
+        }
+    }
+
+    /// Forgets the derivative kept from the previous step, for a step that does not start where the last one ended.
+    pub(crate) fn restart(&mut self) {
+        self.fsal = false;
+    }
+
+    /// Returns an estimate of `|h * lambda|` over the last attempted step of size `h`, ending at `y_next`, `lambda`
+    /// being the dominant eigenvalue of the Jacobian.
+    ///
+    /// The last stage and the end point are both at `x + h`, so that their difference probes the Jacobian there.
+    pub(crate) fn stiffness_ratio(&self, h: F, y_next: &[F]) -> F {
+        let mut numerator = F::zero();
+        let mut denominator = F::zero();
+        for (i, y_next) in y_next.iter().enumerate() {
+            numerator += (self.k7[i] - self.k6[i]).powi(2);
+            denominator += (*y_next - self.y_temp[i]).powi(2);
+        }
+        if denominator > F::zero() { h.abs() * (numerator / denominator).sqrt() } else { F::zero() }
+    }
+}
+
+impl<F: Real> EmbeddedSysMethod<F> for Dopri5<F> {
+    fn error_order(&self) -> usize {
+        4
+    }
+
+    fn step<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F], h: F, y_next: &mut [F], error: &mut [F]) {
+        if !self.fsal {
+            ode.eval_into(x, y, &mut self.k1);
+            self.fsal = true;
+        }
+        add_scaled(&mut self.y_temp, y, h * lit(A21), &self.k1);
+        ode.eval_into(x + h * lit(C2), &self.y_temp, &mut self.k2);
+        for (i, y) in y.iter().enumerate() {
+            self.y_temp[i] = *y + h * (self.k1[i] * lit(A31) + self.k2[i] * lit(A32));
+        }
+        ode.eval_into(x + h * lit(C3), &self.y_temp, &mut self.k3);
+        for (i, y) in y.iter().enumerate() {
+            self.y_temp[i] = *y + h * (self.k1[i] * lit(A41) + self.k2[i] * lit(A42) + self.k3[i] * lit(A43));
+        }
+        ode.eval_into(x + h * lit(C4), &self.y_temp, &mut self.k4);
+        for (i, y) in y.iter().enumerate() {
+            self.y_temp[i] = *y
+                + h * (self.k1[i] * lit(A51) + self.k2[i] * lit(A52) + self.k3[i] * lit(A53) + self.k4[i] * lit(A54));
+        }
+        ode.eval_into(x + h * lit(C5), &self.y_temp, &mut self.k5);
+        for (i, y) in y.iter().enumerate() {
+            self.y_temp[i] = *y
+                + h * (self.k1[i] * lit(A61) + self.k2[i] * lit(A62) + self.k3[i] * lit(A63) + self.k4[i] * lit(A64)
+                    + self.k5[i] * lit(A65));
+        }
+        ode.eval_into(x + h, &self.y_temp, &mut self.k6);
+        for (i, y) in y.iter().enumerate() {
+            y_next[i] = *y
+                + h * (self.k1[i] * lit(B1) + self.k3[i] * lit(B3) + self.k4[i] * lit(B4) + self.k5[i] * lit(B5)
+                    + self.k6[i] * lit(B6));
+        }
+        ode.eval_into(x + h, y_next, &mut self.k7);
+
+        for (i, error) in error.iter_mut().enumerate() {
+            *error = h
+                * (self.k1[i] * lit(E1) + self.k3[i] * lit(E3) + self.k4[i] * lit(E4) + self.k5[i] * lit(E5)
+                    + self.k6[i] * lit(E6) + self.k7[i] * lit(E7));
+        }
+    }
+
+    fn accept(&mut self) {
+        for (i, dense) in self.dense.iter_mut().enumerate() {
+            *dense = self.k1[i] * lit(D1) + self.k3[i] * lit(D3) + self.k4[i] * lit(D4) + self.k5[i] * lit(D5)
+                + self.k6[i] * lit(D6) + self.k7[i] * lit(D7);
+        }
+
+        // The derivative at the end of the step becomes the first stage of the next one
+        std::mem::swap(&mut self.f_prev, &mut self.k1);
+        std::mem::swap(&mut self.k1, &mut self.k7);
+    }
+}
+
+impl<F: Real> DenseSysMethod<F> for Dopri5<F> {
+    fn interpolate(&self, theta: F, h: F, y_prev: &[F], y: &[F], out: &mut [F]) {
+        for (i, out) in out.iter_mut().enumerate() {
+            let rcont = continuous_extension(h, y_prev[i], y[i], self.f_prev[i], self.k1[i], self.dense[i]);
+            *out = evaluate_extension(theta, rcont);
+        }
+    }
+}
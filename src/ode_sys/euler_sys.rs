@@ -1,10 +1,13 @@
 //! Euler method for solving systems of ordinary differential equations (ODEs).
-use super::{ODESYS, ODESysSolver, vec_scalar_mul};
+use super::{ODESYS, ODESysSolver, SysMethod, fixed_step, fixed_step_traj, solve_with};
+use super::stepper::SysStepper;
+use crate::{Real, Solution, SolverError, SolverOptions};
+use crate::error::expect_solved;
 
 /// Euler method for solving systems of Ordinary Differential Equations (ODEs).
 ///
 /// This trait defines the [Euler method](https://en.wikipedia.org/wiki/Euler_method) for solving systems of ordinary differential equations.
-pub trait EulerODESysSolver<T: ODESYS> {
+pub trait EulerODESysSolver<T: ODESYS<F>, F: Real = f64> {
     /// Solve the system of ODEs using the Euler method.
     ///
     /// # Arguments
@@ -19,9 +22,14 @@ pub trait EulerODESysSolver<T: ODESYS> {
     ///
     /// The vector of estimated y values at `x_target`.
     /// 
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`EulerODESysSolver::try_eu_solve`].
+    ///
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, euler_sys::EulerODESysSolver};
     /// struct MyODESystem;
     /// impl ODESYS for MyODESystem {
     ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
@@ -41,11 +49,229 @@ pub trait EulerODESysSolver<T: ODESYS> {
     /// let result = solver.eu_solve(&my_ode_system, x0, y0, x_target, h);
     /// println!("Solution at x = {}: {:?}", x_target, result);
     /// ```
-    fn eu_solve(&self, ode: &T, x: f64, y: Vec<f64>, x_target: f64, h: f64) -> Vec<f64>;
+    fn eu_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F>;
+
+    /// Solve the system of ODEs using the Euler method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size or increment for x.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from the initial x up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`EulerODESysSolver::try_eu_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, euler_sys::EulerODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let solution = solver.eu_solve_traj(&my_ode_system, x0, y0, x_target, h);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn eu_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Euler method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size or increment for x.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, euler_sys::EulerODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.try_eu_solve(&my_ode_system, x0, y0.clone(), x_target, h).unwrap();
+    /// assert!((result[0] - 0.5f64.exp()).abs() < 0.2);
+    /// assert!((result[1] - (3.0 * 1.0f64.exp() - 2.0)).abs() < 0.5);
+    ///
+    /// // The first component of y' = [y0^2, -y1] blows up at x = 1
+    /// let blow_up = |_x: &f64, y: &Vec<f64>| vec![y[0] * y[0], -y[1]];
+    /// let error = solver.try_eu_solve(&blow_up, x0, vec![1.0, 1.0], 3.0, h).unwrap_err();
+    /// assert!(matches!(error, SolverError::NonFiniteState { x } if x > 1.0 && x < 3.0));
+    /// ```
+    fn try_eu_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError>;
+
+    /// Create a stepper integrating the system of ODEs with the Euler method, one step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// A [`SysStepper`] holding the current state, starting from the initial `(x, y)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, euler_sys::EulerODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let mut stepper = solver.eu_stepper(&my_ode_system, 0.0, vec![1.0, 2.0], 0.1);
+    ///
+    /// // Integrate a tenth of x at a time
+    /// for x in [0.1, 0.2, 0.3] {
+    ///     let y = stepper.advance_to(x).unwrap();
+    ///     assert!((y[0] - (x * x / 2.0).exp()).abs() < 0.1);
+    ///     assert!((y[1] - (3.0 * x.exp() - x - 1.0)).abs() < 0.1);
+    ///     assert_eq!(stepper.x(), x);
+    /// }
+    /// ```
+    fn eu_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> SysStepper<'a, Euler<F>, T, F>;
+
+    /// Solve the system of ODEs using the Euler method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`EulerODESysSolver::try_eu_solve_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, euler_sys::EulerODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let options = SolverOptions::new().h0(0.1).output(Output::Final);
+    ///
+    /// let solution = solver.eu_solve_with(&my_ode_system, 0.0, vec![1.0, 2.0], 1.0, &options);
+    /// assert_eq!(solution.x, vec![1.0]);
+    /// assert!((solution.y[0][0] - 0.5f64.exp()).abs() < 0.5);
+    /// assert!((solution.y[0][1] - (3.0 * 1.0f64.exp() - 2.0)).abs() < 0.5);
+    /// ```
+    fn eu_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Euler method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverError, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, euler_sys::EulerODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// // The solution is requested at x = 0.5 on the way to x = 1
+    /// let options = SolverOptions::new().h0(0.1).output(Output::Points(vec![0.5]));
+    /// let solution = solver.try_eu_solve_with(&my_ode_system, 0.0, vec![1.0, 2.0], 1.0, &options).unwrap();
+    /// assert_eq!(solution.x, vec![0.5]);
+    /// assert!((solution.y[0][0] - 0.125f64.exp()).abs() < 0.2);
+    /// assert!((solution.y[0][1] - (3.0 * 0.5f64.exp() - 1.5)).abs() < 0.2);
+    ///
+    /// // Ten steps of 0.1 are needed to reach x = 1
+    /// let options = SolverOptions::new().h0(0.1).max_steps(5);
+    /// let error = solver.try_eu_solve_with(&my_ode_system, 0.0, vec![1.0, 2.0], 1.0, &options).unwrap_err();
+    /// assert_eq!(error, SolverError::MaxStepsExceeded { x: 0.0, max_steps: 5 });
+    /// ```
+    fn try_eu_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError>;
 }
 
 // Implementing the Euler method for the system of ODEs Solver
-impl<T: ODESYS> EulerODESysSolver<T> for ODESysSolver {
+impl<F: Real, T: ODESYS<F>> EulerODESysSolver<T, F> for ODESysSolver {
     /// Implementation of the Euler method to solve a system of ODEs.
     ///
     /// This method approximates the solution to the system of ODEs at the specified x_target.
@@ -77,6 +303,7 @@ impl<T: ODESYS> EulerODESysSolver<T> for ODESysSolver {
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, euler_sys::EulerODESysSolver};
     /// struct MyODESystem;
     /// impl ODESYS for MyODESystem {
     ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
@@ -96,18 +323,65 @@ impl<T: ODESYS> EulerODESysSolver<T> for ODESysSolver {
     /// let result = solver.eu_solve(&my_ode_system, x0, y0, x_target, h);
     /// println!("Solution at x = {}: {:?}", x_target, result);
     /// ```
-    fn eu_solve(&self, ode: &T, x: f64, mut y: Vec<f64>, x_target: f64, h: f64) -> Vec<f64> {
-        let mut x_val = x;
+    fn eu_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        expect_solved(self.try_eu_solve(ode, x, y, x_target, h))
+    }
+
+    /// Implementation of the Euler method returning the whole trajectory of the system of ODEs.
+    ///
+    /// See [`EulerODESysSolver::eu_solve`] for the details of the method.
+    fn eu_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F> {
+        expect_solved(fixed_step_traj(ode, Euler::with_dim(y.len()), x, y, h, x_target))
+    }
+
+    /// Implementation of the Euler method reporting failures as a [`SolverError`].
+    ///
+    /// See [`EulerODESysSolver::eu_solve`] for the details of the method.
+    fn try_eu_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError> {
+        fixed_step(ode, Euler::with_dim(y.len()), x, y, h, x_target, |_, _| {})
+    }
+
+    /// Implementation of the Euler method as a [`SysStepper`].
+    ///
+    /// See [`EulerODESysSolver::eu_solve`] for the details of the method.
+    fn eu_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> SysStepper<'a, Euler<F>, T, F> {
+        SysStepper::new(ode, Euler::with_dim(y.len()), x, y, h)
+    }
 
-        while x_val < x_target {
-            let dy = vec_scalar_mul(&ode.eval(&x_val, &y), h);
+    /// Implementation of the Euler method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`EulerODESysSolver::eu_solve`] for the details of the method.
+    fn eu_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F> {
+        expect_solved(self.try_eu_solve_with(ode, x, y, x_target, options))
+    }
 
-            for (idx, val) in dy.iter().enumerate() {
-                y[idx] += *val;
-            }
+    /// Implementation of the Euler method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`EulerODESysSolver::eu_solve`] for the details of the method.
+    fn try_eu_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError> {
+        options.check(x, x_target, y.len())?;
+        let stepper = self.eu_stepper(ode, x, y, options.initial_step(x, x_target));
+        solve_with(stepper.with_max_steps(options.max_steps), x_target, &options.output)
+    }
+}
+
+/// Buffers of the Euler method, parametrizing the [`SysStepper`] of the method.
+pub struct Euler<F> {
+    k: Vec<F>,
+}
+
+impl<F: Real> Euler<F> {
+    /// Allocates the buffers needed to integrate a system of dimension `dim`.
+    pub fn with_dim(dim: usize) -> Self {
+        Euler { k: vec![F::zero(); dim] }
+    }
+}
 
-            x_val += h;
+impl<F: Real> SysMethod<F> for Euler<F> {
+    fn step<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &mut [F], h: F) {
+        ode.eval_into(x, y, &mut self.k);
+        for (y, k) in y.iter_mut().zip(&self.k) {
+            *y += h * *k;
         }
-        y
     }
 }
@@ -0,0 +1,517 @@
+//! Explicit Runge-Kutta methods given by their Butcher tableau, for solving systems of ordinary differential equations (ODEs).
+use super::{ODESYS, ODESysSolver, SysMethod, fixed_step, fixed_step_traj, solve_with};
+use super::stepper::SysStepper;
+use super::adaptive::{AdaptiveSysStepper, DenseSysMethod, EmbeddedSysMethod};
+use crate::{ButcherTableau, Output, Real, Solution, SolverError, SolverOptions};
+use crate::error::expect_solved;
+use crate::scalar::lit;
+use crate::ode::explicit_rk::hermite;
+
+/// Explicit Runge-Kutta methods for solving systems of Ordinary Differential Equations (ODEs).
+///
+/// This trait integrates systems of ordinary differential equations with any
+/// [explicit Runge-Kutta method](https://en.wikipedia.org/wiki/Runge%E2%80%93Kutta_methods#Explicit_Runge%E2%80%93Kutta_methods)
+/// described by a [`ButcherTableau`], such as the ones of the crate or a user-defined one. The methods whose tableau
+/// has an embedded solution are adaptive: the step size `h` given to them is the initial one, which is then adapted so
+/// that the weighted root mean square of the local error estimates stays within the tolerances, see
+/// [`AdaptiveSysStepper`]. The other ones take fixed steps of size `h`, see [`SysStepper`].
+pub trait ExplicitRungeKuttaODESysSolver<T: ODESYS<F>, F: Real = f64> {
+    /// Solve the system of ODEs using the explicit Runge-Kutta method of `tableau`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The Butcher tableau of the method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size, or the initial one if the tableau has an embedded solution.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ExplicitRungeKuttaODESysSolver::try_erk_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ButcherTableau;
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, explicit_rk_sys::ExplicitRungeKuttaODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.erk_solve(&my_ode_system, &ButcherTableau::cash_karp(), x0, y0, x_target, h);
+    /// assert!((result[0] - 0.5f64.exp()).abs() < 1e-5);
+    /// println!("Solution at x = {}: {:?}", x_target, result);
+    /// ```
+    fn erk_solve(&self, ode: &T, tableau: &ButcherTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F>;
+
+    /// Solve the system of ODEs using the explicit Runge-Kutta method of `tableau`, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The Butcher tableau of the method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size, or the initial one if the tableau has an embedded solution.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from the initial point up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ExplicitRungeKuttaODESysSolver::try_erk_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ButcherTableau;
+    /// # use damndiff::ode_sys::{ODESysSolver, explicit_rk_sys::ExplicitRungeKuttaODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    ///
+    /// let solution = solver.erk_solve_traj(&oscillator, &ButcherTableau::ralston(), 0.0, vec![1.0, 0.0], 1.0, 0.1);
+    /// assert_eq!(solution.len(), 11);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn erk_solve_traj(&self, ode: &T, tableau: &ButcherTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the explicit Runge-Kutta method of `tableau`, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The Butcher tableau of the method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size, or the initial one if the tableau has an embedded solution.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ButcherTableau;
+    /// # use damndiff::ode_sys::{ODESysSolver, explicit_rk_sys::ExplicitRungeKuttaODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // The first component blows up at x = 1
+    /// let blow_up = |_x: &f64, y: &Vec<f64>| vec![y[0] * y[0], -y[1]];
+    ///
+    /// match solver.try_erk_solve(&blow_up, &ButcherTableau::tsitouras5(), 0.0, vec![1.0, 1.0], 2.0, 0.1) {
+    ///     Ok(result) => println!("Solution at x = 2: {:?}", result),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_erk_solve(&self, ode: &T, tableau: &ButcherTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError>;
+
+    /// Create a stepper integrating the system of ODEs with the explicit Runge-Kutta method of `tableau`, one fixed step at a time.
+    ///
+    /// The embedded solution of the tableau, if any, is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The Butcher tableau of the method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// A [`SysStepper`] holding the current state, starting from the initial `(x, y)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ButcherTableau;
+    /// # use damndiff::ode_sys::{ODESysSolver, explicit_rk_sys::ExplicitRungeKuttaODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // Upwind discretization of the advection equation on a periodic grid, integrated with SSPRK3
+    /// let advection = |_x: &f64, u: &Vec<f64>| {
+    ///     let n = u.len();
+    ///     (0..n).map(|i| -(u[i] - u[(i + n - 1) % n]) * n as f64).collect::<Vec<f64>>()
+    /// };
+    /// let u0 = (0..50).map(|i| if (10..20).contains(&i) { 1.0 } else { 0.0 }).collect();
+    /// let mut stepper = solver.erk_stepper(&advection, &ButcherTableau::ssprk3(), 0.0, u0, 0.01);
+    ///
+    /// stepper.advance_to(0.5).unwrap();
+    /// // The strong stability preserving method keeps the solution within its initial bounds
+    /// assert!(stepper.y().iter().all(|u| (-1e-12..=1.0 + 1e-12).contains(u)));
+    /// ```
+    fn erk_stepper<'a>(&self, ode: &'a T, tableau: &ButcherTableau, x: F, y: Vec<F>, h: F) -> SysStepper<'a, ExplicitRk<F>, T, F>;
+
+    /// Create a stepper integrating the system of ODEs with the embedded pair of `tableau`, one adaptive step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The Butcher tableau of the method, with an embedded solution.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The initial step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// An [`AdaptiveSysStepper`] holding the current state, starting from the initial `(x, y)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tableau has no embedded solution.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ButcherTableau;
+    /// # use damndiff::ode_sys::{ODESysSolver, explicit_rk_sys::ExplicitRungeKuttaODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let mut stepper = solver.erk_adaptive_stepper(&oscillator, &ButcherTableau::tsitouras5(), 0.0, vec![1.0, 0.0], 0.1);
+    ///
+    /// stepper.step().unwrap();
+    /// let (x_prev, x) = stepper.last_step();
+    /// let middle = stepper.interpolate((x_prev + x) / 2.0).unwrap();
+    /// assert!((middle[0] - ((x_prev + x) / 2.0).cos()).abs() < 1e-6);
+    /// ```
+    fn erk_adaptive_stepper<'a>(&self, ode: &'a T, tableau: &ButcherTableau, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, ExplicitRk<F>, T, F>;
+
+    /// Solve the system of ODEs using the explicit Runge-Kutta method of `tableau`, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The Butcher tableau of the method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the tolerances apply to the tableaus with an embedded solution,
+    ///   while the other ones take steps of size `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`ExplicitRungeKuttaODESysSolver::try_erk_solve_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{ButcherTableau, Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, explicit_rk_sys::ExplicitRungeKuttaODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let options = SolverOptions::new().rtol(1e-8).atol(1e-10).output(Output::Points(vec![1.0, 2.0, 3.0]));
+    ///
+    /// let solution = solver.erk_solve_with(&oscillator, &ButcherTableau::cash_karp(), 0.0, vec![1.0, 0.0], 3.0, &options);
+    /// for (x, y) in solution.iter() {
+    ///     assert!((y[0] - x.cos()).abs() < 1e-6);
+    /// }
+    /// ```
+    fn erk_solve_with(&self, ode: &T, tableau: &ButcherTableau, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the explicit Runge-Kutta method of `tableau`, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The Butcher tableau of the method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the tolerances apply to the tableaus with an embedded solution,
+    ///   while the other ones take steps of size `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{ButcherTableau, Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, explicit_rk_sys::ExplicitRungeKuttaODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let options = SolverOptions::new().h0(0.1).max_steps(5).output(Output::Final);
+    ///
+    /// match solver.try_erk_solve_with(&oscillator, &ButcherTableau::rk4(), 0.0, vec![1.0, 0.0], 1.0, &options) {
+    ///     Ok(solution) => println!("Solution at x = 1: {:?}", solution.last()),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_erk_solve_with(&self, ode: &T, tableau: &ButcherTableau, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError>;
+}
+
+impl<F: Real, T: ODESYS<F>> ExplicitRungeKuttaODESysSolver<T, F> for ODESysSolver {
+    /// Implementation of the explicit Runge-Kutta method of a Butcher tableau for solving systems of ODEs.
+    ///
+    /// This method approximates the solution of the system at the specified x_target. Each step evaluates the stages of
+    /// the tableau one after the other, and combines them with its weights. With an embedded solution, the weighted root
+    /// mean square of the difference between both solutions estimates the local error, and the step is rejected and
+    /// retried with a smaller size when it exceeds the tolerances. When the last stage is evaluated at the end point of
+    /// the step (first same as last), an accepted adaptive step saves one evaluation of the system.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The Butcher tableau of the method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size, or the initial one if the tableau has an embedded solution.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`.
+    ///
+    /// # When to Use:
+    ///
+    /// Experimenting with methods the crate does not name, or picking a tableau suited to the problem, such as SSPRK3
+    /// for the semi-discretization of hyperbolic equations or Tsitouras 5(4) for non-stiff systems at moderate tolerances.
+    ///
+    /// # Pros and Cons:
+    /// - Pros: Any explicit method at the cost of writing its coefficients, with step size control and dense output when the tableau provides them.
+    /// - Cons: Slightly slower than a method written out by hand, and no check that the coefficients reach the stated order.
+    ///
+    /// # Stability Analysis:
+    ///
+    /// Conditionally stable, with the stability region of the chosen tableau: like every explicit method, it is inefficient on stiff systems.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ButcherTableau;
+    /// # use damndiff::ode_sys::{ODESysSolver, explicit_rk_sys::ExplicitRungeKuttaODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    ///
+    /// let result = solver.erk_solve(&oscillator, &ButcherTableau::tsitouras5(), 0.0, vec![1.0, 0.0], 10.0, 0.1);
+    /// assert!((result[0] - 10.0f64.cos()).abs() < 1e-4);
+    /// ```
+    fn erk_solve(&self, ode: &T, tableau: &ButcherTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        expect_solved(self.try_erk_solve(ode, tableau, x, y, x_target, h))
+    }
+
+    /// Implementation of the explicit Runge-Kutta method of a Butcher tableau returning the whole trajectory of the system of ODEs.
+    ///
+    /// See [`ExplicitRungeKuttaODESysSolver::erk_solve`] for the details of the method.
+    fn erk_solve_traj(&self, ode: &T, tableau: &ButcherTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F> {
+        if tableau.embedded_order().is_some() {
+            expect_solved(solve_with(self.erk_adaptive_stepper(ode, tableau, x, y, h), x_target, &Output::Steps))
+        } else {
+            let method = ExplicitRk::new(tableau.clone(), y.len());
+            expect_solved(fixed_step_traj(ode, method, x, y, h, x_target))
+        }
+    }
+
+    /// Implementation of the explicit Runge-Kutta method of a Butcher tableau reporting failures as a [`SolverError`].
+    ///
+    /// See [`ExplicitRungeKuttaODESysSolver::erk_solve`] for the details of the method.
+    fn try_erk_solve(&self, ode: &T, tableau: &ButcherTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError> {
+        if tableau.embedded_order().is_some() {
+            let mut stepper = self.erk_adaptive_stepper(ode, tableau, x, y, h);
+            stepper.advance_to(x_target)?;
+            Ok(stepper.into_y())
+        } else {
+            let method = ExplicitRk::new(tableau.clone(), y.len());
+            fixed_step(ode, method, x, y, h, x_target, |_, _| {})
+        }
+    }
+
+    /// Implementation of the explicit Runge-Kutta method of a Butcher tableau as a [`SysStepper`].
+    ///
+    /// See [`ExplicitRungeKuttaODESysSolver::erk_solve`] for the details of the method.
+    fn erk_stepper<'a>(&self, ode: &'a T, tableau: &ButcherTableau, x: F, y: Vec<F>, h: F) -> SysStepper<'a, ExplicitRk<F>, T, F> {
+        let method = ExplicitRk::new(tableau.clone(), y.len());
+        SysStepper::new(ode, method, x, y, h)
+    }
+
+    /// Implementation of the explicit Runge-Kutta method of a Butcher tableau as an [`AdaptiveSysStepper`].
+    ///
+    /// See [`ExplicitRungeKuttaODESysSolver::erk_solve`] for the details of the method.
+    fn erk_adaptive_stepper<'a>(&self, ode: &'a T, tableau: &ButcherTableau, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, ExplicitRk<F>, T, F> {
+        assert!(tableau.embedded_order().is_some(), "the adaptive steppers need a tableau with an embedded solution");
+        let method = ExplicitRk::new(tableau.clone(), y.len());
+        AdaptiveSysStepper::new(ode, method, x, y, h)
+    }
+
+    /// Implementation of the explicit Runge-Kutta method of a Butcher tableau with the settings of a [`SolverOptions`].
+    ///
+    /// See [`ExplicitRungeKuttaODESysSolver::erk_solve`] for the details of the method.
+    fn erk_solve_with(&self, ode: &T, tableau: &ButcherTableau, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F> {
+        expect_solved(self.try_erk_solve_with(ode, tableau, x, y, x_target, options))
+    }
+
+    /// Implementation of the explicit Runge-Kutta method of a Butcher tableau with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`ExplicitRungeKuttaODESysSolver::erk_solve`] for the details of the method.
+    fn try_erk_solve_with(&self, ode: &T, tableau: &ButcherTableau, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError> {
+        options.check(x, x_target, y.len())?;
+        let h = options.initial_step(x, x_target);
+        if tableau.embedded_order().is_some() {
+            let stepper = self.erk_adaptive_stepper(ode, tableau, x, y, h);
+            solve_with(stepper.with_options(options), x_target, &options.output)
+        } else {
+            let stepper = self.erk_stepper(ode, tableau, x, y, h);
+            solve_with(stepper.with_max_steps(options.max_steps), x_target, &options.output)
+        }
+    }
+}
+
+/// Stage buffers of an explicit Runge-Kutta method given by its [`ButcherTableau`] for a system of ODEs, parametrizing
+/// the [`SysStepper`] of the method, or its [`AdaptiveSysStepper`] when the tableau has an embedded solution.
+///
+/// It keeps the derivatives of the stages of the last accepted step for the continuous extension. The methods without
+/// a continuous extension of their own are interpolated with a cubic Hermite polynomial, whose derivative at the end
+/// of the step is the last stage for the methods with the first-same-as-last property.
+pub struct ExplicitRk<F> {
+    tableau: ButcherTableau,
+    /// Derivatives of the stages of the last attempted step.
+    k: Vec<Vec<F>>,
+    /// Derivatives of the stages of the last accepted step.
+    k_prev: Vec<Vec<F>>,
+    y_temp: Vec<F>,
+    /// Derivative at the end of the last accepted step, once prepared for the Hermite interpolation.
+    f_end: Vec<F>,
+    /// Whether `k[0]` holds the derivative at the current point.
+    fsal: bool,
+}
+
+impl<F: Real> ExplicitRk<F> {
+    /// Creates the method of `tableau`, with the buffers needed to integrate a system of dimension `dim`.
+    pub fn new(tableau: ButcherTableau, dim: usize) -> Self {
+        let stages = tableau.stages();
+        ExplicitRk {
+            tableau,
+            k: vec![vec![F::zero(); dim]; stages],
+            k_prev: vec![vec![F::zero(); dim]; stages],
+            y_temp: vec![F::zero(); dim],
+            f_end: vec![F::zero(); dim],
+            fsal: false,
+        }
+    }
+
+    /// Returns the Butcher tableau of the method.
+    pub fn tableau(&self) -> &ButcherTableau {
+        &self.tableau
+    }
+
+    /// Advances `y` from `x` to `x + h`, where the derivative `f` at `(x, y)` is already known.
+    pub(crate) fn step_from<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &mut [F], f: &[F], h: F) {
+        self.k[0].copy_from_slice(f);
+        self.fsal = true;
+        self.stages(ode, x, y, h);
+        accumulate(y, h, &self.tableau.b, &self.k);
+    }
+
+    /// Evaluates the stages of a step of size `h` from `(x, y)`.
+    fn stages<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F], h: F) {
+        if !self.fsal {
+            ode.eval_into(x, y, &mut self.k[0]);
+            self.fsal = true;
+        }
+        for i in 1..self.k.len() {
+            self.y_temp.copy_from_slice(y);
+            accumulate(&mut self.y_temp, h, &self.tableau.a[i], &self.k);
+            ode.eval_into(x + h * lit(self.tableau.c[i]), &self.y_temp, &mut self.k[i]);
+        }
+    }
+}
+
+impl<F: Real> SysMethod<F> for ExplicitRk<F> {
+    fn step<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &mut [F], h: F) {
+        self.fsal = false;
+        self.stages(ode, x, y, h);
+        accumulate(y, h, &self.tableau.b, &self.k);
+    }
+}
+
+impl<F: Real> EmbeddedSysMethod<F> for ExplicitRk<F> {
+    fn error_order(&self) -> usize {
+        self.tableau.error_order()
+    }
+
+    fn step<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F], h: F, y_next: &mut [F], error: &mut [F]) {
+        self.stages(ode, x, y, h);
+        y_next.copy_from_slice(y);
+        accumulate(y_next, h, &self.tableau.b, &self.k);
+
+        let e = self.tableau.e.as_ref().expect("the adaptive steppers need a tableau with an embedded solution");
+        error.fill(F::zero());
+        accumulate(error, h, e, &self.k);
+    }
+
+    fn accept(&mut self) {
+        std::mem::swap(&mut self.k, &mut self.k_prev);
+
+        // The last stage of a first-same-as-last method is the derivative at the new point
+        self.fsal = self.tableau.is_fsal();
+        if self.fsal {
+            self.k[0].copy_from_slice(&self.k_prev[self.k_prev.len() - 1]);
+        }
+    }
+}
+
+impl<F: Real> DenseSysMethod<F> for ExplicitRk<F> {
+    fn prepare<T: ODESYS<F>>(&mut self, ode: &T, x: F, h: F, _y_prev: &[F], y: &[F]) {
+        if !self.tableau.has_dense_output() && !self.tableau.is_fsal() {
+            ode.eval_into(x + h, y, &mut self.f_end);
+        }
+    }
+
+    fn interpolate(&self, theta: F, h: F, y_prev: &[F], y: &[F], out: &mut [F]) {
+        if self.tableau.has_dense_output() {
+            out.copy_from_slice(y_prev);
+            for (i, k) in self.k_prev.iter().enumerate() {
+                let weight = h * self.tableau.dense_weight(i, theta);
+                for (out, k) in out.iter_mut().zip(k) {
+                    *out += weight * *k;
+                }
+            }
+            return;
+        }
+
+        let f_end = if self.tableau.is_fsal() { &self.k_prev[self.k_prev.len() - 1] } else { &self.f_end };
+        let (h00, h10, h01, h11) = hermite(theta);
+        for (i, out) in out.iter_mut().enumerate() {
+            *out = h00 * y_prev[i] + h10 * h * self.k_prev[0][i] + h01 * y[i] + h11 * h * f_end[i];
+        }
+    }
+}
+
+/// Adds `h * sum(weights[j] * k[j])` to `out`, over the weights given.
+pub(super) fn accumulate<F: Real>(out: &mut [F], h: F, weights: &[f64], k: &[Vec<F>]) {
+    for (weight, k) in weights.iter().zip(k) {
+        if *weight != 0.0 {
+            let scale = h * lit(*weight);
+            for (out, k) in out.iter_mut().zip(k) {
+                *out += scale * *k;
+            }
+        }
+    }
+}
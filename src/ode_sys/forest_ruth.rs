@@ -1,10 +1,14 @@
 //! Forest-Ruth method for solving systems of ordinary differential equations (ODEs).
-use super::{ODESYS, ODESysSolver, add_vec, vec_scalar_mul};
+use super::{ODESYS, ODESysSolver, SysMethod, add_scaled, fixed_step, fixed_step_traj, solve_with};
+use super::stepper::SysStepper;
+use crate::{Real, Solution, SolverError, SolverOptions};
+use crate::error::expect_solved;
+use crate::scalar::lit;
 
 /// Forest-Ruth method for solving systems of Ordinary Differential Equations (ODEs).
 ///
 /// This trait defines the [Forest-Ruth method](https://en.wikipedia.org/wiki/Symplectic_integrator) for solving systems of ordinary differential equations.
-pub trait FRODESysSolver<T: ODESYS> {
+pub trait FRODESysSolver<T: ODESYS<F>, F: Real = f64> {
     /// Solve the system of ODEs using the Forest-Ruth method.
     ///
     /// # Arguments
@@ -19,9 +23,14 @@ pub trait FRODESysSolver<T: ODESYS> {
     ///
     /// The vector of estimated y values at `x_target`.
     /// 
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`FRODESysSolver::try_fr_solve`].
+    ///
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, forest_ruth::FRODESysSolver};
     /// struct MyODESystem;
     /// impl ODESYS for MyODESystem {
     ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
@@ -41,11 +50,228 @@ pub trait FRODESysSolver<T: ODESYS> {
     /// let result = solver.fr_solve(&my_ode_system, x0, y0, x_target, h);
     /// println!("Solution at x = {}: {:?}", x_target, result);
     /// ```
-    fn fr_solve(&self, ode: &T, x: f64, y: Vec<f64>, x_target: f64, h: f64) -> Vec<f64>;
+    fn fr_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F>;
+
+    /// Solve the system of ODEs using the Forest-Ruth method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size or increment for x.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from the initial x up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`FRODESysSolver::try_fr_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, forest_ruth::FRODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let solution = solver.fr_solve_traj(&my_ode_system, x0, y0, x_target, h);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn fr_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Forest-Ruth method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size or increment for x.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, forest_ruth::FRODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.try_fr_solve(&my_ode_system, x0, y0.clone(), x_target, h).unwrap();
+    /// assert!((result[0] - 0.5f64.exp()).abs() < 0.05);
+    /// assert!((result[1] - (3.0 * 1.0f64.exp() - 2.0)).abs() < 0.2);
+    ///
+    /// // A step size this small needs more steps than the default limit allows
+    /// let error = solver.try_fr_solve(&my_ode_system, x0, y0, x_target, 1e-8).unwrap_err();
+    /// assert_eq!(error, SolverError::MaxStepsExceeded { x: 0.0, max_steps: 10_000_000 });
+    /// ```
+    fn try_fr_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError>;
+
+    /// Create a stepper integrating the system of ODEs with the Forest-Ruth method, one step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// A [`SysStepper`] holding the current state, starting from the initial `(x, y)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, forest_ruth::FRODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let mut stepper = solver.fr_stepper(&my_ode_system, 0.0, vec![1.0, 2.0], 0.1);
+    ///
+    /// // Integrate a tenth of x at a time
+    /// for x in [0.1, 0.2, 0.3] {
+    ///     let y = stepper.advance_to(x).unwrap();
+    ///     assert!((y[0] - (x * x / 2.0).exp()).abs() < 0.05);
+    ///     assert!((y[1] - (3.0 * x.exp() - x - 1.0)).abs() < 0.05);
+    ///     assert_eq!(stepper.x(), x);
+    /// }
+    /// ```
+    fn fr_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> SysStepper<'a, ForestRuth<F>, T, F>;
+
+    /// Solve the system of ODEs using the Forest-Ruth method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`FRODESysSolver::try_fr_solve_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, forest_ruth::FRODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let options = SolverOptions::new().h0(0.1).output(Output::Final);
+    ///
+    /// let solution = solver.fr_solve_with(&my_ode_system, 0.0, vec![1.0, 2.0], 1.0, &options);
+    /// assert_eq!(solution.x, vec![1.0]);
+    /// assert!((solution.y[0][0] - 0.5f64.exp()).abs() < 0.2);
+    /// assert!((solution.y[0][1] - (3.0 * 1.0f64.exp() - 2.0)).abs() < 0.2);
+    /// ```
+    fn fr_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Forest-Ruth method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverError, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, forest_ruth::FRODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// // The solution is requested at x = 0.5 on the way to x = 1
+    /// let options = SolverOptions::new().h0(0.1).output(Output::Points(vec![0.5]));
+    /// let solution = solver.try_fr_solve_with(&my_ode_system, 0.0, vec![1.0, 2.0], 1.0, &options).unwrap();
+    /// assert_eq!(solution.x, vec![0.5]);
+    /// assert!((solution.y[0][0] - 0.125f64.exp()).abs() < 0.05);
+    /// assert!((solution.y[0][1] - (3.0 * 0.5f64.exp() - 1.5)).abs() < 0.05);
+    ///
+    /// // Ten steps of 0.1 are needed to reach x = 1
+    /// let options = SolverOptions::new().h0(0.1).max_steps(5);
+    /// let error = solver.try_fr_solve_with(&my_ode_system, 0.0, vec![1.0, 2.0], 1.0, &options).unwrap_err();
+    /// assert_eq!(error, SolverError::MaxStepsExceeded { x: 0.0, max_steps: 5 });
+    /// ```
+    fn try_fr_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError>;
 }
 
 // Implementing the Forest-Ruth method for the system of ODEs Solver
-impl<T: ODESYS> FRODESysSolver<T> for ODESysSolver {
+impl<F: Real, T: ODESYS<F>> FRODESysSolver<T, F> for ODESysSolver {
     /// Implementation of the Forest-Ruth method to solve a system of ODEs.
     ///
     /// This method approximates the solution to the system of ODEs at the specified x_target.
@@ -77,6 +303,7 @@ impl<T: ODESYS> FRODESysSolver<T> for ODESysSolver {
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, forest_ruth::FRODESysSolver};
     /// struct MyODESystem;
     /// impl ODESYS for MyODESystem {
     ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
@@ -96,41 +323,86 @@ impl<T: ODESYS> FRODESysSolver<T> for ODESysSolver {
     /// let result = solver.fr_solve(&my_ode_system, x0, y0, x_target, h);
     /// println!("Solution at x = {}: {:?}", x_target, result);
     /// ```
-    fn fr_solve(&self, ode: &T, mut x: f64, mut y: Vec<f64>, x_target: f64, h: f64) -> Vec<f64> {
-        while x < x_target {
-            // Implement Forest-Ruth method for solving the ODE at each step
-            
-            // Stage 1
-            let k1 = vec_scalar_mul(&ode.eval(&x, &y), h);
-            let y_temp_1 = add_vec(&y, &vec_scalar_mul(&k1, 0.5));
+    fn fr_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        expect_solved(self.try_fr_solve(ode, x, y, x_target, h))
+    }
+
+    /// Implementation of the Forest-Ruth method returning the whole trajectory of the system of ODEs.
+    ///
+    /// See [`FRODESysSolver::fr_solve`] for the details of the method.
+    fn fr_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F> {
+        expect_solved(fixed_step_traj(ode, ForestRuth::with_dim(y.len()), x, y, h, x_target))
+    }
+
+    /// Implementation of the Forest-Ruth method reporting failures as a [`SolverError`].
+    ///
+    /// See [`FRODESysSolver::fr_solve`] for the details of the method.
+    fn try_fr_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError> {
+        fixed_step(ode, ForestRuth::with_dim(y.len()), x, y, h, x_target, |_, _| {})
+    }
+
+    /// Implementation of the Forest-Ruth method as a [`SysStepper`].
+    ///
+    /// See [`FRODESysSolver::fr_solve`] for the details of the method.
+    fn fr_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> SysStepper<'a, ForestRuth<F>, T, F> {
+        SysStepper::new(ode, ForestRuth::with_dim(y.len()), x, y, h)
+    }
 
-            // Stage 2
-            let k2 = vec_scalar_mul(&ode.eval(&(x + h * 0.5), &y_temp_1), h);
-            let y_temp_2 = add_vec(&y, &vec_scalar_mul(&k2, 0.5));
+    /// Implementation of the Forest-Ruth method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`FRODESysSolver::fr_solve`] for the details of the method.
+    fn fr_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F> {
+        expect_solved(self.try_fr_solve_with(ode, x, y, x_target, options))
+    }
 
-            // Stage 3
-            let k3 = vec_scalar_mul(&ode.eval(&(x + h * 0.5), &y_temp_2), h);
-            let y_temp_3 = add_vec(&y, &vec_scalar_mul(&k3, 2.0));
+    /// Implementation of the Forest-Ruth method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`FRODESysSolver::fr_solve`] for the details of the method.
+    fn try_fr_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError> {
+        options.check(x, x_target, y.len())?;
+        let stepper = self.fr_stepper(ode, x, y, options.initial_step(x, x_target));
+        solve_with(stepper.with_max_steps(options.max_steps), x_target, &options.output)
+    }
+}
 
-            // Stage 4
-            let k4 = vec_scalar_mul(&ode.eval(&(x + h), &y_temp_3), h);
+/// Stage buffers of the Forest-Ruth method, parametrizing the [`SysStepper`] of the method.
+pub struct ForestRuth<F> {
+    k1: Vec<F>,
+    k2: Vec<F>,
+    k3: Vec<F>,
+    k4: Vec<F>,
+    y_temp: Vec<F>,
+}
+
+impl<F: Real> ForestRuth<F> {
+    /// Allocates the buffers needed to integrate a system of dimension `dim`.
+    pub fn with_dim(dim: usize) -> Self {
+        ForestRuth {
+            k1: vec![F::zero(); dim],
+            k2: vec![F::zero(); dim],
+            k3: vec![F::zero(); dim],
+            k4: vec![F::zero(); dim],
+            y_temp: vec![F::zero(); dim],
+        }
+    }
+}
 
-            // Update y using weighted averages of these stages
-            y = add_vec(
-                &y,
-                &vec_scalar_mul(
-                    &add_vec(
-                        &k1,
-                        &add_vec(
-                            &vec_scalar_mul(&add_vec(&k2, &k3), 2.0),
-                            &k4,
-                        ),
-                    ),
-                    1.0 / 6.0,
-                ),
-            );
-            x += h;
+impl<F: Real> SysMethod<F> for ForestRuth<F> {
+    fn step<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &mut [F], h: F) {
+        // Stage 1
+        ode.eval_into(x, y, &mut self.k1);
+        add_scaled(&mut self.y_temp, y, h * lit(0.5), &self.k1);
+        // Stage 2
+        ode.eval_into(x + h * lit(0.5), &self.y_temp, &mut self.k2);
+        add_scaled(&mut self.y_temp, y, h * lit(0.5), &self.k2);
+        // Stage 3
+        ode.eval_into(x + h * lit(0.5), &self.y_temp, &mut self.k3);
+        add_scaled(&mut self.y_temp, y, h * lit(2.0), &self.k3);
+        // Stage 4
+        ode.eval_into(x + h, &self.y_temp, &mut self.k4);
+        // Update y using weighted averages of these stages
+        for (i, y) in y.iter_mut().enumerate() {
+            *y += h * (self.k1[i] + (self.k2[i] + self.k3[i]) * lit(2.0) + self.k4[i]) / lit(6.0);
         }
-        y
     }
 }
@@ -1,10 +1,14 @@
 //! Leapfrog method for solving systems of ordinary differential equations (ODEs).
-use super::{ODESYS, ODESysSolver, add_vec};
+use super::{ODESYS, ODESysSolver, SysMethod, fixed_step, fixed_step_traj, solve_with};
+use super::stepper::SysStepper;
+use crate::{Real, Solution, SolverError, SolverOptions};
+use crate::error::expect_solved;
+use crate::scalar::lit;
 
 /// Leapfrog method for solving systems of Ordinary Differential Equations (ODEs).
 ///
 /// This trait defines the [Leapfrog method](https://young.physics.ucsc.edu/115/leapfrog.pdf) for solving systems of ordinary differential equations.
-pub trait LeapfrogODESysSolver<T: ODESYS> {
+pub trait LeapfrogODESysSolver<T: ODESYS<F>, F: Real = f64> {
     /// Solve the system of ODEs using the Leapfrog method.
     ///
     /// # Arguments
@@ -19,9 +23,14 @@ pub trait LeapfrogODESysSolver<T: ODESYS> {
     ///
     /// The vector of estimated y values at `x_target`.
     /// 
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`LeapfrogODESysSolver::try_lf_solve`].
+    ///
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, leapfrog::LeapfrogODESysSolver};
     /// struct MyODESystem;
     /// impl ODESYS for MyODESystem {
     ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
@@ -41,11 +50,227 @@ pub trait LeapfrogODESysSolver<T: ODESYS> {
     /// let result = solver.lf_solve(&my_ode_system, x0, y0, x_target, h);
     /// println!("Solution at x = {}: {:?}", x_target, result);
     /// ```
-    fn lf_solve(&self, ode: &T, x: f64, y: Vec<f64>, x_target: f64, h: f64) -> Vec<f64>;
+    fn lf_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F>;
+
+    /// Solve the system of ODEs using the Leapfrog method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size or increment for x.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from the initial x up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`LeapfrogODESysSolver::try_lf_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, leapfrog::LeapfrogODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let solution = solver.lf_solve_traj(&my_ode_system, x0, y0, x_target, h);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn lf_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Leapfrog method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size or increment for x.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, leapfrog::LeapfrogODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.try_lf_solve(&my_ode_system, x0, y0.clone(), x_target, h).unwrap();
+    /// assert!((result[0] - 0.5f64.exp()).abs() < 0.05);
+    /// assert!((result[1] - (3.0 * 1.0f64.exp() - 2.0)).abs() < 0.2);
+    ///
+    /// let error = solver.try_lf_solve(&my_ode_system, x0, y0, x_target, f64::INFINITY).unwrap_err();
+    /// assert_eq!(error, SolverError::InvalidStepSize { x: 0.0, h: f64::INFINITY });
+    /// ```
+    fn try_lf_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError>;
+
+    /// Create a stepper integrating the system of ODEs with the Leapfrog method, one step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// A [`SysStepper`] holding the current state, starting from the initial `(x, y)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, leapfrog::LeapfrogODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let mut stepper = solver.lf_stepper(&my_ode_system, 0.0, vec![1.0, 2.0], 0.1);
+    ///
+    /// // Integrate a tenth of x at a time
+    /// for x in [0.1, 0.2, 0.3] {
+    ///     let y = stepper.advance_to(x).unwrap();
+    ///     assert!((y[0] - (x * x / 2.0).exp()).abs() < 0.05);
+    ///     assert!((y[1] - (3.0 * x.exp() - x - 1.0)).abs() < 0.05);
+    ///     assert_eq!(stepper.x(), x);
+    /// }
+    /// ```
+    fn lf_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> SysStepper<'a, Leapfrog<F>, T, F>;
+
+    /// Solve the system of ODEs using the Leapfrog method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`LeapfrogODESysSolver::try_lf_solve_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, leapfrog::LeapfrogODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let options = SolverOptions::new().h0(0.1).output(Output::Final);
+    ///
+    /// let solution = solver.lf_solve_with(&my_ode_system, 0.0, vec![1.0, 2.0], 1.0, &options);
+    /// assert_eq!(solution.x, vec![1.0]);
+    /// assert!((solution.y[0][0] - 0.5f64.exp()).abs() < 0.2);
+    /// assert!((solution.y[0][1] - (3.0 * 1.0f64.exp() - 2.0)).abs() < 0.2);
+    /// ```
+    fn lf_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Leapfrog method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverError, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, leapfrog::LeapfrogODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// // The solution is requested at x = 0.5 on the way to x = 1
+    /// let options = SolverOptions::new().h0(0.1).output(Output::Points(vec![0.5]));
+    /// let solution = solver.try_lf_solve_with(&my_ode_system, 0.0, vec![1.0, 2.0], 1.0, &options).unwrap();
+    /// assert_eq!(solution.x, vec![0.5]);
+    /// assert!((solution.y[0][0] - 0.125f64.exp()).abs() < 0.05);
+    /// assert!((solution.y[0][1] - (3.0 * 0.5f64.exp() - 1.5)).abs() < 0.05);
+    ///
+    /// // Ten steps of 0.1 are needed to reach x = 1
+    /// let options = SolverOptions::new().h0(0.1).max_steps(5);
+    /// let error = solver.try_lf_solve_with(&my_ode_system, 0.0, vec![1.0, 2.0], 1.0, &options).unwrap_err();
+    /// assert_eq!(error, SolverError::MaxStepsExceeded { x: 0.0, max_steps: 5 });
+    /// ```
+    fn try_lf_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError>;
 }
 
 // Implementing the Leapfrog method for the system of ODEs Solver
-impl<T: ODESYS> LeapfrogODESysSolver<T> for ODESysSolver {
+impl<F: Real, T: ODESYS<F>> LeapfrogODESysSolver<T, F> for ODESysSolver {
     /// Implementation of the Leapfrog method to solve a system of ODEs.
     ///
     /// This method approximates the solution to the system of ODEs at the specified x_target.
@@ -77,6 +302,7 @@ impl<T: ODESYS> LeapfrogODESysSolver<T> for ODESysSolver {
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, leapfrog::LeapfrogODESysSolver};
     /// struct MyODESystem;
     /// impl ODESYS for MyODESystem {
     ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
@@ -96,29 +322,69 @@ impl<T: ODESYS> LeapfrogODESysSolver<T> for ODESysSolver {
     /// let result = solver.lf_solve(&my_ode_system, x0, y0, x_target, h);
     /// println!("Solution at x = {}: {:?}", x_target, result);
     /// ```
-    fn lf_solve(&self, ode: &T, x: f64, mut y: Vec<f64>, x_target: f64, h: f64) -> Vec<f64> {
-        let mut x_val = x;
+    fn lf_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        expect_solved(self.try_lf_solve(ode, x, y, x_target, h))
+    }
 
-        while x_val < x_target {
-            let dy = ode.eval(&x_val, &y);
+    /// Implementation of the Leapfrog method returning the whole trajectory of the system of ODEs.
+    ///
+    /// See [`LeapfrogODESysSolver::lf_solve`] for the details of the method.
+    fn lf_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F> {
+        expect_solved(fixed_step_traj(ode, Leapfrog::with_dim(y.len()), x, y, h, x_target))
+    }
 
-            let mut half_dy = vec![0.0; y.len()];
-            for (idx, val) in dy.iter().enumerate() {
-                half_dy[idx] = val * h / 2.0;
-            }
+    /// Implementation of the Leapfrog method reporting failures as a [`SolverError`].
+    ///
+    /// See [`LeapfrogODESysSolver::lf_solve`] for the details of the method.
+    fn try_lf_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError> {
+        fixed_step(ode, Leapfrog::with_dim(y.len()), x, y, h, x_target, |_, _| {})
+    }
 
-            y = add_vec(&y, &half_dy);
-            x_val += h;
+    /// Implementation of the Leapfrog method as a [`SysStepper`].
+    ///
+    /// See [`LeapfrogODESysSolver::lf_solve`] for the details of the method.
+    fn lf_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> SysStepper<'a, Leapfrog<F>, T, F> {
+        SysStepper::new(ode, Leapfrog::with_dim(y.len()), x, y, h)
+    }
 
-            let next_dy = ode.eval(&x_val, &y);
+    /// Implementation of the Leapfrog method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`LeapfrogODESysSolver::lf_solve`] for the details of the method.
+    fn lf_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F> {
+        expect_solved(self.try_lf_solve_with(ode, x, y, x_target, options))
+    }
+
+    /// Implementation of the Leapfrog method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`LeapfrogODESysSolver::lf_solve`] for the details of the method.
+    fn try_lf_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError> {
+        options.check(x, x_target, y.len())?;
+        let stepper = self.lf_stepper(ode, x, y, options.initial_step(x, x_target));
+        solve_with(stepper.with_max_steps(options.max_steps), x_target, &options.output)
+    }
+}
 
-            let mut next_half_dy = vec![0.0; y.len()];
-            for (idx, val) in next_dy.iter().enumerate() {
-                next_half_dy[idx] = val * h / 2.0;
-            }
+/// Buffers of the Leapfrog method, parametrizing the [`SysStepper`] of the method.
+pub struct Leapfrog<F> {
+    dy: Vec<F>,
+}
 
-            y = add_vec(&y, &next_half_dy);
+impl<F: Real> Leapfrog<F> {
+    /// Allocates the buffers needed to integrate a system of dimension `dim`.
+    pub fn with_dim(dim: usize) -> Self {
+        Leapfrog { dy: vec![F::zero(); dim] }
+    }
+}
+
+impl<F: Real> SysMethod<F> for Leapfrog<F> {
+    fn step<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &mut [F], h: F) {
+        ode.eval_into(x, y, &mut self.dy);
+        for (y, dy) in y.iter_mut().zip(&self.dy) {
+            *y += *dy * h / lit(2.0);
+        }
+        ode.eval_into(x + h, y, &mut self.dy);
+        for (y, dy) in y.iter_mut().zip(&self.dy) {
+            *y += *dy * h / lit(2.0);
         }
-        y
     }
 }
@@ -1,14 +1,57 @@
 /// A module containing implementations related to solving systems of Ordinary Differential Equations (ODEs).
 pub mod rk_sys; 
+pub mod adams_bashforth_sys;
 pub mod leapfrog;
 pub mod forest_ruth;
 pub mod euler_sys;
 pub mod radau;
+pub mod rkf_sys;
+pub mod bogacki_shampine_sys;
+pub mod dormand_prince_sys;
+pub mod dop853;
+pub mod bdf;
+pub mod rosenbrock;
+pub mod sdirk;
+pub mod auto_switch;
+pub mod explicit_rk_sys;
+pub mod stepper;
+pub mod adaptive;
+
+use crate::{Output, Real, Solution, SolverError, Stats};
+use crate::stats::Counted;
+use crate::scalar::{lit, to_f64};
+use stepper::SysStepper;
+use adaptive::{AdaptiveSysStepper, EmbeddedSysMethod};
 
 /// A trait representing a system of Ordinary Differential Equations (ODEs).
-pub trait ODESYS {
+///
+/// The scalar type `F` defaults to `f64` and can be any [`Real`] type.
+/// It is implemented for every closure `Fn(&F, &Vec<F>) -> Vec<F>`, so a system can be passed to the solvers without declaring a type for it.
+///
+/// # Example
+///
+/// ```
+/// # use damndiff::SolverError;
+/// # use damndiff::ode_sys::{ODESysSolver, rk_sys::RungeKuttaODESysSolver};
+/// let solver = ODESysSolver;
+/// let omega = 2.0;
+///
+/// // Harmonic oscillator with the angular frequency captured from the environment
+/// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -omega * omega * y[0]];
+/// let result = solver.rk_solve(&oscillator, 0.0, vec![1.0, 0.0], 1.0, 0.01);
+/// assert!((result[0] - 2.0f64.cos()).abs() < 1e-8);
+/// assert!((result[1] + 2.0 * 2.0f64.sin()).abs() < 1e-8);
+///
+/// // A right-hand side missing a component is reported before the first step
+/// let truncated = |_x: &f64, y: &Vec<f64>| vec![y[1]];
+/// let error = solver.try_rk_solve(&truncated, 0.0, vec![1.0, 0.0], 1.0, 0.01).unwrap_err();
+/// assert_eq!(error, SolverError::DimensionMismatch { x: 0.0, expected: 2, found: 1 });
+/// ```
+pub trait ODESYS<F: Real = f64> {
     /// Evaluates the system of ODEs at a given x and y value.
     ///
+    /// The default implementation allocates the result and fills it with [`ODESYS::eval_into`].
+    ///
     /// # Arguments
     ///
     /// * `x` - The value of the independent variable.
@@ -17,17 +60,458 @@ pub trait ODESYS {
     /// # Returns
     ///
     /// A vector representing the derivatives of the ODE system at the given x and y.
-    fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64>;
+    #[allow(clippy::ptr_arg)]
+    fn eval(&self, x: &F, y: &Vec<F>) -> Vec<F> {
+        let mut dydx = vec![F::zero(); y.len()];
+        self.eval_into(*x, y, &mut dydx);
+        dydx
+    }
+
+    /// Evaluates the system of ODEs at a given x and y value, writing the derivatives into `dydx`.
+    ///
+    /// This is the entry point used by the solvers, which call it on preallocated buffers: implement it directly to avoid
+    /// allocating on every evaluation. The default implementation copies the result of [`ODESYS::eval`], so at least one
+    /// of the two methods must be implemented.
+    ///
+    /// # Panics
+    ///
+    /// The default implementation panics if [`ODESYS::eval`] returns a vector whose length differs from the one of `y`.
+    /// The solvers check it on the initial state, and report a [`SolverError::DimensionMismatch`] instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The value of the independent variable.
+    /// * `y` - A slice containing the values of dependent variables.
+    /// * `dydx` - The slice, as long as `y`, receiving the derivatives of the ODE system at the given x and y.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, rk_sys::RungeKuttaODESysSolver};
+    /// struct HeatEquation {
+    ///     diffusivity: f64,
+    /// }
+    ///
+    /// impl ODESYS for HeatEquation {
+    ///     fn eval_into(&self, _x: f64, y: &[f64], dydx: &mut [f64]) {
+    ///         // Second-order central differences with homogeneous Dirichlet boundaries
+    ///         let n = y.len();
+    ///         for i in 0..n {
+    ///             let left = if i > 0 { y[i - 1] } else { 0.0 };
+    ///             let right = if i + 1 < n { y[i + 1] } else { 0.0 };
+    ///             dydx[i] = self.diffusivity * (left - 2.0 * y[i] + right);
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let heat = HeatEquation { diffusivity: 0.1 };
+    /// let n = 100;
+    ///
+    /// // The slowest sine mode keeps its shape and decays as exp(-diffusivity * lambda * x)
+    /// let wavenumber = std::f64::consts::PI / (n + 1) as f64;
+    /// let lambda = 4.0 * (wavenumber / 2.0).sin().powi(2);
+    /// let mode: Vec<f64> = (1..=n).map(|i| (wavenumber * i as f64).sin()).collect();
+    ///
+    /// let result = solver.rk_solve(&heat, 0.0, mode.clone(), 1.0, 0.1);
+    /// let decay = (-heat.diffusivity * lambda).exp();
+    /// for (value, initial) in result.iter().zip(&mode) {
+    ///     assert!((value - decay * initial).abs() < 1e-12);
+    /// }
+    /// ```
+    fn eval_into(&self, x: F, y: &[F], dydx: &mut [F]) {
+        let values = self.eval(&x, &y.to_vec());
+        assert_eq!(values.len(), y.len(), "the right-hand side has dimension {} instead of {}", values.len(), y.len());
+        dydx.copy_from_slice(&values);
+    }
+
+    /// Writes the Jacobian matrix `df/dy` of the system at a given x and y value into `jacobian`, row by row.
+    ///
+    /// The implicit solvers call it to build the matrices of their Newton iterations. The default implementation provides
+    /// no Jacobian and returns `false`, in which case the solvers approximate it by finite differences of
+    /// [`ODESYS::eval_into`], at the cost of one evaluation of the system per component.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The value of the independent variable.
+    /// * `y` - A slice containing the values of dependent variables.
+    /// * `jacobian` - The slice, of length `n * n` for a system of dimension `n`, receiving `df[i]/dy[j]` at index `i * n + j`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the Jacobian has been written into `jacobian`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, bdf::BDFODESysSolver};
+    /// struct VanDerPol {
+    ///     mu: f64,
+    /// }
+    ///
+    /// impl ODESYS for VanDerPol {
+    ///     fn eval_into(&self, _x: f64, y: &[f64], dydx: &mut [f64]) {
+    ///         dydx[0] = y[1];
+    ///         dydx[1] = self.mu * (1.0 - y[0] * y[0]) * y[1] - y[0];
+    ///     }
+    ///
+    ///     fn jacobian_into(&self, _x: f64, y: &[f64], jacobian: &mut [f64]) -> bool {
+    ///         jacobian.copy_from_slice(&[
+    ///             0.0, 1.0,
+    ///             -2.0 * self.mu * y[0] * y[1] - 1.0, self.mu * (1.0 - y[0] * y[0]),
+    ///         ]);
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let stiff = VanDerPol { mu: 1000.0 };
+    /// let solution = solver.bdf_solve_traj(&stiff, 0.0, vec![2.0, 0.0], 3000.0, 1e-6);
+    /// println!("{} steps, {} Jacobians", solution.stats.accepted_steps, solution.stats.jacobian_evals);
+    /// ```
+    fn jacobian_into(&self, _x: F, _y: &[F], _jacobian: &mut [F]) -> bool {
+        false
+    }
+
+    /// Writes the partial derivatives `df/dx` of the system at a given x and y value into `dfdx`.
+    ///
+    /// The Rosenbrock solvers call it at the start of every step. The default implementation provides no derivatives
+    /// and returns `false`, in which case the solvers approximate them by a central difference of
+    /// [`ODESYS::eval_into`], at the cost of two evaluations of the system. An autonomous system, whose derivatives do
+    /// not depend on x, saves these evaluations by writing zeros.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The value of the independent variable.
+    /// * `y` - A slice containing the values of dependent variables.
+    /// * `dfdx` - The slice, of the dimension of the system, receiving `df[i]/dx` at index `i`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the derivatives have been written into `dfdx`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverOptions;
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, rosenbrock::{RosenbrockODESysSolver, RosenbrockTableau}};
+    /// struct VanDerPol {
+    ///     mu: f64,
+    /// }
+    ///
+    /// impl ODESYS for VanDerPol {
+    ///     fn eval_into(&self, _x: f64, y: &[f64], dydx: &mut [f64]) {
+    ///         dydx[0] = y[1];
+    ///         dydx[1] = self.mu * (1.0 - y[0] * y[0]) * y[1] - y[0];
+    ///     }
+    ///
+    ///     fn dfdx_into(&self, _x: f64, _y: &[f64], dfdx: &mut [f64]) -> bool {
+    ///         dfdx.fill(0.0);
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let stiff = VanDerPol { mu: 1000.0 };
+    /// let as_closure = |_x: &f64, y: &Vec<f64>| vec![y[1], 1000.0 * (1.0 - y[0] * y[0]) * y[1] - y[0]];
+    /// let options = SolverOptions::new().rtol(1e-6).atol(1e-6);
+    ///
+    /// let autonomous = solver.ros_solve_with(&stiff, &RosenbrockTableau::rodas4(), 0.0, vec![2.0, 0.0], 3000.0, &options);
+    /// let differenced = solver.ros_solve_with(&as_closure, &RosenbrockTableau::rodas4(), 0.0, vec![2.0, 0.0], 3000.0, &options);
+    /// // The same solution, with two evaluations saved at every Jacobian
+    /// assert_eq!(autonomous.y, differenced.y);
+    /// assert_eq!(differenced.stats.evals - autonomous.stats.evals, 2 * differenced.stats.jacobian_evals);
+    /// ```
+    fn dfdx_into(&self, _x: F, _y: &[F], _dfdx: &mut [F]) -> bool {
+        false
+    }
+
+    /// Writes the constant mass matrix `M` of a system of the form `M y' = f(x, y)` into `mass`, row by row.
+    ///
+    /// A singular mass matrix turns the system into differential-algebraic equations, such as the ones of a chemical
+    /// reaction whose conservation laws are kept as algebraic equations, which the Radau IIA solver integrates when
+    /// they have index 1 and consistent initial values. The other solvers fail with a
+    /// [`SolverError::UnsupportedMassMatrix`] on such a system. The default implementation returns `false`, meaning the
+    /// identity.
+    ///
+    /// # Arguments
+    ///
+    /// * `mass` - The slice, of length `n * n` for a system of dimension `n`, receiving `M[i][j]` at index `i * n + j`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the mass matrix has been written into `mass`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, bdf::BDFODESysSolver, radau::RadauODESysSolver};
+    /// // Robertson's kinetics, with the conservation of mass replacing the equation of the third species
+    /// struct Robertson;
+    ///
+    /// impl ODESYS for Robertson {
+    ///     fn eval_into(&self, _x: f64, y: &[f64], dydx: &mut [f64]) {
+    ///         dydx[0] = -0.04 * y[0] + 1e4 * y[1] * y[2];
+    ///         dydx[1] = 0.04 * y[0] - 1e4 * y[1] * y[2] - 3e7 * y[1] * y[1];
+    ///         dydx[2] = y[0] + y[1] + y[2] - 1.0;
+    ///     }
+    ///
+    ///     fn mass_matrix_into(&self, mass: &mut [f64]) -> bool {
+    ///         mass.fill(0.0);
+    ///         mass[0] = 1.0;
+    ///         mass[4] = 1.0;
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let y = solver.radau_solve(&Robertson, 0.0, vec![1.0, 0.0, 0.0], 40.0, 1e-6);
+    /// assert!((y[0] - 0.7158).abs() < 1e-3);
+    /// assert!((y[0] + y[1] + y[2] - 1.0).abs() < 1e-12);
+    ///
+    /// let error = solver.try_bdf_solve(&Robertson, 0.0, vec![1.0, 0.0, 0.0], 40.0, 1e-6).unwrap_err();
+    /// assert_eq!(error, SolverError::UnsupportedMassMatrix { x: 0.0 });
+    /// ```
+    fn mass_matrix_into(&self, _mass: &mut [F]) -> bool {
+        false
+    }
+}
+
+impl<F, Fun> ODESYS<F> for Fun
+where
+    F: Real,
+    Fun: Fn(&F, &Vec<F>) -> Vec<F>,
+{
+    fn eval(&self, x: &F, y: &Vec<F>) -> Vec<F> {
+        self(x, y)
+    }
 }
 
+impl<F: Real, T: ODESYS<F> + ?Sized> ODESYS<F> for Counted<'_, T> {
+    fn eval(&self, x: &F, y: &Vec<F>) -> Vec<F> {
+        self.count();
+        self.inner.eval(x, y)
+    }
+
+    fn eval_into(&self, x: F, y: &[F], dydx: &mut [F]) {
+        self.count();
+        self.inner.eval_into(x, y, dydx);
+    }
+
+    fn jacobian_into(&self, x: F, y: &[F], jacobian: &mut [F]) -> bool {
+        self.inner.jacobian_into(x, y, jacobian)
+    }
+
+    fn dfdx_into(&self, x: F, y: &[F], dfdx: &mut [F]) -> bool {
+        self.inner.dfdx_into(x, y, dfdx)
+    }
+
+    fn mass_matrix_into(&self, mass: &mut [F]) -> bool {
+        self.inner.mass_matrix_into(mass)
+    }
+}
+
+/// Struct implementing the solver for a system of ODEs, with one trait per method.
+///
+/// The step size `h` given to the methods is a magnitude: when `x_target` is less than the initial x the system is integrated backwards.
+/// The `try_*` methods report a failed integration as a [`SolverError`], while the other ones panic with its message.
+/// The `*_stepper` methods return a [`SysStepper`], or an [`AdaptiveSysStepper`] for the adaptive methods, integrating the
+/// system incrementally, while the `*_with` methods take their settings from a [`SolverOptions`](crate::SolverOptions).
+///
+/// # Example
+///
+/// ```
+/// # use damndiff::ode_sys::{ODESysSolver, rk_sys::RungeKuttaODESysSolver, rkf_sys::RKFODESysSolver};
+/// let solver = ODESysSolver;
+/// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+///
+/// // A step size of 0.3 does not divide the interval [0, 1]: the last step is shortened to end exactly at x = 1
+/// let solution = solver.rk_solve_traj(&oscillator, 0.0, vec![1.0, 0.0], 1.0, 0.3);
+/// assert_eq!(solution.x.len(), 5);
+/// assert_eq!(solution.x[4], 1.0);
+/// assert!((solution.y[4][0] - 1.0f64.cos()).abs() < 1e-4);
+///
+/// // The adaptive methods shorten their last step as well
+/// let solution = solver.rkf_solve_traj(&oscillator, 0.0, vec![1.0, 0.0], 1.0, 0.3);
+/// assert_eq!(*solution.x.last().unwrap(), 1.0);
+/// assert!((solution.y.last().unwrap()[0] - 1.0f64.cos()).abs() < 1e-5);
+///
+/// // With x_target less than the initial x the system is integrated backwards, with steps of the same magnitude
+/// let solution = solver.rk_solve_traj(&oscillator, 1.0, vec![1.0f64.cos(), -1.0f64.sin()], 0.0, 0.3);
+/// assert_eq!(solution.x.len(), 5);
+/// assert!(solution.x.windows(2).all(|pair| pair[1] < pair[0]));
+/// assert_eq!(solution.x[4], 0.0);
+/// assert!((solution.y[4][0] - 1.0).abs() < 1e-4);
+///
+/// let y = solver.try_rkf_solve(&oscillator, 1.0, vec![1.0f64.cos(), -1.0f64.sin()], 0.0, 0.3).unwrap();
+/// assert!((y[0] - 1.0).abs() < 1e-5 && y[1].abs() < 1e-5);
+/// ```
 pub struct ODESysSolver;
 
-/// Adds two vectors element-wise.
-fn add_vec(a: &[f64], b: &[f64]) -> Vec<f64> {
-    a.iter().zip(b.iter()).map(|(&x, &y)| x + y).collect()
+/// A one-step method for systems of ODEs, advancing the state in place with its own preallocated buffers.
+///
+/// It is implemented by the method types of the submodules, such as [`rk_sys::Rk4`], which parametrize a [`SysStepper`].
+pub trait SysMethod<F: Real> {
+    /// Advances `y` from `x` to `x + h`.
+    fn step<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &mut [F], h: F);
+}
+
+/// Advances the one-step method `method` with fixed step size `h` from `(x0, y0)` up to `x_target`, through a [`SysStepper`].
+///
+/// Every accepted point, including the initial one, is passed to `record`.
+fn fixed_step<M, F, T, R>(ode: &T, method: M, x0: F, y0: Vec<F>, h: F, x_target: F, mut record: R) -> Result<Vec<F>, SolverError>
+where
+    M: SysMethod<F>,
+    F: Real,
+    T: ODESYS<F>,
+    R: FnMut(F, &[F]),
+{
+    record(x0, &y0);
+    let mut stepper = SysStepper::new(ode, method, x0, y0, h);
+    stepper.advance_with(x_target, record)?;
+    Ok(stepper.into_y())
+}
+
+/// Same as [`fixed_step`], collecting every accepted point into a [`Solution`].
+fn fixed_step_traj<M, F, T>(ode: &T, method: M, x0: F, y0: Vec<F>, h: F, x_target: F) -> Result<Solution<Vec<F>, F>, SolverError>
+where
+    M: SysMethod<F>,
+    F: Real,
+    T: ODESYS<F>,
+{
+    solve_with(SysStepper::new(ode, method, x0, y0, h), x_target, &Output::Steps)
+}
+
+/// A stepper the `*_with` methods can drive towards the points of an [`Output`].
+trait Advance<F: Real> {
+    /// Returns the current state `(x, y)`.
+    fn state(&self) -> (F, &[F]);
+
+    /// Integrates up to `x_target`, passing every accepted point after the current one to `record`.
+    fn advance(&mut self, x_target: F, record: &mut dyn FnMut(F, &[F])) -> Result<(), SolverError>;
+
+    /// Integrates up to the output point `x`, which the steppers interpolating their solution may step past.
+    fn advance_to_point(&mut self, x: F) -> Result<(), SolverError> {
+        self.advance(x, &mut |_, _| {})
+    }
+
+    /// Returns the statistics of the steps taken so far.
+    fn stats(&self) -> Stats<F>;
+}
+
+impl<M: SysMethod<F>, T: ODESYS<F>, F: Real> Advance<F> for SysStepper<'_, M, T, F> {
+    fn state(&self) -> (F, &[F]) {
+        SysStepper::state(self)
+    }
+
+    fn advance(&mut self, x_target: F, record: &mut dyn FnMut(F, &[F])) -> Result<(), SolverError> {
+        self.advance_with(x_target, record)
+    }
+
+    fn stats(&self) -> Stats<F> {
+        SysStepper::stats(self)
+    }
+}
+
+impl<M: EmbeddedSysMethod<F>, T: ODESYS<F>, F: Real> Advance<F> for AdaptiveSysStepper<'_, M, T, F> {
+    fn state(&self) -> (F, &[F]) {
+        AdaptiveSysStepper::state(self)
+    }
+
+    fn advance(&mut self, x_target: F, record: &mut dyn FnMut(F, &[F])) -> Result<(), SolverError> {
+        self.advance_with(x_target, record)
+    }
+
+    fn advance_to_point(&mut self, x: F) -> Result<(), SolverError> {
+        self.advance_to(x).map(|_| ())
+    }
+
+    fn stats(&self) -> Stats<F> {
+        AdaptiveSysStepper::stats(self)
+    }
+}
+
+/// Integrates up to `x_target` with `stepper`, storing the points selected by `output`.
+fn solve_with<F, S>(mut stepper: S, x_target: F, output: &Output<F>) -> Result<Solution<Vec<F>, F>, SolverError>
+where
+    F: Real,
+    S: Advance<F>,
+{
+    let mut solution = Solution::new();
+    match output {
+        Output::Steps => {
+            let (x, y) = stepper.state();
+            solution.push(x, y.to_vec());
+            stepper.advance(x_target, &mut |x, y| solution.push(x, y.to_vec()))?;
+        }
+        Output::Final => {
+            stepper.advance(x_target, &mut |_, _| {})?;
+            solution.push(x_target, stepper.state().1.to_vec());
+        }
+        Output::Points(points) => {
+            for &x in points {
+                stepper.advance_to_point(x)?;
+                solution.push(x, stepper.state().1.to_vec());
+            }
+            stepper.advance(x_target, &mut |_, _| {})?;
+        }
+    }
+    solution.stats = stepper.stats();
+    Ok(solution)
+}
+
+/// Writes `y + a * k` into `out`.
+fn add_scaled<F: Real>(out: &mut [F], y: &[F], a: F, k: &[F]) {
+    for ((out, y), k) in out.iter_mut().zip(y).zip(k) {
+        *out = *y + a * *k;
+    }
+}
+
+/// Magnitude under which a component of the state is not trusted to scale the increment of the forward differences.
+const JACOBIAN_THRESHOLD: f64 = 1e-3;
+
+/// Writes the Jacobian of `ode` at `(x, y)` into `jacobian`, approximating it by forward differences when the system
+/// provides none, `f` being the derivatives at `(x, y)` and `work` a buffer of the dimension of the system. The
+/// increment of each component is proportional to its magnitude, so that it stays well above its rounding.
+fn jacobian<F: Real, T: ODESYS<F>>(ode: &T, x: F, y: &mut [F], f: &[F], jacobian: &mut [F], work: &mut [F]) {
+    if ode.jacobian_into(x, y, jacobian) {
+        return;
+    }
+
+    let n = y.len();
+    for j in 0..n {
+        let y_j = y[j];
+        y[j] = y_j + F::epsilon().sqrt() * y_j.abs().max(lit(JACOBIAN_THRESHOLD));
+        // The increment actually represented, which differs from the requested one by the rounding of y_j + delta
+        let delta = y[j] - y_j;
+        ode.eval_into(x, y, work);
+        y[j] = y_j;
+        for i in 0..n {
+            jacobian[i * n + j] = (work[i] - f[i]) / delta;
+        }
+    }
+}
+
+/// Checks that `ode` has no mass matrix, for the solvers integrating `y' = f(x, y)`, at the start `x` of the integration
+/// of a state of dimension `dim`.
+fn check_no_mass_matrix<F: Real, T: ODESYS<F> + ?Sized>(ode: &T, x: F, dim: usize) -> Result<(), SolverError> {
+    if ode.mass_matrix_into(&mut vec![F::zero(); dim * dim]) {
+        return Err(SolverError::UnsupportedMassMatrix { x: to_f64(x) });
+    }
+    Ok(())
+}
+
+/// Returns `true` if every component of `y` is finite.
+fn is_finite<F: Real>(y: &[F]) -> bool {
+    y.iter().all(|value| value.is_finite())
 }
 
-/// Multiplies a vector by a scalar.
-fn vec_scalar_mul(a: &[f64], scalar: f64) -> Vec<f64> {
-    a.iter().map(|&x| x * scalar).collect()
+/// Checks that `ode` returns as many derivatives as `y` has components, evaluating it once at `(x, y)`.
+fn check_dimension<F: Real, T: ODESYS<F> + ?Sized>(ode: &T, x: F, y: &[F]) -> Result<(), SolverError> {
+    let found = ode.eval(&x, &y.to_vec()).len();
+    if found != y.len() {
+        return Err(SolverError::DimensionMismatch { x: to_f64(x), expected: y.len(), found });
+    }
+    Ok(())
 }
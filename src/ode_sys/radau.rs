@@ -1,11 +1,26 @@
-//! Radau IA (Implicit-Explicit) method for solving systems of ordinary differential equations (ODEs).
-use super::{ODESYS, ODESysSolver, add_vec, vec_scalar_mul};
+//! Radau IIA method for solving stiff systems of ordinary differential equations (ODEs).
+use super::{ODESYS, ODESysSolver, add_scaled, jacobian, solve_with};
+use super::adaptive::{AdaptiveSysStepper, DenseSysMethod, EmbeddedSysMethod};
+use crate::{Output, Real, Solution, SolverError, SolverOptions, Stats};
+use crate::controller::{rms_norm, scaled_error};
+use crate::error::expect_solved;
+use crate::linalg::Lu;
+use crate::scalar::lit;
 
-/// Radau IA (Implicit-Explicit) method for solving systems of Ordinary Differential Equations (ODEs).
+/// Radau IIA method for solving stiff systems of Ordinary Differential Equations (ODEs).
 ///
-/// This trait defines the [Radau IA method](https://www.sciencedirect.com/science/article/pii/S037704279900134X) for solving systems of ordinary differential equations.
-pub trait RadauODESysSolver<T: ODESYS> {
-    /// Solve the system of ODEs using the Radau IA method.
+/// This trait defines the 3-stage [Radau IIA method](https://en.wikipedia.org/wiki/List_of_Runge%E2%80%93Kutta_methods#Radau_IIA_methods)
+/// of order 5, the implicit Runge-Kutta method of Hairer and Wanner's RADAU5, for solving stiff systems of ordinary
+/// differential equations and differential-algebraic equations of index 1. The step size `h` given to the methods is
+/// the initial one, which is then adapted so that the weighted root mean square of the local error estimates stays
+/// within the tolerances, see [`AdaptiveSysStepper`]. Without a [`SolverOptions`], the tolerances are the defaults of
+/// [`SolverOptions::new`].
+///
+/// The Jacobian is the one of [`ODESYS::jacobian_into`] when the system provides it, and a finite-difference
+/// approximation otherwise. A system given with a mass matrix by [`ODESYS::mass_matrix_into`] is integrated in the
+/// form `M y' = f(x, y)`.
+pub trait RadauODESysSolver<T: ODESYS<F>, F: Real = f64> {
+    /// Solve the system of ODEs using the Radau IIA method.
     ///
     /// # Arguments
     ///
@@ -13,15 +28,20 @@ pub trait RadauODESysSolver<T: ODESYS> {
     /// * `x` - The initial x value.
     /// * `y` - The initial vector of y values (corresponding to the initial x).
     /// * `x_target` - The x value where the solution is desired.
-    /// * `h` - The step size or increment for x.
+    /// * `h` - The initial step size.
     ///
     /// # Returns
     ///
     /// The vector of estimated y values at `x_target`.
-    /// 
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RadauODESysSolver::try_radau_solve`].
+    ///
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, radau::RadauODESysSolver};
     /// struct MyODESystem;
     /// impl ODESYS for MyODESystem {
     ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
@@ -38,17 +58,46 @@ pub trait RadauODESysSolver<T: ODESYS> {
     /// let h = 0.1;
     /// let x_target = 1.0;
     ///
-    /// let result = solver.ia_solve(&my_ode_system, x0, y0, x_target, h);
+    /// let result = solver.radau_solve(&my_ode_system, x0, y0, x_target, h);
+    /// assert!((result[0] - 0.5f64.exp()).abs() < 1e-4);
     /// println!("Solution at x = {}: {:?}", x_target, result);
     /// ```
-    fn ia_solve(&self, ode: &T, x: f64, y: Vec<f64>, x_target: f64, h: f64) -> Vec<f64>;
-}
+    fn radau_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F>;
 
-// Implementing the Radau IA method for the system of ODEs Solver
-impl<T: ODESYS> RadauODESysSolver<T> for ODESysSolver {
-    /// Implementation of the Radau IA method to solve a system of ODEs.
+    /// Solve the system of ODEs using the Radau IIA method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from the initial x up to `x_target`.
     ///
-    /// This method approximates the solution to the system of ODEs at the specified x_target.
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RadauODESysSolver::try_radau_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, radau::RadauODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    ///
+    /// // The accepted points are as far apart as the tolerances allow
+    /// let solution = solver.radau_solve_traj(&oscillator, 0.0, vec![1.0, 0.0], 10.0, 0.1);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn radau_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Radau IIA method, reporting failures instead of panicking.
     ///
     /// # Arguments
     ///
@@ -56,54 +105,793 @@ impl<T: ODESYS> RadauODESysSolver<T> for ODESysSolver {
     /// * `x` - The initial x value.
     /// * `y` - The initial vector of y values (corresponding to the initial x).
     /// * `x_target` - The x value where the solution is desired.
-    /// * `h` - The step size or increment for x.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`, or the [`SolverError`] that stopped the integration, such as a
+    /// [`SolverError::StepSizeUnderflow`] when the solution blows up.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode_sys::{ODESysSolver, radau::RadauODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // The first component blows up at x = 1
+    /// let blow_up = |_x: &f64, y: &Vec<f64>| vec![y[0] * y[0], -y[1]];
+    ///
+    /// let error = solver.try_radau_solve(&blow_up, 0.0, vec![1.0, 1.0], 2.0, 0.1).unwrap_err();
+    /// assert!(matches!(error, SolverError::StepSizeUnderflow { x, .. } if (x - 1.0).abs() < 1e-3));
+    ///
+    /// // Away from the singularity the solution is y = [1 / (1 - x), e^(-x)]
+    /// let result = solver.try_radau_solve(&blow_up, 0.0, vec![1.0, 1.0], 0.5, 0.1).unwrap();
+    /// assert!((result[0] - 2.0).abs() < 1e-6);
+    /// assert!((result[1] - (-0.5f64).exp()).abs() < 1e-6);
+    /// ```
+    fn try_radau_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError>;
+
+    /// Create a stepper integrating the system of ODEs with the Radau IIA method, one adaptive step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The initial step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// An [`AdaptiveSysStepper`] holding the current state, starting from the initial `(x, y)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, radau::RadauODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let mut stepper = solver.radau_stepper(&oscillator, 0.0, vec![1.0, 0.0], 0.1);
+    ///
+    /// for state in stepper.by_ref().take(5) {
+    ///     let (x, y) = state.unwrap();
+    ///     assert!((y[0] - x.cos()).abs() < 1e-8);
+    ///     assert!((y[1] + x.sin()).abs() < 1e-8);
+    /// }
+    /// // The solution is smooth enough for the step size to grow
+    /// assert!(stepper.h() > 0.1);
+    /// ```
+    fn radau_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, Radau5<F>, T, F>;
+
+    /// Solve the system of ODEs using the Radau IIA method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration, including one absolute tolerance per component if needed.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RadauODESysSolver::try_radau_solve_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, radau::RadauODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // Species concentrations of very different magnitudes
+    /// let kinetics = |_x: &f64, y: &Vec<f64>| vec![-0.04 * y[0], 0.04 * y[0] - 10.0 * y[1]];
+    /// let options = SolverOptions::new()
+    ///     .rtol(1e-6)
+    ///     .atol_per_component(vec![1e-8, 1e-12])
+    ///     .output(Output::Points(vec![1.0, 10.0, 100.0]));
+    ///
+    /// let solution = solver.radau_solve_with(&kinetics, 0.0, vec![1.0, 0.0], 100.0, &options);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn radau_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Radau IIA method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration, including one absolute tolerance per component if needed.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverError, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, radau::RadauODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let options = SolverOptions::new().rtol(1e-9).atol(1e-9).output(Output::Final);
+    /// let solution = solver.try_radau_solve_with(&oscillator, 0.0, vec![1.0, 0.0], 10.0, &options).unwrap();
+    /// assert_eq!(solution.x, vec![10.0]);
+    /// assert!((solution.y[0][0] - 10.0f64.cos()).abs() < 1e-8);
+    /// assert!((solution.y[0][1] + 10.0f64.sin()).abs() < 1e-8);
+    ///
+    /// // Ten steps are far from enough to follow the oscillation up to x = 100
+    /// let options = SolverOptions::new().rtol(1e-9).atol(1e-9).max_steps(10);
+    /// let error = solver.try_radau_solve_with(&oscillator, 0.0, vec![1.0, 0.0], 100.0, &options).unwrap_err();
+    /// assert!(matches!(error, SolverError::MaxStepsExceeded { max_steps: 10, .. }));
+    /// ```
+    fn try_radau_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError>;
+
+    /// Solve the system of ODEs using the Radau IIA method, under the former name of [`RadauODESysSolver::radau_solve`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
     ///
     /// # Returns
     ///
     /// The vector of estimated y values at `x_target`.
-    /// 
-    /// # When to Use: 
-    /// 
-    /// Suitable for certain classes of stiff problems where implicit and explicit methods can be combined.
-    /// 
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RadauODESysSolver::try_radau_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, radau::RadauODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let decay = |_x: &f64, y: &Vec<f64>| vec![-y[0]];
+    ///
+    /// #[allow(deprecated)]
+    /// let result = solver.ia_solve(&decay, 0.0, vec![1.0], 1.0, 0.1);
+    /// assert_eq!(result, solver.radau_solve(&decay, 0.0, vec![1.0], 1.0, 0.1));
+    /// ```
+    #[deprecated(note = "use `radau_solve`, the method being Radau IIA")]
+    fn ia_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        self.radau_solve(ode, x, y, x_target, h)
+    }
+}
+
+// Implementing the Radau IIA method for the system of ODEs Solver
+impl<F: Real, T: ODESYS<F>> RadauODESysSolver<T, F> for ODESysSolver {
+    /// Implementation of the Radau IIA method to solve a system of ODEs.
+    ///
+    /// Each step solves the collocation equations of the three stages, at the nodes `(4 - sqrt(6)) / 10`,
+    /// `(4 + sqrt(6)) / 10` and 1, by a simplified Newton iteration. The iteration is decoupled by the eigenvectors of
+    /// the inverse Runge-Kutta matrix into one real system and one complex system of the dimension of the problem, whose
+    /// matrices are factorized once and reused as long as the step size stays the same; the step size is only changed
+    /// when it can grow by at least 20%. The Jacobian is recomputed after the steps whose iteration converged slowly,
+    /// and when the iteration fails, before halving the step size. The iteration starts from the extrapolation of the
+    /// previous collocation polynomial.
+    ///
+    /// The local error is estimated with an embedded formula of order 3, filtered through the real iteration matrix so
+    /// that it stays bounded on stiff components, and the step size follows the predictive controller of Gustafsson.
+    /// The solution between two accepted points is given by the collocation polynomial, of order 3, see
+    /// [`AdaptiveSysStepper::interpolate`].
+    ///
+    /// # When to Use:
+    ///
+    /// The method of choice for stiff systems solved to tight tolerances, for differential-algebraic equations of index
+    /// 1 and for problems with eigenvalues close to the imaginary axis, where the BDF methods of high order are not stable.
+    ///
     /// # Pros and Cons:
-    /// - Pros: Stiffness handling with potential for higher accuracy.
-    /// - Cons: May be complex to implement, can be computationally demanding.
-    /// 
-    /// # Stability Analysis: 
-    /// 
-    /// Conditionally stable, suitable for some stiff problems.
-    /// 
+    /// - Pros: Order 5 with L-stability, so that very stiff components are damped in a single step, and few Jacobians.
+    /// - Cons: Three evaluations of the system and two linear solves, one of them complex, per Newton iteration, and
+    ///   dense linear algebra in the dimension of the system.
+    ///
+    /// # Stability Analysis:
+    ///
+    /// L-stable: the method is stable for any step size on the whole left half-plane, and the stiff components of the
+    /// solution are damped rather than merely kept bounded.
+    ///
     /// # Example
     ///
     /// ```
-    /// struct MyODESystem;
-    /// impl ODESYS for MyODESystem {
-    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
-    ///         // Define the system of ODEs
-    ///         // Example: dy/dx = x * y, dz/dx = x + y
-    ///         vec![x * y[0], x + y[1]]
-    ///     }
-    /// }
+    /// # use damndiff::SolverOptions;
+    /// # use damndiff::ode_sys::{ODESysSolver, radau::RadauODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // Van der Pol oscillator with relaxation oscillations of stiffness ratio 1e6
+    /// let van_der_pol = |_x: &f64, y: &Vec<f64>| vec![y[1], 1e3 * (1.0 - y[0] * y[0]) * y[1] - y[0]];
+    /// let options = SolverOptions::new().rtol(1e-6).atol(1e-6);
     ///
+    /// let mut stepper = solver.radau_stepper(&van_der_pol, 0.0, vec![2.0, 0.0], 1e-6).with_options(&options);
+    /// let y = stepper.advance_to(3000.0).unwrap();
+    /// assert!((y[0] + 1.5).abs() < 0.1);
+    ///
+    /// // An explicit method would need billions of steps
+    /// let stats = stepper.stats();
+    /// assert!(stats.accepted_steps < 2000);
+    /// println!("{} steps, {} Jacobians, {} linear solves", stats.accepted_steps, stats.jacobian_evals, stats.linear_solves);
+    /// ```
+    ///
+    /// The quadrature of the method is exact for polynomials of degree up to 4, so that such a system is solved to
+    /// rounding error whatever the tolerances:
+    ///
+    /// ```
+    /// # use damndiff::SolverOptions;
+    /// # use damndiff::ode_sys::{ODESysSolver, radau::RadauODESysSolver};
     /// let solver = ODESysSolver;
-    /// let my_ode_system = MyODESystem;
-    /// let x0 = 0.0;
-    /// let y0 = vec![1.0, 2.0];
-    /// let h = 0.1;
-    /// let x_target = 1.0;
+    /// let polynomial = |x: &f64, _y: &Vec<f64>| vec![2.0 * x, 4.0 * x * x * x];
     ///
-    /// let result = solver.ia_solve(&my_ode_system, x0, y0, x_target, h);
-    /// println!("Solution at x = {}: {:?}", x_target, result);
+    /// for rtol in [1e-3, 1e-6, 1e-9, 1e-12] {
+    ///     let options = SolverOptions::new().rtol(rtol).atol(rtol);
+    ///     let solution = solver.radau_solve_with(&polynomial, 0.0, vec![0.0, 0.0], 10.0, &options);
+    ///     let (_, y) = solution.last().unwrap();
+    ///     assert!((y[0] / 100.0 - 1.0).abs() < 1e-13);
+    ///     assert!((y[1] / 10000.0 - 1.0).abs() < 1e-13);
+    /// }
     /// ```
-    fn ia_solve(&self, ode: &T, mut x: f64, mut y: Vec<f64>, x_target: f64, h: f64) -> Vec<f64> {
-        while x < x_target {
+    fn radau_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        expect_solved(self.try_radau_solve(ode, x, y, x_target, h))
+    }
+
+    /// Implementation of the Radau IIA method returning the whole trajectory of the system of ODEs.
+    ///
+    /// See [`RadauODESysSolver::radau_solve`] for the details of the method.
+    fn radau_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F> {
+        expect_solved(solve_with(self.radau_stepper(ode, x, y, h), x_target, &Output::Steps))
+    }
+
+    /// Implementation of the Radau IIA method reporting failures as a [`SolverError`].
+    ///
+    /// See [`RadauODESysSolver::radau_solve`] for the details of the method.
+    fn try_radau_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError> {
+        let mut stepper = self.radau_stepper(ode, x, y, h);
+        stepper.advance_to(x_target)?;
+        Ok(stepper.into_y())
+    }
+
+    /// Implementation of the Radau IIA method as an [`AdaptiveSysStepper`].
+    ///
+    /// See [`RadauODESysSolver::radau_solve`] for the details of the method.
+    fn radau_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, Radau5<F>, T, F> {
+        let method = Radau5::new(y.len());
+        AdaptiveSysStepper::new(ode, method, x, y, h)
+    }
+
+    /// Implementation of the Radau IIA method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`RadauODESysSolver::radau_solve`] for the details of the method.
+    fn radau_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F> {
+        expect_solved(self.try_radau_solve_with(ode, x, y, x_target, options))
+    }
+
+    /// Implementation of the Radau IIA method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`RadauODESysSolver::radau_solve`] for the details of the method.
+    fn try_radau_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError> {
+        options.check(x, x_target, y.len())?;
+        let stepper = self.radau_stepper(ode, x, y, options.initial_step(x, x_target));
+        solve_with(stepper.with_options(options), x_target, &options.output)
+    }
+}
+
+/// Square root of 6, appearing in the nodes and the coefficients of the method.
+const SQRT_6: f64 = 2.449_489_742_783_178;
+
+/// Nodes of the three stages.
+const C: [f64; 3] = [(4.0 - SQRT_6) / 10.0, (4.0 + SQRT_6) / 10.0, 1.0];
+
+/// Weights of the stage increments in the embedded error estimate, divided by the step size.
+const E: [f64; 3] = [(-13.0 - 7.0 * SQRT_6) / 3.0, (-13.0 + 7.0 * SQRT_6) / 3.0, -1.0 / 3.0];
+
+/// Real eigenvalue of the inverse of the Runge-Kutta matrix.
+const MU_REAL: f64 = 3.637_834_252_744_496;
+
+/// Real part and opposite of the imaginary part of the complex eigenvalues of the inverse of the Runge-Kutta matrix.
+const MU_COMPLEX: (f64, f64) = (2.681_082_873_627_752, 3.050_430_199_247_41);
+
+/// Eigenvectors `T` of the inverse of the Runge-Kutta matrix, transforming the stage increments `Z = T W`.
+const T: [[f64; 3]; 3] = [
+    [0.094_438_762_488_975_24, -0.141_255_295_020_954_2, 0.030_029_194_105_147_42],
+    [0.250_213_122_965_333_3, 0.204_129_352_293_8, -0.382_942_112_757_261_9],
+    [1.0, 1.0, 0.0],
+];
+
+/// Inverse of `T`.
+const TI: [[f64; 3]; 3] = [
+    [4.178_718_591_551_904, 0.327_682_820_761_062_4, 0.523_376_445_499_449_5],
+    [-4.178_718_591_551_904, -0.327_682_820_761_062_4, 0.476_623_554_500_550_5],
+    [0.502_872_634_945_786_8, -2.571_926_949_855_605, 0.596_039_204_828_224_9],
+];
+
+/// Coefficients of the collocation polynomial, `y(x + theta * h) = y + sum_j (sum_i Z_i P[i][j]) theta^(j + 1)`.
+const P: [[f64; 3]; 3] = [
+    [13.0 / 3.0 + 7.0 * SQRT_6 / 3.0, -23.0 / 3.0 - 22.0 * SQRT_6 / 3.0, 10.0 / 3.0 + 5.0 * SQRT_6],
+    [13.0 / 3.0 - 7.0 * SQRT_6 / 3.0, -23.0 / 3.0 + 22.0 * SQRT_6 / 3.0, 10.0 / 3.0 - 5.0 * SQRT_6],
+    [1.0 / 3.0, -8.0 / 3.0, 10.0 / 3.0],
+];
+
+/// Maximum number of Newton iterations in a step.
+const NEWTON_MAX_ITERATIONS: usize = 6;
+
+/// Smallest factor by which the step size shrinks after a rejected step.
+const MIN_FACTOR: f64 = 0.2;
+
+/// Largest factor by which the step size grows after an accepted step.
+const MAX_FACTOR: f64 = 10.0;
+
+/// State of the Radau IIA method for a system of ODEs, parametrizing the [`AdaptiveSysStepper`] of the method.
+///
+/// It keeps the Jacobian, the factorized iteration matrices and the collocation polynomial of the last accepted step,
+/// which are reused by the following steps.
+#[derive(Debug, Clone)]
+pub struct Radau5<F> {
+    dim: usize,
+    /// Mass matrix of the system, `None` for the identity, read at the start of the integration.
+    mass: Option<Vec<F>>,
+    /// Current point, the derivatives there and whether they must be evaluated again.
+    x: F,
+    f: Vec<F>,
+    f_stale: bool,
+    started: bool,
+    jacobian: Vec<F>,
+    /// Whether the Jacobian has been evaluated at the current point, so that recomputing it cannot help the iteration.
+    jacobian_current: bool,
+    /// Whether the Jacobian must be recomputed before the next step.
+    jacobian_due: bool,
+    /// Iteration matrices of the real eigenvalue and, in real form, of the complex pair, with their factorizations for
+    /// the step size `lu_h`.
+    matrix_real: Vec<F>,
+    matrix_complex: Vec<F>,
+    lu_real: Lu<F>,
+    lu_complex: Lu<F>,
+    lu_h: Option<F>,
+    rtol: F,
+    atol: Vec<F>,
+    /// Stage increments `Z`, their transforms `W` and the derivatives at the stages.
+    z: [Vec<F>; 3],
+    w: [Vec<F>; 3],
+    stage_f: [Vec<F>; 3],
+    /// Buffers of the iteration and of the error estimate.
+    stage_y: Vec<F>,
+    rhs_real: Vec<F>,
+    rhs_complex: Vec<F>,
+    work: Vec<F>,
+    scale: Vec<F>,
+    /// Coefficients of the collocation polynomial of the last accepted step, from `y_old` at `x_dense` with size `h_dense`.
+    q: [Vec<F>; 3],
+    y_old: Vec<F>,
+    x_dense: F,
+    h_dense: F,
+    dense_valid: bool,
+    /// Outcome of the last attempted step.
+    h_attempt: F,
+    converged: bool,
+    iterations: usize,
+    rate: F,
+    error_norm: F,
+    /// Whether the last attempted step has not been accepted.
+    pending: bool,
+    /// Size and error norm of the last accepted step, for the predictive step size control.
+    last_accepted: Option<(F, F)>,
+    next_factor: F,
+    jacobian_evals: usize,
+    linear_solves: usize,
+}
+
+impl<F: Real> Radau5<F> {
+    /// Eigenvectors `T` of the inverse of the Runge-Kutta matrix, which decouple the Newton iteration of a step into a
+    /// real system and a complex one.
+    pub const TRANSFORM: [[f64; 3]; 3] = T;
+
+    /// Inverse of [`Radau5::TRANSFORM`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::radau::Radau5;
+    /// let t = Radau5::<f64>::TRANSFORM;
+    /// let ti = Radau5::<f64>::TRANSFORM_INVERSE;
+    /// for i in 0..3 {
+    ///     for j in 0..3 {
+    ///         let product: f64 = (0..3).map(|k| t[i][k] * ti[k][j]).sum();
+    ///         let identity = if i == j { 1.0 } else { 0.0 };
+    ///         assert!((product - identity).abs() < 1e-15);
+    ///     }
+    /// }
+    /// ```
+    pub const TRANSFORM_INVERSE: [[f64; 3]; 3] = TI;
+
+    /// Creates the state of the Radau IIA method for a system of dimension `dim`.
+    pub fn new(dim: usize) -> Self {
+        let buffer = || vec![F::zero(); dim];
+        Radau5 {
+            dim,
+            mass: None,
+            x: F::zero(),
+            f: buffer(),
+            f_stale: true,
+            started: false,
+            jacobian: vec![F::zero(); dim * dim],
+            jacobian_current: false,
+            jacobian_due: true,
+            matrix_real: vec![F::zero(); dim * dim],
+            matrix_complex: vec![F::zero(); 4 * dim * dim],
+            lu_real: Lu::new(dim),
+            lu_complex: Lu::new(2 * dim),
+            lu_h: None,
+            rtol: F::zero(),
+            atol: buffer(),
+            z: [buffer(), buffer(), buffer()],
+            w: [buffer(), buffer(), buffer()],
+            stage_f: [buffer(), buffer(), buffer()],
+            stage_y: buffer(),
+            rhs_real: buffer(),
+            rhs_complex: vec![F::zero(); 2 * dim],
+            work: buffer(),
+            scale: buffer(),
+            q: [buffer(), buffer(), buffer()],
+            y_old: buffer(),
+            x_dense: F::zero(),
+            h_dense: F::zero(),
+            dense_valid: false,
+            h_attempt: F::zero(),
+            converged: false,
+            iterations: 0,
+            rate: F::zero(),
+            error_norm: F::zero(),
+            pending: false,
+            last_accepted: None,
+            next_factor: F::one(),
+            jacobian_evals: 0,
+            linear_solves: 0,
+        }
+    }
+
+    /// Starts the integration at `x`, reading the mass matrix of the system and forgetting the previous steps.
+    fn start<T: ODESYS<F>>(&mut self, ode: &T, x: F) {
+        let mut mass = vec![F::zero(); self.dim * self.dim];
+        self.mass = if ode.mass_matrix_into(&mut mass) { Some(mass) } else { None };
+        self.x = x;
+        self.f_stale = true;
+        self.jacobian_due = true;
+        self.dense_valid = false;
+        self.pending = false;
+        self.last_accepted = None;
+        self.started = true;
+    }
+
+    /// Writes `M v` into `out`, `M` being the mass matrix.
+    fn apply_mass(mass: &Option<Vec<F>>, v: &[F], out: &mut [F]) {
+        match mass {
+            None => out.copy_from_slice(v),
+            Some(mass) => {
+                for (row, out) in mass.chunks(v.len()).zip(out.iter_mut()) {
+                    *out = row.iter().zip(v).fold(F::zero(), |sum, (m, v)| sum + *m * *v);
+                }
+            }
+        }
+    }
+
+    /// Returns the entry `(i, j)` of the mass matrix.
+    fn mass_at(&self, i: usize, j: usize) -> F {
+        match &self.mass {
+            None if i == j => F::one(),
+            None => F::zero(),
+            Some(mass) => mass[i * self.dim + j],
+        }
+    }
+
+    /// Writes into `z` the starting stage increments of a step of size `h` from `(x, y)`: the collocation polynomial of
+    /// the last accepted step extrapolated to the new stages, or zero at the start of the integration.
+    fn initial_guess(&mut self, x: F, y: &[F], h: F) {
+        for (stage, c) in C.iter().enumerate() {
+            if !self.dense_valid {
+                self.z[stage].fill(F::zero());
+                continue;
+            }
+            let theta = (x + lit::<F>(*c) * h - self.x_dense) / self.h_dense;
+            for (i, z) in self.z[stage].iter_mut().enumerate() {
+                let polynomial = ((self.q[2][i] * theta + self.q[1][i]) * theta + self.q[0][i]) * theta;
+                *z = self.y_old[i] + polynomial - y[i];
+            }
+        }
+    }
+
+    /// Factorizes the iteration matrices `mu / h * M - J` of the real eigenvalue and of the complex pair.
+    ///
+    /// # Returns
+    ///
+    /// `false` if one of them is singular.
+    fn factor(&mut self, h: F) -> bool {
+        let n = self.dim;
+        let real = lit::<F>(MU_REAL) / h;
+        let (alpha, beta) = (lit::<F>(MU_COMPLEX.0) / h, lit::<F>(MU_COMPLEX.1) / h);
+
+        // The complex system ((alpha - i beta) M - J) (u + i v) = a + i b, in the real form
+        // [[alpha M - J, beta M], [-beta M, alpha M - J]] [u; v] = [a; b]
+        for i in 0..n {
+            for j in 0..n {
+                let mass = self.mass_at(i, j);
+                let jacobian = self.jacobian[i * n + j];
+                self.matrix_real[i * n + j] = real * mass - jacobian;
+                let diagonal = alpha * mass - jacobian;
+                self.matrix_complex[i * 2 * n + j] = diagonal;
+                self.matrix_complex[i * 2 * n + n + j] = beta * mass;
+                self.matrix_complex[(n + i) * 2 * n + j] = -beta * mass;
+                self.matrix_complex[(n + i) * 2 * n + n + j] = diagonal;
+            }
+        }
+
+        let factored = self.lu_real.factor(&self.matrix_real) && self.lu_complex.factor(&self.matrix_complex);
+        self.lu_h = if factored { Some(h) } else { None };
+        factored
+    }
+
+    /// Solves the collocation equations of a step of size `h` from `(x, y)` by simplified Newton iteration, starting
+    /// from the stage increments in `z`, which receive the solution.
+    fn newton<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F], h: F) {
+        let n = self.dim;
+        let tolerance = (lit::<F>(10.0) * F::epsilon() / self.rtol).max(lit::<F>(0.03).min(self.rtol.sqrt()));
+        let real = lit::<F>(MU_REAL) / h;
+        let (alpha, beta) = (lit::<F>(MU_COMPLEX.0) / h, lit::<F>(MU_COMPLEX.1) / h);
+        self.converged = false;
+        self.rate = F::zero();
+
+        for i in 0..n {
+            for (row, w) in TI.iter().zip(self.w.iter_mut()) {
+                w[i] = (0..3).fold(F::zero(), |sum, j| sum + lit::<F>(row[j]) * self.z[j][i]);
+            }
+        }
+
+        let mut last_norm: Option<F> = None;
+        for iteration in 0..NEWTON_MAX_ITERATIONS {
+            self.iterations = iteration + 1;
+            for (stage, c) in C.iter().enumerate() {
+                add_scaled(&mut self.stage_y, y, F::one(), &self.z[stage]);
+                ode.eval_into(x + lit::<F>(*c) * h, &self.stage_y, &mut self.stage_f[stage]);
+            }
+            if !self.stage_f.iter().all(|f| f.iter().all(|f| f.is_finite())) {
+                return;
+            }
+
+            // Right-hand sides of the transformed iteration, with the mass matrix applied to W
+            Self::apply_mass(&self.mass, &self.w[0], &mut self.work);
+            for i in 0..n {
+                let f = (0..3).fold(F::zero(), |sum, j| sum + lit::<F>(TI[0][j]) * self.stage_f[j][i]);
+                self.rhs_real[i] = f - real * self.work[i];
+            }
+            Self::apply_mass(&self.mass, &self.w[1], &mut self.work);
+            for i in 0..n {
+                let f = (0..3).fold(F::zero(), |sum, j| sum + lit::<F>(TI[1][j]) * self.stage_f[j][i]);
+                self.rhs_complex[i] = f - alpha * self.work[i];
+                self.rhs_complex[n + i] = beta * self.work[i];
+            }
+            Self::apply_mass(&self.mass, &self.w[2], &mut self.work);
+            for i in 0..n {
+                let f = (0..3).fold(F::zero(), |sum, j| sum + lit::<F>(TI[2][j]) * self.stage_f[j][i]);
+                self.rhs_complex[i] -= beta * self.work[i];
+                self.rhs_complex[n + i] += f - alpha * self.work[i];
+            }
+            self.lu_real.solve(&mut self.rhs_real);
+            self.lu_complex.solve(&mut self.rhs_complex);
+            self.linear_solves += 2;
+
+            let mut sum = F::zero();
+            for i in 0..n {
+                for dw in [self.rhs_real[i], self.rhs_complex[i], self.rhs_complex[n + i]] {
+                    let scaled = scaled_error(dw, self.scale[i]);
+                    sum += scaled * scaled;
+                }
+            }
+            let norm = (sum / lit((3 * n).max(1) as f64)).sqrt();
+
+            let rate = last_norm.map(|last| norm / last);
+            if let Some(rate) = rate {
+                self.rate = rate;
+                let remaining = (NEWTON_MAX_ITERATIONS - iteration) as i32;
+                if rate >= F::one() || rate.powi(remaining) / (F::one() - rate) * norm > tolerance {
+                    return;
+                }
+            }
+
+            for i in 0..n {
+                self.w[0][i] += self.rhs_real[i];
+                self.w[1][i] += self.rhs_complex[i];
+                self.w[2][i] += self.rhs_complex[n + i];
+                for (row, z) in T.iter().zip(self.z.iter_mut()) {
+                    z[i] = (0..3).fold(F::zero(), |sum, j| sum + lit::<F>(row[j]) * self.w[j][i]);
+                }
+            }
+            if norm == F::zero() || rate.is_some_and(|rate| rate / (F::one() - rate) * norm < tolerance) {
+                self.converged = true;
+                return;
+            }
+            last_norm = Some(norm);
+        }
+    }
+
+    /// Returns the weighted root mean square of `error` over the tolerances of the step from `y` to `y_next`.
+    fn norm(&self, error: &[F], y: &[F], y_next: &[F]) -> F {
+        let mut sum = F::zero();
+        for i in 0..error.len() {
+            let scale = self.atol[i] + self.rtol * y[i].abs().max(y_next[i].abs());
+            let scaled = scaled_error(error[i], scale);
+            sum += scaled * scaled;
+        }
+        (sum / lit(error.len().max(1) as f64)).sqrt()
+    }
+
+    /// Returns the factor of the predictive controller of Gustafsson for a step of size `h` and error norm `error`.
+    fn predict_factor(&self, h: F, error: F) -> F {
+        let exponent = lit::<F>(-0.25);
+        let multiplier = match self.last_accepted {
+            Some((h_old, error_old)) if error > F::zero() => (h.abs() / h_old) * (error_old / error).powf(-exponent),
+            _ => F::one(),
+        };
+        let factor = if error > F::zero() { error.powf(exponent) } else { F::infinity() };
+        multiplier.min(F::one()) * factor
+    }
+
+    /// Safety factor applied to the optimal step size, smaller when the Newton iteration needed many iterations.
+    fn safety(&self) -> F {
+        let iterations = lit::<F>((2 * NEWTON_MAX_ITERATIONS + 1) as f64);
+        lit::<F>(0.9) * iterations / lit((2 * NEWTON_MAX_ITERATIONS + self.iterations) as f64)
+    }
+}
+
+impl<F: Real> EmbeddedSysMethod<F> for Radau5<F> {
+    fn error_order(&self) -> usize {
+        3
+    }
+
+    fn step<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F], h: F, y_next: &mut [F], error: &mut [F]) {
+        let n = self.dim;
+        if !self.started || x != self.x {
+            self.start(ode, x);
+        }
+        if self.f_stale {
+            ode.eval_into(x, y, &mut self.f);
+            self.f_stale = false;
+        }
+        if self.jacobian_due {
+            self.stage_y.copy_from_slice(y);
+            jacobian(ode, x, &mut self.stage_y, &self.f, &mut self.jacobian, &mut self.work);
+            self.jacobian_evals += 1;
+            self.jacobian_current = true;
+            self.jacobian_due = false;
+            self.lu_h = None;
+        }
+        let rejected = self.pending;
+        self.pending = true;
+        self.h_attempt = h;
+
+        for ((scale, atol), y) in self.scale.iter_mut().zip(&self.atol).zip(y) {
+            *scale = *atol + self.rtol * y.abs();
+        }
+
+        loop {
+            self.initial_guess(x, y, h);
+            if self.lu_h == Some(h) || self.factor(h) {
+                self.newton(ode, x, y, h);
+            } else {
+                self.converged = false;
+            }
+            if self.converged || self.jacobian_current {
+                break;
+            }
+            // Retry with a Jacobian at the current point
+            self.stage_y.copy_from_slice(y);
+            jacobian(ode, x, &mut self.stage_y, &self.f, &mut self.jacobian, &mut self.work);
+            self.jacobian_evals += 1;
+            self.jacobian_current = true;
+            self.lu_h = None;
+        }
+
+        if !self.converged {
+            y_next.copy_from_slice(y);
+            error.fill(F::zero());
+            return;
+        }
+        for i in 0..n {
+            y_next[i] = y[i] + self.z[2][i];
+            self.stage_y[i] = (0..3).fold(F::zero(), |sum, j| sum + lit::<F>(E[j]) * self.z[j][i]) / h;
+        }
+
+        // Error estimate (mu / h M - J)^-1 (f + M Z E / h), computed again at a perturbed point after a rejection
+        Self::apply_mass(&self.mass, &self.stage_y, &mut self.rhs_real);
+        add_scaled(error, &self.f, F::one(), &self.rhs_real);
+        self.lu_real.solve(error);
+        self.linear_solves += 1;
+        self.error_norm = self.norm(error, y, y_next);
+
+        if rejected && self.error_norm > F::one() {
+            add_scaled(&mut self.stage_y, y, F::one(), error);
+            ode.eval_into(x, &self.stage_y, &mut self.work);
+            add_scaled(error, &self.work, F::one(), &self.rhs_real);
+            self.lu_real.solve(error);
+            self.linear_solves += 1;
+            self.error_norm = self.norm(error, y, y_next);
+        }
+
+        // Keep the start of the step for the collocation polynomial
+        self.y_old.copy_from_slice(y);
+    }
+
+    fn accept(&mut self) {
+        let h = self.h_attempt;
+        self.pending = false;
+
+        let recompute_jacobian = self.iterations > 2 && self.rate > lit(1e-3);
+        let mut factor = (self.safety() * self.predict_factor(h, self.error_norm)).min(lit(MAX_FACTOR));
+        if !recompute_jacobian && factor < lit(1.2) {
+            factor = F::one();
+        }
+        self.next_factor = factor;
+        self.last_accepted = Some((h.abs(), self.error_norm));
+        self.jacobian_due = recompute_jacobian;
+        self.jacobian_current = false;
+
+        for i in 0..self.dim {
+            for (j, q) in self.q.iter_mut().enumerate() {
+                q[i] = (0..3).fold(F::zero(), |sum, stage| sum + lit::<F>(P[stage][j]) * self.z[stage][i]);
+            }
+        }
+        self.x_dense = self.x;
+        self.h_dense = h;
+        self.dense_valid = true;
+        self.x += h;
+        self.f_stale = true;
+    }
+
+    fn error_norm(&self, error: &[F], scale: &[F]) -> F {
+        if !self.converged {
+            return F::infinity();
+        }
+
+        rms_norm(error, scale)
+    }
+
+    fn step_factor(&self, error: F) -> Option<F> {
+        if !self.converged {
+            return Some(lit(0.5));
+        }
+        if error > F::one() {
+            let factor = self.safety() * self.predict_factor(self.h_attempt, error);
+            return Some(factor.max(lit(MIN_FACTOR)));
+        }
+        Some(self.next_factor)
+    }
+
+    fn set_tolerances(&mut self, rtol: F, atol: &[F]) {
+        self.rtol = rtol;
+        self.atol.copy_from_slice(atol);
+    }
+
+    fn record_work(&mut self, stats: &mut Stats<F>) {
+        stats.jacobian_evals += self.jacobian_evals;
+        stats.linear_solves += self.linear_solves;
+        self.jacobian_evals = 0;
+        self.linear_solves = 0;
+    }
+
+    fn supports_mass_matrix(&self) -> bool {
+        true
+    }
+}
 
-            let k1 = ode.eval(&x, &add_vec(&y, &vec![h; y.len()]));
-             
-            y = add_vec(&y, &vec_scalar_mul(&k1, h));
-            x += h;
+impl<F: Real> DenseSysMethod<F> for Radau5<F> {
+    fn interpolate(&self, theta: F, _h: F, y_prev: &[F], _y: &[F], out: &mut [F]) {
+        for i in 0..out.len() {
+            out[i] = y_prev[i] + ((self.q[2][i] * theta + self.q[1][i]) * theta + self.q[0][i]) * theta;
         }
-        y
-    }     
+    }
 }
@@ -1,10 +1,14 @@
 //! Runge-Kutta (RK4) method for solving systems of ordinary differential equations (ODEs).
-use super::{ODESYS, ODESysSolver, add_vec, vec_scalar_mul};
+use super::{ODESYS, ODESysSolver, fixed_step, fixed_step_traj, solve_with};
+use super::stepper::SysStepper;
+use super::explicit_rk_sys::ExplicitRk;
+use crate::{ButcherTableau, Real, Solution, SolverError, SolverOptions};
+use crate::error::expect_solved;
 
 /// Runge-Kutta (RK4) method for solving systems of Ordinary Differential Equations (ODEs).
 ///
 /// This trait defines the [Runge-Kutta (RK4) method](https://en.wikipedia.org/wiki/Runge%E2%80%93Kutta_methods) for solving systems of ordinary differential equations.
-pub trait RungeKuttaODESysSolver<T: ODESYS> {
+pub trait RungeKuttaODESysSolver<T: ODESYS<F>, F: Real = f64> {
     /// Solve the system of ODEs using the Runge-Kutta (RK4) method.
     ///
     /// # Arguments
@@ -19,9 +23,14 @@ pub trait RungeKuttaODESysSolver<T: ODESYS> {
     ///
     /// The vector of estimated y values at `x_target`.
     /// 
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RungeKuttaODESysSolver::try_rk_solve`].
+    ///
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, rk_sys::RungeKuttaODESysSolver};
     /// struct MyODESystem;
     /// impl ODESYS for MyODESystem {
     ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
@@ -41,11 +50,228 @@ pub trait RungeKuttaODESysSolver<T: ODESYS> {
     /// let result = solver.rk_solve(&my_ode_system, x0, y0, x_target, h);
     /// println!("Solution at x = {}: {:?}", x_target, result);
     /// ```
-    fn rk_solve(&self, ode: &T, x: f64, y: Vec<f64>, x_target: f64, h: f64) -> Vec<f64>;
+    fn rk_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F>;
+
+    /// Solve the system of ODEs using the Runge-Kutta (RK4) method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size or increment for x.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from the initial x up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RungeKuttaODESysSolver::try_rk_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, rk_sys::RungeKuttaODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let solution = solver.rk_solve_traj(&my_ode_system, x0, y0, x_target, h);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn rk_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Runge-Kutta (RK4) method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The step size or increment for x.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, rk_sys::RungeKuttaODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.try_rk_solve(&my_ode_system, x0, y0.clone(), x_target, h).unwrap();
+    /// assert!((result[0] - 0.5f64.exp()).abs() < 1e-5);
+    /// assert!((result[1] - (3.0 * 1.0f64.exp() - 2.0)).abs() < 1e-4);
+    ///
+    /// // A zero step size never reaches x_target
+    /// let error = solver.try_rk_solve(&my_ode_system, x0, y0, x_target, 0.0).unwrap_err();
+    /// assert_eq!(error, SolverError::InvalidStepSize { x: 0.0, h: 0.0 });
+    /// ```
+    fn try_rk_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError>;
+
+    /// Create a stepper integrating the system of ODEs with the Runge-Kutta (RK4) method, one step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// A [`SysStepper`] holding the current state, starting from the initial `(x, y)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, rk_sys::RungeKuttaODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let mut stepper = solver.rk_stepper(&my_ode_system, 0.0, vec![1.0, 2.0], 0.1);
+    ///
+    /// // Integrate a tenth of x at a time
+    /// for x in [0.1, 0.2, 0.3] {
+    ///     let y = stepper.advance_to(x).unwrap();
+    ///     assert!((y[0] - (x * x / 2.0).exp()).abs() < 1e-5);
+    ///     assert!((y[1] - (3.0 * x.exp() - x - 1.0)).abs() < 1e-5);
+    ///     assert_eq!(stepper.x(), x);
+    /// }
+    /// ```
+    fn rk_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> SysStepper<'a, Rk4<F>, T, F>;
+
+    /// Solve the system of ODEs using the Runge-Kutta (RK4) method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RungeKuttaODESysSolver::try_rk_solve_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, rk_sys::RungeKuttaODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let options = SolverOptions::new().h0(0.1).output(Output::Final);
+    ///
+    /// let solution = solver.rk_solve_with(&my_ode_system, 0.0, vec![1.0, 2.0], 1.0, &options);
+    /// assert_eq!(solution.x, vec![1.0]);
+    /// assert!((solution.y[0][0] - 0.5f64.exp()).abs() < 1e-5);
+    /// assert!((solution.y[0][1] - (3.0 * 1.0f64.exp() - 2.0)).abs() < 1e-5);
+    /// ```
+    fn rk_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Runge-Kutta (RK4) method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration: the step size is `h0`, capped at `h_max`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverError, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, rk_sys::RungeKuttaODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// // The solution is requested at x = 0.5 on the way to x = 1
+    /// let options = SolverOptions::new().h0(0.1).output(Output::Points(vec![0.5]));
+    /// let solution = solver.try_rk_solve_with(&my_ode_system, 0.0, vec![1.0, 2.0], 1.0, &options).unwrap();
+    /// assert_eq!(solution.x, vec![0.5]);
+    /// assert!((solution.y[0][0] - 0.125f64.exp()).abs() < 1e-5);
+    /// assert!((solution.y[0][1] - (3.0 * 0.5f64.exp() - 1.5)).abs() < 1e-5);
+    ///
+    /// // Ten steps of 0.1 are needed to reach x = 1
+    /// let options = SolverOptions::new().h0(0.1).max_steps(5);
+    /// let error = solver.try_rk_solve_with(&my_ode_system, 0.0, vec![1.0, 2.0], 1.0, &options).unwrap_err();
+    /// assert_eq!(error, SolverError::MaxStepsExceeded { x: 0.0, max_steps: 5 });
+    /// ```
+    fn try_rk_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError>;
 }
 
 // Implementing the Runge-Kutta (RK4) method for the system of ODEs Solver
-impl<T: ODESYS> RungeKuttaODESysSolver<T> for ODESysSolver {
+impl<F: Real, T: ODESYS<F>> RungeKuttaODESysSolver<T, F> for ODESysSolver {
     /// Implementation of the Runge-Kutta (RK4) method to solve a system of ODEs.
     ///
     /// This method approximates the solution to the system of ODEs at the specified x_target.
@@ -77,6 +303,7 @@ impl<T: ODESYS> RungeKuttaODESysSolver<T> for ODESysSolver {
     /// # Example
     ///
     /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, rk_sys::RungeKuttaODESysSolver};
     /// struct MyODESystem;
     /// impl ODESYS for MyODESystem {
     ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
@@ -96,36 +323,48 @@ impl<T: ODESYS> RungeKuttaODESysSolver<T> for ODESysSolver {
     /// let result = solver.rk_solve(&my_ode_system, x0, y0, x_target, h);
     /// println!("Solution at x = {}: {:?}", x_target, result);
     /// ```
-    fn rk_solve(&self, ode: &T, mut x: f64, mut y: Vec<f64>, x_target: f64, h: f64) -> Vec<f64>{
-        while x < x_target {
-            let result = rk4_step(x, &y, h, &|x, y| ode.eval(&x, &y.to_vec()));
-            y.clone_from_slice(&result);
-            x += h;
-        }
-        y
+    fn rk_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        expect_solved(self.try_rk_solve(ode, x, y, x_target, h))
     }
-}
 
-/// Function to compute a single RK4 step for a system of ODEs.
-fn rk4_step(x: f64, y_n: &[f64], h: f64, f: &dyn Fn(f64, &[f64]) -> Vec<f64>) -> Vec<f64> {
-    let k1 = vec_scalar_mul(&f(x, y_n), h);
-    let k2 = vec_scalar_mul(&f(x + h / 2.0, &add_vec(y_n, &vec_scalar_mul(&k1, 0.5))), h);
-    let k3 = vec_scalar_mul(&f(x + h / 2.0, &add_vec(y_n, &vec_scalar_mul(&k2, 0.5))), h);
-    let k4 = vec_scalar_mul(&f(x + h, &add_vec(y_n, &k3)), h);
+    /// Implementation of the Runge-Kutta (RK4) method returning the whole trajectory of the system of ODEs.
+    ///
+    /// See [`RungeKuttaODESysSolver::rk_solve`] for the details of the method.
+    fn rk_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F> {
+        expect_solved(fixed_step_traj(ode, Rk4::new(ButcherTableau::rk4(), y.len()), x, y, h, x_target))
+    }
 
-    let result = add_vec(
-        y_n,
-        &vec_scalar_mul(
-            &add_vec(
-                &k1,
-                &add_vec(
-                    &vec_scalar_mul(&k2, 2.0),
-                    &add_vec(&vec_scalar_mul(&k3, 2.0), &k4),
-                ),
-            ),
-            1.0 / 6.0,
-        ),
-    );
+    /// Implementation of the Runge-Kutta (RK4) method reporting failures as a [`SolverError`].
+    ///
+    /// See [`RungeKuttaODESysSolver::rk_solve`] for the details of the method.
+    fn try_rk_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError> {
+        fixed_step(ode, Rk4::new(ButcherTableau::rk4(), y.len()), x, y, h, x_target, |_, _| {})
+    }
+
+    /// Implementation of the Runge-Kutta (RK4) method as a [`SysStepper`].
+    ///
+    /// See [`RungeKuttaODESysSolver::rk_solve`] for the details of the method.
+    fn rk_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> SysStepper<'a, Rk4<F>, T, F> {
+        SysStepper::new(ode, Rk4::new(ButcherTableau::rk4(), y.len()), x, y, h)
+    }
+
+    /// Implementation of the Runge-Kutta (RK4) method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`RungeKuttaODESysSolver::rk_solve`] for the details of the method.
+    fn rk_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F> {
+        expect_solved(self.try_rk_solve_with(ode, x, y, x_target, options))
+    }
 
-    result
+    /// Implementation of the Runge-Kutta (RK4) method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`RungeKuttaODESysSolver::rk_solve`] for the details of the method.
+    fn try_rk_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError> {
+        options.check(x, x_target, y.len())?;
+        let stepper = self.rk_stepper(ode, x, y, options.initial_step(x, x_target));
+        solve_with(stepper.with_max_steps(options.max_steps), x_target, &options.output)
+    }
 }
+
+/// Stage buffers of the RK4 method for a system of ODEs, parametrizing the [`SysStepper`] of the method: the
+/// [`ExplicitRk`] method of [`ButcherTableau::rk4`].
+pub type Rk4<F> = ExplicitRk<F>;
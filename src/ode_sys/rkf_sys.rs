@@ -0,0 +1,287 @@
+//! Runge-Kutta-Fehlberg (RKF45) method for solving systems of ordinary differential equations (ODEs).
+use super::{ODESYS, ODESysSolver, solve_with};
+use super::adaptive::AdaptiveSysStepper;
+use super::explicit_rk_sys::ExplicitRk;
+use crate::{ButcherTableau, Output, Real, Solution, SolverError, SolverOptions};
+use crate::error::expect_solved;
+
+/// Runge-Kutta-Fehlberg (RKF45) method for solving systems of Ordinary Differential Equations (ODEs).
+///
+/// This trait defines the adaptive [Runge-Kutta-Fehlberg (RKF) method](https://en.wikipedia.org/wiki/Runge%E2%80%93Kutta%E2%80%93Fehlberg_method)
+/// for solving systems of ordinary differential equations. The step size `h` given to the methods is the initial one,
+/// which is then adapted so that the weighted root mean square of the local error estimates stays within the tolerances,
+/// see [`AdaptiveSysStepper`]. Without a [`SolverOptions`], the tolerances are the defaults of [`SolverOptions::new`].
+pub trait RKFODESysSolver<T: ODESYS<F>, F: Real = f64> {
+    /// Solve the system of ODEs using the Runge-Kutta-Fehlberg (RKF45) method.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RKFODESysSolver::try_rkf_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, rkf_sys::RKFODESysSolver};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.rkf_solve(&my_ode_system, x0, y0, x_target, h);
+    /// assert!((result[0] - 0.5f64.exp()).abs() < 1e-5);
+    /// println!("Solution at x = {}: {:?}", x_target, result);
+    /// ```
+    fn rkf_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F>;
+
+    /// Solve the system of ODEs using the Runge-Kutta-Fehlberg (RKF45) method, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from the initial x up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RKFODESysSolver::try_rkf_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, rkf_sys::RKFODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    ///
+    /// // The accepted points are as far apart as the tolerances allow
+    /// let solution = solver.rkf_solve_traj(&oscillator, 0.0, vec![1.0, 0.0], 10.0, 0.1);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn rkf_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Runge-Kutta-Fehlberg (RKF45) method, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`, or the [`SolverError`] that stopped the integration, such as a
+    /// [`SolverError::StepSizeUnderflow`] when the solution blows up.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::SolverError;
+    /// # use damndiff::ode_sys::{ODESysSolver, rkf_sys::RKFODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // The first component blows up at x = 1
+    /// let blow_up = |_x: &f64, y: &Vec<f64>| vec![y[0] * y[0], -y[1]];
+    ///
+    /// match solver.try_rkf_solve(&blow_up, 0.0, vec![1.0, 1.0], 2.0, 0.1) {
+    ///     Ok(result) => println!("Solution at x = 2: {:?}", result),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_rkf_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError>;
+
+    /// Create a stepper integrating the system of ODEs with the Runge-Kutta-Fehlberg (RKF45) method, one adaptive step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The initial step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// An [`AdaptiveSysStepper`] holding the current state, starting from the initial `(x, y)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, rkf_sys::RKFODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let mut stepper = solver.rkf_stepper(&oscillator, 0.0, vec![1.0, 0.0], 0.1);
+    ///
+    /// for state in stepper.by_ref().take(5) {
+    ///     let (x, y) = state.unwrap();
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// println!("Next step size: {}", stepper.h());
+    /// ```
+    fn rkf_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, Rkf45<F>, T, F>;
+
+    /// Solve the system of ODEs using the Runge-Kutta-Fehlberg (RKF45) method, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration, including one absolute tolerance per component if needed.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RKFODESysSolver::try_rkf_solve_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, rkf_sys::RKFODESysSolver};
+    /// let solver = ODESysSolver;
+    /// // Species concentrations of very different magnitudes
+    /// let kinetics = |_x: &f64, y: &Vec<f64>| vec![-0.04 * y[0], 0.04 * y[0] - 10.0 * y[1]];
+    /// let options = SolverOptions::new()
+    ///     .rtol(1e-6)
+    ///     .atol_per_component(vec![1e-8, 1e-12])
+    ///     .output(Output::Points(vec![1.0, 10.0, 100.0]));
+    ///
+    /// let solution = solver.rkf_solve_with(&kinetics, 0.0, vec![1.0, 0.0], 100.0, &options);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn rkf_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Runge-Kutta-Fehlberg (RKF45) method, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration, including one absolute tolerance per component if needed.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, rkf_sys::RKFODESysSolver};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let options = SolverOptions::new().rtol(1e-9).atol(1e-9).max_steps(10).output(Output::Final);
+    ///
+    /// match solver.try_rkf_solve_with(&oscillator, 0.0, vec![1.0, 0.0], 100.0, &options) {
+    ///     Ok(solution) => println!("Solution at x = 100: {:?}", solution.last()),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_rkf_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError>;
+}
+
+// Implementing the Runge-Kutta-Fehlberg (RKF45) method for the system of ODEs Solver
+impl<F: Real, T: ODESYS<F>> RKFODESysSolver<T, F> for ODESysSolver {
+    /// Implementation of the Runge-Kutta-Fehlberg (RKF45) method to solve a system of ODEs.
+    ///
+    /// Each step computes the 4th- and 5th-order solutions of the embedded Fehlberg pair with six evaluations of the
+    /// system, propagates the 4th-order one and uses their difference as the estimate of its local error.
+    ///
+    /// # When to Use:
+    ///
+    /// Suitable for non-stiff systems whose solution changes pace along the integration, or when the accuracy matters
+    /// more than a fixed grid of output points.
+    ///
+    /// # Pros and Cons:
+    /// - Pros: Controls the local error of every component, takes large steps where the solution is smooth.
+    /// - Cons: Six evaluations per step, and rejected steps are wasted work.
+    ///
+    /// # Stability Analysis:
+    ///
+    /// Conditionally stable: on stiff systems the step size control keeps the steps small enough to stay stable, at a high cost.
+    fn rkf_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        expect_solved(self.try_rkf_solve(ode, x, y, x_target, h))
+    }
+
+    /// Implementation of the Runge-Kutta-Fehlberg (RKF45) method returning the whole trajectory of the system of ODEs.
+    ///
+    /// See [`RKFODESysSolver::rkf_solve`] for the details of the method.
+    fn rkf_solve_traj(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F> {
+        expect_solved(solve_with(self.rkf_stepper(ode, x, y, h), x_target, &Output::Steps))
+    }
+
+    /// Implementation of the Runge-Kutta-Fehlberg (RKF45) method reporting failures as a [`SolverError`].
+    ///
+    /// See [`RKFODESysSolver::rkf_solve`] for the details of the method.
+    fn try_rkf_solve(&self, ode: &T, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError> {
+        let mut stepper = self.rkf_stepper(ode, x, y, h);
+        stepper.advance_to(x_target)?;
+        Ok(stepper.into_y())
+    }
+
+    /// Implementation of the Runge-Kutta-Fehlberg (RKF45) method as an [`AdaptiveSysStepper`].
+    ///
+    /// See [`RKFODESysSolver::rkf_solve`] for the details of the method.
+    fn rkf_stepper<'a>(&self, ode: &'a T, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, Rkf45<F>, T, F> {
+        let method = ExplicitRk::new(ButcherTableau::rkf45(), y.len());
+        AdaptiveSysStepper::new(ode, method, x, y, h)
+    }
+
+    /// Implementation of the Runge-Kutta-Fehlberg (RKF45) method with the settings of a [`SolverOptions`].
+    ///
+    /// See [`RKFODESysSolver::rkf_solve`] for the details of the method.
+    fn rkf_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F> {
+        expect_solved(self.try_rkf_solve_with(ode, x, y, x_target, options))
+    }
+
+    /// Implementation of the Runge-Kutta-Fehlberg (RKF45) method with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`RKFODESysSolver::rkf_solve`] for the details of the method.
+    fn try_rkf_solve_with(&self, ode: &T, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError> {
+        options.check(x, x_target, y.len())?;
+        let stepper = self.rkf_stepper(ode, x, y, options.initial_step(x, x_target));
+        solve_with(stepper.with_options(options), x_target, &options.output)
+    }
+}
+
+/// Stage buffers of the embedded RKF45 pair for a system of ODEs, parametrizing the [`AdaptiveSysStepper`] of the
+/// method: the [`ExplicitRk`] method of [`ButcherTableau::rkf45`].
+pub type Rkf45<F> = ExplicitRk<F>;
@@ -0,0 +1,729 @@
+//! Rosenbrock (linearly implicit) methods for solving moderately stiff systems of ordinary differential equations (ODEs).
+use super::{ODESYS, ODESysSolver, is_finite, jacobian, solve_with};
+use super::adaptive::{AdaptiveSysStepper, DenseSysMethod, EmbeddedSysMethod};
+use super::explicit_rk_sys::accumulate;
+use crate::{Output, Real, Solution, SolverError, SolverOptions, Stats};
+use crate::controller::rms_norm;
+use crate::error::expect_solved;
+use crate::linalg::Lu;
+use crate::scalar::lit;
+use crate::ode::explicit_rk::hermite;
+
+/// Rosenbrock methods for solving systems of Ordinary Differential Equations (ODEs).
+///
+/// This trait integrates stiff systems of ordinary differential equations with the
+/// [Rosenbrock methods](https://en.wikipedia.org/wiki/Rosenbrock_methods) described by a [`RosenbrockTableau`], such as
+/// ROS3P, Rodas4 and Rodas5 or a user-defined one. These linearly implicit methods replace the Newton iterations of the
+/// fully implicit ones by one linear solve per stage, with a matrix built from the Jacobian of the system at the start
+/// of the step. The step size `h` given to the methods is the initial one, which is then adapted so that the weighted
+/// root mean square of the embedded error estimates stays within the tolerances, see [`AdaptiveSysStepper`]. Without a
+/// [`SolverOptions`], the tolerances are the defaults of [`SolverOptions::new`].
+///
+/// The Jacobian is the one of [`ODESYS::jacobian_into`] when the system provides it, and a finite-difference
+/// approximation otherwise. The same goes for the derivative with respect to x and [`ODESYS::dfdx_into`].
+pub trait RosenbrockODESysSolver<T: ODESYS<F>, F: Real = f64> {
+    /// Solve the system of ODEs using the Rosenbrock method of `tableau`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The coefficients of the Rosenbrock method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RosenbrockODESysSolver::try_ros_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, rosenbrock::{RosenbrockODESysSolver, RosenbrockTableau}};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.ros_solve(&my_ode_system, &RosenbrockTableau::rodas4(), x0, y0, x_target, h);
+    /// assert!((result[0] - 0.5f64.exp()).abs() < 1e-4);
+    /// println!("Solution at x = {}: {:?}", x_target, result);
+    /// ```
+    fn ros_solve(&self, ode: &T, tableau: &RosenbrockTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F>;
+
+    /// Solve the system of ODEs using the Rosenbrock method of `tableau`, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The coefficients of the Rosenbrock method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from the initial point up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RosenbrockODESysSolver::try_ros_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, rosenbrock::{RosenbrockODESysSolver, RosenbrockTableau}};
+    /// let solver = ODESysSolver;
+    /// // Fast relaxation towards a slowly moving equilibrium
+    /// let relaxation = |x: &f64, y: &Vec<f64>| vec![-1e4 * (y[0] - x.cos()), y[0] - y[1]];
+    ///
+    /// let solution = solver.ros_solve_traj(&relaxation, &RosenbrockTableau::ros3p(), 0.0, vec![1.0, 0.0], 5.0, 1e-3);
+    /// println!("{} steps", solution.stats.accepted_steps);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn ros_solve_traj(&self, ode: &T, tableau: &RosenbrockTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Rosenbrock method of `tableau`, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The coefficients of the Rosenbrock method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, rosenbrock::{RosenbrockODESysSolver, RosenbrockTableau}};
+    /// let solver = ODESysSolver;
+    /// // The first component blows up at x = 1
+    /// let blow_up = |_x: &f64, y: &Vec<f64>| vec![y[0] * y[0], -y[1]];
+    ///
+    /// match solver.try_ros_solve(&blow_up, &RosenbrockTableau::rodas5(), 0.0, vec![1.0, 1.0], 2.0, 0.1) {
+    ///     Ok(result) => println!("Solution at x = 2: {:?}", result),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_ros_solve(&self, ode: &T, tableau: &RosenbrockTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError>;
+
+    /// Create a stepper integrating the system of ODEs with the Rosenbrock method of `tableau`, one adaptive step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The coefficients of the Rosenbrock method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The initial step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// An [`AdaptiveSysStepper`] holding the current state, starting from the initial `(x, y)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, rosenbrock::{RosenbrockODESysSolver, RosenbrockTableau}};
+    /// let solver = ODESysSolver;
+    /// let relaxation = |x: &f64, y: &Vec<f64>| vec![-1e4 * (y[0] - x.cos())];
+    /// let mut stepper = solver.ros_stepper(&relaxation, &RosenbrockTableau::rodas4(), 0.0, vec![1.0], 0.01);
+    ///
+    /// stepper.advance_to(2.0).unwrap();
+    /// let (x_prev, x) = stepper.last_step();
+    /// let middle = stepper.interpolate((x_prev + x) / 2.0).unwrap();
+    /// assert!((middle[0] - ((x_prev + x) / 2.0).cos()).abs() < 1e-3);
+    /// ```
+    fn ros_stepper<'a>(&self, ode: &'a T, tableau: &RosenbrockTableau, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, Rosenbrock<F>, T, F>;
+
+    /// Solve the system of ODEs using the Rosenbrock method of `tableau`, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The coefficients of the Rosenbrock method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`RosenbrockODESysSolver::try_ros_solve_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, rosenbrock::{RosenbrockODESysSolver, RosenbrockTableau}};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let options = SolverOptions::new().rtol(1e-8).atol(1e-10).output(Output::Points(vec![1.0, 2.0, 3.0]));
+    ///
+    /// let solution = solver.ros_solve_with(&oscillator, &RosenbrockTableau::rodas5(), 0.0, vec![1.0, 0.0], 3.0, &options);
+    /// for (x, y) in solution.iter() {
+    ///     assert!((y[0] - x.cos()).abs() < 1e-6);
+    /// }
+    /// ```
+    fn ros_solve_with(&self, ode: &T, tableau: &RosenbrockTableau, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the Rosenbrock method of `tableau`, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The coefficients of the Rosenbrock method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, rosenbrock::{RosenbrockODESysSolver, RosenbrockTableau}};
+    /// let solver = ODESysSolver;
+    /// let van_der_pol = |_x: &f64, y: &Vec<f64>| vec![y[1], 1e3 * (1.0 - y[0] * y[0]) * y[1] - y[0]];
+    /// let options = SolverOptions::new().max_steps(10).output(Output::Final);
+    ///
+    /// match solver.try_ros_solve_with(&van_der_pol, &RosenbrockTableau::rodas4(), 0.0, vec![2.0, 0.0], 3000.0, &options) {
+    ///     Ok(solution) => println!("Solution at x = 3000: {:?}", solution.last()),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_ros_solve_with(&self, ode: &T, tableau: &RosenbrockTableau, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError>;
+}
+
+// Implementing the Rosenbrock methods for the system of ODEs Solver
+impl<F: Real, T: ODESYS<F>> RosenbrockODESysSolver<T, F> for ODESysSolver {
+    /// Implementation of the Rosenbrock method of a tableau to solve a system of ODEs.
+    ///
+    /// Each step evaluates the Jacobian `J` of the system at the start of the step, along with the derivative of the
+    /// system with respect to x by a central difference, and factorizes the matrix `I / (gamma h) - J` once. Every stage
+    /// then costs one evaluation of the system and one linear solve with this matrix, and the propagated and embedded
+    /// solutions are combinations of the stage increments. A rejected step reuses the Jacobian with a smaller step size,
+    /// and a singular matrix is handled as a rejection. The solution between two accepted points is interpolated with a
+    /// cubic Hermite polynomial, see [`AdaptiveSysStepper::interpolate`].
+    ///
+    /// The accuracy of the methods relies on an exact Jacobian: a finite-difference approximation is accurate to about
+    /// the square root of the machine precision, which is enough for the usual tolerances.
+    ///
+    /// # When to Use:
+    ///
+    /// Moderately stiff systems of small to medium dimension solved to moderate tolerances, such as control models and
+    /// chemical kinetics, where a Newton iteration would converge in few iterations anyway. ROS3P suits the nonlinear
+    /// semi-discretization of parabolic equations, Rodas4 is a robust default and Rodas5 pays off at tighter tolerances.
+    ///
+    /// # Pros and Cons:
+    /// - Pros: No iteration to converge, so that the cost of a step is known in advance, one Jacobian and one
+    ///   factorization per step, and stiffly accurate methods (Rodas4, Rodas5) that suit differential-algebraic problems.
+    /// - Cons: A new Jacobian at every step, which is expensive for large systems without an analytic one, and a loss
+    ///   of accuracy when the Jacobian is poorly approximated.
+    ///
+    /// # Stability Analysis:
+    ///
+    /// ROS3P is A-stable, while Rodas4 and Rodas5 are L-stable: the very stiff components of the solution are damped in
+    /// a single step, whatever the step size.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, rosenbrock::{RosenbrockODESysSolver, RosenbrockTableau}};
+    /// let solver = ODESysSolver;
+    /// // Robertson's chemical kinetics, with rate constants spanning nine orders of magnitude
+    /// let robertson = |_x: &f64, y: &Vec<f64>| vec![
+    ///     -0.04 * y[0] + 1e4 * y[1] * y[2],
+    ///     0.04 * y[0] - 1e4 * y[1] * y[2] - 3e7 * y[1] * y[1],
+    ///     3e7 * y[1] * y[1],
+    /// ];
+    ///
+    /// let solution = solver.ros_solve_traj(&robertson, &RosenbrockTableau::rodas4(), 0.0, vec![1.0, 0.0, 0.0], 40.0, 1e-6);
+    /// let y = solution.last().unwrap().1;
+    /// assert!((y[0] - 0.7158).abs() < 1e-3);
+    /// assert!(solution.stats.accepted_steps < 200);
+    /// ```
+    fn ros_solve(&self, ode: &T, tableau: &RosenbrockTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        expect_solved(self.try_ros_solve(ode, tableau, x, y, x_target, h))
+    }
+
+    /// Implementation of the Rosenbrock method of a tableau returning the whole trajectory of the system of ODEs.
+    ///
+    /// See [`RosenbrockODESysSolver::ros_solve`] for the details of the method.
+    fn ros_solve_traj(&self, ode: &T, tableau: &RosenbrockTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F> {
+        expect_solved(solve_with(self.ros_stepper(ode, tableau, x, y, h), x_target, &Output::Steps))
+    }
+
+    /// Implementation of the Rosenbrock method of a tableau reporting failures as a [`SolverError`].
+    ///
+    /// See [`RosenbrockODESysSolver::ros_solve`] for the details of the method.
+    fn try_ros_solve(&self, ode: &T, tableau: &RosenbrockTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError> {
+        let mut stepper = self.ros_stepper(ode, tableau, x, y, h);
+        stepper.advance_to(x_target)?;
+        Ok(stepper.into_y())
+    }
+
+    /// Implementation of the Rosenbrock method of a tableau as an [`AdaptiveSysStepper`].
+    ///
+    /// See [`RosenbrockODESysSolver::ros_solve`] for the details of the method.
+    fn ros_stepper<'a>(&self, ode: &'a T, tableau: &RosenbrockTableau, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, Rosenbrock<F>, T, F> {
+        let method = Rosenbrock::new(tableau.clone(), y.len());
+        AdaptiveSysStepper::new(ode, method, x, y, h)
+    }
+
+    /// Implementation of the Rosenbrock method of a tableau with the settings of a [`SolverOptions`].
+    ///
+    /// See [`RosenbrockODESysSolver::ros_solve`] for the details of the method.
+    fn ros_solve_with(&self, ode: &T, tableau: &RosenbrockTableau, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F> {
+        expect_solved(self.try_ros_solve_with(ode, tableau, x, y, x_target, options))
+    }
+
+    /// Implementation of the Rosenbrock method of a tableau with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`RosenbrockODESysSolver::ros_solve`] for the details of the method.
+    fn try_ros_solve_with(&self, ode: &T, tableau: &RosenbrockTableau, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError> {
+        options.check(x, x_target, y.len())?;
+        let stepper = self.ros_stepper(ode, tableau, x, y, options.initial_step(x, x_target));
+        solve_with(stepper.with_options(options), x_target, &options.output)
+    }
+}
+
+/// Coefficients of a Rosenbrock method with an embedded solution, in the transformed form of Hairer and Wanner.
+///
+/// Stage `i` of a step of size `h` from `(x, y)` solves
+/// `(I / (gamma h) - J) U[i] = f(x + alpha[i] h, y + sum(a[i][j] U[j])) + sum(c[i][j] U[j]) / h + gamma[i] h df/dx`,
+/// `J` being the Jacobian of the system at `(x, y)`, and the propagated solution is `y + sum(m[i] U[i])`. The nodes
+/// `alpha[i]` and the coefficients `gamma[i]` of the derivative with respect to x are deduced from the other ones.
+///
+/// # Example
+///
+/// ```
+/// # use damndiff::ode_sys::{ODESysSolver, rosenbrock::{RosenbrockODESysSolver, RosenbrockTableau}};
+/// // ROS2 of Verwer et al., with the linearly implicit Euler method as embedded solution
+/// let gamma = 1.0 + 0.5f64.sqrt();
+/// let ros2 = RosenbrockTableau::new(
+///     gamma,
+///     &[&[], &[1.0 / gamma]],
+///     &[&[], &[-2.0 / gamma]],
+///     &[1.5 / gamma, 0.5 / gamma],
+///     &[1.0 / gamma, 0.0],
+///     2,
+///     1,
+/// );
+/// let result = ODESysSolver.ros_solve(&|x: &f64, y: &Vec<f64>| vec![x - y[0]], &ros2, 0.0, vec![1.0], 1.0, 0.01);
+/// assert!((result[0] - 2.0 * (-1.0f64).exp()).abs() < 1e-3);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RosenbrockTableau {
+    /// Diagonal coefficient, shared by all the stages.
+    gamma: f64,
+    /// Coefficients of the previous stages in the argument of each stage, row `i` holding `i` of them.
+    pub(crate) a: Vec<Vec<f64>>,
+    /// Coefficients of the previous stages in the right-hand side of each stage, row `i` holding `i` of them.
+    pub(crate) c: Vec<Vec<f64>>,
+    /// Weights of the propagated solution.
+    pub(crate) m: Vec<f64>,
+    /// Differences `m - m_hat` between the weights of the propagated and of the embedded solutions.
+    pub(crate) e: Vec<f64>,
+    /// Nodes of the stages.
+    pub(crate) alpha: Vec<f64>,
+    /// Coefficients of the derivative with respect to x in each stage.
+    pub(crate) gamma_x: Vec<f64>,
+    order: usize,
+    embedded_order: usize,
+}
+
+impl RosenbrockTableau {
+    /// Creates the tableau of a Rosenbrock method of order `order` in transformed form, with diagonal coefficient
+    /// `gamma`, stage coefficients `a` and `c`, weights `m` and the weights `m_hat` of an embedded solution of order
+    /// `embedded_order`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method has no stage, if `gamma` is not positive, if the weights do not have one entry per stage,
+    /// or if the row `i` of `a` or `c` does not hold exactly `i` coefficients.
+    pub fn new(gamma: f64, a: &[&[f64]], c: &[&[f64]], m: &[f64], m_hat: &[f64], order: usize, embedded_order: usize) -> Self {
+        let stages = m.len();
+        assert!(stages > 0, "a Rosenbrock method needs at least one stage");
+        assert!(gamma > 0.0, "the diagonal coefficient of a Rosenbrock method must be positive, {} given", gamma);
+        assert_eq!(m_hat.len(), stages, "{} embedded weights given for {} stages", m_hat.len(), stages);
+        for (name, rows) in [("a", a), ("c", c)] {
+            assert_eq!(rows.len(), stages, "{} rows of {} given for {} stages", rows.len(), name, stages);
+            for (i, row) in rows.iter().enumerate() {
+                assert_eq!(row.len(), i, "row {} of {} needs {} coefficients, {} given", i, name, i, row.len());
+            }
+        }
+
+        // Matrix Gamma of the original form, whose inverse is diag(1 / gamma) - c
+        let mut big_gamma: Vec<Vec<f64>> = Vec::with_capacity(stages);
+        for (i, row) in c.iter().enumerate() {
+            let mut gamma_row = vec![0.0; stages];
+            for j in 0..i {
+                gamma_row[j] = gamma * (j..i).map(|k| row[k] * big_gamma[k][j]).sum::<f64>();
+            }
+            gamma_row[i] = gamma;
+            big_gamma.push(gamma_row);
+        }
+        let gamma_x: Vec<f64> = big_gamma.iter().map(|row| row.iter().sum()).collect();
+        let alpha = a.iter().map(|row| row.iter().zip(&gamma_x).map(|(a, gamma_x)| a * gamma_x).sum()).collect();
+
+        RosenbrockTableau {
+            gamma,
+            a: a.iter().map(|row| row.to_vec()).collect(),
+            c: c.iter().map(|row| row.to_vec()).collect(),
+            m: m.to_vec(),
+            e: m.iter().zip(m_hat).map(|(m, m_hat)| m - m_hat).collect(),
+            alpha,
+            gamma_x,
+            order,
+            embedded_order,
+        }
+    }
+
+    /// Returns the number of stages.
+    pub fn stages(&self) -> usize {
+        self.m.len()
+    }
+
+    /// Returns the diagonal coefficient `gamma`.
+    pub fn gamma(&self) -> f64 {
+        self.gamma
+    }
+
+    /// Returns the order of the propagated solution.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Returns the order of the embedded solution.
+    pub fn embedded_order(&self) -> usize {
+        self.embedded_order
+    }
+
+    /// Returns the order of the error estimate, that is the lower order of the pair.
+    pub(crate) fn error_order(&self) -> usize {
+        self.order.min(self.embedded_order)
+    }
+
+    /// ROS3P of Lang and Verwer, a 3-stage A-stable method of order 3 with an embedded solution of order 2, which keeps
+    /// its order on the semi-discretization of parabolic equations.
+    ///
+    /// On linear systems with constant coefficients, its second stage is a multiple of the first one, so that both
+    /// solutions coincide and the error estimate vanishes: the step size is then only bounded by `h_max`. Prefer
+    /// [`RosenbrockTableau::rodas4`] for such systems.
+    pub fn ros3p() -> Self {
+        RosenbrockTableau::new(
+            0.788_675_134_594_812_9,
+            &[&[], &[1.267_949_192_431_122_7], &[1.267_949_192_431_122_7, 0.0]],
+            &[&[], &[-1.607_695_154_586_736_2], &[-3.464_101_615_137_754_6, -1.732_050_807_568_877_3]],
+            &[2.0, 0.577_350_269_189_625_8, 0.422_649_730_810_374_2],
+            &[2.113_248_654_051_871_2, 1.0, 0.422_649_730_810_374_2],
+            3,
+            2,
+        )
+    }
+
+    /// Rodas4 of Hairer and Wanner, a 6-stage stiffly accurate and L-stable method of order 4 with an embedded solution
+    /// of order 3.
+    pub fn rodas4() -> Self {
+        const A5: [f64; 4] = [1.221_224_509_226_641, 6.019_134_481_288_629, 12.537_083_329_320_87, -0.687_886_036_105_895];
+        RosenbrockTableau::new(
+            0.25,
+            &[
+                &[],
+                &[1.544],
+                &[0.946_678_528_081_582_6, 0.255_701_169_898_328_4],
+                &[3.314_825_187_068_521, 2.896_124_015_972_201, 0.998_641_913_997_781_7],
+                &A5,
+                &[A5[0], A5[1], A5[2], A5[3], 1.0],
+            ],
+            &[
+                &[],
+                &[-5.668_8],
+                &[-2.430_093_356_833_875, -0.206_359_915_709_191_5],
+                &[-0.107_352_905_815_137_5, -9.594_562_251_023_355, -20.470_286_148_096_16],
+                &[7.496_443_313_967_647, -10.246_804_314_643_52, -33.999_903_528_199_05, 11.708_908_932_061_6],
+                &[8.083_246_795_921_522, -7.981_132_988_064_893, -31.521_594_328_743_71, 16.319_305_431_231_36, -6.058_818_238_834_054],
+            ],
+            &[A5[0], A5[1], A5[2], A5[3], 1.0, 1.0],
+            &[A5[0], A5[1], A5[2], A5[3], 1.0, 0.0],
+            4,
+            3,
+        )
+    }
+
+    /// Rodas5 of Di Marzo, an 8-stage stiffly accurate and L-stable method of order 5 with an embedded solution of
+    /// order 4.
+    pub fn rodas5() -> Self {
+        const A6: [f64; 5] = [
+            -14.096_407_730_512_59,
+            6.925_207_756_232_704,
+            -41.475_108_932_107_28,
+            2.343_771_018_586_405,
+            24.132_152_291_960_62,
+        ];
+        RosenbrockTableau::new(
+            0.19,
+            &[
+                &[],
+                &[2.0],
+                &[3.040_894_194_418_781, 1.041_747_909_077_569],
+                &[2.576_417_536_461_461, 1.622_083_060_776_64, -0.908_966_856_026_453_2],
+                &[2.760_842_080_225_597, 1.446_624_659_844_071, -0.303_698_008_455_373_8, 0.287_749_860_032_544_3],
+                &A6,
+                &[A6[0], A6[1], A6[2], A6[3], A6[4], 1.0],
+                &[A6[0], A6[1], A6[2], A6[3], A6[4], 1.0, 1.0],
+            ],
+            &[
+                &[],
+                &[-10.313_238_851_339_93],
+                &[-21.048_231_176_500_03, -7.234_992_135_176_716],
+                &[32.227_515_418_533_23, -4.943_732_386_540_191, 19.449_220_310_418_79],
+                &[-20.698_655_795_900_63, -8.816_374_604_402_768, 1.260_436_877_740_897, -0.749_564_761_378_714_6],
+                &[-46.220_043_527_112_57, -17.495_348_628_574_72, -289.638_958_289_205_7, 93.608_554_004_009_06, 318.382_253_421_214_7],
+                &[34.200_137_334_729_35, -14.155_354_027_176_9, 57.823_356_409_884, 25.833_629_854_123_65, 1.408_950_972_071_624, -6.551_835_421_242_162],
+                &[
+                    42.570_767_422_911_01,
+                    -13.807_706_720_179_97,
+                    93.989_384_324_271_24,
+                    18.779_196_337_145_03,
+                    -31.583_591_872_233_7,
+                    -6.685_968_952_921_985,
+                    -5.810_979_938_412_932,
+                ],
+            ],
+            &[A6[0], A6[1], A6[2], A6[3], A6[4], 1.0, 1.0, 1.0],
+            &[A6[0], A6[1], A6[2], A6[3], A6[4], 1.0, 1.0, 0.0],
+            5,
+            4,
+        )
+    }
+}
+
+/// State of a Rosenbrock method given by its [`RosenbrockTableau`] for a system of ODEs, parametrizing the
+/// [`AdaptiveSysStepper`] of the method.
+///
+/// It keeps the Jacobian and the factorized matrix of the current point, which are reused when a step is rejected, and
+/// the derivatives at the start of the last accepted step for the continuous extension.
+#[derive(Debug, Clone)]
+pub struct Rosenbrock<F> {
+    tableau: RosenbrockTableau,
+    dim: usize,
+    /// Point of the Jacobian, the derivatives there and with respect to x, and whether they are up to date.
+    x: F,
+    f: Vec<F>,
+    f_x: Vec<F>,
+    jacobian: Vec<F>,
+    jacobian_current: bool,
+    /// Matrix `I / (gamma h) - J` with its factorization for the step size `lu_h`.
+    matrix: Vec<F>,
+    lu: Lu<F>,
+    lu_h: Option<F>,
+    /// Stage increments of the last attempted step.
+    u: Vec<Vec<F>>,
+    stage_y: Vec<F>,
+    work: Vec<F>,
+    /// Derivatives at both ends of the last accepted step, the one at the end once prepared for the interpolation.
+    f_prev: Vec<F>,
+    f_end: Vec<F>,
+    /// Whether the last attempted step failed, because of a singular matrix or of an overflow.
+    failed: bool,
+    jacobian_evals: usize,
+    linear_solves: usize,
+}
+
+impl<F: Real> Rosenbrock<F> {
+    /// Creates the method of `tableau`, with the buffers needed to integrate a system of dimension `dim`.
+    pub fn new(tableau: RosenbrockTableau, dim: usize) -> Self {
+        let buffer = || vec![F::zero(); dim];
+        Rosenbrock {
+            u: vec![buffer(); tableau.stages()],
+            tableau,
+            dim,
+            x: F::zero(),
+            f: buffer(),
+            f_x: buffer(),
+            jacobian: vec![F::zero(); dim * dim],
+            jacobian_current: false,
+            matrix: vec![F::zero(); dim * dim],
+            lu: Lu::new(dim),
+            lu_h: None,
+            stage_y: buffer(),
+            work: buffer(),
+            f_prev: buffer(),
+            f_end: buffer(),
+            failed: false,
+            jacobian_evals: 0,
+            linear_solves: 0,
+        }
+    }
+
+    /// Returns the coefficients of the method.
+    pub fn tableau(&self) -> &RosenbrockTableau {
+        &self.tableau
+    }
+
+    /// Evaluates the derivatives, the Jacobian and the derivatives with respect to x at `(x, y)`, for a step of size `h`.
+    ///
+    /// Unless the system provides them, the derivatives with respect to x are approximated by a central difference
+    /// whose increment is proportional to the step size, the scale on which the method resolves the variations in x,
+    /// rather than to `x`, whose magnitude says nothing about them.
+    fn linearize<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F], h: F) {
+        ode.eval_into(x, y, &mut self.f);
+        self.stage_y.copy_from_slice(y);
+        jacobian(ode, x, &mut self.stage_y, &self.f, &mut self.jacobian, &mut self.work);
+
+        if !ode.dfdx_into(x, y, &mut self.f_x) {
+            // At least the spacing of the floating-point numbers around x, so that both points differ from x
+            let delta = (F::epsilon().cbrt() * h.abs()).max(F::epsilon() * x.abs());
+            let (x_minus, x_plus) = (x - delta, x + delta);
+            ode.eval_into(x_minus, y, &mut self.work);
+            ode.eval_into(x_plus, y, &mut self.f_x);
+            for (f_x, f_minus) in self.f_x.iter_mut().zip(&self.work) {
+                *f_x = (*f_x - *f_minus) / (x_plus - x_minus);
+            }
+        }
+
+        self.x = x;
+        self.jacobian_current = true;
+        self.jacobian_evals += 1;
+        self.lu_h = None;
+    }
+
+    /// Factorizes `I / (gamma h) - J` for the step size `h`.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the matrix is singular.
+    fn factor(&mut self, h: F) -> bool {
+        let diagonal = (h * lit(self.tableau.gamma)).recip();
+        for (i, (row, jacobian)) in self.matrix.chunks_mut(self.dim).zip(self.jacobian.chunks(self.dim)).enumerate() {
+            for (entry, jacobian) in row.iter_mut().zip(jacobian) {
+                *entry = -*jacobian;
+            }
+            row[i] += diagonal;
+        }
+
+        let factored = self.lu.factor(&self.matrix);
+        self.lu_h = if factored { Some(h) } else { None };
+        factored
+    }
+
+    /// Computes the stage increments of a step of size `h` from `(x, y)`.
+    fn stages<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F], h: F) {
+        let tableau = &self.tableau;
+        for i in 0..tableau.stages() {
+            let (previous, current) = self.u.split_at_mut(i);
+            let u = &mut current[0];
+            if i == 0 {
+                u.copy_from_slice(&self.f);
+            } else {
+                self.stage_y.copy_from_slice(y);
+                accumulate(&mut self.stage_y, F::one(), &tableau.a[i], previous);
+                ode.eval_into(x + h * lit(tableau.alpha[i]), &self.stage_y, u);
+                accumulate(u, h.recip(), &tableau.c[i], previous);
+            }
+
+            let scale = h * lit(tableau.gamma_x[i]);
+            for (u, f_x) in u.iter_mut().zip(&self.f_x) {
+                *u += scale * *f_x;
+            }
+            self.lu.solve(u);
+            self.linear_solves += 1;
+        }
+    }
+}
+
+impl<F: Real> EmbeddedSysMethod<F> for Rosenbrock<F> {
+    fn error_order(&self) -> usize {
+        self.tableau.error_order()
+    }
+
+    fn step<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F], h: F, y_next: &mut [F], error: &mut [F]) {
+        if !self.jacobian_current || x != self.x {
+            self.linearize(ode, x, y, h);
+        }
+
+        self.failed = !(self.lu_h == Some(h) || self.factor(h));
+        if !self.failed {
+            self.stages(ode, x, y, h);
+            y_next.copy_from_slice(y);
+            accumulate(y_next, F::one(), &self.tableau.m, &self.u);
+            error.fill(F::zero());
+            accumulate(error, F::one(), &self.tableau.e, &self.u);
+            self.failed = !is_finite(y_next) || !is_finite(error);
+        }
+
+        // Retry with a smaller step
+        if self.failed {
+            y_next.copy_from_slice(y);
+            error.fill(F::zero());
+        }
+    }
+
+    fn accept(&mut self) {
+        std::mem::swap(&mut self.f, &mut self.f_prev);
+        self.jacobian_current = false;
+    }
+
+    fn error_norm(&self, error: &[F], scale: &[F]) -> F {
+        if self.failed {
+            return F::infinity();
+        }
+
+        rms_norm(error, scale)
+    }
+
+    fn record_work(&mut self, stats: &mut Stats<F>) {
+        stats.jacobian_evals += self.jacobian_evals;
+        stats.linear_solves += self.linear_solves;
+        self.jacobian_evals = 0;
+        self.linear_solves = 0;
+    }
+}
+
+impl<F: Real> DenseSysMethod<F> for Rosenbrock<F> {
+    fn prepare<T: ODESYS<F>>(&mut self, ode: &T, x: F, h: F, _y_prev: &[F], y: &[F]) {
+        ode.eval_into(x + h, y, &mut self.f_end);
+    }
+
+    fn interpolate(&self, theta: F, h: F, y_prev: &[F], y: &[F], out: &mut [F]) {
+        let (h00, h10, h01, h11) = hermite(theta);
+        for (i, out) in out.iter_mut().enumerate() {
+            *out = h00 * y_prev[i] + h10 * h * self.f_prev[i] + h01 * y[i] + h11 * h * self.f_end[i];
+        }
+    }
+}
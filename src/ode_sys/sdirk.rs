@@ -0,0 +1,871 @@
+//! Singly diagonally implicit Runge-Kutta (SDIRK and ESDIRK) methods for solving stiff systems of ordinary differential equations (ODEs).
+use super::{ODESYS, ODESysSolver, jacobian, solve_with};
+use super::adaptive::{AdaptiveSysStepper, DenseSysMethod, EmbeddedSysMethod};
+use super::explicit_rk_sys::accumulate;
+use crate::{Output, Real, Solution, SolverError, SolverOptions, Stats};
+use crate::controller::rms_norm;
+use crate::error::expect_solved;
+use crate::linalg::Lu;
+use crate::scalar::lit;
+use crate::ode::explicit_rk::hermite;
+
+/// SDIRK methods for solving systems of Ordinary Differential Equations (ODEs).
+///
+/// This trait integrates stiff systems of ordinary differential equations with the
+/// [singly diagonally implicit Runge-Kutta methods](https://en.wikipedia.org/wiki/Runge%E2%80%93Kutta_methods#Diagonally_Implicit_Runge%E2%80%93Kutta_methods)
+/// described by an [`SdirkTableau`], such as TR-BDF2, the methods of Kværnø and the ESDIRK methods of Kennedy and
+/// Carpenter, or a user-defined one. The stages are solved one after the other by Newton iterations sharing the same
+/// matrix, since all of them have the same diagonal coefficient. The step size `h` given to the methods is the initial
+/// one, which is then adapted so that the weighted root mean square of the embedded error estimates stays within the
+/// tolerances, see [`AdaptiveSysStepper`]. Without a [`SolverOptions`], the tolerances are the defaults of
+/// [`SolverOptions::new`].
+///
+/// The Jacobian is the one of [`ODESYS::jacobian_into`] when the system provides it, and a finite-difference
+/// approximation otherwise.
+pub trait SdirkODESysSolver<T: ODESYS<F>, F: Real = f64> {
+    /// Solve the system of ODEs using the SDIRK method of `tableau`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The coefficients of the SDIRK method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`SdirkODESysSolver::try_sdirk_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESYS, ODESysSolver, sdirk::{SdirkODESysSolver, SdirkTableau}};
+    /// struct MyODESystem;
+    /// impl ODESYS for MyODESystem {
+    ///     fn eval(&self, x: &f64, y: &Vec<f64>) -> Vec<f64> {
+    ///         // Define the system of ODEs
+    ///         // Example: dy/dx = x * y, dz/dx = x + y
+    ///         vec![x * y[0], x + y[1]]
+    ///     }
+    /// }
+    ///
+    /// let solver = ODESysSolver;
+    /// let my_ode_system = MyODESystem;
+    /// let x0 = 0.0;
+    /// let y0 = vec![1.0, 2.0];
+    /// let h = 0.1;
+    /// let x_target = 1.0;
+    ///
+    /// let result = solver.sdirk_solve(&my_ode_system, &SdirkTableau::kvaerno5(), x0, y0, x_target, h);
+    /// assert!((result[0] - 0.5f64.exp()).abs() < 1e-4);
+    /// println!("Solution at x = {}: {:?}", x_target, result);
+    /// ```
+    fn sdirk_solve(&self, ode: &T, tableau: &SdirkTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F>;
+
+    /// Solve the system of ODEs using the SDIRK method of `tableau`, keeping the whole trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The coefficients of the SDIRK method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding every accepted `(x, y)` pair, from the initial point up to `x_target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`SdirkODESysSolver::try_sdirk_solve`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, sdirk::{SdirkODESysSolver, SdirkTableau}};
+    /// let solver = ODESysSolver;
+    /// // Fast relaxation towards a slowly moving equilibrium
+    /// let relaxation = |x: &f64, y: &Vec<f64>| vec![-1e4 * (y[0] - x.cos()), y[0] - y[1]];
+    ///
+    /// let solution = solver.sdirk_solve_traj(&relaxation, &SdirkTableau::tr_bdf2(), 0.0, vec![1.0, 0.0], 5.0, 1e-3);
+    /// println!("{} steps", solution.stats.accepted_steps);
+    /// for (x, y) in solution.iter() {
+    ///     println!("Solution at x = {}: {:?}", x, y);
+    /// }
+    /// ```
+    fn sdirk_solve_traj(&self, ode: &T, tableau: &SdirkTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the SDIRK method of `tableau`, reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The coefficients of the SDIRK method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the solution is desired.
+    /// * `h` - The initial step size.
+    ///
+    /// # Returns
+    ///
+    /// The vector of estimated y values at `x_target`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, sdirk::{SdirkODESysSolver, SdirkTableau}};
+    /// let solver = ODESysSolver;
+    /// // The first component blows up at x = 1
+    /// let blow_up = |_x: &f64, y: &Vec<f64>| vec![y[0] * y[0], -y[1]];
+    ///
+    /// match solver.try_sdirk_solve(&blow_up, &SdirkTableau::kencarp4(), 0.0, vec![1.0, 1.0], 2.0, 0.1) {
+    ///     Ok(result) => println!("Solution at x = 2: {:?}", result),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_sdirk_solve(&self, ode: &T, tableau: &SdirkTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError>;
+
+    /// Create a stepper integrating the system of ODEs with the SDIRK method of `tableau`, one adaptive step at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The coefficients of the SDIRK method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `h` - The initial step size, negative to integrate towards decreasing x.
+    ///
+    /// # Returns
+    ///
+    /// An [`AdaptiveSysStepper`] holding the current state, starting from the initial `(x, y)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, sdirk::{SdirkODESysSolver, SdirkTableau}};
+    /// let solver = ODESysSolver;
+    /// let relaxation = |x: &f64, y: &Vec<f64>| vec![-1e4 * (y[0] - x.cos())];
+    /// let mut stepper = solver.sdirk_stepper(&relaxation, &SdirkTableau::tr_bdf2(), 0.0, vec![1.0], 0.01);
+    ///
+    /// stepper.advance_to(2.0).unwrap();
+    /// let (x_prev, x) = stepper.last_step();
+    /// let middle = stepper.interpolate((x_prev + x) / 2.0).unwrap();
+    /// assert!((middle[0] - ((x_prev + x) / 2.0).cos()).abs() < 1e-3);
+    /// ```
+    fn sdirk_stepper<'a>(&self, ode: &'a T, tableau: &SdirkTableau, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, Sdirk<F>, T, F>;
+
+    /// Solve the system of ODEs using the SDIRK method of `tableau`, with the settings of a [`SolverOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The coefficients of the SDIRK method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integration fails, see [`SdirkODESysSolver::try_sdirk_solve_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, sdirk::{SdirkODESysSolver, SdirkTableau}};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    /// let options = SolverOptions::new().rtol(1e-8).atol(1e-10).output(Output::Points(vec![1.0, 2.0, 3.0]));
+    ///
+    /// let solution = solver.sdirk_solve_with(&oscillator, &SdirkTableau::kvaerno4(), 0.0, vec![1.0, 0.0], 3.0, &options);
+    /// for (x, y) in solution.iter() {
+    ///     assert!((y[0] - x.cos()).abs() < 1e-6);
+    /// }
+    /// ```
+    fn sdirk_solve_with(&self, ode: &T, tableau: &SdirkTableau, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F>;
+
+    /// Solve the system of ODEs using the SDIRK method of `tableau`, with the settings of a [`SolverOptions`], reporting failures instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `ode` - The ODE object implementing the `ODESYS` trait.
+    /// * `tableau` - The coefficients of the SDIRK method.
+    /// * `x` - The initial x value.
+    /// * `y` - The initial vector of y values (corresponding to the initial x).
+    /// * `x_target` - The x value where the integration ends.
+    /// * `options` - The settings of the integration.
+    ///
+    /// # Returns
+    ///
+    /// A [`Solution`] holding the points selected by the output of `options`, or the [`SolverError`] that stopped the integration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, sdirk::{SdirkODESysSolver, SdirkTableau}};
+    /// let solver = ODESysSolver;
+    /// let van_der_pol = |_x: &f64, y: &Vec<f64>| vec![y[1], 1e3 * (1.0 - y[0] * y[0]) * y[1] - y[0]];
+    /// let options = SolverOptions::new().max_steps(10).output(Output::Final);
+    ///
+    /// match solver.try_sdirk_solve_with(&van_der_pol, &SdirkTableau::kencarp4(), 0.0, vec![2.0, 0.0], 3000.0, &options) {
+    ///     Ok(solution) => println!("Solution at x = 3000: {:?}", solution.last()),
+    ///     Err(error) => println!("Integration failed: {}", error),
+    /// }
+    /// ```
+    fn try_sdirk_solve_with(&self, ode: &T, tableau: &SdirkTableau, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError>;
+}
+
+// Implementing the SDIRK methods for the system of ODEs Solver
+impl<F: Real, T: ODESYS<F>> SdirkODESysSolver<T, F> for ODESysSolver {
+    /// Implementation of the SDIRK method of a tableau to solve a system of ODEs.
+    ///
+    /// Each implicit stage `Y[i] = y + h sum(a[i][j] f(x + c[j] h, Y[j]))` is solved by a simplified Newton iteration
+    /// with the matrix `I - gamma h J`, `gamma` being the diagonal coefficient shared by all the stages, starting from
+    /// an extrapolation of the previous stages. The matrix is factorized once per step size and reused by all the
+    /// stages, and the Jacobian `J` is kept from one step to the next: it is only recomputed when an iteration diverges
+    /// or converges too slowly, before halving the step size. The first stage of the ESDIRK methods is explicit.
+    ///
+    /// The local error is estimated by the difference with the embedded solution of the tableau, filtered by the
+    /// factorized matrix so that the stiff components do not cut the step size down. The solution between two accepted
+    /// points is interpolated with a cubic Hermite polynomial, see [`AdaptiveSysStepper::interpolate`].
+    ///
+    /// # When to Use:
+    ///
+    /// Stiff systems solved to low or moderate tolerances, especially when the Jacobian is expensive, since it is reused
+    /// over many steps. TR-BDF2 is the cheapest at loose tolerances, the methods of Kennedy and Carpenter and Kværnø's
+    /// method of order 5 pay off at tighter ones.
+    ///
+    /// # Pros and Cons:
+    /// - Pros: One factorization of the dimension of the system per step size, shared by all the stages, instead of the
+    ///   real and complex ones of the fully implicit methods, and stiffly accurate L-stable methods of orders 2 to 5.
+    /// - Cons: Several sequential Newton iterations per step, and a stage order of at most 2, which reduces the
+    ///   accuracy of the methods of higher order on very stiff problems.
+    ///
+    /// # Stability Analysis:
+    ///
+    /// The built-in methods are L-stable: the very stiff components of the solution are damped in a single step,
+    /// whatever the step size.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::ode_sys::{ODESysSolver, sdirk::{SdirkODESysSolver, SdirkTableau}};
+    /// let solver = ODESysSolver;
+    /// // Robertson's chemical kinetics, with rate constants spanning nine orders of magnitude
+    /// let robertson = |_x: &f64, y: &Vec<f64>| vec![
+    ///     -0.04 * y[0] + 1e4 * y[1] * y[2],
+    ///     0.04 * y[0] - 1e4 * y[1] * y[2] - 3e7 * y[1] * y[1],
+    ///     3e7 * y[1] * y[1],
+    /// ];
+    ///
+    /// let solution = solver.sdirk_solve_traj(&robertson, &SdirkTableau::kencarp4(), 0.0, vec![1.0, 0.0, 0.0], 40.0, 1e-6);
+    /// let y = solution.last().unwrap().1;
+    /// assert!((y[0] - 0.7158).abs() < 1e-3);
+    /// // The Jacobian is reused over many steps
+    /// assert!(solution.stats.jacobian_evals < solution.stats.accepted_steps);
+    /// ```
+    fn sdirk_solve(&self, ode: &T, tableau: &SdirkTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Vec<F> {
+        expect_solved(self.try_sdirk_solve(ode, tableau, x, y, x_target, h))
+    }
+
+    /// Implementation of the SDIRK method of a tableau returning the whole trajectory of the system of ODEs.
+    ///
+    /// See [`SdirkODESysSolver::sdirk_solve`] for the details of the method.
+    fn sdirk_solve_traj(&self, ode: &T, tableau: &SdirkTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Solution<Vec<F>, F> {
+        expect_solved(solve_with(self.sdirk_stepper(ode, tableau, x, y, h), x_target, &Output::Steps))
+    }
+
+    /// Implementation of the SDIRK method of a tableau reporting failures as a [`SolverError`].
+    ///
+    /// See [`SdirkODESysSolver::sdirk_solve`] for the details of the method.
+    fn try_sdirk_solve(&self, ode: &T, tableau: &SdirkTableau, x: F, y: Vec<F>, x_target: F, h: F) -> Result<Vec<F>, SolverError> {
+        let mut stepper = self.sdirk_stepper(ode, tableau, x, y, h);
+        stepper.advance_to(x_target)?;
+        Ok(stepper.into_y())
+    }
+
+    /// Implementation of the SDIRK method of a tableau as an [`AdaptiveSysStepper`].
+    ///
+    /// See [`SdirkODESysSolver::sdirk_solve`] for the details of the method.
+    fn sdirk_stepper<'a>(&self, ode: &'a T, tableau: &SdirkTableau, x: F, y: Vec<F>, h: F) -> AdaptiveSysStepper<'a, Sdirk<F>, T, F> {
+        let method = Sdirk::new(tableau.clone(), y.len());
+        AdaptiveSysStepper::new(ode, method, x, y, h)
+    }
+
+    /// Implementation of the SDIRK method of a tableau with the settings of a [`SolverOptions`].
+    ///
+    /// See [`SdirkODESysSolver::sdirk_solve`] for the details of the method.
+    fn sdirk_solve_with(&self, ode: &T, tableau: &SdirkTableau, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Solution<Vec<F>, F> {
+        expect_solved(self.try_sdirk_solve_with(ode, tableau, x, y, x_target, options))
+    }
+
+    /// Implementation of the SDIRK method of a tableau with the settings of a [`SolverOptions`], reporting failures as a [`SolverError`].
+    ///
+    /// See [`SdirkODESysSolver::sdirk_solve`] for the details of the method.
+    fn try_sdirk_solve_with(&self, ode: &T, tableau: &SdirkTableau, x: F, y: Vec<F>, x_target: F, options: &SolverOptions<F>) -> Result<Solution<Vec<F>, F>, SolverError> {
+        options.check(x, x_target, y.len())?;
+        let stepper = self.sdirk_stepper(ode, tableau, x, y, options.initial_step(x, x_target));
+        solve_with(stepper.with_options(options), x_target, &options.output)
+    }
+}
+
+/// Coefficients of a singly diagonally implicit Runge-Kutta method with an embedded solution.
+///
+/// All the stages share the same diagonal coefficient `gamma`, except the first one of the ESDIRK methods, which is
+/// explicit. The nodes of the stages are the sums of the rows of the Runge-Kutta matrix.
+///
+/// # Example
+///
+/// ```
+/// # use damndiff::ode_sys::{ODESysSolver, sdirk::{SdirkODESysSolver, SdirkTableau}};
+/// // Implicit trapezoidal rule, with the implicit Euler method as embedded solution
+/// let trapezoidal = SdirkTableau::new(&[&[0.0], &[0.5, 0.5]], &[0.5, 0.5], &[0.0, 1.0], 2, 1);
+/// assert!(trapezoidal.is_stiffly_accurate());
+///
+/// let result = ODESysSolver.sdirk_solve(&|x: &f64, y: &Vec<f64>| vec![x - y[0]], &trapezoidal, 0.0, vec![1.0], 1.0, 0.01);
+/// assert!((result[0] - 2.0 * (-1.0f64).exp()).abs() < 1e-3);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SdirkTableau {
+    /// Coefficients of the stages, row `i` holding the `i + 1` weights of the previous stages and of itself.
+    pub(crate) a: Vec<Vec<f64>>,
+    /// Nodes of the stages.
+    pub(crate) c: Vec<f64>,
+    /// Weights of the propagated solution.
+    pub(crate) b: Vec<f64>,
+    /// Differences `b - b_hat` between the weights of the propagated and of the embedded solutions.
+    pub(crate) e: Vec<f64>,
+    gamma: f64,
+    order: usize,
+    embedded_order: usize,
+}
+
+impl SdirkTableau {
+    /// Creates the tableau of a method of order `order`, with stage coefficients `a`, weights `b` and the weights
+    /// `b_hat` of an embedded solution of order `embedded_order`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method has no stage, if the weights do not have one entry per stage, if the row `i` of `a` does not
+    /// hold exactly `i + 1` coefficients, or if the diagonal coefficients are not all the same positive value, the
+    /// first one excepted, which can be zero.
+    pub fn new(a: &[&[f64]], b: &[f64], b_hat: &[f64], order: usize, embedded_order: usize) -> Self {
+        let stages = b.len();
+        assert!(stages > 0, "an SDIRK method needs at least one stage");
+        assert_eq!(b_hat.len(), stages, "{} embedded weights given for {} stages", b_hat.len(), stages);
+        assert_eq!(a.len(), stages, "{} rows of coefficients given for {} stages", a.len(), stages);
+        for (i, row) in a.iter().enumerate() {
+            assert_eq!(row.len(), i + 1, "row {} of an SDIRK method needs {} coefficients, {} given", i, i + 1, row.len());
+        }
+
+        let gamma = a[stages - 1][stages - 1];
+        assert!(gamma > 0.0, "the diagonal coefficient of an SDIRK method must be positive, {} given", gamma);
+        for (i, row) in a.iter().enumerate() {
+            assert!(
+                row[i] == gamma || (i == 0 && row[i] == 0.0),
+                "the diagonal coefficient of stage {} is {}, instead of {}",
+                i,
+                row[i],
+                gamma
+            );
+        }
+
+        SdirkTableau {
+            a: a.iter().map(|row| row.to_vec()).collect(),
+            c: a.iter().map(|row| row.iter().sum()).collect(),
+            b: b.to_vec(),
+            e: b.iter().zip(b_hat).map(|(b, b_hat)| b - b_hat).collect(),
+            gamma,
+            order,
+            embedded_order,
+        }
+    }
+
+    /// Returns the number of stages.
+    pub fn stages(&self) -> usize {
+        self.b.len()
+    }
+
+    /// Returns the diagonal coefficient `gamma` shared by the implicit stages.
+    pub fn gamma(&self) -> f64 {
+        self.gamma
+    }
+
+    /// Returns the order of the propagated solution.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Returns the order of the embedded solution.
+    pub fn embedded_order(&self) -> usize {
+        self.embedded_order
+    }
+
+    /// Returns `true` if the first stage is explicit (ESDIRK method).
+    pub fn has_explicit_first_stage(&self) -> bool {
+        self.a[0][0] == 0.0
+    }
+
+    /// Returns `true` if the propagated solution is the last stage, so that the solution of the stiff components is
+    /// as accurate as the stages.
+    pub fn is_stiffly_accurate(&self) -> bool {
+        self.a[self.stages() - 1] == self.b
+    }
+
+    /// Returns the order of the error estimate, that is the lower order of the pair.
+    pub(crate) fn error_order(&self) -> usize {
+        self.order.min(self.embedded_order)
+    }
+
+    /// TR-BDF2 of Bank et al., a trapezoidal stage followed by a BDF2 one, of order 2 with an embedded solution of
+    /// order 3, as analysed by Hosea and Shampine.
+    pub fn tr_bdf2() -> Self {
+        let d = 1.0 - 0.5f64.sqrt();
+        let w = 0.5f64.sqrt() / 2.0;
+        SdirkTableau::new(
+            &[&[0.0], &[d, d], &[w, w, d]],
+            &[w, w, d],
+            &[(1.0 - w) / 3.0, (3.0 * w + 1.0) / 3.0, d / 3.0],
+            2,
+            3,
+        )
+    }
+
+    /// Kværnø's ESDIRK method of order 3 with 4 stages, with an embedded solution of order 2, both stiffly accurate.
+    pub fn kvaerno3() -> Self {
+        const GAMMA: f64 = 0.435_866_521_508_459;
+        const B: [f64; 4] = [0.308_809_969_976_746_53, 1.490_563_388_421_780_6, -1.235_239_879_906_986_1, GAMMA];
+        SdirkTableau::new(
+            &[&[0.0], &[GAMMA, GAMMA], &[0.490_563_388_421_780_54, 0.073_570_090_069_760_42, GAMMA], &B],
+            &B,
+            &[0.490_563_388_421_780_54, 0.073_570_090_069_760_42, GAMMA, 0.0],
+            3,
+            2,
+        )
+    }
+
+    /// Kværnø's ESDIRK method of order 4 with 5 stages, with an embedded solution of order 3, both stiffly accurate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use damndiff::{Output, SolverOptions};
+    /// # use damndiff::ode_sys::{ODESysSolver, sdirk::{SdirkODESysSolver, SdirkTableau}};
+    /// let solver = ODESysSolver;
+    /// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+    ///
+    /// // With loose tolerances and a step size pinned by its bounds, every step is accepted
+    /// let error = |h: f64| {
+    ///     let options = SolverOptions::new().rtol(1e6).atol(1e6).h0(h).h_min(h).h_max(h).output(Output::Final);
+    ///     let solution = solver.sdirk_solve_with(&oscillator, &SdirkTableau::kvaerno4(), 0.0, vec![1.0, 0.0], 1.0, &options);
+    ///     (solution.last().unwrap().1[0] - 1.0f64.cos()).abs()
+    /// };
+    ///
+    /// // Halving the step size divides the error by 2^4
+    /// let ratio = error(0.1) / error(0.05);
+    /// assert!(ratio > 14.0 && ratio < 18.0);
+    /// ```
+    pub fn kvaerno4() -> Self {
+        const GAMMA: f64 = 0.572_816_062_5;
+        const B_HAT: [f64; 4] = [0.262_603_290_273_977_55, -0.311_904_327_414_785_37, 0.476_484_974_640_807_8, GAMMA];
+        const B: [f64; 5] = [
+            0.197_216_548_321_028_48,
+            0.176_843_783_906_613_4,
+            0.815_442_181_403_551_5,
+            -0.762_318_576_131_193_4,
+            GAMMA,
+        ];
+        SdirkTableau::new(
+            &[&[0.0], &[GAMMA, GAMMA], &[0.167_235_462_041_899_27, -0.142_946_536_861_287_2, GAMMA], &B_HAT, &B],
+            &B,
+            &[B_HAT[0], B_HAT[1], B_HAT[2], B_HAT[3], 0.0],
+            4,
+            3,
+        )
+    }
+
+    /// Kværnø's ESDIRK method of order 5 with 7 stages, with an embedded solution of order 4, both stiffly accurate.
+    pub fn kvaerno5() -> Self {
+        const GAMMA: f64 = 0.26;
+        const B_HAT: [f64; 6] = [
+            0.138_556_402_312_682_24,
+            0.0,
+            -0.042_453_372_017_520_43,
+            0.024_466_578_980_031_41,
+            0.619_430_390_724_806_8,
+            GAMMA,
+        ];
+        const B: [f64; 7] = [
+            0.136_597_511_776_402_92,
+            0.0,
+            -0.054_969_087_965_383_76,
+            -0.041_186_267_283_210_46,
+            0.629_933_048_990_164,
+            0.069_624_794_482_027_28,
+            GAMMA,
+        ];
+        SdirkTableau::new(
+            &[
+                &[0.0],
+                &[GAMMA, GAMMA],
+                &[0.13, 0.840_333_209_967_908_1, GAMMA],
+                &[0.223_719_614_783_205_04, 0.476_755_323_197_997, -0.064_708_953_631_126_15, GAMMA],
+                &[0.166_485_643_232_483_22, 0.104_500_188_415_917_2, 0.036_314_822_720_987_15, -0.130_907_044_510_739_98, GAMMA],
+                &B_HAT,
+                &B,
+            ],
+            &B,
+            &[B_HAT[0], B_HAT[1], B_HAT[2], B_HAT[3], B_HAT[4], B_HAT[5], 0.0],
+            5,
+            4,
+        )
+    }
+
+    /// The ESDIRK method of order 3 with 4 stages of Kennedy and Carpenter, implicit part of their additive method
+    /// ARK3(2)4L\[2\]SA, with an embedded solution of order 2.
+    pub fn kencarp3() -> Self {
+        const GAMMA: f64 = 1_767_732_205_903.0 / 4_055_673_282_236.0;
+        const B: [f64; 4] = [
+            1_471_266_399_579.0 / 7_840_856_788_654.0,
+            -4_482_444_167_858.0 / 7_529_755_066_697.0,
+            11_266_239_266_428.0 / 11_593_286_722_821.0,
+            GAMMA,
+        ];
+        SdirkTableau::new(
+            &[
+                &[0.0],
+                &[GAMMA, GAMMA],
+                &[2_746_238_789_719.0 / 10_658_868_560_708.0, -640_167_445_237.0 / 6_845_629_431_997.0, GAMMA],
+                &B,
+            ],
+            &B,
+            &[
+                2_756_255_671_327.0 / 12_835_298_489_170.0,
+                -10_771_552_573_575.0 / 22_201_958_757_719.0,
+                9_247_589_265_047.0 / 10_645_013_368_117.0,
+                2_193_209_047_091.0 / 5_459_859_503_100.0,
+            ],
+            3,
+            2,
+        )
+    }
+
+    /// The ESDIRK method of order 4 with 6 stages of Kennedy and Carpenter, implicit part of their additive method
+    /// ARK4(3)6L\[2\]SA, with an embedded solution of order 3.
+    pub fn kencarp4() -> Self {
+        const GAMMA: f64 = 0.25;
+        const B: [f64; 6] = [82_889.0 / 524_892.0, 0.0, 15_625.0 / 83_664.0, 69_875.0 / 102_672.0, -2_260.0 / 8_211.0, GAMMA];
+        SdirkTableau::new(
+            &[
+                &[0.0],
+                &[GAMMA, GAMMA],
+                &[8_611.0 / 62_500.0, -1_743.0 / 31_250.0, GAMMA],
+                &[5_012_029.0 / 34_652_500.0, -654_441.0 / 2_922_500.0, 174_375.0 / 388_108.0, GAMMA],
+                &[
+                    15_267_082_809.0 / 155_376_265_600.0,
+                    -71_443_401.0 / 120_774_400.0,
+                    730_878_875.0 / 902_184_768.0,
+                    2_285_395.0 / 8_070_912.0,
+                    GAMMA,
+                ],
+                &B,
+            ],
+            &B,
+            &[
+                4_586_570_599.0 / 29_645_900_160.0,
+                0.0,
+                178_811_875.0 / 945_068_544.0,
+                814_220_225.0 / 1_159_782_912.0,
+                -3_700_637.0 / 11_593_932.0,
+                61_727.0 / 225_920.0,
+            ],
+            4,
+            3,
+        )
+    }
+}
+
+/// Maximum number of Newton iterations for a stage.
+const NEWTON_MAX_ITERATIONS: usize = 10;
+
+/// State of an SDIRK method given by its [`SdirkTableau`] for a system of ODEs, parametrizing the
+/// [`AdaptiveSysStepper`] of the method.
+///
+/// It keeps the Jacobian and the factorized Newton matrix, which are reused from one step to the next, and the
+/// derivatives at both ends of the last accepted step for the continuous extension.
+#[derive(Debug, Clone)]
+pub struct Sdirk<F> {
+    tableau: SdirkTableau,
+    dim: usize,
+    /// Derivatives at the current point and whether they are known.
+    f: Vec<F>,
+    f_known: bool,
+    jacobian: Vec<F>,
+    /// Whether the Jacobian has been evaluated, and whether it was at the current point, in which case recomputing it
+    /// cannot help the iteration.
+    jacobian_known: bool,
+    jacobian_current: bool,
+    /// Matrix `I - gamma h J` with its factorization for the step size `lu_h`.
+    matrix: Vec<F>,
+    lu: Lu<F>,
+    lu_h: Option<F>,
+    rtol: F,
+    atol: Vec<F>,
+    /// Derivatives at the stages of the last attempted step.
+    k: Vec<Vec<F>>,
+    /// Buffers of the Newton iteration: explicit part and value of the current stage, its derivatives and the update.
+    known: Vec<F>,
+    stage_y: Vec<F>,
+    stage_f: Vec<F>,
+    dy: Vec<F>,
+    scale: Vec<F>,
+    /// Whether the iteration of every stage of the last attempted step converged.
+    converged: bool,
+    /// Derivatives at both ends of the last accepted step, the one at the end once prepared for the interpolation.
+    f_prev: Vec<F>,
+    f_end: Vec<F>,
+    jacobian_evals: usize,
+    linear_solves: usize,
+}
+
+impl<F: Real> Sdirk<F> {
+    /// Creates the method of `tableau`, with the buffers needed to integrate a system of dimension `dim`.
+    pub fn new(tableau: SdirkTableau, dim: usize) -> Self {
+        let buffer = || vec![F::zero(); dim];
+        Sdirk {
+            k: vec![buffer(); tableau.stages()],
+            tableau,
+            dim,
+            f: buffer(),
+            f_known: false,
+            jacobian: vec![F::zero(); dim * dim],
+            jacobian_known: false,
+            jacobian_current: false,
+            matrix: vec![F::zero(); dim * dim],
+            lu: Lu::new(dim),
+            lu_h: None,
+            rtol: F::zero(),
+            atol: buffer(),
+            known: buffer(),
+            stage_y: buffer(),
+            stage_f: buffer(),
+            dy: buffer(),
+            scale: buffer(),
+            converged: false,
+            f_prev: buffer(),
+            f_end: buffer(),
+            jacobian_evals: 0,
+            linear_solves: 0,
+        }
+    }
+
+    /// Returns the coefficients of the method.
+    pub fn tableau(&self) -> &SdirkTableau {
+        &self.tableau
+    }
+
+    /// Evaluates the Jacobian at `(x, y)`, `f` holding the derivatives there.
+    fn update_jacobian<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F]) {
+        self.stage_y.copy_from_slice(y);
+        jacobian(ode, x, &mut self.stage_y, &self.f, &mut self.jacobian, &mut self.dy);
+        self.jacobian_evals += 1;
+        self.jacobian_known = true;
+        self.jacobian_current = true;
+        self.lu_h = None;
+    }
+
+    /// Factorizes `I - gamma h J` for the step size `h`.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the matrix is singular.
+    fn factor(&mut self, h: F) -> bool {
+        let c = h * lit(self.tableau.gamma);
+        for (index, m) in self.matrix.iter_mut().enumerate() {
+            let diagonal = if index % (self.dim + 1) == 0 { F::one() } else { F::zero() };
+            *m = diagonal - c * self.jacobian[index];
+        }
+
+        let factored = self.lu.factor(&self.matrix);
+        self.lu_h = if factored { Some(h) } else { None };
+        factored
+    }
+
+    /// Computes the derivatives at the stages of a step of size `h` from `(x, y)`.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the iteration of a stage diverged or converged too slowly.
+    fn stages<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F], h: F) -> bool {
+        let gamma_h = h * lit(self.tableau.gamma);
+        for i in 0..self.tableau.stages() {
+            if self.tableau.a[i][i] == 0.0 {
+                self.k[i].copy_from_slice(&self.f);
+                continue;
+            }
+
+            // Explicit part of the stage, and extrapolation of the last derivative as the initial guess
+            let previous = &self.k[..i];
+            self.known.copy_from_slice(y);
+            accumulate(&mut self.known, h, &self.tableau.a[i][..i], previous);
+            let guess = previous.last().unwrap_or(&self.f);
+            for ((stage_y, known), guess) in self.stage_y.iter_mut().zip(&self.known).zip(guess) {
+                *stage_y = *known + gamma_h * *guess;
+            }
+
+            if !self.newton(ode, x + h * lit(self.tableau.c[i]), gamma_h) {
+                return false;
+            }
+            for ((k, stage_y), known) in self.k[i].iter_mut().zip(&self.stage_y).zip(&self.known) {
+                *k = (*stage_y - *known) / gamma_h;
+            }
+        }
+        true
+    }
+
+    /// Solves `Y = known + gamma_h f(x, Y)` for the stage value `Y` in `stage_y`, by simplified Newton iteration with
+    /// the factorized matrix.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the iteration diverged or would not converge within the allowed number of iterations.
+    fn newton<T: ODESYS<F>>(&mut self, ode: &T, x: F, gamma_h: F) -> bool {
+        let tolerance = (lit::<F>(10.0) * F::epsilon() / self.rtol).max(lit::<F>(0.03).min(self.rtol.sqrt()));
+
+        let mut last_norm: Option<F> = None;
+        for iteration in 0..NEWTON_MAX_ITERATIONS {
+            ode.eval_into(x, &self.stage_y, &mut self.stage_f);
+            if !self.stage_f.iter().all(|f| f.is_finite()) {
+                return false;
+            }
+
+            for (((dy, known), stage_y), f) in self.dy.iter_mut().zip(&self.known).zip(&self.stage_y).zip(&self.stage_f) {
+                *dy = *known + gamma_h * *f - *stage_y;
+            }
+            self.lu.solve(&mut self.dy);
+            self.linear_solves += 1;
+            let norm = rms_norm(&self.dy, &self.scale);
+
+            let rate = last_norm.map(|last| norm / last);
+            if let Some(rate) = rate {
+                let remaining = (NEWTON_MAX_ITERATIONS - iteration) as i32;
+                if rate >= F::one() || rate.powi(remaining) / (F::one() - rate) * norm > tolerance {
+                    return false;
+                }
+            }
+
+            for (stage_y, dy) in self.stage_y.iter_mut().zip(&self.dy) {
+                *stage_y += *dy;
+            }
+            if norm == F::zero() || rate.is_some_and(|rate| rate / (F::one() - rate) * norm < tolerance) {
+                return true;
+            }
+            last_norm = Some(norm);
+        }
+        false
+    }
+}
+
+impl<F: Real> EmbeddedSysMethod<F> for Sdirk<F> {
+    fn error_order(&self) -> usize {
+        self.tableau.error_order()
+    }
+
+    fn step<T: ODESYS<F>>(&mut self, ode: &T, x: F, y: &[F], h: F, y_next: &mut [F], error: &mut [F]) {
+        if !self.f_known {
+            ode.eval_into(x, y, &mut self.f);
+            self.f_known = true;
+        }
+        if !self.jacobian_known {
+            self.update_jacobian(ode, x, y);
+        }
+        for ((scale, atol), y) in self.scale.iter_mut().zip(&self.atol).zip(y) {
+            *scale = *atol + self.rtol * y.abs();
+        }
+
+        loop {
+            self.converged = (self.lu_h == Some(h) || self.factor(h)) && self.stages(ode, x, y, h);
+            if self.converged || self.jacobian_current {
+                break;
+            }
+            // Retry with a Jacobian at the current point
+            self.update_jacobian(ode, x, y);
+        }
+
+        // Retry with a smaller step
+        if !self.converged {
+            y_next.copy_from_slice(y);
+            error.fill(F::zero());
+            return;
+        }
+
+        y_next.copy_from_slice(y);
+        accumulate(y_next, h, &self.tableau.b, &self.k);
+        error.fill(F::zero());
+        accumulate(error, h, &self.tableau.e, &self.k);
+        self.lu.solve(error);
+        self.linear_solves += 1;
+    }
+
+    fn accept(&mut self) {
+        // The derivatives of the last stage are only accurate to the Newton tolerance in the stiff components, which
+        // would spoil a finite-difference Jacobian, so the ones at the new point are evaluated anew
+        std::mem::swap(&mut self.f, &mut self.f_prev);
+        self.f_known = false;
+        self.jacobian_current = false;
+    }
+
+    fn error_norm(&self, error: &[F], scale: &[F]) -> F {
+        if !self.converged {
+            return F::infinity();
+        }
+        rms_norm(error, scale)
+    }
+
+    fn step_factor(&self, _error: F) -> Option<F> {
+        if !self.converged {
+            return Some(lit(0.5));
+        }
+        None
+    }
+
+    fn set_tolerances(&mut self, rtol: F, atol: &[F]) {
+        self.rtol = rtol;
+        self.atol.copy_from_slice(atol);
+    }
+
+    fn record_work(&mut self, stats: &mut Stats<F>) {
+        stats.jacobian_evals += self.jacobian_evals;
+        stats.linear_solves += self.linear_solves;
+        self.jacobian_evals = 0;
+        self.linear_solves = 0;
+    }
+}
+
+impl<F: Real> DenseSysMethod<F> for Sdirk<F> {
+    fn prepare<T: ODESYS<F>>(&mut self, ode: &T, x: F, h: F, _y_prev: &[F], y: &[F]) {
+        ode.eval_into(x + h, y, &mut self.f_end);
+    }
+
+    fn interpolate(&self, theta: F, h: F, y_prev: &[F], y: &[F], out: &mut [F]) {
+        let (h00, h10, h01, h11) = hermite(theta);
+        for (i, out) in out.iter_mut().enumerate() {
+            *out = h00 * y_prev[i] + h10 * h * self.f_prev[i] + h01 * y[i] + h11 * h * self.f_end[i];
+        }
+    }
+}
+
@@ -0,0 +1,230 @@
+//! Stateful steppers for incremental integration of a system of ODEs.
+use super::{ODESYS, check_dimension, check_no_mass_matrix, SysMethod, is_finite};
+use crate::{Real, SolverError, Stats};
+use crate::stats::Counted;
+use crate::scalar::{lit, to_f64};
+use crate::grid::{DEFAULT_MAX_STEPS, check_step_size, grid_point, signed_step, step_count};
+
+/// A stateful integrator advancing a system of ODEs with the fixed-step method `M`, one step or one interval at a time.
+///
+/// It is created by the `*_stepper` methods of the solver traits, for instance
+/// [`RungeKuttaODESysSolver::rk_stepper`](super::rk_sys::RungeKuttaODESysSolver::rk_stepper), and keeps the current
+/// state `(x, y)` and the buffers of the method between calls, so that the integration can be interleaved with other work.
+///
+/// The step size `h` is signed: [`SysStepper::step`] and the [`Iterator`] implementation move towards increasing x when
+/// it is positive and towards decreasing x when it is negative. [`SysStepper::advance_to`] uses its magnitude in the
+/// direction of the requested point, shortening the last step so that it lands exactly on it.
+///
+/// # Example
+///
+/// ```
+/// # use damndiff::ode_sys::{ODESysSolver, rk_sys::RungeKuttaODESysSolver};
+/// let solver = ODESysSolver;
+/// let oscillator = |_x: &f64, y: &Vec<f64>| vec![y[1], -y[0]];
+/// let mut stepper = solver.rk_stepper(&oscillator, 0.0, vec![1.0, 0.0], 0.01);
+///
+/// // Advance one frame of the simulation at a time
+/// for frame in 1..=10 {
+///     let x = frame as f64 * 0.1;
+///     stepper.advance_to(x).unwrap();
+///     assert_eq!(stepper.x(), x);
+///     assert!((stepper.y()[0] - x.cos()).abs() < 1e-9);
+///     assert!((stepper.y()[1] + x.sin()).abs() < 1e-9);
+/// }
+///
+/// // Or take single steps through the iterator
+/// for (i, state) in stepper.by_ref().take(5).enumerate() {
+///     let (x, y) = state.unwrap();
+///     assert!((x - (1.0 + (i + 1) as f64 * 0.01)).abs() < 1e-12);
+///     assert!((y[0] - x.cos()).abs() < 1e-9);
+/// }
+/// ```
+pub struct SysStepper<'a, M, T, F: Real = f64> {
+    ode: &'a T,
+    method: M,
+    x: F,
+    y: Vec<F>,
+    /// Buffer receiving the state at the end of a step, swapped with `y` once the step is accepted.
+    y_next: Vec<F>,
+    h: F,
+    /// Point from which the grid of [`SysStepper::step`] is laid out.
+    x_start: F,
+    /// Number of steps taken from `x_start`.
+    steps: usize,
+    /// Maximum number of steps taken by a call to `advance_to`.
+    max_steps: usize,
+    stats: Stats<F>,
+    /// Whether the dimension of the system has been checked against the one of the state, and its lack of mass matrix.
+    dimension_checked: bool,
+    failed: bool,
+}
+
+impl<'a, M, T, F> SysStepper<'a, M, T, F>
+where
+    M: SysMethod<F>,
+    T: ODESYS<F>,
+    F: Real,
+{
+    /// Creates a stepper at `(x0, y0)` integrating with `method`, whose buffers match the dimension of `y0`.
+    pub(crate) fn new(ode: &'a T, method: M, x0: F, y0: Vec<F>, h: F) -> Self {
+        let dim = y0.len();
+        SysStepper {
+            ode,
+            method,
+            x: x0,
+            y: y0,
+            y_next: vec![F::zero(); dim],
+            h,
+            x_start: x0,
+            steps: 0,
+            max_steps: DEFAULT_MAX_STEPS,
+            stats: Stats::default(),
+            dimension_checked: false,
+            failed: false,
+        }
+    }
+
+    /// Sets the maximum number of steps a call to [`SysStepper::advance_to`] may take, 10 000 000 by default.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Returns the current value of the independent variable.
+    pub fn x(&self) -> F {
+        self.x
+    }
+
+    /// Returns the current values of the dependent variables.
+    pub fn y(&self) -> &[F] {
+        &self.y
+    }
+
+    /// Returns the current state `(x, y)`.
+    pub fn state(&self) -> (F, &[F]) {
+        (self.x, &self.y)
+    }
+
+    /// Returns the signed step size.
+    pub fn h(&self) -> F {
+        self.h
+    }
+
+    /// Returns the statistics of the steps taken so far.
+    pub fn stats(&self) -> Stats<F> {
+        self.stats.clone()
+    }
+
+    /// Consumes the stepper, returning the current values of the dependent variables.
+    pub fn into_y(self) -> Vec<F> {
+        self.y
+    }
+
+    /// Takes a single step of size `h`.
+    ///
+    /// # Returns
+    ///
+    /// The new state `(x, y)`, or the [`SolverError`] that prevented the step, in which case the state is left unchanged.
+    pub fn step(&mut self) -> Result<(F, &[F]), SolverError> {
+        check_step_size(self.x, self.h)?;
+        self.check_state()?;
+
+        let x_next = self.x_start + lit::<F>((self.steps + 1) as f64) * self.h;
+        self.take_step(x_next)?;
+        self.steps += 1;
+        Ok(self.state())
+    }
+
+    /// Integrates up to `x_target`, which can lie on either side of the current x.
+    ///
+    /// # Returns
+    ///
+    /// The y values at `x_target`, or the [`SolverError`] that stopped the integration, in which case the stepper is
+    /// left at the last accepted point.
+    pub fn advance_to(&mut self, x_target: F) -> Result<&[F], SolverError> {
+        self.advance_with(x_target, |_, _| {})?;
+        Ok(&self.y)
+    }
+
+    /// Same as [`SysStepper::advance_to`], passing every accepted point after the current one to `record`.
+    pub(crate) fn advance_with<R: FnMut(F, &[F])>(&mut self, x_target: F, mut record: R) -> Result<(), SolverError> {
+        check_step_size(self.x, self.h)?;
+        self.check_state()?;
+
+        let x0 = self.x;
+        let h = signed_step(x0, self.h, x_target);
+        let n = step_count(x0, h, x_target);
+        if n > self.max_steps {
+            return Err(SolverError::MaxStepsExceeded { x: to_f64(x0), max_steps: self.max_steps });
+        }
+
+        let mut result = Ok(());
+        for i in 0..n {
+            result = self.take_step(grid_point(x0, h, x_target, i + 1, n));
+            if result.is_err() {
+                break;
+            }
+            record(self.x, &self.y);
+        }
+
+        // Later single steps are laid out from the point reached
+        self.x_start = self.x;
+        self.steps = 0;
+        result
+    }
+
+    fn check_state(&mut self) -> Result<(), SolverError> {
+        if !is_finite(&self.y) {
+            return Err(SolverError::NonFiniteState { x: to_f64(self.x) });
+        }
+        if !self.dimension_checked {
+            let ode = Counted::new(self.ode);
+            let result = check_dimension(&ode, self.x, &self.y);
+            self.stats.evals += ode.evals();
+            result?;
+            check_no_mass_matrix(self.ode, self.x, self.y.len())?;
+            self.dimension_checked = true;
+        }
+        Ok(())
+    }
+
+    /// Advances the state from the current x to `x_next`.
+    fn take_step(&mut self, x_next: F) -> Result<(), SolverError> {
+        if x_next == self.x {
+            return Err(SolverError::StepSizeUnderflow { x: to_f64(self.x), h: to_f64(self.h) });
+        }
+        self.y_next.copy_from_slice(&self.y);
+        let h = x_next - self.x;
+        let ode = Counted::new(self.ode);
+        self.method.step(&ode, self.x, &mut self.y_next, h);
+        self.stats.evals += ode.evals();
+        if !is_finite(&self.y_next) {
+            return Err(SolverError::NonFiniteState { x: to_f64(self.x) });
+        }
+        std::mem::swap(&mut self.y, &mut self.y_next);
+        self.x = x_next;
+        self.stats.accepted_steps += 1;
+        self.stats.h_final = h;
+        Ok(())
+    }
+}
+
+impl<M, T, F> Iterator for SysStepper<'_, M, T, F>
+where
+    M: SysMethod<F>,
+    T: ODESYS<F>,
+    F: Real,
+{
+    type Item = Result<(F, Vec<F>), SolverError>;
+
+    /// Takes a single step, yielding a copy of the new state. The iteration ends after the first failed step.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let state = self.step().map(|(x, y)| (x, y.to_vec()));
+        self.failed = state.is_err();
+        Some(state)
+    }
+}
@@ -0,0 +1,197 @@
+//! Settings shared by all the solvers.
+use crate::{Real, SolverError};
+use crate::scalar::{lit, to_f64};
+use crate::grid::DEFAULT_MAX_STEPS;
+
+/// Default relative and absolute tolerance of the adaptive methods.
+const DEFAULT_TOLERANCE: f64 = 1e-6;
+
+/// Number of steps covering the integration interval when no initial step size is given.
+const DEFAULT_STEPS: f64 = 100.0;
+
+/// The points of the integration stored in the [`Solution`](crate::Solution) returned by the `*_with` methods.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Output<F: Real = f64> {
+    /// Every accepted step, starting from the initial condition.
+    Steps,
+    /// Only the final point, at `x_target`.
+    Final,
+    /// The given values of x, visited in order before the integration continues up to `x_target`.
+    ///
+    /// They must follow each other in the direction of integration, between the initial x and `x_target`: the `try_*`
+    /// methods report a [`SolverError::InvalidOutputPoint`] otherwise.
+    ///
+    /// The solvers restart their grid at each of these points, so that the solution is computed there without interpolation.
+    Points(Vec<F>),
+}
+
+/// Settings of an integration, accepted by the `*_with` methods of every solver.
+///
+/// It is built starting from the defaults of [`SolverOptions::new`] and overriding only the needed settings.
+/// The tolerances, `h_min` and `h_max` drive the step size control of the adaptive methods, while the fixed-step methods
+/// take steps of size `h0`, capped at `h_max`.
+///
+/// # Example
+///
+/// ```
+/// # use damndiff::{Output, SolverOptions};
+/// # use damndiff::ode::{ODESolver, rkf::RKFODESolver};
+/// let solver = ODESolver;
+/// let options = SolverOptions::new()
+///     .rtol(1e-8)
+///     .atol(1e-10)
+///     .h0(0.01)
+///     .h_max(0.5)
+///     .output(Output::Points(vec![0.5, 1.0, 1.5]));
+///
+/// let solution = solver.rkf_ivp_with(&|_x: f64, y: f64| -y, 0.0, 1.0, 2.0, &options);
+/// assert_eq!(solution.x, vec![0.5, 1.0, 1.5]);
+/// for (x, y) in solution.iter() {
+///     assert!((y - (-x).exp()).abs() < 1e-7);
+/// }
+/// ```
+///
+/// The settings are checked against the problem by the `try_*_with` methods:
+///
+/// ```
+/// # use damndiff::{Output, SolverError, SolverOptions};
+/// # use damndiff::ode::{ODESolver, dormand_prince::DormandPrinceODESolver};
+/// let solver = ODESolver;
+/// let decay = |_x: f64, y: f64| -y;
+///
+/// // An ODE has a single component
+/// let options = SolverOptions::new().atol_per_component(vec![]);
+/// let error = solver.try_dopri5_ivp_with(&decay, 0.0, 1.0, 1.0, &options).unwrap_err();
+/// assert_eq!(error, SolverError::DimensionMismatch { x: 0.0, expected: 1, found: 0 });
+///
+/// // The points go backwards, then beyond x_target
+/// let options = SolverOptions::new().output(Output::Points(vec![0.8, 0.2, 5.0]));
+/// let error = solver.try_dopri5_ivp_with(&decay, 0.0, 1.0, 1.0, &options).unwrap_err();
+/// assert_eq!(error, SolverError::InvalidOutputPoint { x: 0.0, point: 0.2 });
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolverOptions<F: Real = f64> {
+    pub(crate) rtol: F,
+    pub(crate) atol: Vec<F>,
+    pub(crate) h0: Option<F>,
+    pub(crate) h_min: F,
+    pub(crate) h_max: F,
+    pub(crate) max_steps: usize,
+    pub(crate) output: Output<F>,
+}
+
+impl<F: Real> SolverOptions<F> {
+    /// Creates the default settings: tolerances of `1e-6`, no bound on the step size, at most 10 000 000 steps and
+    /// every accepted step in the output. Without an explicit `h0` the interval is covered by 100 steps.
+    pub fn new() -> Self {
+        SolverOptions {
+            rtol: lit(DEFAULT_TOLERANCE),
+            atol: vec![lit(DEFAULT_TOLERANCE)],
+            h0: None,
+            h_min: F::zero(),
+            h_max: F::infinity(),
+            max_steps: DEFAULT_MAX_STEPS,
+            output: Output::Steps,
+        }
+    }
+
+    /// Sets the relative tolerance of the local error.
+    pub fn rtol(mut self, rtol: F) -> Self {
+        self.rtol = rtol;
+        self
+    }
+
+    /// Sets the absolute tolerance of the local error, shared by every component of a system.
+    pub fn atol(mut self, atol: F) -> Self {
+        self.atol = vec![atol];
+        self
+    }
+
+    /// Sets one absolute tolerance for each component of a system.
+    pub fn atol_per_component(mut self, atol: Vec<F>) -> Self {
+        self.atol = atol;
+        self
+    }
+
+    /// Sets the initial step size, which is the step size of the fixed-step methods. Its sign is ignored.
+    pub fn h0(mut self, h0: F) -> Self {
+        self.h0 = Some(h0);
+        self
+    }
+
+    /// Sets the smallest step size the adaptive methods may take.
+    pub fn h_min(mut self, h_min: F) -> Self {
+        self.h_min = h_min;
+        self
+    }
+
+    /// Sets the largest step size any method may take.
+    pub fn h_max(mut self, h_max: F) -> Self {
+        self.h_max = h_max;
+        self
+    }
+
+    /// Sets the maximum number of steps taken to reach each point of the output, or `x_target` when the output is not
+    /// made of [`Output::Points`].
+    pub fn max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Sets the points stored in the returned solution.
+    pub fn output(mut self, output: Output<F>) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// Returns the absolute tolerance of the `i`-th component.
+    pub(crate) fn atol_at(&self, i: usize) -> F {
+        match self.atol.as_slice() {
+            [atol] => *atol,
+            atol => atol[i],
+        }
+    }
+
+    /// Checks the settings against an integration from `x0` to `x_target` of a state of dimension `dim`, 1 for an ODE.
+    ///
+    /// The number of absolute tolerances must be 1 or `dim`, and the points of [`Output::Points`] must follow each other
+    /// in the direction of integration without leaving the interval from `x0` to `x_target`.
+    pub(crate) fn check(&self, x0: F, x_target: F, dim: usize) -> Result<(), SolverError> {
+        if self.atol.len() != 1 && self.atol.len() != dim {
+            return Err(SolverError::DimensionMismatch { x: to_f64(x0), expected: dim, found: self.atol.len() });
+        }
+
+        if let Output::Points(points) = &self.output {
+            let direction = x_target - x0;
+            // Whether `b` is reached from `a` in the direction of integration, NaN never being reached
+            let ordered = |a: F, b: F| if direction == F::zero() { a == b } else { (b - a) * direction >= F::zero() };
+            let mut x_last = x0;
+            for &point in points {
+                if !(ordered(x_last, point) && ordered(point, x_target)) {
+                    return Err(SolverError::InvalidOutputPoint { x: to_f64(x0), point: to_f64(point) });
+                }
+                x_last = point;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the magnitude of the first step of an integration from `x0` to `x_target`.
+    pub(crate) fn initial_step(&self, x0: F, x_target: F) -> F {
+        let h0 = self.h0.map(F::abs).unwrap_or_else(|| {
+            let span = (x_target - x0).abs();
+            if span > F::zero() {
+                span / lit(DEFAULT_STEPS)
+            } else {
+                F::one()
+            }
+        });
+        h0.min(self.h_max)
+    }
+}
+
+impl<F: Real> Default for SolverOptions<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
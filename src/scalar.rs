@@ -0,0 +1,35 @@
+//! Scalar types the solvers can work with.
+use std::fmt::Debug;
+use std::ops::{AddAssign, DivAssign, MulAssign, SubAssign};
+
+use num_traits::Float;
+
+/// A real scalar type usable by the solvers.
+///
+/// It is implemented for every [`num_traits::Float`] that also supports the compound assignment operators, such as `f64`
+/// (the default everywhere in the crate), `f32` for embedded targets, or dual and higher-precision numbers providing a
+/// `Float` implementation.
+///
+/// # Example
+///
+/// ```
+/// # use damndiff::ode::{ODESolver, rk::RungeKuttaODESolver};
+/// let solver = ODESolver;
+///
+/// // dy/dx = -y, integrated in single precision
+/// let result: f32 = solver.rk4_ivp(&|_x: f32, y: f32| -y, 0.0, 1.0, 0.1, 1.0);
+/// assert!((result - (-1.0f32).exp()).abs() < 1e-5);
+/// ```
+pub trait Real: Float + AddAssign + SubAssign + MulAssign + DivAssign + Debug + Default + 'static {}
+
+impl<T> Real for T where T: Float + AddAssign + SubAssign + MulAssign + DivAssign + Debug + Default + 'static {}
+
+/// Converts an `f64` constant, such as a method coefficient, into the scalar type.
+pub(crate) fn lit<F: Real>(value: f64) -> F {
+    F::from(value).expect("the scalar type cannot represent an f64 constant")
+}
+
+/// Converts a scalar into an `f64`, used to report errors independently of the scalar type.
+pub(crate) fn to_f64<F: Real>(value: F) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
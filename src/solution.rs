@@ -0,0 +1,74 @@
+//! Trajectories produced by the solvers.
+use crate::Stats;
+
+/// The numerical solution of an initial value problem.
+///
+/// It stores every accepted point `(x, y)` of the integration, starting from the initial condition
+/// and ending at the last point reached by the solver. `Y` is the scalar type `X` for a single ODE and `Vec<X>`
+/// for a system of ODEs, `X` being `f64` unless the problem is defined on another [`Real`](crate::Real) type.
+/// The solvers also record the [`Stats`] of the integration.
+///
+/// # Example
+///
+/// ```
+/// # use damndiff::Solution;
+/// let mut solution = Solution::new();
+/// solution.push(0.0, 1.0);
+/// solution.push(0.1, 1.1);
+///
+/// assert_eq!(solution.len(), 2);
+/// assert_eq!(solution.last(), Some((0.1, &1.1)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution<Y, X = f64> {
+    /// The accepted values of the independent variable.
+    pub x: Vec<X>,
+    /// The values of the dependent variable(s), one for each entry of `x`.
+    pub y: Vec<Y>,
+    /// The statistics of the integration that produced the solution.
+    pub stats: Stats<X>,
+}
+
+impl<Y, X: Copy + Default> Solution<Y, X> {
+    /// Creates an empty solution.
+    pub fn new() -> Self {
+        Solution { x: Vec::new(), y: Vec::new(), stats: Stats::default() }
+    }
+
+    /// Creates an empty solution with room for `capacity` points.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Solution { x: Vec::with_capacity(capacity), y: Vec::with_capacity(capacity), stats: Stats::default() }
+    }
+
+    /// Appends the point `(x, y)` to the solution.
+    pub fn push(&mut self, x: X, y: Y) {
+        self.x.push(x);
+        self.y.push(y);
+    }
+
+    /// Returns the number of stored points.
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    /// Returns `true` if the solution holds no point.
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+
+    /// Returns the last stored point, that is the one closest to `x_target`.
+    pub fn last(&self) -> Option<(X, &Y)> {
+        self.x.last().copied().zip(self.y.last())
+    }
+
+    /// Returns an iterator over the stored `(x, y)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (X, &Y)> {
+        self.x.iter().copied().zip(self.y.iter())
+    }
+}
+
+impl<Y, X: Copy + Default> Default for Solution<Y, X> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
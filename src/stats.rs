@@ -0,0 +1,73 @@
+//! Counters describing the cost of an integration.
+use std::cell::Cell;
+
+/// Statistics of an integration, stored in the returned [`Solution`](crate::Solution) and exposed by the steppers.
+///
+/// The counters of the implicit methods, `jacobian_evals` and `linear_solves`, stay at zero for the explicit ones, as
+/// does `rejected_steps` for the methods without step size control.
+///
+/// # Example
+///
+/// ```
+/// # use damndiff::ode::{ODESolver, rk::RungeKuttaODESolver, rkf::RKFODESolver};
+/// let solver = ODESolver;
+/// let decay = |_x: f64, y: f64| -y;
+///
+/// let rk4 = solver.rk4_ivp_traj(&decay, 0.0, 1.0, 0.1, 1.0);
+/// let rkf = solver.rkf_ivp_traj(&decay, 0.0, 1.0, 0.1, 1.0);
+/// assert_eq!(rk4.stats.evals, 4 * rk4.stats.accepted_steps);
+/// println!("RK4: {} evaluations, RKF: {} evaluations", rk4.stats.evals, rkf.stats.evals);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Stats<F = f64> {
+    /// Number of evaluations of the right-hand side, through `eval` or `eval_into`.
+    pub evals: usize,
+    /// Number of accepted steps.
+    pub accepted_steps: usize,
+    /// Number of steps rejected by the step size control and retried with a smaller step.
+    pub rejected_steps: usize,
+    /// Number of evaluations of the Jacobian.
+    pub jacobian_evals: usize,
+    /// Number of solutions of a linear system, including the ones of the Newton iterations.
+    pub linear_solves: usize,
+    /// Signed size of the last accepted step, zero before the first one.
+    pub h_final: F,
+    /// Value of x at which the methods with stiffness detection first found the problem to be stiff, suggesting to
+    /// switch to an implicit method.
+    pub stiffness_detected: Option<F>,
+    /// Switches between a non-stiff and a stiff method, in the order in which they happened, for the solvers choosing
+    /// the method on the fly.
+    pub switches: Vec<MethodSwitch<F>>,
+}
+
+/// A change of method made by the solvers choosing the method on the fly, such as
+/// [`AutoSwitchODESysSolver`](crate::ode_sys::auto_switch::AutoSwitchODESysSolver).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MethodSwitch<F = f64> {
+    /// Value of x at which the new method takes its first step.
+    pub x: F,
+    /// Whether the new method is the stiff one, rather than the non-stiff one.
+    pub to_stiff: bool,
+}
+
+/// A right-hand side counting its evaluations, wrapped around the one of the user for the duration of a step.
+pub(crate) struct Counted<'a, T: ?Sized> {
+    pub(crate) inner: &'a T,
+    evals: Cell<usize>,
+}
+
+impl<'a, T: ?Sized> Counted<'a, T> {
+    pub(crate) fn new(inner: &'a T) -> Self {
+        Counted { inner, evals: Cell::new(0) }
+    }
+
+    /// Records one evaluation of the right-hand side.
+    pub(crate) fn count(&self) {
+        self.evals.set(self.evals.get() + 1);
+    }
+
+    /// Returns the number of evaluations recorded so far.
+    pub(crate) fn evals(&self) -> usize {
+        self.evals.get()
+    }
+}
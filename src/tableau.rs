@@ -0,0 +1,269 @@
+//! Butcher tableaus of the explicit Runge-Kutta methods.
+use crate::Real;
+use crate::scalar::lit;
+
+/// The coefficients of an explicit Runge-Kutta method, optionally with an embedded solution and a continuous extension.
+///
+/// A method of `s` stages advances `(x, y)` by a step `h` as
+///
+/// ```text
+/// k[i] = f(x + c[i] * h, y + h * sum(a[i][j] * k[j], j < i))
+/// y_next = y + h * sum(b[i] * k[i])
+/// ```
+///
+/// The embedded weights `b_hat` give a second solution of another order, whose difference with `y_next` estimates the
+/// local error and drives the step size control. The continuous extension replaces each weight `b[i]` by a polynomial
+/// `b[i](theta)` without constant term, which gives the solution at `x + theta * h`.
+///
+/// The tableaus of the methods of the crate are built by the associated functions, such as [`ButcherTableau::rk4`],
+/// and any other one can be described with [`ButcherTableau::new`]. They are integrated by the generic engines
+/// [`ExplicitRungeKuttaODESolver`](crate::ode::explicit_rk::ExplicitRungeKuttaODESolver) and
+/// [`ExplicitRungeKuttaODESysSolver`](crate::ode_sys::explicit_rk_sys::ExplicitRungeKuttaODESysSolver).
+///
+/// # Example
+///
+/// ```
+/// # use damndiff::ButcherTableau;
+/// # use damndiff::ode::{ODESolver, explicit_rk::ExplicitRungeKuttaODESolver};
+/// // Explicit midpoint method
+/// let midpoint = ButcherTableau::new(&[0.0, 0.5], &[&[], &[0.5]], &[0.0, 1.0], 2);
+///
+/// let solver = ODESolver;
+/// let result = solver.erk_ivp(&|_x: f64, y: f64| -y, &midpoint, 0.0, 1.0, 0.01, 1.0);
+/// assert!((result - (-1.0f64).exp()).abs() < 1e-5);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ButcherTableau {
+    /// Nodes of the stages.
+    pub(crate) c: Vec<f64>,
+    /// Coefficients of the stages, row `i` holding the `i` weights of the previous stages.
+    pub(crate) a: Vec<Vec<f64>>,
+    /// Weights of the propagated solution.
+    pub(crate) b: Vec<f64>,
+    order: usize,
+    /// Differences `b - b_hat` between the weights of the propagated and of the embedded solutions.
+    pub(crate) e: Option<Vec<f64>>,
+    embedded_order: Option<usize>,
+    /// Coefficients of `theta, theta^2, ...` in the weight of each stage in the continuous extension.
+    pub(crate) dense: Option<Vec<Vec<f64>>>,
+    /// Whether the last stage is evaluated at the end point of the step, see [`ButcherTableau::is_fsal`].
+    fsal: bool,
+}
+
+impl ButcherTableau {
+    /// Creates the tableau of an explicit method of order `order`, with nodes `c`, stage coefficients `a` and weights `b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method has no stage, if `c` and `b` have a different number of stages, or if the row `i` of `a`
+    /// does not hold exactly `i` coefficients.
+    pub fn new(c: &[f64], a: &[&[f64]], b: &[f64], order: usize) -> Self {
+        let stages = c.len();
+        assert!(stages > 0, "a Runge-Kutta method needs at least one stage");
+        assert_eq!(b.len(), stages, "{} weights given for {} stages", b.len(), stages);
+        assert_eq!(a.len(), stages, "{} rows of coefficients given for {} stages", a.len(), stages);
+        for (i, row) in a.iter().enumerate() {
+            assert_eq!(row.len(), i, "row {} of an explicit method needs {} coefficients, {} given", i, i, row.len());
+        }
+
+        // The last stage is the derivative at the end point when it is evaluated at the propagated solution
+        let fsal = stages > 1 && c[stages - 1] == 1.0 && a[stages - 1] == &b[..stages - 1] && b[stages - 1] == 0.0;
+
+        ButcherTableau {
+            c: c.to_vec(),
+            a: a.iter().map(|row| row.to_vec()).collect(),
+            b: b.to_vec(),
+            order,
+            e: None,
+            embedded_order: None,
+            dense: None,
+            fsal,
+        }
+    }
+
+    /// Adds the weights `b_hat` of an embedded solution of order `order`, making the method adaptive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `b_hat` does not have one weight per stage.
+    pub fn with_embedded(mut self, b_hat: &[f64], order: usize) -> Self {
+        assert_eq!(b_hat.len(), self.stages(), "{} embedded weights given for {} stages", b_hat.len(), self.stages());
+        self.e = Some(self.b.iter().zip(b_hat).map(|(b, b_hat)| b - b_hat).collect());
+        self.embedded_order = Some(order);
+        self
+    }
+
+    /// Adds a continuous extension, row `i` of `coefficients` holding the coefficients of `theta, theta^2, ...` in the
+    /// polynomial weight of stage `i`, whose sum must be `b[i]`.
+    ///
+    /// Without it, the adaptive steppers interpolate the solution with a cubic Hermite polynomial.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `coefficients` does not have one row per stage, or if its rows have different lengths.
+    pub fn with_dense_output(mut self, coefficients: &[&[f64]]) -> Self {
+        assert_eq!(coefficients.len(), self.stages(), "{} rows of dense output coefficients given for {} stages", coefficients.len(), self.stages());
+        assert!(
+            coefficients.iter().all(|row| row.len() == coefficients[0].len()),
+            "the dense output coefficients of all the stages need the same degree"
+        );
+        self.dense = Some(coefficients.iter().map(|row| row.to_vec()).collect());
+        self
+    }
+
+    /// Returns the number of stages.
+    pub fn stages(&self) -> usize {
+        self.c.len()
+    }
+
+    /// Returns the order of the propagated solution.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Returns the order of the embedded solution, or `None` if the method has none.
+    pub fn embedded_order(&self) -> Option<usize> {
+        self.embedded_order
+    }
+
+    /// Returns `true` if the last stage is the derivative at the end point of the step (first same as last), so that an
+    /// adaptive stepper reuses it as the first stage of the next step.
+    pub fn is_fsal(&self) -> bool {
+        self.fsal
+    }
+
+    /// Returns `true` if the method has its own continuous extension.
+    pub fn has_dense_output(&self) -> bool {
+        self.dense.is_some()
+    }
+
+    /// Returns the order of the error estimate, that is the lower order of the pair.
+    pub(crate) fn error_order(&self) -> usize {
+        self.order.min(self.embedded_order.unwrap_or(self.order))
+    }
+
+    /// Returns the weight of stage `i` in the continuous extension at `theta`.
+    pub(crate) fn dense_weight<F: Real>(&self, i: usize, theta: F) -> F {
+        let coefficients = &self.dense.as_ref().expect("the method has no continuous extension")[i];
+        let mut weight = F::zero();
+        for coefficient in coefficients.iter().rev() {
+            weight = (weight + lit(*coefficient)) * theta;
+        }
+        weight
+    }
+
+    /// Heun's method, or explicit trapezoidal rule, of order 2.
+    pub fn heun() -> Self {
+        ButcherTableau::new(&[0.0, 1.0], &[&[], &[1.0]], &[0.5, 0.5], 2)
+    }
+
+    /// Ralston's method of order 2, minimizing the bound of the local error among the 2-stage methods.
+    pub fn ralston() -> Self {
+        ButcherTableau::new(&[0.0, 2.0 / 3.0], &[&[], &[2.0 / 3.0]], &[0.25, 0.75], 2)
+    }
+
+    /// The strong stability preserving method of order 3 of Shu and Osher (SSPRK3), suited to hyperbolic problems.
+    pub fn ssprk3() -> Self {
+        ButcherTableau::new(&[0.0, 1.0, 0.5], &[&[], &[1.0], &[0.25, 0.25]], &[1.0 / 6.0, 1.0 / 6.0, 2.0 / 3.0], 3)
+    }
+
+    /// The classic Runge-Kutta method of order 4 (RK4).
+    pub fn rk4() -> Self {
+        ButcherTableau::new(
+            &[0.0, 0.5, 0.5, 1.0],
+            &[&[], &[0.5], &[0.0, 0.5], &[0.0, 0.0, 1.0]],
+            &[1.0 / 6.0, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 6.0],
+            4,
+        )
+    }
+
+    /// The Bogacki–Shampine 3(2) pair, propagating the 3rd-order solution, with the first-same-as-last property.
+    pub fn bogacki_shampine() -> Self {
+        ButcherTableau::new(
+            &[0.0, 0.5, 0.75, 1.0],
+            &[&[], &[0.5], &[0.0, 0.75], &[2.0 / 9.0, 1.0 / 3.0, 4.0 / 9.0]],
+            &[2.0 / 9.0, 1.0 / 3.0, 4.0 / 9.0, 0.0],
+            3,
+        )
+        .with_embedded(&[7.0 / 24.0, 0.25, 1.0 / 3.0, 0.125], 2)
+    }
+
+    /// The Runge-Kutta-Fehlberg 4(5) pair, propagating the 4th-order solution as in the classic Fehlberg method.
+    pub fn rkf45() -> Self {
+        ButcherTableau::new(
+            &[0.0, 0.25, 3.0 / 8.0, 12.0 / 13.0, 1.0, 0.5],
+            &[
+                &[],
+                &[0.25],
+                &[3.0 / 32.0, 9.0 / 32.0],
+                &[1932.0 / 2197.0, -7200.0 / 2197.0, 7296.0 / 2197.0],
+                &[439.0 / 216.0, -8.0, 3680.0 / 513.0, -845.0 / 4104.0],
+                &[-8.0 / 27.0, 2.0, -3544.0 / 2565.0, 1859.0 / 4104.0, -11.0 / 40.0],
+            ],
+            &[25.0 / 216.0, 0.0, 1408.0 / 2565.0, 2197.0 / 4104.0, -0.2, 0.0],
+            4,
+        )
+        .with_embedded(&[16.0 / 135.0, 0.0, 6656.0 / 12825.0, 28561.0 / 56430.0, -9.0 / 50.0, 2.0 / 55.0], 5)
+    }
+
+    /// The Cash–Karp 5(4) pair, propagating the 5th-order solution.
+    pub fn cash_karp() -> Self {
+        ButcherTableau::new(
+            &[0.0, 0.2, 0.3, 0.6, 1.0, 7.0 / 8.0],
+            &[
+                &[],
+                &[0.2],
+                &[3.0 / 40.0, 9.0 / 40.0],
+                &[0.3, -0.9, 1.2],
+                &[-11.0 / 54.0, 2.5, -70.0 / 27.0, 35.0 / 27.0],
+                &[1631.0 / 55296.0, 175.0 / 512.0, 575.0 / 13824.0, 44275.0 / 110592.0, 253.0 / 4096.0],
+            ],
+            &[37.0 / 378.0, 0.0, 250.0 / 621.0, 125.0 / 594.0, 0.0, 512.0 / 1771.0],
+            5,
+        )
+        .with_embedded(&[2825.0 / 27648.0, 0.0, 18575.0 / 48384.0, 13525.0 / 55296.0, 277.0 / 14336.0, 0.25], 4)
+    }
+
+    /// The Tsitouras 5(4) pair (Tsit5), propagating the 5th-order solution, with the first-same-as-last property and a
+    /// continuous extension of order 4.
+    pub fn tsitouras5() -> Self {
+        let b = [
+            0.09646076681806523, 0.01, 0.4798896504144996, 1.379008574103742, -3.290069515436081, 2.324710524099774,
+        ];
+        ButcherTableau::new(
+            &[0.0, 0.161, 0.327, 0.9, 0.9800255409045097, 1.0, 1.0],
+            &[
+                &[],
+                &[0.161],
+                &[-0.008480655492356989, 0.335480655492357],
+                &[2.897153057105493, -6.359448489975075, 4.3622954328695815],
+                &[5.325864828439257, -11.748883564062828, 7.4955393428898365, -0.09249506636175525],
+                &[5.86145544294642, -12.92096931784711, 8.159367898576159, -0.071584973281401, -0.028269050394068383],
+                &b,
+            ],
+            &[b[0], b[1], b[2], b[3], b[4], b[5], 0.0],
+            5,
+        )
+        .with_embedded(
+            &[
+                b[0] + 0.0017800110522257773,
+                b[1] + 0.0008164344596567469,
+                b[2] - 0.007880878010261995,
+                b[3] + 0.1447110071732629,
+                b[4] - 0.5823571654525552,
+                b[5] + 0.45808210592918697,
+                -1.0 / 66.0,
+            ],
+            4,
+        )
+        .with_dense_output(&[
+            &[1.0, -2.763706197274826, 2.9132554618219126, -1.0530884977290216],
+            &[0.0, 0.1317, -0.2234, 0.1017],
+            &[0.0, 3.930296236894751, -5.941033872131505, 2.490627285651253],
+            &[0.0, -12.411077166933676, 30.33818863028232, -16.548102889244902],
+            &[0.0, 37.50931341651104, -88.1789048947664, 47.37952196281928],
+            &[0.0, -27.896526289197286, 65.09189467479368, -34.87065786149661],
+            &[0.0, 1.5, -4.0, 2.5],
+        ])
+    }
+}